@@ -4,13 +4,19 @@ use crate::{
         subscription::{SubscriptionId, SubscriptionKind, SubscriptionParams},
         transaction::EthTransactionRequest,
     },
-    types::{EvmMineOptions, Forking, GethDebugTracingOptions, Index},
+    types::{
+        AccountUpdate, EvmMineOptions, Forking, FundedAccount, GethDebugTracingOptions, Index,
+        StateOverride,
+    },
 };
 use ethers_core::{
     abi::ethereum_types::H64,
-    types::{Address, BlockId, BlockNumber, Bytes, Filter, TxHash, H256, U256},
+    types::{
+        transaction::eip712::TypedData, Address, BlockId, BlockNumber, Bytes, Filter, TxHash, H256,
+        U256,
+    },
 };
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use serde_helpers::Params;
 
 pub mod block;
@@ -42,6 +48,12 @@ pub enum EthRequest {
     #[serde(rename = "net_listening", with = "empty_params")]
     NetListening(()),
 
+    #[serde(rename = "eth_protocolVersion", with = "empty_params")]
+    EthProtocolVersion(()),
+
+    #[serde(rename = "eth_syncing", with = "empty_params")]
+    EthSyncing(()),
+
     #[serde(rename = "eth_gasPrice", with = "empty_params")]
     EthGasPrice(()),
 
@@ -52,10 +64,17 @@ pub enum EthRequest {
     EthBlockNumber(()),
 
     #[serde(rename = "eth_getBalance")]
-    EthGetBalance(Address, Option<BlockId>),
+    EthGetBalance(
+        Address,
+        #[serde(default, deserialize_with = "lenient_block_id_opt")] Option<BlockId>,
+    ),
 
     #[serde(rename = "eth_getStorageAt")]
-    EthGetStorageAt(Address, U256, Option<BlockId>),
+    EthGetStorageAt(
+        Address,
+        U256,
+        #[serde(default, deserialize_with = "lenient_block_id_opt")] Option<BlockId>,
+    ),
 
     #[serde(rename = "eth_getBlockByHash")]
     EthGetBlockByHash(H256, bool),
@@ -64,7 +83,10 @@ pub enum EthRequest {
     EthGetBlockByNumber(#[serde(deserialize_with = "lenient_block_number")] BlockNumber, bool),
 
     #[serde(rename = "eth_getTransactionCount")]
-    EthGetTransactionCount(Address, Option<BlockId>),
+    EthGetTransactionCount(
+        Address,
+        #[serde(default, deserialize_with = "lenient_block_id_opt")] Option<BlockId>,
+    ),
 
     #[serde(rename = "eth_getBlockTransactionCountByHash", with = "sequence")]
     EthGetTransactionCountByHash(H256),
@@ -85,12 +107,19 @@ pub enum EthRequest {
     EthGetUnclesCountByNumber(BlockNumber),
 
     #[serde(rename = "eth_getCode")]
-    EthGetCodeAt(Address, Option<BlockId>),
+    EthGetCodeAt(
+        Address,
+        #[serde(default, deserialize_with = "lenient_block_id_opt")] Option<BlockId>,
+    ),
 
     /// The sign method calculates an Ethereum specific signature with:
     #[serde(rename = "eth_sign")]
     EthSign(Address, Bytes),
 
+    /// Signs EIP-712 typed data
+    #[serde(rename = "eth_signTypedData_v4")]
+    EthSignTypedDataV4(Address, TypedData),
+
     #[serde(rename = "eth_sendTransaction", with = "sequence")]
     EthSendTransaction(Box<EthTransactionRequest>),
 
@@ -98,7 +127,11 @@ pub enum EthRequest {
     EthSendRawTransaction(Bytes),
 
     #[serde(rename = "eth_call")]
-    EthCall(CallRequest, #[serde(default)] Option<BlockId>),
+    EthCall(
+        CallRequest,
+        #[serde(default, deserialize_with = "lenient_block_id_opt")] Option<BlockId>,
+        #[serde(default)] Option<StateOverride>,
+    ),
 
     #[serde(rename = "eth_createAccessList")]
     EthCreateAccessList(CallRequest, #[serde(default)] Option<BlockId>),
@@ -121,6 +154,9 @@ pub enum EthRequest {
     #[serde(rename = "eth_getTransactionReceipt", with = "sequence")]
     EthGetTransactionReceipt(H256),
 
+    #[serde(rename = "eth_getBlockReceipts", with = "sequence")]
+    EthGetBlockReceipts(BlockId),
+
     #[serde(rename = "eth_getUncleByBlockHashAndIndex")]
     EthGetUncleByBlockHashAndIndex(H256, Index),
 
@@ -179,6 +215,18 @@ pub enum EthRequest {
     #[serde(rename = "debug_traceTransaction")]
     DebugTraceTransaction(H256, #[serde(default)] GethDebugTracingOptions),
 
+    /// geth's `debug_traceBlockByNumber` endpoint, traces all transactions of the block
+    #[serde(rename = "debug_traceBlockByNumber")]
+    DebugTraceBlockByNumber(BlockNumber, #[serde(default)] GethDebugTracingOptions),
+
+    /// geth's `debug_traceBlockByHash` endpoint, traces all transactions of the block
+    #[serde(rename = "debug_traceBlockByHash")]
+    DebugTraceBlockByHash(H256, #[serde(default)] GethDebugTracingOptions),
+
+    /// geth's `debug_setHead` endpoint, rewinds the chain to the given block
+    #[serde(rename = "debug_setHead", deserialize_with = "deserialize_number_seq")]
+    DebugSetHead(U256),
+
     /// Trace transaction endpoint for parity's `trace_transaction`
     #[serde(rename = "trace_transaction", with = "sequence")]
     TraceTransaction(H256),
@@ -211,9 +259,11 @@ pub enum EthRequest {
         /// Number of blocks to mine, if not set `1` block is mined
         #[serde(default, deserialize_with = "deserialize_number_opt")]
         Option<U256>,
-        /// The time interval between each block in seconds, defaults to `1` seconds
+        /// The difference between the timestamps of consecutive blocks in seconds
+        ///
         /// The interval is applied only to blocks mined in the given method invocation, not to
-        /// blocks mined afterwards. Set this to `0` to instantly mine _all_ blocks
+        /// blocks mined afterwards, and takes precedence over the interval set via
+        /// `anvil_setBlockTimestampInterval`. If not set, the timestamps follow the usual rules.
         #[serde(default, deserialize_with = "deserialize_number_opt")]
         Option<U256>,
     ),
@@ -243,6 +293,38 @@ pub enum EthRequest {
     #[serde(rename = "anvil_reset", alias = "hardhat_reset", with = "sequence")]
     Reset(#[serde(default)] Option<Forking>),
 
+    /// Moves the chain head back by the given number of blocks
+    #[serde(rename = "anvil_rollback", deserialize_with = "deserialize_number_seq")]
+    Rollback(U256),
+
+    /// Returns the url, block number and chain id of the active fork
+    #[serde(rename = "anvil_getFork", with = "empty_params")]
+    GetFork(()),
+
+    /// Returns the current state and settings of the node
+    #[serde(rename = "anvil_nodeInfo", with = "empty_params")]
+    NodeInfo(()),
+
+    /// Executes the transactions in order on top of the block's state without committing them
+    #[serde(rename = "anvil_simulateBundle")]
+    SimulateBundle(Vec<CallRequest>, #[serde(default)] Option<BlockId>),
+
+    /// Returns the state root of the current state
+    #[serde(rename = "anvil_getStateRoot", with = "empty_params")]
+    GetStateRoot(()),
+
+    /// Returns the accounts and storage slots that changed since the fork
+    #[serde(rename = "anvil_dumpStateDiff", with = "empty_params")]
+    DumpStateDiff(()),
+
+    /// Returns how much data was fetched from the fork, optionally resetting the counters
+    #[serde(rename = "anvil_getForkStats")]
+    GetForkStats(#[serde(default)] Option<Params<Option<bool>>>),
+
+    /// Returns the hashes of the transactions the next block would include, in order
+    #[serde(rename = "anvil_getMempoolOrder", with = "empty_params")]
+    GetMempoolOrder(()),
+
     /// Sets the backend rpc url
     #[serde(rename = "anvil_setRpcUrl", with = "sequence")]
     SetRpcUrl(String),
@@ -251,10 +333,22 @@ pub enum EthRequest {
     #[serde(rename = "anvil_setBalance", alias = "hardhat_setBalance")]
     SetBalance(Address, #[serde(deserialize_with = "deserialize_number")] U256),
 
+    /// Adds to the balance of an account.
+    #[serde(rename = "anvil_addBalance")]
+    AddBalance(Address, #[serde(deserialize_with = "deserialize_number")] U256),
+
+    /// Sets the balances of multiple accounts at once
+    #[serde(rename = "anvil_setFundedAccounts", with = "sequence")]
+    SetFundedAccounts(Vec<FundedAccount>),
+
     /// Sets the code of a contract
     #[serde(rename = "anvil_setCode", alias = "hardhat_setCode")]
     SetCode(Address, Bytes),
 
+    /// Deploys the initcode from an optional sender with an optional value, without a transaction
+    #[serde(rename = "anvil_deployCode")]
+    DeployCode(Bytes, #[serde(default)] Option<Address>, #[serde(default)] Option<U256>),
+
     /// Sets the nonce of an address
     #[serde(rename = "anvil_setNonce", alias = "hardhat_setNonce")]
     SetNonce(Address, #[serde(deserialize_with = "deserialize_number")] U256),
@@ -269,10 +363,34 @@ pub enum EthRequest {
         U256,
     ),
 
+    /// Writes multiple slots of the account's storage at once
+    #[serde(rename = "anvil_setStorageBatch")]
+    SetStorageBatch(
+        Address,
+        /// `(slot, value)` pairs
+        Vec<(U256, U256)>,
+    ),
+
+    /// Sets the balance, nonce, code and storage of an account at once
+    #[serde(rename = "anvil_setAccount")]
+    SetAccount(Address, AccountUpdate),
+
+    /// Returns the changes state cheats made to accounts, if recorded
+    #[serde(rename = "anvil_getCheatLog", with = "empty_params")]
+    GetCheatLog(()),
+
     /// Sets the coinbase address
     #[serde(rename = "anvil_setCoinbase", alias = "hardhat_setCoinbase", with = "sequence")]
     SetCoinbase(Address),
 
+    /// Sets the coinbase of the next block only
+    #[serde(rename = "anvil_setNextBlockCoinbase", with = "sequence")]
+    SetNextBlockCoinbase(Address),
+
+    /// Sets the chain id
+    #[serde(rename = "anvil_setChainId", deserialize_with = "deserialize_number_seq")]
+    SetChainId(U256),
+
     /// Enable or disable logging
     #[serde(
         rename = "anvil_setLoggingEnabled",
@@ -289,6 +407,26 @@ pub enum EthRequest {
     )]
     SetMinGasPrice(U256),
 
+    /// Removes the minimum gas price, so that transactions with any gas price are accepted
+    #[serde(rename = "anvil_removeMinGasPrice", with = "empty_params")]
+    RemoveMinGasPrice(()),
+
+    /// Sets the priority fee that is suggested to wallets via `eth_maxPriorityFeePerGas`
+    #[serde(
+        rename = "anvil_setMinSuggestedPriorityFee",
+        deserialize_with = "deserialize_number_seq"
+    )]
+    SetMinSuggestedPriorityFee(U256),
+
+    /// Sets the block gas limit
+    #[serde(
+        rename = "anvil_setBlockGasLimit",
+        alias = "evm_setBlockGasLimit",
+        alias = "anvil_setGasLimit",
+        deserialize_with = "deserialize_number_seq"
+    )]
+    SetBlockGasLimit(U256),
+
     /// Sets the base fee of the next block
     #[serde(
         rename = "anvil_setNextBlockBaseFeePerGas",
@@ -297,6 +435,26 @@ pub enum EthRequest {
     )]
     SetNextBlockBaseFeePerGas(U256),
 
+    /// Sets the gas used of the next block, that the base fee of the block after it is based on
+    #[serde(rename = "anvil_setNextBlockGasUsed", deserialize_with = "deserialize_number_seq")]
+    SetNextBlockGasUsed(U256),
+
+    /// Makes the next submitted transaction revert with the given reason
+    #[serde(rename = "anvil_setNextTransactionFails", with = "sequence")]
+    SetNextTransactionFails(String),
+
+    /// Waits until the transaction is mined or the timeout (in milliseconds) elapses
+    #[serde(rename = "anvil_waitForTransaction")]
+    WaitForTransaction(H256, u64),
+
+    /// Sets the number of the current head block, without mining the blocks in between
+    #[serde(rename = "anvil_setBlockNumber", deserialize_with = "deserialize_number_seq")]
+    SetBlockNumber(U256),
+
+    /// Sets the owner of an ERC721 token by writing the token's storage
+    #[serde(rename = "anvil_setErc721Owner")]
+    SetErc721Owner(Address, #[serde(deserialize_with = "deserialize_number")] U256, Address),
+
     // Ganache compatible calls
     /// Snapshot the state of the blockchain at the current block.
     #[serde(rename = "anvil_snapshot", alias = "evm_snapshot", with = "empty_params")]
@@ -327,6 +485,10 @@ pub enum EthRequest {
     )]
     EvmSetNextBlockTimeStamp(u64),
 
+    /// Sets the timestamp of the next block and mines it, returning the mined block
+    #[serde(rename = "anvil_mineWithTimestamp", with = "sequence")]
+    MineWithTimestamp(u64),
+
     /// Similar to `evm_increaseTime` but takes sets a block timestamp `interval`.
     ///
     /// The timestamp of the next block will be computed as `lastBlock_timestamp + interval`.
@@ -367,6 +529,69 @@ pub enum EthRequest {
     /// Ref: [Here](https://geth.ethereum.org/docs/rpc/ns-txpool#txpool_content)
     #[serde(rename = "txpool_content", with = "empty_params")]
     TxPoolContent(()),
+
+    /// Generates a new account, the password is ignored
+    #[serde(rename = "personal_newAccount", with = "sequence")]
+    PersonalNewAccount(String),
+
+    /// Unlocks an account, this is a noop since all accounts are unlocked
+    #[serde(rename = "personal_unlockAccount")]
+    PersonalUnlockAccount(Address, String, #[serde(default)] Option<u64>),
+
+    /// Sends a transaction, the password is ignored
+    #[serde(rename = "personal_sendTransaction")]
+    PersonalSendTransaction(Box<EthTransactionRequest>, String),
+
+    /// Returns the version of the Otterscan API that is supported
+    #[serde(rename = "ots_getApiLevel", with = "empty_params")]
+    OtsGetApiLevel(()),
+
+    /// Returns the value transfers, contract creations and self destructs of a transaction
+    #[serde(rename = "ots_getInternalOperations", with = "sequence")]
+    OtsGetInternalOperations(H256),
+
+    /// Returns a page of transactions involving the address, mined before the block
+    #[serde(rename = "ots_searchTransactionsBefore")]
+    OtsSearchTransactionsBefore(Address, u64, usize),
+
+    /// Returns a page of transactions involving the address, mined after the block
+    #[serde(rename = "ots_searchTransactionsAfter")]
+    OtsSearchTransactionsAfter(Address, u64, usize),
+
+    /// Returns the hash of the transaction the sender sent with the nonce
+    #[serde(rename = "ots_getTransactionBySenderAndNonce")]
+    OtsGetTransactionBySenderAndNonce(
+        Address,
+        #[serde(deserialize_with = "deserialize_number")] U256,
+    ),
+
+    /// Returns the block without its transactions, with the total fees of its transactions
+    #[serde(rename = "ots_getBlockDetails", deserialize_with = "lenient_block_number_seq")]
+    OtsGetBlockDetails(BlockNumber),
+
+    /// Returns a page of the transactions of the block with their receipts
+    #[serde(rename = "ots_getBlockTransactions")]
+    OtsGetBlockTransactions(
+        #[serde(deserialize_with = "lenient_block_number")] BlockNumber,
+        usize,
+        usize,
+    ),
+
+    /// Returns whether the address has code at the block
+    #[serde(rename = "ots_hasCode")]
+    OtsHasCode(Address, #[serde(deserialize_with = "lenient_block_number")] BlockNumber),
+
+    /// Returns all calls made by a transaction
+    #[serde(rename = "ots_traceTransaction", with = "sequence")]
+    OtsTraceTransaction(H256),
+
+    /// Returns the revert data of a transaction
+    #[serde(rename = "ots_getTransactionError", with = "sequence")]
+    OtsGetTransactionError(H256),
+
+    /// Returns the transaction that created the contract and the address that deployed it
+    #[serde(rename = "ots_getContractCreator", with = "sequence")]
+    OtsGetContractCreator(Address),
 }
 
 /// Represents ethereum JSON-RPC API
@@ -383,13 +608,31 @@ pub enum EthPubSub {
 }
 
 /// Container type for either a request or a pub sub
-#[derive(Clone, Debug, PartialEq, Deserialize)]
-#[serde(untagged)]
+///
+/// Every call that's not a pub sub is deserialized as [EthRequest], so a request fails with the
+/// same error as over HTTP, e.g. "unknown variant" for an unknown method.
+#[derive(Clone, Debug, PartialEq)]
 pub enum EthRpcCall {
     Request(Box<EthRequest>),
     PubSub(EthPubSub),
 }
 
+impl<'de> Deserialize<'de> for EthRpcCall {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let call = serde_json::Value::deserialize(deserializer)?;
+        let call = match call.get("method").and_then(|method| method.as_str()) {
+            Some("eth_subscribe" | "eth_unsubscribe") => {
+                serde_json::from_value(call).map(EthRpcCall::PubSub)
+            }
+            _ => serde_json::from_value(call).map(|request| EthRpcCall::Request(Box::new(request))),
+        };
+        call.map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -436,6 +679,17 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_eth_syncing() {
+        let s = r#"{"method": "eth_syncing", "params":[]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "eth_protocolVersion", "params":[]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_eth_block_number() {
         let s = r#"{"method": "eth_blockNumber", "params":[]}"#;
@@ -626,6 +880,32 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_personal_methods() {
+        let s = r#"{"method": "personal_newAccount", "params": ["password"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "personal_unlockAccount", "params": ["0xd84de507f3fada7df80908082d3239466db55a71", "password"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "personal_unlockAccount", "params": ["0xd84de507f3fada7df80908082d3239466db55a71", "password", 300]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "personal_sendTransaction", "params": [{"from": "0xd84de507f3fada7df80908082d3239466db55a71", "to": "0x295a70b2de5e3953354a6a8344e616ed314d7251", "value": "0x1"}, "password"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_custom_add_balance() {
+        let s = r#"{"method": "anvil_addBalance", "params": ["0xd84de507f3fada7df80908082d3239466db55a71", "0x539"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_custom_set_code() {
         let s = r#"{"method": "anvil_setCode", "params": ["0xd84de507f3fada7df80908082d3239466db55a71", "0x0123456789abcdef"]}"#;
@@ -648,6 +928,100 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_set_funded_accounts() {
+        let s = r#"{"method": "anvil_setFundedAccounts", "params": [[{"address": "0x295a70b2de5e3953354a6a8344e616ed314d7251", "balance": "0x3635c9adc5dea00000"}, {"address": "0x0000000000000000000000000000000000000001", "balance": "0x0"}]]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::SetFundedAccounts(accounts) => assert_eq!(accounts.len(), 2),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_otterscan() {
+        let s = r#"{"method": "ots_getApiLevel", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_getInternalOperations", "params": ["0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_searchTransactionsBefore", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", 0, 25]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_searchTransactionsAfter", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", 10, 25]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_getTransactionBySenderAndNonce", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", 1]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_getBlockDetails", "params": [1]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_getBlockTransactions", "params": ["0x1", 0, 25]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_hasCode", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", "latest"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_traceTransaction", "params": ["0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_getTransactionError", "params": ["0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "ots_getContractCreator", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_custom_set_storage_batch() {
+        let s = r#"{"method": "anvil_setStorageBatch", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", [["0x0", "0x01"], ["0x1", "0x02"]]]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "anvil_setStorageBatch", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", [["0x0"]]]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        assert!(serde_json::from_value::<EthRequest>(value).is_err());
+    }
+
+    #[test]
+    fn test_serde_custom_set_account() {
+        let s = r#"{"method": "anvil_setAccount", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", {"balance": "0x100", "nonce": "0x1", "code": "0x6000", "storage": {"0x0": "0x01"}}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::SetAccount(_, account) => {
+                assert_eq!(account.balance, Some(256u64.into()));
+                assert_eq!(account.nonce, Some(1u64.into()));
+                assert_eq!(account.storage.len(), 1);
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "anvil_setAccount", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", {"balance": "0x100"}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        match serde_json::from_value::<EthRequest>(value).unwrap() {
+            EthRequest::SetAccount(_, account) => {
+                assert!(account.code.is_none());
+                assert!(account.storage.is_empty());
+            }
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn test_serde_custom_set_storage_at() {
         let s = r#"{"method": "anvil_setStorageAt", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", "0x0", "0x00"]}"#;
@@ -676,6 +1050,70 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_set_chain_id() {
+        let s = r#"{"method": "anvil_setChainId", "params": ["0x539"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::SetChainId(1337u64.into()));
+
+        let s = r#"{"method": "anvil_setChainId", "params": [1337]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::SetChainId(1337u64.into()));
+    }
+
+    #[test]
+    fn test_serde_custom_get_cheat_log() {
+        let s = r#"{"method": "anvil_getCheatLog", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::GetCheatLog(()));
+    }
+
+    #[test]
+    fn test_serde_custom_remove_min_gas_price() {
+        let s = r#"{"method": "anvil_removeMinGasPrice", "params": []}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::RemoveMinGasPrice(()));
+    }
+
+    #[test]
+    fn test_serde_custom_min_suggested_priority_fee() {
+        let s = r#"{"method": "anvil_setMinSuggestedPriorityFee", "params": ["0x3b9aca00"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::SetMinSuggestedPriorityFee(fee) => {
+                assert_eq!(fee, U256::from(1_000_000_000u64))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_custom_node_info() {
+        let s = r#"{"method": "anvil_nodeInfo", "params": [] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_custom_set_block_gas_limit() {
+        for method in ["anvil_setBlockGasLimit", "evm_setBlockGasLimit", "anvil_setGasLimit"] {
+            let s = format!(r#"{{"method": "{}", "params": ["0x1c9c380"]}}"#, method);
+            let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+            let req = serde_json::from_value::<EthRequest>(value).unwrap();
+            match req {
+                EthRequest::SetBlockGasLimit(gas_limit) => {
+                    assert_eq!(gas_limit, U256::from(30_000_000u64))
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
     #[test]
     fn test_serde_custom_next_block_base_fee() {
         let s = r#"{"method": "anvil_setNextBlockBaseFeePerGas", "params": ["0x0"]}"#;
@@ -683,6 +1121,208 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_set_block_number() {
+        let s = r#"{"method": "anvil_setBlockNumber", "params": ["0x64"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::SetBlockNumber(num) => assert_eq!(num, 100u64.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_debug_set_head() {
+        let s = r#"{"method": "debug_setHead", "params": ["0x1"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::DebugSetHead(num) => assert_eq!(num, 1u64.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_custom_rollback() {
+        let s = r#"{"method": "anvil_rollback", "params": ["0x2"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::Rollback(n) => assert_eq!(n, 2u64.into()),
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "anvil_rollback", "params": [3]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_eth_sign_typed_data_v4() {
+        let s = r#"{"method": "eth_signTypedData_v4", "params": ["0xd84de507f3fada7df80908082d3239466db55a71", {"types": {"EIP712Domain": [{"name": "name", "type": "string"}, {"name": "chainId", "type": "uint256"}], "Mail": [{"name": "contents", "type": "string"}]}, "primaryType": "Mail", "domain": {"name": "Ether Mail", "chainId": 1}, "message": {"contents": "Hello, Bob!"}}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::EthSignTypedDataV4(_, data) => assert_eq!(data.primary_type, "Mail"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_custom_next_block_coinbase() {
+        let s = r#"{"method": "anvil_setNextBlockCoinbase", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_custom_wait_for_transaction() {
+        let s = r#"{"method": "anvil_waitForTransaction", "params": ["0x4a3b0fce2cb9707b0baa68640cf2fe858c8bb4121b2a8cb904ff369d38a560ff", 5000]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::WaitForTransaction(hash, timeout) => {
+                assert_eq!(
+                    hash,
+                    "0x4a3b0fce2cb9707b0baa68640cf2fe858c8bb4121b2a8cb904ff369d38a560ff"
+                        .parse()
+                        .unwrap()
+                );
+                assert_eq!(timeout, 5000);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_custom_deploy_code() {
+        let s = r#"{"method": "anvil_deployCode", "params": ["0x6000"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::DeployCode(code, from, value) => {
+                assert_eq!(code.as_ref(), &[0x60, 0x00]);
+                assert!(from.is_none());
+                assert!(value.is_none());
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "anvil_deployCode", "params": ["0x6000", "0xd84de507f3fada7df80908082d3239466db55a71", "0x64"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::DeployCode(_, from, value) => {
+                assert_eq!(
+                    from,
+                    Some("0xd84de507f3fada7df80908082d3239466db55a71".parse().unwrap())
+                );
+                assert_eq!(value, Some(100u64.into()));
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_custom_set_next_transaction_fails() {
+        let s =
+            r#"{"method": "anvil_setNextTransactionFails", "params": ["insufficient allowance"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::SetNextTransactionFails(reason) => {
+                assert_eq!(reason, "insufficient allowance")
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_custom_set_next_block_gas_used() {
+        let s = r#"{"method": "anvil_setNextBlockGasUsed", "params": ["0x1c9c380"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::SetNextBlockGasUsed(gas) => assert_eq!(gas, 30_000_000u64.into()),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_serde_custom_set_erc721_owner() {
+        let s = r#"{"method": "anvil_setErc721Owner", "params": ["0xd84de507f3fada7df80908082d3239466db55a71", "0x1", "0x295a70b2de5e3953354a6a8344e616ed314d7251"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_custom_get_fork() {
+        let s = r#"{"method": "anvil_getFork", "params": [] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_custom_simulate_bundle() {
+        let s = r#"{"method": "anvil_simulateBundle", "params": [[{"from": "0xd84de507f3fada7df80908082d3239466db55a71", "to": "0x295a70b2de5e3953354a6a8344e616ed314d7251", "value": "0x1"}, {"data": "0x"}], "latest"] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::SimulateBundle(txs, block) => {
+                assert_eq!(txs.len(), 2);
+                assert_eq!(block, Some(BlockId::Number(BlockNumber::Latest)));
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "anvil_simulateBundle", "params": [[]] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_custom_get_fork_stats() {
+        let s = r#"{"method": "anvil_getForkStats", "params": [true] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::GetForkStats(reset) => {
+                assert_eq!(reset.and_then(|p| p.params), Some(true))
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "anvil_getForkStats", "params": [] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "anvil_getForkStats"}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_custom_dump_state_diff() {
+        let s = r#"{"method": "anvil_dumpStateDiff", "params": [] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_custom_get_state_root() {
+        let s = r#"{"method": "anvil_getStateRoot", "params": [] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_custom_get_mempool_order() {
+        let s = r#"{"method": "anvil_getMempoolOrder", "params": [] }"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_custom_snapshot() {
         let s = r#"{"method": "anvil_snapshot", "params": [] }"#;
@@ -738,6 +1378,14 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_custom_mine_with_timestamp() {
+        let s = r#"{"method": "anvil_mineWithTimestamp", "params": [100]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        assert_eq!(req, EthRequest::MineWithTimestamp(100));
+    }
+
     #[test]
     fn test_serde_custom_timestamp_interval() {
         let s = r#"{"method": "anvil_setBlockTimestampInterval", "params": [100]}"#;
@@ -796,6 +1444,21 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_eth_uncle_count_by_block_number() {
+        let s =
+            r#"{"jsonrpc":"2.0","method":"eth_getUncleCountByBlockNumber","params":["latest"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_eth_uncle_by_block_number_and_index() {
+        let s = r#"{"jsonrpc":"2.0","method":"eth_getUncleByBlockNumberAndIndex","params":["0x1", "0x0"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_eth_block_tx_count_by_block_hash() {
         let s = r#"{"jsonrpc":"2.0","method":"eth_getBlockTransactionCountByHash","params":["0x4a3b0fce2cb9707b0baa68640cf2fe858c8bb4121b2a8cb904ff369d38a560ff"]}"#;
@@ -843,6 +1506,22 @@ mod tests {
         let _req = serde_json::from_value::<EthPubSub>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_eth_rpc_call() {
+        let s = r#"{"method": "eth_subscribe", "params": ["newHeads"]}"#;
+        let call = serde_json::from_str::<EthRpcCall>(s).unwrap();
+        assert!(matches!(call, EthRpcCall::PubSub(_)));
+
+        let s = r#"{"method": "eth_blockNumber", "params": []}"#;
+        let call = serde_json::from_str::<EthRpcCall>(s).unwrap();
+        assert!(matches!(call, EthRpcCall::Request(_)));
+
+        // the errors are the same as for an `EthRequest`
+        let s = r#"{"method": "anvil_unknownMethod", "params": []}"#;
+        let err = serde_json::from_str::<EthRpcCall>(s).unwrap_err();
+        assert!(err.to_string().contains("unknown variant"));
+    }
+
     #[test]
     fn test_serde_debug_trace_transaction() {
         let s = r#"{"method": "debug_traceTransaction", "params": ["0x4a3b0fce2cb9707b0baa68640cf2fe858c8bb4121b2a8cb904ff369d38a560ff"]}"#;
@@ -858,6 +1537,58 @@ mod tests {
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
     }
 
+    #[test]
+    fn test_serde_debug_trace_block() {
+        let s = r#"{"method": "debug_traceBlockByNumber", "params": ["0x1"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let req = serde_json::from_value::<EthRequest>(value).unwrap();
+        match req {
+            EthRequest::DebugTraceBlockByNumber(number, _) => {
+                assert_eq!(number, BlockNumber::Number(1u64.into()))
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "debug_traceBlockByNumber", "params": ["latest", {"disableStorage": true}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "debug_traceBlockByHash", "params": ["0x4a3b0fce2cb9707b0baa68640cf2fe858c8bb4121b2a8cb904ff369d38a560ff", {}]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_eth_block_receipts() {
+        let s = r#"{"method": "eth_getBlockReceipts", "params": ["latest"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "eth_getBlockReceipts", "params": ["0x1"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "eth_getBlockReceipts", "params": ["0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
+    #[test]
+    fn test_serde_eth_transaction_by_block_and_index() {
+        let s = r#"{"method": "eth_getTransactionByBlockHashAndIndex", "params": ["0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3", "0x0"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s =
+            r#"{"method": "eth_getTransactionByBlockNumberAndIndex", "params": ["latest", "0x1"]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "eth_getTransactionByBlockNumberAndIndex", "params": ["0x5", 2]}"#;
+        let value: serde_json::Value = serde_json::from_str(s).unwrap();
+        let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+    }
+
     #[test]
     fn test_serde_eth_storage() {
         let s = r#"{"method": "eth_getStorageAt", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", "0x0", "latest"]}"#;
@@ -884,6 +1615,37 @@ mod tests {
 
         let s = r#"{"method": "eth_call", "params":  [{"data":"0xcfae3217","from":"0xd84de507f3fada7df80908082d3239466db55a71","to":"0xcbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"}, { "blockHash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3" }]}"#;
         let _req = serde_json::from_str::<EthRequest>(s).unwrap();
+
+        let s = r#"{"method": "eth_call", "params":  [{"data":"0xcfae3217","from":"0xd84de507f3fada7df80908082d3239466db55a71","to":"0xcbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"}, { "blockHash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3", "requireCanonical": true }]}"#;
+        let req = serde_json::from_str::<EthRequest>(s).unwrap();
+        match req {
+            EthRequest::EthCall(_, block, overrides) => {
+                assert_eq!(
+                    block,
+                    Some(BlockId::Hash(
+                        "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                            .parse()
+                            .unwrap()
+                    ))
+                );
+                assert!(overrides.is_none());
+            }
+            _ => unreachable!(),
+        }
+
+        let s = r#"{"method": "eth_call", "params":  [{"data":"0xcfae3217","from":"0xd84de507f3fada7df80908082d3239466db55a71","to":"0xcbe828fdc46e3b1c351ec90b1a5e7d9742c0398d"}, "latest", {"0xcbe828fdc46e3b1c351ec90b1a5e7d9742c0398d": {"balance": "0x1", "nonce": "0x2", "code": "0x6000", "stateDiff": {"0x0000000000000000000000000000000000000000000000000000000000000000": "0x0000000000000000000000000000000000000000000000000000000000000001"}}}]}"#;
+        let req = serde_json::from_str::<EthRequest>(s).unwrap();
+        match req {
+            EthRequest::EthCall(_, _, overrides) => {
+                let overrides = overrides.unwrap();
+                let account = overrides.values().next().unwrap();
+                assert_eq!(account.balance, Some(1u64.into()));
+                assert_eq!(account.nonce, Some(2u64.into()));
+                assert_eq!(account.state_diff.as_ref().unwrap().len(), 1);
+                assert!(account.state.is_none());
+            }
+            _ => unreachable!(),
+        }
     }
 
     #[test]
@@ -892,6 +1654,22 @@ mod tests {
         let value: serde_json::Value = serde_json::from_str(s).unwrap();
 
         let _req = serde_json::from_value::<EthRequest>(value).unwrap();
+
+        let s = r#"{"method": "eth_getBalance", "params": ["0x295a70b2de5e3953354a6a8344e616ed314d7251", { "blockHash": "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3", "requireCanonical": true }]}"#;
+        let req = serde_json::from_str::<EthRequest>(s).unwrap();
+        match req {
+            EthRequest::EthGetBalance(_, block) => {
+                assert_eq!(
+                    block,
+                    Some(BlockId::Hash(
+                        "0xd4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                            .parse()
+                            .unwrap()
+                    ))
+                );
+            }
+            _ => unreachable!(),
+        }
     }
 
     #[test]
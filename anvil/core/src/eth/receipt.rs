@@ -1,4 +1,4 @@
-use crate::eth::utils::enveloped;
+use crate::eth::{transaction::DEPOSIT_TX_TYPE, utils::enveloped};
 use ethers_core::{
     types::{Address, Bloom, Bytes, H256, U256},
     utils::{
@@ -83,6 +83,7 @@ impl Decodable for EIP658Receipt {
 // same underlying data structure
 pub type EIP2930Receipt = EIP658Receipt;
 pub type EIP1559Receipt = EIP658Receipt;
+pub type DepositReceipt = EIP658Receipt;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TypedReceipt {
@@ -92,6 +93,8 @@ pub enum TypedReceipt {
     EIP2930(EIP2930Receipt),
     /// EIP-1559 receipt
     EIP1559(EIP1559Receipt),
+    /// Optimism deposit receipt
+    Deposit(DepositReceipt),
 }
 
 // == impl TypedReceipt ==
@@ -100,18 +103,30 @@ impl TypedReceipt {
     /// Returns the gas used by the transactions
     pub fn gas_used(&self) -> U256 {
         match self {
-            TypedReceipt::Legacy(r) | TypedReceipt::EIP2930(r) | TypedReceipt::EIP1559(r) => {
-                r.gas_used
-            }
+            TypedReceipt::Legacy(r) |
+            TypedReceipt::EIP2930(r) |
+            TypedReceipt::EIP1559(r) |
+            TypedReceipt::Deposit(r) => r.gas_used,
+        }
+    }
+
+    /// Returns true if the transaction was successful
+    pub fn status(&self) -> bool {
+        match self {
+            TypedReceipt::Legacy(r) |
+            TypedReceipt::EIP2930(r) |
+            TypedReceipt::EIP1559(r) |
+            TypedReceipt::Deposit(r) => r.status_code == 1,
         }
     }
 
     /// Returns the gas used by the transactions
     pub fn logs_bloom(&self) -> &Bloom {
         match self {
-            TypedReceipt::Legacy(r) | TypedReceipt::EIP2930(r) | TypedReceipt::EIP1559(r) => {
-                &r.logs_bloom
-            }
+            TypedReceipt::Legacy(r) |
+            TypedReceipt::EIP2930(r) |
+            TypedReceipt::EIP1559(r) |
+            TypedReceipt::Deposit(r) => &r.logs_bloom,
         }
     }
 }
@@ -122,6 +137,7 @@ impl Encodable for TypedReceipt {
             TypedReceipt::Legacy(r) => r.rlp_append(s),
             TypedReceipt::EIP2930(r) => enveloped(1, r, s),
             TypedReceipt::EIP1559(r) => enveloped(2, r, s),
+            TypedReceipt::Deposit(r) => enveloped(DEPOSIT_TX_TYPE, r, s),
         }
     }
 }
@@ -146,6 +162,10 @@ impl Decodable for TypedReceipt {
             return rlp::decode(s).map(TypedReceipt::EIP1559)
         }
 
+        if first == DEPOSIT_TX_TYPE {
+            return rlp::decode(s).map(TypedReceipt::Deposit)
+        }
+
         Err(DecoderError::Custom("unknown receipt type"))
     }
 }
@@ -156,6 +176,7 @@ impl From<TypedReceipt> for EIP658Receipt {
             TypedReceipt::Legacy(receipt) => receipt,
             TypedReceipt::EIP2930(receipt) => receipt,
             TypedReceipt::EIP1559(receipt) => receipt,
+            TypedReceipt::Deposit(receipt) => receipt,
         }
     }
 }
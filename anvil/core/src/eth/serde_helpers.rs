@@ -1,6 +1,6 @@
 //! custom serde helper functions
 
-use ethers_core::types::{BlockNumber, U256};
+use ethers_core::types::{BlockId, BlockNumber, H256, U256};
 use serde::{Deserialize, Deserializer};
 
 #[derive(Deserialize)]
@@ -105,6 +105,48 @@ where
     LenientBlockNumber::deserialize(deserializer).map(Into::into)
 }
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LenientBlockId {
+    #[serde(rename_all = "camelCase")]
+    Hash {
+        block_hash: H256,
+        // only hashes of canonical blocks are resolved, so this is always enforced
+        #[serde(default)]
+        #[allow(unused)]
+        require_canonical: bool,
+    },
+    #[serde(rename_all = "camelCase")]
+    Number {
+        #[serde(deserialize_with = "lenient_block_number")]
+        block_number: BlockNumber,
+    },
+    BlockNumber(LenientBlockNumber),
+    BlockHash(H256),
+}
+
+impl From<LenientBlockId> for BlockId {
+    fn from(b: LenientBlockId) -> Self {
+        match b {
+            LenientBlockId::Hash { block_hash, .. } | LenientBlockId::BlockHash(block_hash) => {
+                BlockId::Hash(block_hash)
+            }
+            LenientBlockId::Number { block_number } => BlockId::Number(block_number),
+            LenientBlockId::BlockNumber(b) => BlockId::Number(b.into()),
+        }
+    }
+}
+
+/// Deserializes an optional block parameter that is either a block number, a tag, a block hash or
+/// an [EIP-1898](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-1898.md) object:
+/// `{ "blockNumber": "0x1" }` or `{ "blockHash": "0x..", "requireCanonical": true }`
+pub fn lenient_block_id_opt<'de, D>(deserializer: D) -> Result<Option<BlockId>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<LenientBlockId>::deserialize(deserializer)?.map(Into::into))
+}
+
 /// Same as `lenient_block_number` but requires to be `[num; 1]`
 pub fn lenient_block_number_seq<'de, D>(deserializer: D) -> Result<BlockNumber, D::Error>
 where
@@ -2,13 +2,14 @@
 
 use crate::eth::transaction::{
     EIP1559TransactionRequest, EIP2930TransactionRequest, LegacyTransactionRequest,
-    TypedTransaction, TypedTransactionRequest,
+    TypedTransaction, TypedTransactionRequest, DEPOSIT_TX_TYPE,
 };
 use ethers_core::types::{
     transaction::eip2718::TypedTransaction as EthersTypedTransactionRequest, Address,
     Eip1559TransactionRequest as EthersEip1559TransactionRequest,
-    Eip2930TransactionRequest as EthersEip2930TransactionRequest, Transaction as EthersTransaction,
-    TransactionRequest as EthersLegacyTransactionRequest, U256, U64,
+    Eip2930TransactionRequest as EthersEip2930TransactionRequest, OtherFields,
+    Transaction as EthersTransaction, TransactionRequest as EthersLegacyTransactionRequest, U256,
+    U64,
 };
 
 impl From<TypedTransactionRequest> for EthersTypedTransactionRequest {
@@ -103,8 +104,8 @@ impl From<TypedTransaction> for EthersTransaction {
                 to: None,
                 value: t.value,
                 gas_price: Some(t.gas_price),
-                max_fee_per_gas: Some(t.gas_price),
-                max_priority_fee_per_gas: Some(t.gas_price),
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
                 gas: t.gas_limit,
                 input: t.input.clone(),
                 chain_id: t.chain_id().map(Into::into),
@@ -125,8 +126,8 @@ impl From<TypedTransaction> for EthersTransaction {
                 to: None,
                 value: t.value,
                 gas_price: Some(t.gas_price),
-                max_fee_per_gas: Some(t.gas_price),
-                max_priority_fee_per_gas: Some(t.gas_price),
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
                 gas: t.gas_limit,
                 input: t.input.clone(),
                 chain_id: Some(t.chain_id.into()),
@@ -135,7 +136,7 @@ impl From<TypedTransaction> for EthersTransaction {
                 s: U256::from(t.s.as_bytes()),
                 access_list: Some(t.access_list),
                 transaction_type: Some(1u64.into()),
-                other: Default::default(),
+                other: y_parity_fields(t.odd_y_parity),
             },
             TypedTransaction::EIP1559(t) => EthersTransaction {
                 hash,
@@ -157,8 +158,42 @@ impl From<TypedTransaction> for EthersTransaction {
                 s: U256::from(t.s.as_bytes()),
                 access_list: Some(t.access_list),
                 transaction_type: Some(2u64.into()),
-                other: Default::default(),
+                other: y_parity_fields(t.odd_y_parity),
+            },
+            TypedTransaction::Deposit(t) => EthersTransaction {
+                hash,
+                nonce: t.nonce,
+                block_hash: None,
+                block_number: None,
+                transaction_index: None,
+                from: t.from,
+                to: None,
+                value: t.value,
+                gas_price: Some(U256::zero()),
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
+                gas: t.gas_limit,
+                input: t.input.clone(),
+                chain_id: None,
+                v: U64::zero(),
+                r: U256::zero(),
+                s: U256::zero(),
+                access_list: None,
+                transaction_type: Some(DEPOSIT_TX_TYPE.into()),
+                // the deposit specific fields, as returned by Optimism nodes
+                other: serde_json::from_value(serde_json::json!({
+                    "sourceHash": t.source_hash,
+                    "mint": t.mint,
+                    "isSystemTx": t.is_system_tx,
+                }))
+                .unwrap_or_default(),
             },
         }
     }
 }
+
+/// Returns the `yParity` field of typed transactions, which is the same as their `v` value
+fn y_parity_fields(odd_y_parity: bool) -> OtherFields {
+    serde_json::from_value(serde_json::json!({ "yParity": U64::from(odd_y_parity as u8) }))
+        .unwrap_or_default()
+}
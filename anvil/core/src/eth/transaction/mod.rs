@@ -358,6 +358,8 @@ pub enum TypedTransaction {
     EIP2930(EIP2930Transaction),
     /// EIP-1559 transaction
     EIP1559(EIP1559Transaction),
+    /// Optimism deposit transaction
+    Deposit(DepositTransaction),
 }
 
 // == impl TypedTransaction ==
@@ -368,6 +370,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.gas_price,
             TypedTransaction::EIP2930(tx) => tx.gas_price,
             TypedTransaction::EIP1559(tx) => tx.max_fee_per_gas,
+            TypedTransaction::Deposit(_) => U256::zero(),
         }
     }
 
@@ -376,6 +379,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.gas_limit,
             TypedTransaction::EIP2930(tx) => tx.gas_limit,
             TypedTransaction::EIP1559(tx) => tx.gas_limit,
+            TypedTransaction::Deposit(tx) => tx.gas_limit,
         }
     }
 
@@ -384,6 +388,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.value,
             TypedTransaction::EIP2930(tx) => tx.value,
             TypedTransaction::EIP1559(tx) => tx.value,
+            TypedTransaction::Deposit(tx) => tx.value,
         }
     }
 
@@ -392,6 +397,38 @@ impl TypedTransaction {
             TypedTransaction::Legacy(tx) => &tx.input,
             TypedTransaction::EIP2930(tx) => &tx.input,
             TypedTransaction::EIP1559(tx) => &tx.input,
+            TypedTransaction::Deposit(tx) => &tx.input,
+        }
+    }
+
+    /// Returns the fee per gas the miner receives, given the `base_fee` of the block
+    ///
+    /// For EIP-1559 transactions this is `min(maxPriorityFeePerGas, maxFeePerGas - baseFee)`, for
+    /// all other transactions `gasPrice - baseFee`. Deposit transactions don't pay a tip.
+    pub fn effective_tip(&self, base_fee: U256) -> U256 {
+        match self {
+            TypedTransaction::Legacy(tx) => tx.gas_price.saturating_sub(base_fee),
+            TypedTransaction::EIP2930(tx) => tx.gas_price.saturating_sub(base_fee),
+            TypedTransaction::EIP1559(tx) => {
+                tx.max_priority_fee_per_gas.min(tx.max_fee_per_gas.saturating_sub(base_fee))
+            }
+            TypedTransaction::Deposit(_) => U256::zero(),
+        }
+    }
+
+    /// Returns the fee per gas the sender pays, given the `base_fee` of the block
+    ///
+    /// For EIP-1559 transactions this is `baseFee + min(maxPriorityFeePerGas, maxFeePerGas -
+    /// baseFee)`, for all other transactions the `gasPrice`.
+    pub fn effective_gas_price(&self, base_fee: Option<U256>) -> U256 {
+        match self {
+            TypedTransaction::Legacy(tx) => tx.gas_price,
+            TypedTransaction::EIP2930(tx) => tx.gas_price,
+            TypedTransaction::EIP1559(_) => {
+                let base_fee = base_fee.unwrap_or_default();
+                base_fee.saturating_add(self.effective_tip(base_fee))
+            }
+            TypedTransaction::Deposit(_) => U256::zero(),
         }
     }
 
@@ -400,6 +437,34 @@ impl TypedTransaction {
         self.gas_limit().saturating_mul(self.gas_price())
     }
 
+    /// Returns the gas the transaction uses before any code is executed
+    ///
+    /// This is the base cost of `21000`, plus `32000` for contract creations, the calldata cost
+    /// (`4` per zero byte and `16` per non-zero byte) and the cost of the access list (`2400` per
+    /// address and `1900` per storage key). Deposit transactions bought their gas on L1 and have
+    /// no intrinsic gas.
+    pub fn intrinsic_gas(&self) -> U256 {
+        let access_list = match self {
+            TypedTransaction::Legacy(_) => None,
+            TypedTransaction::EIP2930(tx) => Some(&tx.access_list),
+            TypedTransaction::EIP1559(tx) => Some(&tx.access_list),
+            TypedTransaction::Deposit(_) => return U256::zero(),
+        };
+        let mut gas = 21_000u64;
+        if *self.kind() == TransactionKind::Create {
+            gas += 32_000;
+        }
+        gas += self.data().iter().map(|byte| if *byte == 0 { 4 } else { 16 }).sum::<u64>();
+        if let Some(access_list) = access_list {
+            gas += access_list
+                .0
+                .iter()
+                .map(|item| 2_400 + 1_900 * item.storage_keys.len() as u64)
+                .sum::<u64>();
+        }
+        gas.into()
+    }
+
     /// Returns a helper type that contains commonly used values as fields
     pub fn essentials(&self) -> TransactionEssentials {
         match self {
@@ -439,6 +504,18 @@ impl TypedTransaction {
                 chain_id: Some(t.chain_id),
                 access_list: t.access_list.clone(),
             },
+            TypedTransaction::Deposit(t) => TransactionEssentials {
+                kind: t.kind,
+                input: t.input.clone(),
+                nonce: t.nonce,
+                gas_limit: t.gas_limit,
+                gas_price: Some(U256::zero()),
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
+                value: t.value,
+                chain_id: None,
+                access_list: Default::default(),
+            },
         }
     }
 
@@ -447,6 +524,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(t) => t.nonce(),
             TypedTransaction::EIP2930(t) => t.nonce(),
             TypedTransaction::EIP1559(t) => t.nonce(),
+            TypedTransaction::Deposit(t) => t.nonce(),
         }
     }
 
@@ -455,6 +533,7 @@ impl TypedTransaction {
             TypedTransaction::Legacy(t) => t.chain_id(),
             TypedTransaction::EIP2930(t) => Some(t.chain_id),
             TypedTransaction::EIP1559(t) => Some(t.chain_id),
+            TypedTransaction::Deposit(_) => None,
         }
     }
 
@@ -463,24 +542,34 @@ impl TypedTransaction {
             TypedTransaction::Legacy(t) => t.hash(),
             TypedTransaction::EIP2930(t) => t.hash(),
             TypedTransaction::EIP1559(t) => t.hash(),
+            TypedTransaction::Deposit(t) => t.hash(),
         }
     }
 
     /// Recovers the Ethereum address which was used to sign the transaction.
+    ///
+    /// Deposit transactions aren't signed, this returns their `from` address.
     pub fn recover(&self) -> Result<Address, SignatureError> {
         match self {
             TypedTransaction::Legacy(tx) => tx.recover(),
             TypedTransaction::EIP2930(tx) => tx.recover(),
             TypedTransaction::EIP1559(tx) => tx.recover(),
+            TypedTransaction::Deposit(tx) => Ok(tx.from),
         }
     }
 
+    /// Returns true if this is an Optimism deposit transaction
+    pub fn is_deposit(&self) -> bool {
+        matches!(self, TypedTransaction::Deposit(_))
+    }
+
     /// Returns what kind of transaction this is
     pub fn kind(&self) -> &TransactionKind {
         match self {
             TypedTransaction::Legacy(tx) => &tx.kind,
             TypedTransaction::EIP2930(tx) => &tx.kind,
             TypedTransaction::EIP1559(tx) => &tx.kind,
+            TypedTransaction::Deposit(tx) => &tx.kind,
         }
     }
 
@@ -490,6 +579,8 @@ impl TypedTransaction {
     }
 
     /// Returns the Signature of the transaction
+    ///
+    /// Deposit transactions aren't signed and return an empty signature.
     pub fn signature(&self) -> Signature {
         match self {
             TypedTransaction::Legacy(tx) => tx.signature,
@@ -505,6 +596,9 @@ impl TypedTransaction {
                 let s = U256::from_big_endian(&tx.s[..]);
                 Signature { r, s, v: v.into() }
             }
+            TypedTransaction::Deposit(_) => {
+                Signature { r: U256::zero(), s: U256::zero(), v: 0u64.into() }
+            }
         }
     }
 }
@@ -515,6 +609,7 @@ impl Encodable for TypedTransaction {
             TypedTransaction::Legacy(tx) => tx.rlp_append(s),
             TypedTransaction::EIP2930(tx) => enveloped(1, tx, s),
             TypedTransaction::EIP1559(tx) => enveloped(2, tx, s),
+            TypedTransaction::Deposit(tx) => enveloped(DEPOSIT_TX_TYPE, tx, s),
         }
     }
 }
@@ -533,6 +628,9 @@ impl Decodable for TypedTransaction {
         if first == 0x02 {
             return rlp::decode(s).map(TypedTransaction::EIP1559)
         }
+        if first == DEPOSIT_TX_TYPE {
+            return rlp::decode(s).map(TypedTransaction::Deposit)
+        }
         Err(DecoderError::Custom("invalid tx type"))
     }
 }
@@ -783,6 +881,80 @@ impl Decodable for EIP1559Transaction {
     }
 }
 
+/// The EIP-2718 type of Optimism deposit transactions
+pub const DEPOSIT_TX_TYPE: u8 = 0x7E;
+
+/// An Optimism deposit transaction, which are derived from L1 on an L2
+///
+/// Deposits aren't signed, the sender is part of the transaction. Before it's executed, `mint`
+/// is added to the balance of the sender, and the gas of a deposit isn't paid on L2.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositTransaction {
+    /// The nonce of the sender when the deposit is executed
+    ///
+    /// This isn't part of the encoding, the node assigns the sender's next nonce.
+    pub nonce: U256,
+    /// Uniquely identifies the source of the deposit
+    pub source_hash: H256,
+    pub from: Address,
+    pub kind: TransactionKind,
+    /// The wei minted to the sender on L2
+    pub mint: U256,
+    pub value: U256,
+    pub gas_limit: U256,
+    /// Whether this is a system transaction
+    pub is_system_tx: bool,
+    pub input: Bytes,
+}
+
+impl DepositTransaction {
+    pub fn nonce(&self) -> &U256 {
+        &self.nonce
+    }
+
+    pub fn hash(&self) -> H256 {
+        let encoded = rlp::encode(self);
+        let mut out = vec![0; 1 + encoded.len()];
+        out[0] = DEPOSIT_TX_TYPE;
+        out[1..].copy_from_slice(&encoded);
+        H256::from_slice(keccak256(&out).as_slice())
+    }
+}
+
+impl Encodable for DepositTransaction {
+    fn rlp_append(&self, s: &mut RlpStream) {
+        s.begin_list(8);
+        s.append(&self.source_hash);
+        s.append(&self.from);
+        s.append(&self.kind);
+        s.append(&self.mint);
+        s.append(&self.value);
+        s.append(&self.gas_limit);
+        s.append(&self.is_system_tx);
+        s.append(&self.input.as_ref());
+    }
+}
+
+impl Decodable for DepositTransaction {
+    fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+        if rlp.item_count()? != 8 {
+            return Err(DecoderError::RlpIncorrectListLen)
+        }
+
+        Ok(Self {
+            nonce: U256::zero(),
+            source_hash: rlp.val_at(0)?,
+            from: rlp.val_at(1)?,
+            kind: rlp.val_at(2)?,
+            mint: rlp.val_at(3)?,
+            value: rlp.val_at(4)?,
+            gas_limit: rlp.val_at(5)?,
+            is_system_tx: rlp.val_at(6)?,
+            input: rlp.val_at::<Vec<u8>>(7)?.into(),
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TransactionEssentials {
     pub kind: TransactionKind,
@@ -913,6 +1085,22 @@ impl PendingTransaction {
                     access_list: to_access_list(access_list.0.clone()),
                 }
             }
+            TypedTransaction::Deposit(tx) => {
+                let DepositTransaction { gas_limit, kind, value, input, .. } = tx;
+                // deposits don't pay for gas and are executed regardless of the sender's nonce
+                TxEnv {
+                    caller,
+                    transact_to: transact_to(kind),
+                    data: input.0.clone(),
+                    chain_id: None,
+                    nonce: None,
+                    value: *value,
+                    gas_price: U256::zero(),
+                    gas_priority_fee: None,
+                    gas_limit: gas_limit.as_u64(),
+                    access_list: vec![],
+                }
+            }
         }
     }
 }
@@ -928,6 +1116,8 @@ pub struct TransactionInfo {
     pub logs: Vec<Log>,
     pub logs_bloom: Bloom,
     pub traces: Vec<CallTraceNode>,
+    /// The output of the transaction, this is the revert data if the transaction reverted
+    pub out: Option<Bytes>,
 }
 
 // === impl TransactionInfo ===
@@ -953,6 +1143,29 @@ mod tests {
     use super::*;
     use ethers_core::utils::hex;
 
+    #[test]
+    fn can_compute_intrinsic_gas() {
+        let mut tx = LegacyTransaction {
+            nonce: U256::zero(),
+            gas_price: U256::one(),
+            gas_limit: U256::from(100_000u64),
+            kind: TransactionKind::Call(Address::random()),
+            value: U256::zero(),
+            input: Bytes::from(vec![0, 1, 0, 2]),
+            signature: Signature { r: U256::zero(), s: U256::zero(), v: 27 },
+        };
+        assert_eq!(
+            TypedTransaction::Legacy(tx.clone()).intrinsic_gas(),
+            U256::from(21_000u64 + 2 * 4 + 2 * 16)
+        );
+
+        tx.kind = TransactionKind::Create;
+        assert_eq!(
+            TypedTransaction::Legacy(tx).intrinsic_gas(),
+            U256::from(53_000u64 + 2 * 4 + 2 * 16)
+        );
+    }
+
     #[test]
     fn can_recover_sender() {
         let bytes = hex::decode("f85f800182520894095e7baea6a6c7c4c2dfeb977efac326af552d870a801ba048b55bfa915ac795c431978d8a6a992b628d557da5ff759b307d495a36649353a0efffd310ac743f371de3b9f7f9cb56c0b28ad43601b4ab949f53faa07bd2c804").unwrap();
@@ -977,4 +1190,30 @@ mod tests {
             "0f65fe9276bc9a24ae7083ae28e2660ef72df99e".parse().unwrap()
         );
     }
+
+    #[test]
+    fn can_encode_and_decode_deposit() {
+        let deposit = DepositTransaction {
+            nonce: U256::zero(),
+            source_hash: H256::repeat_byte(1),
+            from: Address::repeat_byte(2),
+            kind: TransactionKind::Call(Address::repeat_byte(3)),
+            mint: U256::from(1_000u64),
+            value: U256::from(100u64),
+            gas_limit: U256::from(21_000u64),
+            is_system_tx: false,
+            input: Bytes::from(vec![0xab]),
+        };
+        let tx = TypedTransaction::Deposit(deposit.clone());
+
+        let encoded = rlp::encode(&tx);
+        let decoded: TypedTransaction = rlp::decode(&encoded).unwrap();
+        assert_eq!(decoded, tx);
+        assert_eq!(decoded.hash(), deposit.hash());
+        assert_eq!(decoded.recover().unwrap(), deposit.from);
+
+        // the typed envelope starts with the deposit type
+        let envelope: Vec<u8> = rlp::decode(&encoded).unwrap();
+        assert_eq!(envelope[0], DEPOSIT_TX_TYPE);
+    }
 }
@@ -1,9 +1,10 @@
-use ethers_core::types::{H256, U256};
+use crate::eth::receipt::Log;
+use ethers_core::types::{Address, Block, Bytes, Transaction, TransactionReceipt, H256, U256, U64};
 use serde::{
     de::{Error, Visitor},
     Deserialize, Deserializer, Serialize, Serializer,
 };
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 
 /// Bindings for additional `debug_traceTransaction` options
 ///
@@ -25,6 +26,72 @@ pub struct GethDebugTracingOptions {
     pub timeout: Option<String>,
 }
 
+/// The result of tracing a transaction with geth's default struct logger
+///
+/// See <https://geth.ethereum.org/docs/rpc/ns-debug#debug_tracetransaction>
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethTrace {
+    /// The gas used by the transaction
+    pub gas: u64,
+    pub failed: bool,
+    /// The hex encoded output, without `0x` prefix
+    pub return_value: String,
+    pub struct_logs: Vec<GethStructLog>,
+}
+
+/// A single executed opcode of a [GethTrace]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GethStructLog {
+    pub pc: u64,
+    pub op: String,
+    /// The gas remaining before the opcode is executed
+    pub gas: u64,
+    pub gas_cost: u64,
+    /// The call depth, starting at 1
+    pub depth: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+    /// The memory as hex encoded 32 byte words, without `0x` prefix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Vec<String>>,
+    /// The storage slots accessed so far by the executed code, only set for `SLOAD` and `SSTORE`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage: Option<BTreeMap<String, String>>,
+}
+
+/// The trace of a single transaction of a traced block, see `debug_traceBlockByNumber`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct GethTraceResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<GethTrace>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A transaction receipt with the L1 fee fields of Optimism, see `--optimism`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OptimismTransactionReceipt {
+    #[serde(flatten)]
+    pub receipt: TransactionReceipt,
+    /// Not set for deposit transactions, which don't pay an L1 fee
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub l1_fee: Option<L1FeeInfo>,
+}
+
+/// The fee an Optimism transaction pays for its data posted to L1
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1FeeInfo {
+    pub l1_fee: U256,
+    pub l1_gas_used: U256,
+    /// The base fee of L1
+    pub l1_gas_price: U256,
+    /// The decimal fee scalar, e.g. `"0.684"`
+    pub l1_fee_scalar: String,
+}
+
 /// Represents the params to set forking which can take various forms
 ///  - untagged
 ///  - tagged `forking`
@@ -69,6 +136,295 @@ impl<'de> Deserialize<'de> for Forking {
     }
 }
 
+/// The origin of the currently active fork, returned by `anvil_getFork`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkInfo {
+    pub json_rpc_url: String,
+    pub block_number: u64,
+    pub chain_id: u64,
+}
+
+/// Information about the node and its current state, returned by `anvil_nodeInfo`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeInfo {
+    pub current_block_number: U64,
+    pub current_block_timestamp: u64,
+    pub current_block_hash: H256,
+    pub hard_fork: String,
+    pub transaction_order: String,
+    pub environment: NodeEnvironment,
+    /// The origin of the fork, `None` if the node is not forked
+    pub fork: Option<ForkInfo>,
+}
+
+/// The chain and fee settings of the node, see [NodeInfo]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeEnvironment {
+    pub chain_id: u64,
+    pub gas_limit: U256,
+    pub base_fee: U256,
+    pub gas_price: U256,
+    /// The priority fee returned by `eth_maxPriorityFeePerGas`
+    pub suggested_priority_fee: U256,
+}
+
+/// The outcome of a transaction of a bundle simulated with `anvil_simulateBundle`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedTransaction {
+    pub gas_used: U256,
+    pub reverted: bool,
+    /// The output of the transaction, or the revert data if it reverted
+    pub return_data: Bytes,
+    pub logs: Vec<Log>,
+}
+
+/// Counters of the data that was requested from the fork, returned by `anvil_getForkStats`
+///
+/// A cache miss is counted for every request of an account or storage slot that wasn't fetched
+/// yet, concurrent misses of the same value only fetch it once.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForkStats {
+    pub accounts_fetched: u64,
+    pub account_cache_hits: u64,
+    pub account_cache_misses: u64,
+    pub storage_slots_fetched: u64,
+    pub storage_cache_hits: u64,
+    pub storage_cache_misses: u64,
+    /// All requests sent to the fork endpoint, including blocks and transactions
+    pub provider_requests: u64,
+}
+
+/// Overrides of an account's state that only apply to a single `eth_call`
+///
+/// See <https://geth.ethereum.org/docs/rpc/ns-eth#3-object---state-override-set>
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AccountOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    /// Replaces the entire storage of the account, all other slots are empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<BTreeMap<H256, H256>>,
+    /// Replaces only the given storage slots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+/// The state overrides of an `eth_call`, by account
+pub type StateOverride = BTreeMap<Address, AccountOverride>;
+
+/// The changes of an account since the fork, see `anvil_dumpStateDiff`
+///
+/// Only the fields that differ from the forked state are set.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountStateDiff {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<U256, U256>,
+}
+
+impl AccountStateDiff {
+    /// Returns `true` if nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.balance.is_none() &&
+            self.nonce.is_none() &&
+            self.code.is_none() &&
+            self.storage.is_empty()
+    }
+}
+
+/// The fields of an account to set with `anvil_setAccount`
+///
+/// Omitted fields are left unchanged, only the given storage slots are written.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct AccountUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<U256, U256>,
+}
+
+/// An account and the balance it is funded with, see `anvil_setFundedAccounts`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundedAccount {
+    pub address: Address,
+    pub balance: U256,
+}
+
+/// A change of an account that was made by a cheat, see `anvil_getCheatLog`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheatLogEntry {
+    /// The cheat that made the change, like `anvil_setBalance`
+    pub method: String,
+    pub address: Address,
+    #[serde(flatten)]
+    pub change: CheatChange,
+    /// Unix timestamp in seconds of when the cheat was applied
+    pub timestamp: u64,
+}
+
+/// The changed field of an account with its old and new value
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "field", rename_all = "camelCase")]
+pub enum CheatChange {
+    #[serde(rename_all = "camelCase")]
+    Balance { old_value: U256, new_value: U256 },
+    #[serde(rename_all = "camelCase")]
+    Nonce { old_value: U256, new_value: U256 },
+    #[serde(rename_all = "camelCase")]
+    Code { old_value: Bytes, new_value: Bytes },
+    #[serde(rename_all = "camelCase")]
+    Storage { slot: U256, old_value: U256, new_value: U256 },
+}
+
+/// The kind of an internal operation, see `ots_getInternalOperations`
+///
+/// Serialized as the numeric code Otterscan expects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OtsInternalOperationType {
+    Transfer = 0,
+    SelfDestruct = 1,
+    Create = 2,
+    Create2 = 3,
+}
+
+impl Serialize for OtsInternalOperationType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+/// A value transfer, contract creation or self destruct that happened within a transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OtsInternalOperation {
+    #[serde(rename = "type")]
+    pub r#type: OtsInternalOperationType,
+    pub from: Address,
+    pub to: Address,
+    pub value: U256,
+}
+
+/// A transaction receipt with the timestamp of its block, as returned by the `ots_search*`
+/// methods
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OtsReceipt {
+    #[serde(flatten)]
+    pub receipt: TransactionReceipt,
+    pub timestamp: U64,
+}
+
+/// A page of transactions that involve an address, see `ots_searchTransactionsBefore`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsSearchTransactions {
+    pub txs: Vec<Transaction>,
+    pub receipts: Vec<OtsReceipt>,
+    /// Whether the page contains the most recent transactions
+    pub first_page: bool,
+    /// Whether the page contains the oldest transactions
+    pub last_page: bool,
+}
+
+/// A block with the number of its transactions, as returned by `ots_getBlockDetails` and
+/// `ots_getBlockTransactions`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsBlock<TX> {
+    #[serde(flatten)]
+    pub block: Block<TX>,
+    pub transaction_count: usize,
+}
+
+/// The newly issued ether of a block
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsIssuance {
+    pub block_reward: U256,
+    pub uncle_reward: U256,
+    pub issuance: U256,
+}
+
+/// A block without its transactions, see `ots_getBlockDetails`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsBlockDetails {
+    pub block: OtsBlock<H256>,
+    pub issuance: OtsIssuance,
+    /// The fees paid by all transactions of the block
+    pub total_fees: U256,
+}
+
+/// A page of the transactions of a block and their receipts, see `ots_getBlockTransactions`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct OtsBlockTransactions {
+    pub fullblock: OtsBlock<Transaction>,
+    pub receipts: Vec<TransactionReceipt>,
+}
+
+/// The kind of a call of `ots_traceTransaction`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum OtsTraceType {
+    #[serde(rename = "CALL")]
+    Call,
+    #[serde(rename = "STATICCALL")]
+    StaticCall,
+    #[serde(rename = "DELEGATECALL")]
+    DelegateCall,
+    #[serde(rename = "CALLCODE")]
+    CallCode,
+    #[serde(rename = "CREATE")]
+    Create,
+    #[serde(rename = "CREATE2")]
+    Create2,
+    #[serde(rename = "SELFDESTRUCT")]
+    SelfDestruct,
+}
+
+/// A call made by a transaction, see `ots_traceTransaction`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OtsTrace {
+    #[serde(rename = "type")]
+    pub r#type: OtsTraceType,
+    pub depth: usize,
+    pub from: Address,
+    pub to: Address,
+    /// The transferred value, `None` for static and delegate calls
+    pub value: Option<U256>,
+    pub input: Bytes,
+}
+
+/// The transaction that created a contract and the address that deployed it, see
+/// `ots_getContractCreator`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OtsContractCreator {
+    pub hash: H256,
+    pub creator: Address,
+}
+
 /// Additional `evm_mine` options
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
 #[serde(untagged)]
@@ -2,16 +2,19 @@ use crate::RpcHandler;
 
 use anvil_rpc::{
     error::RpcError,
-    request::{Request, RpcCall},
+    request::{Id, Request, RpcCall},
     response::{Response, RpcResponse},
 };
 use axum::{
     extract::{rejection::JsonRejection, Extension},
     Json,
 };
-use futures::{future, FutureExt};
+use futures::{future, stream, FutureExt, StreamExt};
 use tracing::{trace, warn};
 
+/// The maximum number of read-only calls of a batch that are executed at the same time
+pub const MAX_PARALLEL_BATCH_CALLS: usize = 16;
+
 /// Handles incoming JSON-RPC Request
 pub async fn handle<Handler: RpcHandler>(
     request: Result<Json<Request>, JsonRejection>,
@@ -45,14 +48,63 @@ pub async fn handle_request<Handler: RpcHandler>(
 
     match req {
         Request::Single(call) => handle_call(call, handler).await.map(Response::Single),
-        Request::Batch(calls) => {
+        Request::Batch(calls) if !handler.orders_batch_calls() => {
             future::join_all(calls.into_iter().map(move |call| handle_call(call, handler.clone())))
                 .map(responses_as_batch)
                 .await
         }
+        Request::Batch(calls) => {
+            let mut outs = Vec::with_capacity(calls.len());
+            let mut calls = calls.into_iter().peekable();
+            while let Some(call) = calls.next() {
+                if !is_read_only(&call, &handler) {
+                    outs.push(handle_call(call, handler.clone()).await);
+                    continue
+                }
+
+                // consecutive read-only calls can't affect each other
+                let mut read_only = vec![call];
+                while let Some(call) = calls.next_if(|call| is_read_only(call, &handler)) {
+                    read_only.push(call);
+                }
+                outs.extend(handle_calls_in_parallel(read_only, handler.clone()).await);
+            }
+            responses_as_batch(outs)
+        }
     }
 }
 
+/// Returns true if the call is a method call of a read-only method, see
+/// [RpcHandler::is_read_only()]
+fn is_read_only<Handler: RpcHandler>(call: &RpcCall, handler: &Handler) -> bool {
+    matches!(call, RpcCall::MethodCall(call) if handler.is_read_only(&call.method))
+}
+
+/// Executes the calls on separate tasks, at most [MAX_PARALLEL_BATCH_CALLS] at a time, and returns
+/// their responses in the order of the calls
+async fn handle_calls_in_parallel<Handler: RpcHandler>(
+    calls: Vec<RpcCall>,
+    handler: Handler,
+) -> Vec<Option<RpcResponse>> {
+    stream::iter(calls)
+        .map(|call| {
+            let id = match call {
+                RpcCall::MethodCall(ref call) => call.id(),
+                _ => Id::Null,
+            };
+            let task = tokio::spawn(handle_call(call, handler.clone()));
+            async move {
+                task.await.unwrap_or_else(|err| {
+                    warn!(target: "rpc", "call id={} failed: {}", id, err);
+                    Some(RpcResponse::new(id, RpcError::internal_error()))
+                })
+            }
+        })
+        .buffered(MAX_PARALLEL_BATCH_CALLS)
+        .collect()
+        .await
+}
+
 /// handle a single RPC method call
 async fn handle_call<Handler: RpcHandler>(call: RpcCall, handler: Handler) -> Option<RpcResponse> {
     match call {
@@ -102,6 +102,21 @@ pub trait RpcHandler: Clone + Send + Sync + 'static {
     /// Invoked when the request was received
     async fn on_request(&self, request: Self::Request) -> ResponseResult;
 
+    /// Returns true if the calls of a batch request are executed in the order of the batch.
+    ///
+    /// If so, consecutive read-only calls, see [Self::is_read_only], are executed in parallel and
+    /// all other calls one after another. Otherwise all calls of a batch are executed
+    /// concurrently.
+    fn orders_batch_calls(&self) -> bool {
+        false
+    }
+
+    /// Returns true if calls of the `method` never change any state, see
+    /// [Self::orders_batch_calls]
+    fn is_read_only(&self, _method: &str) -> bool {
+        false
+    }
+
     /// Invoked for every incoming `RpcMethodCall`
     ///
     /// This will attempt to deserialize a `{ "method" : "<name>", "params": "<params>" }` message
@@ -48,6 +48,18 @@ pub trait WsRpcHandler: Clone + Send + Sync + Unpin + 'static {
 
     /// Invoked when the request was received
     async fn on_request(&self, request: Self::Request, cx: WsContext<Self>) -> ResponseResult;
+
+    /// Returns true if the calls of a batch request are executed in the order of the batch, see
+    /// [RpcHandler::orders_batch_calls]
+    fn orders_batch_calls(&self) -> bool {
+        false
+    }
+
+    /// Returns true if calls of the `method` never change any state, see
+    /// [RpcHandler::is_read_only]
+    fn is_read_only(&self, _method: &str) -> bool {
+        false
+    }
 }
 
 type WsSubscriptions<SubscriptionId, Subscription> =
@@ -127,6 +139,14 @@ impl<Handler: WsRpcHandler> RpcHandler for ContextAwareHandler<Handler> {
     async fn on_request(&self, request: Self::Request) -> ResponseResult {
         self.handler.on_request(request, self.context.clone()).await
     }
+
+    fn orders_batch_calls(&self) -> bool {
+        self.handler.orders_batch_calls()
+    }
+
+    fn is_read_only(&self, method: &str) -> bool {
+        self.handler.is_read_only(method)
+    }
 }
 
 /// Represents a connection to a client via websocket
@@ -14,7 +14,10 @@ pub struct App {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app = App::parse();
     let _ = fdlimit::raise_fd_limit();
-    app.node.run().await?;
+    if let Err(err) = app.node.run().await {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
 
     Ok(())
 }
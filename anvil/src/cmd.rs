@@ -1,17 +1,28 @@
 use crate::{
-    config::{Hardfork, DEFAULT_MNEMONIC},
-    eth::pool::transactions::TransactionOrder,
+    config::{Hardfork, HardforkTransition, DEFAULT_MNEMONIC},
+    eth::{
+        backend::genesis::{GenesisAccount, SerializableState, StateCompression},
+        pool::{transactions::TransactionOrder, PoolFullBehavior},
+        EthApi,
+    },
+    logging::LogCategory,
     AccountGenerator, NodeConfig, CHAIN_ID,
 };
 use anvil_server::ServerConfig;
 use clap::Parser;
-use ethers::utils::WEI_IN_ETHER;
+use ethers::{
+    types::{Address, H256},
+    utils::{hex, WEI_IN_ETHER},
+};
+use foundry_evm::executor::fork::CacheAllowlist;
 use std::{
     net::IpAddr,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicUsize, Ordering},
         Arc,
     },
+    time::Duration,
 };
 use tracing::log::trace;
 
@@ -54,6 +65,15 @@ pub struct NodeArgs {
     )]
     pub mnemonic: Option<String>,
 
+    #[clap(
+        long,
+        help = "Hex encoded BIP32 seed used for generating accounts instead of a mnemonic phrase. The seed is used as is, so it must have enough entropy.",
+        conflicts_with = "mnemonic",
+        value_name = "HEX",
+        parse(try_from_str = parse_seed)
+    )]
+    pub mnemonic_seed_unsafe: Option<Vec<u8>>,
+
     #[clap(
         long,
         help = "Sets the derivation path of the child key to be derived. [default: m/44'/60'/0'/0/]",
@@ -61,6 +81,51 @@ pub struct NodeArgs {
     )]
     pub derivation_path: Option<String>,
 
+    #[clap(
+        long,
+        help = "JSON file with an array of account definitions, each with an `address` or a `privateKey` and an optional `balance`, `nonce`, `code` and `storage`. The accounts are added to the dev accounts, use `--accounts 0` to only use these.",
+        value_name = "PATH"
+    )]
+    pub accounts_file: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Fork off the endpoint and block a state file was dumped from with `--dump-state`, and apply the accounts and storage of the file on top. `--fork-url` and `--fork-block-number` take precedence over the fork of the file, which is required for files without a fork, like the output of `anvil_dumpStateDiff`.",
+        value_name = "PATH"
+    )]
+    pub fork_state: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Initialize the chain with the accounts, storage and blocks of a state file, as written by `--dump-state`. The file is only read, use `--dump-state` to save the state on exit.",
+        conflicts_with = "state",
+        value_name = "PATH"
+    )]
+    pub load_state: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Dump the accounts, storage and blocks of the chain to a state file on exit. In fork mode only the accounts that changed since the fork are written.",
+        conflicts_with = "state",
+        value_name = "PATH"
+    )]
+    pub dump_state: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "How the state file written by `--dump-state` is compressed: `none` or `gzip`. Compressed state files are detected when they're loaded.",
+        default_value = "none",
+        value_name = "COMPRESSION"
+    )]
+    pub state_compression: StateCompression,
+
+    #[clap(
+        long,
+        help = "Shorthand for `--load-state` and `--dump-state` with the same file. The state is only loaded if the file exists.",
+        value_name = "PATH"
+    )]
+    pub state: Option<PathBuf>,
+
     #[clap(flatten, next_help_heading = "SERVER OPTIONS")]
     pub server_config: ServerConfig,
 
@@ -69,11 +134,44 @@ pub struct NodeArgs {
 
     #[clap(
         long,
-        help = "The EVM hardfork to use.",
-        default_value = "latest",
+        help = "Also log the internal events of these subsystems, comma separated. Ignored with `--silent` or if `RUST_LOG` is set.",
+        possible_values = LogCategory::VARIANTS,
+        use_value_delimiter = true,
+        value_name = "CATEGORIES"
+    )]
+    pub log_categories: Vec<LogCategory>,
+
+    #[clap(
+        long,
+        help = "Print the decoded call traces of mined transactions. Ignored with `--silent`."
+    )]
+    pub print_traces: bool,
+
+    #[clap(
+        long,
+        help = "How much of the execution of mined transactions to print: 1 prints the top-level calls, 2 all calls like `--print-traces`, 3 also storage reads and writes, 4 every executed opcode. Levels 3 and 4 slow down mining. Ignored with `--silent`.",
+        default_value = "0",
+        possible_values = &["0", "1", "2", "3", "4"],
+        value_name = "LEVEL"
+    )]
+    pub trace_verbosity: u8,
+
+    #[clap(long, help = "Don't print the `console.log` output of executed transactions.")]
+    pub disable_console_log: bool,
+
+    #[clap(
+        long,
+        help = "The EVM hardfork to use. Defaults to the hardfork of the fork block when forking mainnet, Goerli or Sepolia, and to latest otherwise.",
         value_name = "HARDFORK"
     )]
-    pub hardfork: Hardfork,
+    pub hardfork: Option<Hardfork>,
+
+    #[clap(
+        long = "hardfork-transition",
+        help = "Activate a hardfork at a later block, e.g. `london:100`. Can be used multiple times.",
+        value_name = "HARDFORK:BLOCK"
+    )]
+    pub hardfork_transitions: Vec<HardforkTransition>,
 
     #[clap(
         short,
@@ -85,6 +183,15 @@ pub struct NodeArgs {
     )]
     pub block_time: Option<u64>,
 
+    #[clap(
+        long,
+        help = "Block time in milliseconds for interval mining, allows sub-second block times. Blocks mined within the same second share their timestamp.",
+        name = "block-time-ms",
+        value_name = "MILLIS",
+        conflicts_with = "block-time"
+    )]
+    pub block_time_ms: Option<u64>,
+
     #[clap(
         long,
         help = "Writes output of `anvil` as json to user-specified file",
@@ -96,10 +203,22 @@ pub struct NodeArgs {
         long,
         visible_alias = "no-mine",
         help = "Disable auto and interval mining, and mine on demand instead.",
-        conflicts_with = "block-time"
+        conflicts_with_all = &["block-time", "block-time-ms"]
     )]
     pub no_mining: bool,
 
+    #[clap(
+        long,
+        help = "Mine a block after every state change via cheats like `anvil_setBalance` or `anvil_setStorageAt`, so that `newHeads` subscribers are notified of it."
+    )]
+    pub mine_on_state_change: bool,
+
+    #[clap(
+        long,
+        help = "Record every change that cheats like `anvil_setBalance` or `anvil_setStorageAt` make to accounts, with the old and new value. The log is returned by `anvil_getCheatLog`."
+    )]
+    pub record_cheats: bool,
+
     #[clap(
         long,
         help = "The host the server will listen on",
@@ -116,31 +235,210 @@ pub struct NodeArgs {
         value_name = "ORDER"
     )]
     pub order: TransactionOrder,
+
+    #[clap(
+        long,
+        help = "Order the transactions of every block with an external command. The candidate transactions are written as JSON to its stdin, it replies with a JSON array of the transaction hashes to include, in order. Takes precedence over `--order`.",
+        value_name = "PATH"
+    )]
+    pub order_command: Option<PathBuf>,
+
+    #[clap(
+        long = "priority-sender",
+        help = "Always include transactions of this sender first. Can be used multiple times.",
+        value_name = "ADDRESS"
+    )]
+    pub priority_senders: Vec<Address>,
+
+    #[clap(
+        long = "priority-selector",
+        help = "Include transactions whose calldata starts with this 4 byte function selector first, after the transactions of priority senders. Can be used multiple times.",
+        value_name = "HEX",
+        parse(try_from_str = parse_selector)
+    )]
+    pub priority_selectors: Vec<[u8; 4]>,
+
+    #[clap(long, help = "The maximum number of transactions in the mempool.", value_name = "N")]
+    pub mempool_limit: Option<usize>,
+
+    #[clap(
+        long = "mempool-full",
+        help = "What happens when a transaction is submitted to a full mempool: `reject` it, or `evict` the transaction with the lowest priority.",
+        default_value = "reject",
+        value_name = "BEHAVIOR"
+    )]
+    pub mempool_full_behavior: PoolFullBehavior,
+
+    #[clap(
+        long,
+        help = "The minimum percentage by which a transaction's gas price must exceed the gas price of the pending transaction with the same nonce to replace it.",
+        default_value = "10",
+        value_name = "PERCENT"
+    )]
+    pub price_bump: u64,
+
+    #[clap(
+        long,
+        help = "Forward signing requests of the --external-signer-account accounts to this signer.",
+        requires = "external-signer-accounts",
+        value_name = "URL"
+    )]
+    pub external_signer: Option<String>,
+
+    #[clap(
+        long = "external-signer-account",
+        help = "An account that is signed for by the --external-signer. Can be used multiple times.",
+        requires = "external-signer",
+        value_name = "ADDRESS"
+    )]
+    pub external_signer_accounts: Vec<Address>,
 }
 
 impl NodeArgs {
-    pub fn into_node_config(self) -> NodeConfig {
+    /// Returns the config of the node
+    ///
+    /// Fails if one of the accounts or state files can't be read.
+    pub fn into_node_config(self) -> Result<NodeConfig, Box<dyn std::error::Error>> {
         let genesis_balance = WEI_IN_ETHER.saturating_mul(self.balance.into());
+        let mut custom_genesis_accounts = Vec::new();
+        let mut state_fork = None;
+        if let Some(path) = self.fork_state.as_ref() {
+            let state = SerializableState::read(path)
+                .map_err(|err| format!("{}: {}", path.display(), err))?;
+            if state.fork.is_none() && self.evm_opts.fork_url.is_none() {
+                return Err(format!(
+                    "{}: the state file was not dumped from a fork, set the fork with --fork-url",
+                    path.display()
+                )
+                .into())
+            }
+            custom_genesis_accounts.extend(state.genesis_accounts());
+            state_fork = state.fork;
+        }
+        let mut state_history = Default::default();
+        if let Some(path) = self.load_state_path() {
+            let state = SerializableState::read(path)
+                .map_err(|err| format!("{}: {}", path.display(), err))?;
+            custom_genesis_accounts.extend(state.genesis_accounts());
+            state_history = state.history;
+        }
+        if let Some(path) = self.accounts_file.as_ref() {
+            custom_genesis_accounts.extend(
+                GenesisAccount::read_file(path)
+                    .map_err(|err| format!("{}: {}", path.display(), err))?,
+            );
+        }
 
-        NodeConfig::default()
+        let fork_block_number = self
+            .evm_opts
+            .fork_block_number
+            .or_else(|| state_fork.as_ref().map(|fork| fork.block_number));
+        // the storage is read at the child of the fork block, which doesn't exist yet for the
+        // latest block
+        if !self.evm_opts.fork_preload_storage.is_empty() &&
+            fork_block_number.is_none() &&
+            self.evm_opts.fork_block_hash.is_none()
+        {
+            return Err(
+                "--fork-preload-storage requires --fork-block-number or --fork-block-hash".into()
+            )
+        }
+
+        Ok(NodeConfig::default()
             .with_gas_limit(self.evm_opts.gas_limit)
+            .with_disable_block_gas_limit(self.evm_opts.disable_block_gas_limit)
+            .with_gas_estimation_cap(self.evm_opts.gas_estimation_cap)
             .with_gas_price(self.evm_opts.gas_price)
+            .with_min_gas_price(self.evm_opts.min_gas_price)
+            .with_gas_price_oracle(self.evm_opts.gas_price_oracle)
             .with_hardfork(self.hardfork)
-            .with_blocktime(self.block_time.map(std::time::Duration::from_secs))
+            .with_hardfork_transitions(self.hardfork_transitions)
+            .with_blocktime(self.block_time())
             .with_no_mining(self.no_mining)
+            .with_mine_on_state_change(self.mine_on_state_change)
+            .with_record_cheats(self.record_cheats)
             .with_account_generator(self.account_generator())
             .with_genesis_balance(genesis_balance)
+            .with_custom_genesis_accounts(custom_genesis_accounts)
+            .with_state_history(state_history)
             .with_port(self.port)
-            .with_eth_rpc_url(self.evm_opts.fork_url)
+            .with_eth_rpc_url(
+                self.evm_opts
+                    .fork_url()
+                    .or_else(|| state_fork.as_ref().map(|fork| fork.json_rpc_url.clone())),
+            )
             .with_base_fee(self.evm_opts.block_base_fee_per_gas)
-            .with_fork_block_number(self.evm_opts.fork_block_number)
+            .with_dynamic_base_fee(!self.evm_opts.static_base_fee)
+            .with_eip1559(self.eip1559())
+            .with_fork_block_number(fork_block_number)
+            .with_fork_block_hash(self.evm_opts.fork_block_hash)
+            .with_fork_batch_size(self.evm_opts.fork_batch_size())
+            .with_fork_max_concurrency(self.evm_opts.fork_max_concurrency)
+            .with_fork_gas_price_passthrough(self.evm_opts.fork_gas_price_passthrough)
+            .with_fork_passthrough_receipts(!self.evm_opts.fork_no_receipt_passthrough)
+            .with_fork_follow_head(self.evm_opts.fork_follow_head)
+            .with_fork_snapshot_on_start(self.evm_opts.fork_snapshot_on_start)
+            .with_fork_preload_storage(self.evm_opts.fork_preload_storage)
+            .with_fork_retry_backoff(self.evm_opts.fork_retry_backoff.map(Duration::from_millis))
+            .with_fork_request_log(self.evm_opts.fork_request_log)
+            .with_fork_offline_on_failure(self.evm_opts.fork_offline_on_failure)
             .with_storage_caching(self.evm_opts.no_storage_caching)
+            .with_fork_cache_allowlist(self.evm_opts.fork_cache_slots)
+            .with_call_caching(self.evm_opts.cache_calls)
+            .with_fork_block_caching(!self.evm_opts.no_block_caching)
             .with_server_config(self.server_config)
             .with_host(self.host)
             .set_silent(self.silent)
+            .with_log_categories(self.log_categories)
+            .with_print_traces(self.print_traces)
+            .with_trace_verbosity(self.trace_verbosity)
+            .with_console_log(!self.disable_console_log)
             .set_config_out(self.config_out)
             .with_chain_id(self.evm_opts.chain_id.unwrap_or(CHAIN_ID))
-            .with_transaction_order(self.order)
+            .with_optimism(self.evm_opts.optimism)
+            .with_transaction_order(self.transaction_order())
+            .with_priority_senders(self.priority_senders)
+            .with_priority_selectors(self.priority_selectors)
+            .with_mempool_limit(self.mempool_limit)
+            .with_mempool_full_behavior(self.mempool_full_behavior)
+            .with_price_bump(self.price_bump)
+            .with_external_signer(self.external_signer, self.external_signer_accounts))
+    }
+
+    /// Returns the state file to load at startup, `--state` is only loaded if it exists
+    fn load_state_path(&self) -> Option<&PathBuf> {
+        self.load_state.as_ref().or_else(|| self.state.as_ref().filter(|path| path.exists()))
+    }
+
+    /// Returns the state file to dump the state to on exit
+    fn dump_state_path(&self) -> Option<PathBuf> {
+        self.dump_state.clone().or_else(|| self.state.clone())
+    }
+
+    /// Returns the configured block time, either `--block-time` or `--block-time-ms`
+    fn block_time(&self) -> Option<Duration> {
+        self.block_time
+            .map(Duration::from_secs)
+            .or_else(|| self.block_time_ms.map(Duration::from_millis))
+    }
+
+    /// Returns whether EIP-1559 was forced on or off
+    fn eip1559(&self) -> Option<bool> {
+        if self.evm_opts.enable_eip1559 {
+            Some(true)
+        } else if self.evm_opts.disable_eip1559 {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the configured transaction order, `--order-command` takes precedence over `--order`
+    fn transaction_order(&self) -> TransactionOrder {
+        match self.order_command {
+            Some(ref command) => TransactionOrder::External(command.clone()),
+            None => self.order.clone(),
+        }
     }
 
     fn account_generator(&self) -> AccountGenerator {
@@ -150,6 +448,9 @@ impl NodeArgs {
         if let Some(ref mnemonic) = self.mnemonic {
             gen = gen.phrase(mnemonic);
         }
+        if let Some(ref seed) = self.mnemonic_seed_unsafe {
+            gen = gen.seed(seed.clone());
+        }
         if let Some(ref derivation) = self.derivation_path {
             gen = gen.derivation_path(derivation);
         }
@@ -160,7 +461,9 @@ impl NodeArgs {
     ///
     /// See also [crate::spawn()]
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
-        let (api, handle) = crate::spawn(self.into_node_config()).await;
+        let dump_state = self.dump_state_path();
+        let state_compression = self.state_compression;
+        let (api, handle) = crate::spawn(self.into_node_config()?).await;
 
         // sets the signal handler to gracefully shutdown.
         let fork = api.get_fork().cloned();
@@ -175,6 +478,11 @@ impl NodeArgs {
                 if let Some(ref fork) = fork {
                     fork.database.read().flush_cache();
                 }
+                if let Some(ref path) = dump_state {
+                    if let Err(err) = write_state(&api, path, state_compression) {
+                        eprintln!("Failed to dump state to {}: {}", path.display(), err);
+                    }
+                }
                 std::process::exit(0);
             }
         })
@@ -184,6 +492,33 @@ impl NodeArgs {
     }
 }
 
+/// Writes the accounts and storage of the node to `path`, as read by `--load-state`
+fn write_state(
+    api: &EthApi,
+    path: &Path,
+    compression: StateCompression,
+) -> Result<(), Box<dyn std::error::Error>> {
+    api.dump_state()?.write(path, compression)?;
+    Ok(())
+}
+
+/// Parses a hex encoded BIP32 seed, which must be between 16 and 64 bytes long
+fn parse_seed(s: &str) -> Result<Vec<u8>, String> {
+    let seed = hex::decode(s.trim_start_matches("0x")).map_err(|err| err.to_string())?;
+    if !(16..=64).contains(&seed.len()) {
+        return Err(format!("seed must be between 16 and 64 bytes long, got {}", seed.len()))
+    }
+    Ok(seed)
+}
+
+/// Parses a hex encoded 4 byte function selector
+fn parse_selector(s: &str) -> Result<[u8; 4], String> {
+    let selector = hex::decode(s.trim_start_matches("0x")).map_err(|err| err.to_string())?;
+    selector.try_into().map_err(|selector: Vec<u8>| {
+        format!("selector must be 4 bytes long, got {}", selector.len())
+    })
+}
+
 // Anvil's evm related arguments
 #[derive(Debug, Clone, Parser)]
 pub struct AnvilEvmArgs {
@@ -199,18 +534,172 @@ pub struct AnvilEvmArgs {
     )]
     pub fork_url: Option<String>,
 
+    /// The API key of the fork endpoint, appended to --fork-url as the last path segment.
+    ///
+    /// Prefer setting this via the environment, so that the key is not part of the process
+    /// arguments, e.g. `ANVIL_FORK_API_KEY=<KEY> anvil --fork-url https://mainnet.infura.io/v3`.
+    /// The key is redacted from all output and ignored without --fork-url.
+    #[clap(
+        long,
+        env = "ANVIL_FORK_API_KEY",
+        hide_env_values = true,
+        value_name = "KEY",
+        help_heading = "FORK CONFIG"
+    )]
+    pub fork_api_key: Option<String>,
+
     /// Fetch state from a specific block number over a remote endpoint.
     ///
     /// See --fork-url.
     #[clap(long, requires = "fork-url", value_name = "BLOCK", help_heading = "FORK CONFIG")]
     pub fork_block_number: Option<u64>,
 
-    /// Initial retry backoff on encountering errors.
+    /// Fetch state from the block with the given hash over a remote endpoint.
+    ///
+    /// Unlike --fork-block-number this is unambiguous, even if the remote chain reorged.
+    ///
+    /// See --fork-url.
+    #[clap(
+        long,
+        requires = "fork-url",
+        conflicts_with = "fork-block-number",
+        value_name = "HASH",
+        help_heading = "FORK CONFIG"
+    )]
+    pub fork_block_hash: Option<H256>,
+
+    /// Initial retry backoff in milliseconds when the fork endpoint rate limits requests.
+    ///
+    /// The backoff doubles with every retry. If the endpoint sends a `Retry-After` header, its
+    /// delay is used instead.
     ///
     /// See --fork-url.
     #[clap(long, requires = "fork-url", value_name = "BACKOFF", help_heading = "FORK CONFIG")]
     pub fork_retry_backoff: Option<u64>,
 
+    /// Appends every request that's sent to the fork endpoint to this file.
+    ///
+    /// Each line is a JSON object with the method, the params and the targeted block of the
+    /// request, which helps to find out which data is fetched and how often requests are rate
+    /// limited.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", value_name = "PATH", help_heading = "FORK CONFIG")]
+    pub fork_request_log: Option<PathBuf>,
+
+    /// Takes the fork offline after this many consecutive requests to the fork endpoint failed.
+    ///
+    /// A request fails if the endpoint is unreachable or still rate limits it after all retries.
+    /// Reading state that failed to be fetched returns an error, the state is fetched again on the
+    /// next read. While offline, no requests are sent to the endpoint anymore: cached state is
+    /// still served, but reading uncached state returns a "fork offline" error. Calls that read
+    /// uncached state fail the same way, and transactions that do stay in the pool.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", value_name = "FAILURES", help_heading = "FORK CONFIG")]
+    pub fork_offline_on_failure: Option<u32>,
+
+    /// Maximum number of storage slots to fetch from the remote endpoint in a single batch.
+    ///
+    /// Storage slots that are requested at the same time are sent to the endpoint in JSON-RPC
+    /// batches of this size. Defaults to 32.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", value_name = "N", help_heading = "FORK CONFIG")]
+    pub fork_batch_size: Option<usize>,
+
+    /// Fetch every storage slot from the remote endpoint with its own request.
+    ///
+    /// Disables JSON-RPC batch requests to the fork endpoint, this is the same as
+    /// `--fork-batch-size 1`. Batching is also disabled automatically once the endpoint rejects a
+    /// batch request, the rejected requests are then resent one by one.
+    ///
+    /// See --fork-url.
+    #[clap(
+        long,
+        requires = "fork-url",
+        conflicts_with = "fork-batch-size",
+        help_heading = "FORK CONFIG"
+    )]
+    pub fork_no_batch: bool,
+
+    /// Suggest the priority fee of the fork block instead of the default of 1 gwei.
+    ///
+    /// `eth_maxPriorityFeePerGas` returns the median priority fee paid in the fork block and
+    /// `eth_gasPrice` adds it to the base fee, like the forked chain at the fork block. Forks of
+    /// blocks before London suggest the median gas price of the fork block instead. The fees are
+    /// fetched again when the fork is reset.
+    ///
+    /// See --fork-url.
+    #[clap(
+        long,
+        requires = "fork-url",
+        conflicts_with = "gas-price",
+        help_heading = "FORK CONFIG"
+    )]
+    pub fork_gas_price_passthrough: bool,
+
+    /// Don't return the receipts of transactions that were mined before the fork.
+    ///
+    /// By default `eth_getTransactionReceipt` fetches receipts that are not part of the local
+    /// chain from the remote endpoint, as long as the transaction was mined at or before the
+    /// forked block.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", help_heading = "FORK CONFIG")]
+    pub fork_no_receipt_passthrough: bool,
+
+    /// Don't pin the fork to the latest block at startup, but fetch state from the remote
+    /// endpoint's latest block instead.
+    ///
+    /// By default, forking without --fork-block-number or --fork-block-hash resolves the latest
+    /// block once and uses it for the entire session, so that the forked state doesn't change
+    /// under you.
+    ///
+    /// When following the head, the fork checks for new blocks every second. State fetched from
+    /// the remote endpoint is fetched again at the new head, local changes are kept, and local
+    /// block numbers continue after the new head.
+    ///
+    /// See --fork-url.
+    #[clap(
+        long,
+        requires = "fork-url",
+        conflicts_with_all = &["fork-block-number", "fork-block-hash"],
+        help_heading = "FORK CONFIG"
+    )]
+    pub fork_follow_head: bool,
+
+    /// Captures the state right after forking, so that `anvil_reset` without params restores it
+    /// instantly without any requests to the fork endpoint.
+    ///
+    /// The captured state is kept in memory for the entire session. This includes a copy of all
+    /// data that was loaded from the fork cache at startup, which can be large for long-lived
+    /// cached forks.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", help_heading = "FORK CONFIG")]
+    pub fork_snapshot_on_start: bool,
+
+    /// Fetch the entire storage of this contract at the fork block into the fork cache at
+    /// startup, instead of one request per storage slot.
+    ///
+    /// This requires `debug_storageRangeAt` and the block after the fork block, so the fork
+    /// block must be set via --fork-block-number or --fork-block-hash. If the fork endpoint
+    /// can't provide either the storage is fetched lazily. Can be used multiple times, the
+    /// contracts are fetched concurrently.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", value_name = "ADDRESS", help_heading = "FORK CONFIG")]
+    pub fork_preload_storage: Vec<Address>,
+
+    /// Maximum number of concurrent requests to the remote endpoint.
+    ///
+    /// Requests beyond the limit are queued until a previous request finished.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", value_name = "N", help_heading = "FORK CONFIG")]
+    pub fork_max_concurrency: Option<usize>,
+
     /// Explicitly disables the use of RPC caching.
     ///
     /// All storage slots are read entirely from the endpoint.
@@ -221,14 +710,78 @@ pub struct AnvilEvmArgs {
     #[clap(long, requires = "fork-url", help_heading = "FORK CONFIG")]
     pub no_storage_caching: bool,
 
+    /// Only writes these accounts and storage slots to the RPC cache.
+    ///
+    /// A comma separated list of `<ADDRESS>` entries, which cache the entire account, and
+    /// `<ADDRESS>:<SLOT>` entries, which cache a single storage slot. Everything else is fetched
+    /// from the endpoint again on every run. By default everything is cached.
+    ///
+    /// See --fork-url.
+    #[clap(
+        long,
+        requires = "fork-url",
+        conflicts_with = "no-storage-caching",
+        value_name = "ADDR:SLOT,...",
+        help_heading = "FORK CONFIG"
+    )]
+    pub fork_cache_slots: Option<CacheAllowlist>,
+
+    /// Memoizes the results of `eth_call`s until the state changes.
+    ///
+    /// Identical calls against the same block return the cached result instead of being executed
+    /// again. The cache is cleared whenever a block is mined or the state is modified.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", help_heading = "FORK CONFIG")]
+    pub cache_calls: bool,
+
+    /// Explicitly disables caching of forked blocks.
+    ///
+    /// By default blocks up to the forked block are stored in the RPC cache once fetched, so
+    /// repeated `eth_getBlockBy*` calls are served locally.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", help_heading = "FORK CONFIG")]
+    pub no_block_caching: bool,
+
     /// The block gas limit.
     #[clap(long, value_name = "GAS_LIMIT", help_heading = "ENVIRONMENT CONFIG")]
     pub gas_limit: Option<u64>,
 
+    /// Disables the block gas limit, so blocks and transactions can use any amount of gas.
+    #[clap(
+        long,
+        alias = "no-gas-limit",
+        conflicts_with = "gas-limit",
+        help_heading = "ENVIRONMENT CONFIG"
+    )]
+    pub disable_block_gas_limit: bool,
+
+    /// The highest gas `eth_estimateGas` searches up to.
+    ///
+    /// By default estimation is bounded by the block gas limit. This lowers the bound, which
+    /// keeps estimation fast if --gas-limit is very high.
+    #[clap(long, value_name = "GAS", help_heading = "ENVIRONMENT CONFIG")]
+    pub gas_estimation_cap: Option<u64>,
+
     /// The gas price.
     #[clap(long, value_name = "GAS_PRICE", help_heading = "ENVIRONMENT CONFIG")]
     pub gas_price: Option<u64>,
 
+    /// The lowest gas price of accepted transactions.
+    ///
+    /// Transactions with a lower gas price are rejected and `eth_gasPrice` never suggests less.
+    /// Ignored while EIP-1559 is active, where the base fee is the lowest price.
+    #[clap(long, value_name = "WEI", help_heading = "ENVIRONMENT CONFIG")]
+    pub min_gas_price: Option<u64>,
+
+    /// Suggests the median effective gas price of the transactions in the last 20 blocks instead
+    /// of a fixed gas price.
+    ///
+    /// Falls back to --gas-price if these blocks don't contain any transactions.
+    #[clap(long, help_heading = "ENVIRONMENT CONFIG")]
+    pub gas_price_oracle: bool,
+
     /// The base fee in a block.
     #[clap(
         long,
@@ -238,7 +791,57 @@ pub struct AnvilEvmArgs {
     )]
     pub block_base_fee_per_gas: Option<u64>,
 
+    /// Keep the base fee constant instead of adjusting it after every block according to EIP-1559.
+    ///
+    /// The base fee stays at --block-base-fee-per-gas unless it is changed manually.
+    #[clap(long, help_heading = "ENVIRONMENT CONFIG")]
+    pub static_base_fee: bool,
+
+    /// Enable EIP-1559 regardless of the hardfork.
+    ///
+    /// Blocks have a base fee and EIP-1559 transactions are accepted.
+    #[clap(long, conflicts_with = "disable-eip1559", help_heading = "ENVIRONMENT CONFIG")]
+    pub enable_eip1559: bool,
+
+    /// Disable EIP-1559 regardless of the hardfork.
+    ///
+    /// Blocks have no base fee, only legacy gas pricing applies and EIP-1559 transactions are
+    /// rejected.
+    #[clap(long, help_heading = "ENVIRONMENT CONFIG")]
+    pub disable_eip1559: bool,
+
     /// The chain ID.
     #[clap(long, value_name = "CHAIN_ID", help_heading = "ENVIRONMENT CONFIG")]
     pub chain_id: Option<u64>,
+
+    /// Accept Optimism deposit transactions.
+    ///
+    /// Deposit transactions (type 0x7E) are sent via `eth_sendRawTransaction`. They mint to the
+    /// sender, don't pay for gas and are mined before all other transactions. Receipts of all
+    /// other transactions include the L1 fee fields, priced by the storage of the `L1Block`
+    /// predeploy.
+    #[clap(long, help_heading = "ENVIRONMENT CONFIG")]
+    pub optimism: bool,
+}
+
+// === impl AnvilEvmArgs ===
+
+impl AnvilEvmArgs {
+    /// Returns the fork url with the `--fork-api-key` appended, if any
+    pub fn fork_url(&self) -> Option<String> {
+        let url = self.fork_url.as_ref()?;
+        match self.fork_api_key {
+            Some(ref key) => Some(format!("{}/{}", url.trim_end_matches('/'), key)),
+            None => Some(url.clone()),
+        }
+    }
+
+    /// Returns the storage batch size, which is `1` if batching is disabled with `--fork-no-batch`
+    pub fn fork_batch_size(&self) -> Option<usize> {
+        if self.fork_no_batch {
+            Some(1)
+        } else {
+            self.fork_batch_size
+        }
+    }
 }
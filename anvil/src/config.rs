@@ -2,35 +2,41 @@ use crate::{
     eth::{
         backend::{
             db::Db,
-            fork::{ClientFork, ClientForkConfig},
-            genesis::GenesisConfig,
-            mem::fork_db::ForkedDatabase,
+            fork::{new_fork_provider, ClientFork, ClientForkConfig, ForkHealth, ForkRequestLog},
+            genesis::{GenesisAccount, GenesisConfig},
+            mem::{fork_db::ForkedDatabase, storage::SerializableHistory},
         },
         fees::{INITIAL_BASE_FEE, INITIAL_GAS_PRICE},
-        pool::transactions::TransactionOrder,
+        pool::{
+            transactions::{TransactionOrder, DEFAULT_PRICE_BUMP},
+            PoolFullBehavior,
+        },
     },
+    logging::LogCategory,
     mem,
     mem::in_memory_db::MemDb,
     FeeManager,
 };
 use anvil_server::ServerConfig;
+use coins_bip32::prelude::XPriv;
 use ethers::{
     core::k256::ecdsa::SigningKey,
     prelude::{rand::thread_rng, Wallet, U256},
-    providers::{Http, Middleware, Provider, RetryClient},
+    providers::Middleware,
     signers::{
         coins_bip39::{English, Mnemonic},
         MnemonicBuilder, Signer,
     },
-    types::BlockNumber,
+    types::{Address, BlockNumber, H256},
     utils::{format_ether, hex, WEI_IN_ETHER},
 };
 use foundry_config::Config;
 use foundry_evm::{
-    executor::fork::{BlockchainDb, BlockchainDbMeta, SharedBackend},
+    executor::fork::{BlockchainDb, BlockchainDbMeta, CacheAllowlist, SharedBackend},
     revm,
     revm::{BlockEnv, CfgEnv, SpecId, TxEnv},
 };
+use futures::future::join_all;
 use parking_lot::RwLock;
 use serde_json::{json, to_writer, Value};
 use std::{
@@ -72,18 +78,46 @@ pub struct NodeConfig {
     pub chain_id: u64,
     /// Default gas limit for all txs
     pub gas_limit: U256,
+    /// If set to `true`, the block gas limit is disabled and the `gas_limit` is ignored
+    pub disable_block_gas_limit: bool,
+    /// Whether Optimism deposit transactions are accepted
+    pub optimism: bool,
+    /// Whether a block is mined after the state was modified by a cheat like `anvil_setBalance`
+    pub mine_on_state_change: bool,
+    /// Whether the changes made by state cheats are recorded, see `anvil_getCheatLog`
+    pub record_cheats: bool,
     /// Default gas price for all txs
     pub gas_price: Option<U256>,
+    /// The lowest gas price of accepted transactions, ignored while EIP-1559 is active
+    pub min_gas_price: Option<U256>,
+    /// Whether the suggested gas price is derived from the transactions of recent blocks
+    pub gas_price_oracle: bool,
+    /// The highest gas `eth_estimateGas` searches up to, capped by the `gas_limit`
+    pub gas_estimation_cap: Option<U256>,
     /// Default base fee
     pub base_fee: Option<U256>,
+    /// Whether to recompute the base fee for every block based on the parent's gas usage, enabled
+    /// by default
+    pub dynamic_base_fee: bool,
+    /// Forces EIP-1559 on or off, regardless of the hardfork
+    pub eip1559: Option<bool>,
     /// The hardfork to use
-    pub hardfork: Hardfork,
+    ///
+    /// If not set, this is the hardfork of the fork block in fork mode and
+    /// [Hardfork::Latest] otherwise
+    pub hardfork: Option<Hardfork>,
+    /// Hardforks that replace `hardfork` once their block is reached
+    pub hardfork_transitions: Vec<HardforkTransition>,
     /// Signer accounts that will be initialised with `genesis_balance` in the genesis block
     pub genesis_accounts: Vec<Wallet<SigningKey>>,
     /// Native token balance of every genesis account in the genesis block
     pub genesis_balance: U256,
     /// Signer accounts that can sign messages/transactions from the EVM node
     pub signer_accounts: Vec<Wallet<SigningKey>>,
+    /// Accounts with custom genesis settings, loaded from an accounts file
+    pub custom_genesis_accounts: Vec<GenesisAccount>,
+    /// Blocks and transactions of a state file that are restored at startup
+    pub state_history: SerializableHistory,
     /// Configured block time for the EVM chain. Use `None` to mine a new block for every tx
     pub block_time: Option<Duration>,
     /// Disable auto, interval mining mode uns use `MiningMode::None` instead
@@ -94,22 +128,76 @@ pub struct NodeConfig {
     pub max_transactions: usize,
     /// don't print anything on startup
     pub silent: bool,
+    /// The subsystems whose internal events are logged as well
+    pub log_categories: Vec<LogCategory>,
     /// url of the rpc server that should be used for any rpc calls
     pub eth_rpc_url: Option<String>,
     /// pins the block number for the state fork
     pub fork_block_number: Option<u64>,
+    /// pins the block hash for the state fork, takes precedence over `fork_block_number`
+    pub fork_block_hash: Option<H256>,
+    /// The maximum number of storage slots that are fetched from the fork endpoint in one batch
+    pub fork_batch_size: Option<usize>,
+    /// The maximum number of concurrent requests to the fork endpoint
+    pub fork_max_concurrency: Option<usize>,
+    /// The initial backoff after the fork endpoint rate limited a request
+    pub fork_retry_backoff: Option<Duration>,
+    /// The file every request to the fork endpoint is appended to
+    pub fork_request_log: Option<PathBuf>,
+    /// The number of consecutive failed requests after which the fork only serves cached data
+    pub fork_offline_on_failure: Option<u32>,
+    /// Whether to suggest the priority fee of the fork block instead of the default
+    pub fork_gas_price_passthrough: bool,
+    /// Whether to return the receipts of transactions mined before the fork, enabled by default
+    pub fork_passthrough_receipts: bool,
+    /// Whether to fetch state from the latest block of the fork endpoint instead of pinning the
+    /// fork to the latest block at startup
+    pub fork_follow_head: bool,
+    /// Whether to keep the state right after forking in memory, so `anvil_reset` can restore it
+    pub fork_snapshot_on_start: bool,
+    /// Contracts whose entire storage is fetched into the fork cache at startup
+    pub fork_preload_storage: Vec<Address>,
     /// The generator used to generate the dev accounts
     pub account_generator: Option<AccountGenerator>,
     /// whether to enable tracing
     pub enable_tracing: bool,
+    /// whether to print the decoded call traces of every mined transaction
+    pub print_traces: bool,
+    /// How much of the execution of mined transactions to print, from 0 (nothing) to 4 (every
+    /// opcode)
+    pub trace_verbosity: u8,
+    /// whether to print the `console.log` output of mined transactions
+    pub enable_console_log: bool,
     /// Explicitly disables the use of RPC caching.
     pub no_storage_caching: bool,
+    /// If set, only these accounts and storage slots of the fork are written to the RPC cache
+    pub fork_cache_allowlist: Option<CacheAllowlist>,
+    /// Whether to memoize the results of `eth_call`s in fork mode
+    pub cache_calls: bool,
+    /// Whether forked blocks are kept in the RPC cache
+    pub cache_fork_blocks: bool,
     /// How to configure the server
     pub server_config: ServerConfig,
     /// The host the server will listen on
     pub host: Option<IpAddr>,
     /// How transactions are sorted in the mempool
     pub transaction_order: TransactionOrder,
+    /// Senders whose transactions are always included first, regardless of the `transaction_order`
+    pub priority_senders: Vec<Address>,
+    /// Function selectors whose calls are included after the transactions of `priority_senders`
+    /// but before all other transactions
+    pub priority_selectors: Vec<[u8; 4]>,
+    /// The maximum number of transactions in the mempool, unlimited if `None`
+    pub mempool_limit: Option<usize>,
+    /// What happens when a transaction is submitted to a full mempool
+    pub mempool_full_behavior: PoolFullBehavior,
+    /// The percentage by which a replacement transaction's gas price must exceed the gas price of
+    /// the transaction it replaces
+    pub price_bump: u64,
+    /// url of an external signer that signs for `external_signer_accounts`
+    pub external_signer_url: Option<String>,
+    /// Accounts whose signing requests are forwarded to the `external_signer_url`
+    pub external_signer_accounts: Vec<Address>,
     /// Filename to write anvil output as json
     pub config_out: Option<String>,
 }
@@ -153,21 +241,25 @@ Private Keys
         }
 
         if let Some(ref gen) = self.account_generator {
+            let source = match gen.seed {
+                Some(ref seed) => format!("Seed:              0x{}", hex::encode(seed)),
+                None => format!("Mnemonic:          {}", gen.phrase),
+            };
             let _ = write!(
                 config_string,
                 r#"
 
 Wallet
 ==================
-Mnemonic:          {}
+{}
 Derivation path:   {}
 "#,
-                gen.phrase,
+                source,
                 gen.get_derivation_path()
             );
         }
 
-        if (SpecId::from(self.hardfork) as u8) < (SpecId::LONDON as u8) {
+        if !self.is_eip1559() {
             let _ = write!(
                 config_string,
                 r#"
@@ -197,7 +289,7 @@ Gas Limit
 ==================
 {}
 "#,
-            Paint::green(format!("\n{}", self.gas_limit))
+            Paint::green(format!("\n{}", self.get_gas_limit()))
         );
 
         if let Some(fork) = fork {
@@ -212,7 +304,7 @@ Block hash:     {:?}
 Chain ID:       {}
 
 "#,
-                fork.eth_rpc_url(),
+                fork.redacted_eth_rpc_url(),
                 fork.block_number(),
                 fork.block_hash(),
                 fork.chain_id()
@@ -233,25 +325,30 @@ Chain ID:       {}
         }
 
         if let Some(ref gen) = self.account_generator {
-            let phrase = gen.get_phrase().to_string();
             let derivation_path = gen.get_derivation_path().to_string();
 
             wallet_description.insert("derivation_path".to_string(), derivation_path);
-            wallet_description.insert("mnemonic".to_string(), phrase);
+            match gen.seed {
+                Some(ref seed) => wallet_description
+                    .insert("seed".to_string(), format!("0x{}", hex::encode(seed))),
+                None => {
+                    wallet_description.insert("mnemonic".to_string(), gen.get_phrase().to_string())
+                }
+            };
         };
 
         if let Some(fork) = fork {
             json!({
               "available_accounts": available_accounts,
               "private_keys": private_keys,
-              "endpoint": fork.eth_rpc_url(),
+              "endpoint": fork.redacted_eth_rpc_url(),
               "block_number": fork.block_number(),
               "block_hash": fork.block_hash(),
               "chain_id": fork.chain_id(),
               "wallet": wallet_description,
               "base_fee": format!("{}", self.get_base_fee()),
               "gas_price": format!("{}", self.get_gas_price()),
-              "gas_limit": format!("{}", self.gas_limit),
+              "gas_limit": format!("{}", self.get_gas_limit()),
             })
         } else {
             json!({
@@ -260,7 +357,7 @@ Chain ID:       {}
               "wallet": wallet_description,
               "base_fee": format!("{}", self.get_base_fee()),
               "gas_price": format!("{}", self.get_gas_price()),
-              "gas_limit": format!("{}", self.gas_limit),
+              "gas_limit": format!("{}", self.get_gas_limit()),
             })
         }
     }
@@ -284,10 +381,20 @@ impl Default for NodeConfig {
         Self {
             chain_id: CHAIN_ID,
             gas_limit: U256::from(30_000_000),
+            disable_block_gas_limit: false,
+            optimism: false,
+            mine_on_state_change: false,
+            record_cheats: false,
             gas_price: None,
-            hardfork: Hardfork::default(),
+            min_gas_price: None,
+            gas_price_oracle: false,
+            gas_estimation_cap: None,
+            hardfork: None,
+            hardfork_transitions: vec![],
             signer_accounts: genesis_accounts.clone(),
             genesis_accounts,
+            custom_genesis_accounts: vec![],
+            state_history: Default::default(),
             // 100ETH default balance
             genesis_balance: WEI_IN_ETHER.saturating_mul(100u64.into()),
             block_time: None,
@@ -296,15 +403,42 @@ impl Default for NodeConfig {
             // TODO make this something dependent on block capacity
             max_transactions: 1_000,
             silent: false,
+            log_categories: Vec::new(),
             eth_rpc_url: None,
             fork_block_number: None,
+            fork_block_hash: None,
+            fork_batch_size: None,
+            fork_max_concurrency: None,
+            fork_retry_backoff: None,
+            fork_request_log: None,
+            fork_offline_on_failure: None,
+            fork_gas_price_passthrough: false,
+            fork_passthrough_receipts: true,
+            fork_follow_head: false,
+            fork_snapshot_on_start: false,
+            fork_preload_storage: vec![],
             account_generator: None,
             base_fee: None,
+            dynamic_base_fee: true,
+            eip1559: None,
             enable_tracing: true,
+            print_traces: false,
+            trace_verbosity: 0,
+            enable_console_log: true,
             no_storage_caching: false,
+            fork_cache_allowlist: None,
+            cache_calls: false,
+            cache_fork_blocks: true,
             server_config: Default::default(),
             host: None,
             transaction_order: Default::default(),
+            priority_senders: vec![],
+            priority_selectors: vec![],
+            mempool_limit: None,
+            mempool_full_behavior: Default::default(),
+            price_bump: DEFAULT_PRICE_BUMP,
+            external_signer_url: None,
+            external_signer_accounts: vec![],
             config_out: None,
         }
     }
@@ -339,6 +473,27 @@ impl NodeConfig {
         })
     }
 
+    /// Sets whether Optimism deposit transactions are accepted
+    #[must_use]
+    pub fn with_optimism(mut self, optimism: bool) -> Self {
+        self.optimism = optimism;
+        self
+    }
+
+    /// Sets whether a block is mined after the state was modified by a cheat
+    #[must_use]
+    pub fn with_mine_on_state_change(mut self, mine_on_state_change: bool) -> Self {
+        self.mine_on_state_change = mine_on_state_change;
+        self
+    }
+
+    /// Sets whether the changes made by state cheats are recorded, see `anvil_getCheatLog`
+    #[must_use]
+    pub fn with_record_cheats(mut self, record_cheats: bool) -> Self {
+        self.record_cheats = record_cheats;
+        self
+    }
+
     /// Sets the gas limit
     #[must_use]
     pub fn with_gas_limit<U: Into<U256>>(mut self, gas_limit: Option<U>) -> Self {
@@ -348,6 +503,29 @@ impl NodeConfig {
         self
     }
 
+    /// Disables the block gas limit
+    #[must_use]
+    pub fn with_disable_block_gas_limit(mut self, disable_block_gas_limit: bool) -> Self {
+        self.disable_block_gas_limit = disable_block_gas_limit;
+        self
+    }
+
+    /// Returns the block gas limit, which is `u64::MAX` if the block gas limit is disabled
+    pub fn get_gas_limit(&self) -> U256 {
+        if self.disable_block_gas_limit {
+            U256::from(u64::MAX)
+        } else {
+            self.gas_limit
+        }
+    }
+
+    /// Sets the highest gas `eth_estimateGas` searches up to
+    #[must_use]
+    pub fn with_gas_estimation_cap<U: Into<U256>>(mut self, gas_estimation_cap: Option<U>) -> Self {
+        self.gas_estimation_cap = gas_estimation_cap.map(Into::into);
+        self
+    }
+
     /// Sets the gas price
     #[must_use]
     pub fn with_gas_price<U: Into<U256>>(mut self, gas_price: Option<U>) -> Self {
@@ -355,6 +533,21 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the lowest gas price of accepted transactions
+    #[must_use]
+    pub fn with_min_gas_price<U: Into<U256>>(mut self, min_gas_price: Option<U>) -> Self {
+        self.min_gas_price = min_gas_price.map(Into::into);
+        self
+    }
+
+    /// Sets whether the suggested gas price is the median effective gas price of the transactions
+    /// in recent blocks instead of the configured gas price
+    #[must_use]
+    pub fn with_gas_price_oracle(mut self, gas_price_oracle: bool) -> Self {
+        self.gas_price_oracle = gas_price_oracle;
+        self
+    }
+
     /// Sets the base fee
     #[must_use]
     pub fn with_base_fee<U: Into<U256>>(mut self, base_fee: Option<U>) -> Self {
@@ -362,10 +555,46 @@ impl NodeConfig {
         self
     }
 
+    /// Sets whether the base fee is adjusted after every block according to EIP-1559
+    #[must_use]
+    pub fn with_dynamic_base_fee(mut self, dynamic_base_fee: bool) -> Self {
+        self.dynamic_base_fee = dynamic_base_fee;
+        self
+    }
+
+    /// Forces EIP-1559 on or off, `None` activates it with the London hardfork
+    #[must_use]
+    pub fn with_eip1559(mut self, eip1559: Option<bool>) -> Self {
+        self.eip1559 = eip1559;
+        self
+    }
+
+    /// Returns true if blocks have a base fee
+    pub fn is_eip1559(&self) -> bool {
+        self.eip1559.unwrap_or_else(|| self.get_hardfork().is_post_london())
+    }
+
+    /// Returns the hardfork to use
+    pub fn get_hardfork(&self) -> Hardfork {
+        self.hardfork.unwrap_or_default()
+    }
+
     /// Sets the hardfork
+    ///
+    /// If unset, the hardfork is inferred from the fork block in fork mode
     #[must_use]
-    pub fn with_hardfork(mut self, hardfork: Hardfork) -> Self {
-        self.hardfork = hardfork;
+    pub fn with_hardfork(mut self, hardfork: impl Into<Option<Hardfork>>) -> Self {
+        self.hardfork = hardfork.into();
+        self
+    }
+
+    /// Sets the hardforks that become active at later blocks
+    #[must_use]
+    pub fn with_hardfork_transitions(
+        mut self,
+        hardfork_transitions: Vec<HardforkTransition>,
+    ) -> Self {
+        self.hardfork_transitions = hardfork_transitions;
         self
     }
 
@@ -392,6 +621,34 @@ impl NodeConfig {
         self.with_signer_accounts(accounts.clone()).with_genesis_accounts(accounts)
     }
 
+    /// Sets the accounts with custom genesis settings
+    ///
+    /// Accounts with a private key are added to the genesis and signer accounts, if they're not
+    /// already included.
+    #[must_use]
+    pub fn with_custom_genesis_accounts(mut self, accounts: Vec<GenesisAccount>) -> Self {
+        for wallet in accounts.iter().filter_map(GenesisAccount::wallet) {
+            let wallet = wallet.with_chain_id(self.chain_id);
+            if !self.signer_accounts.iter().any(|w| w.address() == wallet.address()) {
+                self.signer_accounts.push(wallet.clone());
+            }
+            if !self.genesis_accounts.iter().any(|w| w.address() == wallet.address()) {
+                self.genesis_accounts.push(wallet);
+            }
+        }
+        self.custom_genesis_accounts = accounts;
+        self
+    }
+
+    /// Sets the blocks and transactions of a state file that should be restored at startup
+    ///
+    /// They're ignored in fork mode.
+    #[must_use]
+    pub fn with_state_history(mut self, history: SerializableHistory) -> Self {
+        self.state_history = history;
+        self
+    }
+
     /// Sets the balance of the genesis accounts in the genesis block
     #[must_use]
     pub fn with_genesis_balance<U: Into<U256>>(mut self, balance: U) -> Self {
@@ -432,6 +689,22 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the subsystems whose internal events are logged as well
+    #[must_use]
+    pub fn with_log_categories(mut self, log_categories: Vec<LogCategory>) -> Self {
+        self.log_categories = log_categories;
+        self
+    }
+
+    /// Returns the subsystems whose events are logged, none if the node is silent
+    pub fn get_log_categories(&self) -> Vec<LogCategory> {
+        if self.silent {
+            Vec::new()
+        } else {
+            self.log_categories.clone()
+        }
+    }
+
     /// Sets the file path to write the Anvil node's config info to.
     #[must_use]
     pub fn set_config_out(mut self, config_out: Option<String>) -> Self {
@@ -451,6 +724,28 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the accounts and storage slots that are written to the RPC cache, `None` writes all
+    #[must_use]
+    pub fn with_fork_cache_allowlist(mut self, allowlist: Option<CacheAllowlist>) -> Self {
+        self.fork_cache_allowlist = allowlist;
+        self
+    }
+
+    /// Sets whether to memoize the results of `eth_call`s against the forked state
+    #[must_use]
+    pub fn with_call_caching(mut self, cache_calls: bool) -> Self {
+        self.cache_calls = cache_calls;
+        self
+    }
+
+    /// Sets whether fetched blocks up to the forked block are stored in the RPC cache, so that
+    /// they're not fetched again
+    #[must_use]
+    pub fn with_fork_block_caching(mut self, cache_fork_blocks: bool) -> Self {
+        self.cache_fork_blocks = cache_fork_blocks;
+        self
+    }
+
     /// Sets the `eth_rpc_url` to use when forking
     #[must_use]
     pub fn with_eth_rpc_url<U: Into<String>>(mut self, eth_rpc_url: Option<U>) -> Self {
@@ -465,6 +760,98 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the `fork_block_hash` of the block to fork off from
+    #[must_use]
+    pub fn with_fork_block_hash(mut self, fork_block_hash: Option<H256>) -> Self {
+        self.fork_block_hash = fork_block_hash;
+        self
+    }
+
+    /// Sets the maximum number of storage slots that are fetched from the fork endpoint in a
+    /// single batch
+    #[must_use]
+    pub fn with_fork_batch_size(mut self, fork_batch_size: Option<usize>) -> Self {
+        self.fork_batch_size = fork_batch_size;
+        self
+    }
+
+    /// Sets the maximum number of requests that are sent to the fork endpoint at the same time
+    #[must_use]
+    pub fn with_fork_max_concurrency(mut self, fork_max_concurrency: Option<usize>) -> Self {
+        self.fork_max_concurrency = fork_max_concurrency;
+        self
+    }
+
+    /// Sets the initial backoff after the fork endpoint rate limited a request
+    ///
+    /// The backoff doubles with every retry, unless the endpoint requests a delay via the
+    /// `Retry-After` header
+    #[must_use]
+    pub fn with_fork_retry_backoff<D: Into<Duration>>(
+        mut self,
+        fork_retry_backoff: Option<D>,
+    ) -> Self {
+        self.fork_retry_backoff = fork_retry_backoff.map(Into::into);
+        self
+    }
+
+    /// Sets the file every request to the fork endpoint is appended to
+    #[must_use]
+    pub fn with_fork_request_log(mut self, fork_request_log: Option<PathBuf>) -> Self {
+        self.fork_request_log = fork_request_log;
+        self
+    }
+
+    /// Sets the number of consecutive failed requests to the fork endpoint after which no requests
+    /// are sent anymore and only cached data is served
+    #[must_use]
+    pub fn with_fork_offline_on_failure(mut self, fork_offline_on_failure: Option<u32>) -> Self {
+        self.fork_offline_on_failure = fork_offline_on_failure;
+        self
+    }
+
+    /// Sets whether `eth_gasPrice` and `eth_maxPriorityFeePerGas` reflect the priority fees paid
+    /// in the fork block
+    #[must_use]
+    pub fn with_fork_gas_price_passthrough(mut self, fork_gas_price_passthrough: bool) -> Self {
+        self.fork_gas_price_passthrough = fork_gas_price_passthrough;
+        self
+    }
+
+    /// Sets whether `eth_getTransactionReceipt` fetches the receipts of transactions mined before
+    /// the fork from the fork endpoint
+    #[must_use]
+    pub fn with_fork_passthrough_receipts(mut self, fork_passthrough_receipts: bool) -> Self {
+        self.fork_passthrough_receipts = fork_passthrough_receipts;
+        self
+    }
+
+    /// Sets whether the state of a fork without a `fork_block_number` follows the head of the fork
+    /// endpoint instead of being pinned to its latest block at startup
+    #[must_use]
+    pub fn with_fork_follow_head(mut self, fork_follow_head: bool) -> Self {
+        self.fork_follow_head = fork_follow_head;
+        self
+    }
+
+    /// Sets whether the state right after forking is captured, so that `anvil_reset` without
+    /// params restores it without any requests to the fork endpoint
+    #[must_use]
+    pub fn with_fork_snapshot_on_start(mut self, fork_snapshot_on_start: bool) -> Self {
+        self.fork_snapshot_on_start = fork_snapshot_on_start;
+        self
+    }
+
+    /// Sets the contracts whose entire storage is fetched via `debug_storageRangeAt` when forking
+    ///
+    /// Without a `fork_block_number` or `fork_block_hash` the fork block is the latest block, whose
+    /// child doesn't exist yet, so the storage is fetched lazily.
+    #[must_use]
+    pub fn with_fork_preload_storage(mut self, fork_preload_storage: Vec<Address>) -> Self {
+        self.fork_preload_storage = fork_preload_storage;
+        self
+    }
+
     /// Sets whether to enable tracing
     #[must_use]
     pub fn with_tracing(mut self, enable_tracing: bool) -> Self {
@@ -472,6 +859,38 @@ impl NodeConfig {
         self
     }
 
+    /// Sets whether to print the call traces of mined transactions
+    #[must_use]
+    pub fn with_print_traces(mut self, print_traces: bool) -> Self {
+        self.print_traces = print_traces;
+        self
+    }
+
+    /// Sets how much of the execution of mined transactions to print
+    ///
+    /// See [Backend::with_trace_verbosity](mem::Backend::with_trace_verbosity) for the levels.
+    #[must_use]
+    pub fn with_trace_verbosity(mut self, trace_verbosity: u8) -> Self {
+        self.trace_verbosity = trace_verbosity;
+        self
+    }
+
+    /// Returns the trace verbosity, `print_traces` is the same as a verbosity of 2
+    pub fn get_trace_verbosity(&self) -> u8 {
+        if self.print_traces {
+            self.trace_verbosity.max(2)
+        } else {
+            self.trace_verbosity
+        }
+    }
+
+    /// Sets whether to print the `console.log` output of mined transactions
+    #[must_use]
+    pub fn with_console_log(mut self, enable_console_log: bool) -> Self {
+        self.enable_console_log = enable_console_log;
+        self
+    }
+
     #[must_use]
     pub fn with_server_config(mut self, config: ServerConfig) -> Self {
         self.server_config = config;
@@ -491,6 +910,55 @@ impl NodeConfig {
         self
     }
 
+    /// Sets the senders whose transactions are placed at the front of the mempool
+    #[must_use]
+    pub fn with_priority_senders(mut self, priority_senders: Vec<Address>) -> Self {
+        self.priority_senders = priority_senders;
+        self
+    }
+
+    /// Sets the function selectors whose calls are placed at the front of the mempool, behind the
+    /// transactions of priority senders
+    #[must_use]
+    pub fn with_priority_selectors(mut self, priority_selectors: Vec<[u8; 4]>) -> Self {
+        self.priority_selectors = priority_selectors;
+        self
+    }
+
+    /// Sets the maximum number of transactions in the mempool
+    #[must_use]
+    pub fn with_mempool_limit(mut self, mempool_limit: Option<usize>) -> Self {
+        self.mempool_limit = mempool_limit;
+        self
+    }
+
+    /// Sets whether a full mempool rejects new transactions or evicts lower priority ones
+    #[must_use]
+    pub fn with_mempool_full_behavior(mut self, mempool_full_behavior: PoolFullBehavior) -> Self {
+        self.mempool_full_behavior = mempool_full_behavior;
+        self
+    }
+
+    /// Sets the percentage by which a replacement transaction's gas price must exceed the gas
+    /// price of the pending transaction with the same nonce
+    #[must_use]
+    pub fn with_price_bump(mut self, price_bump: u64) -> Self {
+        self.price_bump = price_bump;
+        self
+    }
+
+    /// Sets the external signer that signs for the given accounts
+    #[must_use]
+    pub fn with_external_signer<U: Into<String>>(
+        mut self,
+        url: Option<U>,
+        accounts: Vec<Address>,
+    ) -> Self {
+        self.external_signer_url = url.map(Into::into);
+        self.external_signer_accounts = accounts;
+        self
+    }
+
     /// Prints the config info
     pub fn print(&self, fork: Option<&ClientFork>) {
         if self.config_out.is_some() {
@@ -530,34 +998,78 @@ impl NodeConfig {
         // configure the revm environment
         let mut env = revm::Env {
             cfg: CfgEnv {
-                spec_id: self.hardfork.into(),
+                spec_id: self.get_hardfork().into(),
                 chain_id: self.chain_id.into(),
                 ..Default::default()
             },
             block: BlockEnv {
-                gas_limit: self.gas_limit,
-                basefee: self.get_base_fee(),
+                gas_limit: self.get_gas_limit(),
+                basefee: if self.is_eip1559() { self.get_base_fee() } else { U256::zero() },
                 ..Default::default()
             },
             tx: TxEnv { chain_id: Some(self.chain_id), ..Default::default() },
         };
-        let fees = FeeManager::new(env.cfg.spec_id, self.get_base_fee(), self.get_gas_price());
+        let fees = FeeManager::new(env.cfg.spec_id, self.get_base_fee(), self.get_gas_price())
+            .with_dynamic_base_fee(self.dynamic_base_fee)
+            .with_eip1559(self.eip1559)
+            .with_min_gas_price(self.min_gas_price);
         let mut fork_timestamp = None;
 
         let (db, fork): (Arc<RwLock<dyn Db>>, Option<ClientFork>) = if let Some(eth_rpc_url) =
             self.eth_rpc_url.clone()
         {
+            let request_log = self.fork_request_log.as_ref().map(|path| {
+                ForkRequestLog::open(path).expect("Failed to open the fork request log")
+            });
+            let health = self.fork_offline_on_failure.map(ForkHealth::new);
             // TODO make provider agnostic
             let provider = Arc::new(
-                Provider::<RetryClient<Http>>::new_client(&eth_rpc_url, 10, 1000)
-                    .expect("Failed to establish provider to fork url"),
+                new_fork_provider(
+                    &eth_rpc_url,
+                    self.fork_max_concurrency,
+                    self.fork_batch_size,
+                    self.fork_retry_backoff,
+                    request_log.clone(),
+                    health.clone(),
+                )
+                .expect("Failed to establish provider to fork url"),
             );
 
+            if let Some(fork_block_hash) = self.fork_block_hash {
+                let block = provider
+                    .get_block(fork_block_hash)
+                    .await
+                    .expect("Failed to get fork block")
+                    .unwrap_or_else(|| {
+                        panic!("Failed to get block for block hash: {:?}", fork_block_hash)
+                    });
+                if block.hash != Some(fork_block_hash) {
+                    panic!(
+                        "Remote returned block {:?} for requested block hash {:?}",
+                        block.hash, fork_block_hash
+                    );
+                }
+                // pin the number of the block, this also enables the block cache
+                self.fork_block_number =
+                    Some(block.number.expect("Fork block has no number").as_u64());
+            }
+
             let fork_block_number = if let Some(fork_block_number) = self.fork_block_number {
                 fork_block_number
             } else {
                 // pick the last block number but also ensure it's not pending anymore
-                find_latest_fork_block(&provider).await.expect("Failed to get fork block number")
+                let latest = find_latest_fork_block(&provider)
+                    .await
+                    .expect("Failed to get fork block number");
+                if !self.fork_follow_head {
+                    // pin the resolved block for the entire session, this also enables the block
+                    // cache
+                    self.fork_block_number = Some(latest);
+                    if !self.silent {
+                        println!("Pinned fork to latest block {}", latest);
+                    }
+                }
+                latest
             };
 
             let block = provider
@@ -598,23 +1110,74 @@ impl NodeConfig {
             }
 
             let block_hash = block.hash.unwrap();
+            if let Some(fork_block_hash) = self.fork_block_hash {
+                if block_hash != fork_block_hash {
+                    panic!(
+                        "Fork block {} has hash {:?}, expected {:?}",
+                        fork_block_number, block_hash, fork_block_hash
+                    );
+                }
+            }
             let chain_id = provider.get_chainid().await.unwrap().as_u64();
             // need to update the dev signers and env with the chain id
             self.set_chain_id(chain_id);
             env.cfg.chain_id = chain_id.into();
             env.tx.chain_id = chain_id.into();
 
+            // forking off an older block of a known chain uses that block's hardfork, so that
+            // blocks mined on top of a pre-London block are legacy blocks
+            if let Some(fork_hardfork) =
+                Hardfork::from_chain_and_number(chain_id, fork_block_number)
+            {
+                match self.hardfork {
+                    Some(hardfork) => {
+                        if !self.silent && !hardfork.is_same_era(fork_hardfork) {
+                            eprintln!(
+                                "{}",
+                                Paint::yellow(format!(
+                                    "Warning: hardfork {:?} doesn't match the hardfork {:?} of fork block {}",
+                                    hardfork, fork_hardfork, fork_block_number
+                                ))
+                            );
+                        }
+                    }
+                    None => {
+                        self.hardfork = Some(fork_hardfork);
+                        env.cfg.spec_id = fork_hardfork.into();
+                        fees.set_spec_id(env.cfg.spec_id);
+                        if !self.is_eip1559() {
+                            env.block.basefee = U256::zero();
+                        }
+                    }
+                }
+            }
+
             let meta = BlockchainDbMeta::new(env.clone(), eth_rpc_url.clone());
 
-            let block_chain_db = BlockchainDb::new(meta, self.block_cache_path());
+            let block_chain_db = BlockchainDb::new_with_allowlist(
+                meta,
+                self.block_cache_path(),
+                self.fork_cache_allowlist.clone(),
+            );
 
             // This will spawn the background thread that will use the provider to fetch
             // blockchain data from the other client
             let backend = SharedBackend::spawn_backend_thread(
                 Arc::clone(&provider),
                 block_chain_db.clone(),
+                // a fork that follows the head is pinned to every new head, see
+                // `Backend::follow_fork_head`
                 Some(fork_block_number.into()),
             );
+            if let Some(batch_size) = self.fork_batch_size {
+                backend
+                    .set_storage_batch_size(batch_size)
+                    .expect("Failed to configure the fork batch size");
+            }
+            if health.is_some() {
+                // uncached state that can't be fetched is reported by the backend instead
+                backend.set_panic_on_failure(false).expect("Failed to configure the fork backend");
+            }
 
             let db = Arc::new(RwLock::new(ForkedDatabase::new(backend, block_chain_db)));
             let fork = ClientFork::new(
@@ -623,12 +1186,46 @@ impl NodeConfig {
                     block_number: fork_block_number,
                     block_hash,
                     provider,
+                    max_concurrency: self.fork_max_concurrency,
+                    batch_size: self.fork_batch_size,
+                    retry_backoff: self.fork_retry_backoff,
+                    request_log,
+                    health,
                     chain_id,
                     timestamp: block.timestamp.as_u64(),
                     base_fee: block.base_fee_per_gas,
+                    cache_blocks: self.cache_fork_blocks,
+                    passthrough_receipts: self.fork_passthrough_receipts,
+                    gas_price_passthrough: self.fork_gas_price_passthrough,
                 },
                 Arc::clone(&db),
-            );
+            )
+            .with_call_cache(self.cache_calls);
+
+            // use the fees of the fork block, so that `eth_gasPrice` matches the forked chain
+            if self.fork_gas_price_passthrough {
+                fork.apply_gas_price_passthrough(&fees).await;
+                if !fees.is_eip1559() {
+                    self.gas_price = Some(fees.gas_price());
+                }
+            }
+
+            // storage that can't be preloaded is fetched lazily
+            let preloads =
+                self.fork_preload_storage.iter().map(|address| fork.preload_storage(*address));
+            for (address, res) in self.fork_preload_storage.iter().zip(join_all(preloads).await) {
+                if let Err(err) = res {
+                    if !self.silent {
+                        eprintln!(
+                            "{}",
+                            Paint::yellow(format!(
+                                "Warning: failed to preload the storage of {:?}, it's fetched lazily instead: {}",
+                                address, err
+                            ))
+                        );
+                    }
+                }
+            }
 
             (db, Some(fork))
         } else {
@@ -638,15 +1235,28 @@ impl NodeConfig {
         let genesis = GenesisConfig {
             balance: self.genesis_balance,
             accounts: self.genesis_accounts.iter().map(|acc| acc.address()).collect(),
+            custom_accounts: self.custom_genesis_accounts.clone(),
         };
         // only memory based backend for now
 
         let backend =
-            mem::Backend::with_genesis(db, Arc::new(RwLock::new(env)), genesis, fees, fork);
+            mem::Backend::with_genesis(db, Arc::new(RwLock::new(env)), genesis, fees, fork)
+                .with_trace_verbosity(if self.silent { 0 } else { self.get_trace_verbosity() })
+                .with_console_log(self.enable_console_log)
+                .with_gas_estimation_cap(self.gas_estimation_cap)
+                .with_optimism(self.optimism)
+                .with_mine_on_state_change(self.mine_on_state_change)
+                .with_record_cheats(self.record_cheats)
+                .with_hardfork_transitions(
+                    self.hardfork_transitions.iter().map(|t| (t.block, t.hardfork.into())),
+                )
+                .with_fork_snapshot(self.fork_snapshot_on_start)
+                .with_gas_price_oracle(self.gas_price_oracle);
 
         if let Some(timestamp) = fork_timestamp {
             backend.time().set_start_timestamp(timestamp.as_u64());
         }
+        backend.load_history(self.state_history.clone());
         backend
     }
 }
@@ -685,12 +1295,66 @@ impl FromStr for Hardfork {
             "berlin" | "10" => Hardfork::Berlin,
             "london" | "11" => Hardfork::London,
             "latest" | "12" => Hardfork::Latest,
+            "merge" | "paris" | "shanghai" | "cancun" => {
+                return Err(format!("Hardfork {} is not supported by the EVM yet", s))
+            }
             _ => return Err(format!("Unknown hardfork {}", s)),
         };
         Ok(hardfork)
     }
 }
 
+impl Hardfork {
+    /// Returns the hardfork that's active at the given block, if the hardfork schedule of the
+    /// chain is known
+    ///
+    /// Known chains are Ethereum mainnet, Goerli and Sepolia. Blocks since London return
+    /// [Hardfork::Latest].
+    pub fn from_chain_and_number(chain_id: u64, block: u64) -> Option<Self> {
+        // activation blocks, latest first
+        const MAINNET: [(u64, Hardfork); 10] = [
+            (12_965_000, Hardfork::Latest),
+            (12_244_000, Hardfork::Berlin),
+            (9_200_000, Hardfork::Muirglacier),
+            (9_069_000, Hardfork::Istanbul),
+            (7_280_000, Hardfork::Petersburg),
+            (4_370_000, Hardfork::Byzantine),
+            (2_675_000, Hardfork::SpuriousDragon),
+            (2_463_000, Hardfork::Tangerine),
+            (1_150_000, Hardfork::Homestead),
+            (0, Hardfork::Frontier),
+        ];
+        // goerli started with petersburg and skipped muir glacier
+        const GOERLI: [(u64, Hardfork); 4] = [
+            (5_062_605, Hardfork::Latest),
+            (4_460_644, Hardfork::Berlin),
+            (1_561_651, Hardfork::Istanbul),
+            (0, Hardfork::Petersburg),
+        ];
+        // sepolia started with london
+        const SEPOLIA: [(u64, Hardfork); 1] = [(0, Hardfork::Latest)];
+
+        let schedule: &[(u64, Hardfork)] = match chain_id {
+            1 => &MAINNET,
+            5 => &GOERLI,
+            11155111 => &SEPOLIA,
+            _ => return None,
+        };
+        schedule.iter().find(|(activation, _)| block >= *activation).map(|(_, hardfork)| *hardfork)
+    }
+
+    /// Returns true if EIP-1559 is active with this hardfork
+    pub fn is_post_london(self) -> bool {
+        (SpecId::from(self) as u8) >= (SpecId::LONDON as u8)
+    }
+
+    /// Returns true if both hardforks are the same, or both are London or later
+    pub fn is_same_era(self, other: Hardfork) -> bool {
+        SpecId::from(self) == SpecId::from(other) ||
+            (self.is_post_london() && other.is_post_london())
+    }
+}
+
 impl Default for Hardfork {
     fn default() -> Self {
         Hardfork::Latest
@@ -716,12 +1380,33 @@ impl From<Hardfork> for SpecId {
     }
 }
 
+/// A hardfork that becomes active at a block, parsed from `<hardfork>:<block>`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct HardforkTransition {
+    pub hardfork: Hardfork,
+    pub block: u64,
+}
+
+impl FromStr for HardforkTransition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hardfork, block) =
+            s.split_once(':').ok_or_else(|| format!("Expected <hardfork>:<block>, got {}", s))?;
+        let hardfork = hardfork.parse()?;
+        let block = block.parse().map_err(|_| format!("Invalid block number {}", block))?;
+        Ok(HardforkTransition { hardfork, block })
+    }
+}
+
 /// Can create dev accounts
 #[derive(Debug, Clone)]
 pub struct AccountGenerator {
     chain_id: u64,
     amount: usize,
     phrase: String,
+    /// Raw BIP32 seed that's used instead of the `phrase` if set
+    seed: Option<Vec<u8>>,
     derivation_path: Option<String>,
 }
 
@@ -733,6 +1418,7 @@ impl AccountGenerator {
             phrase: Mnemonic::<English>::new(&mut thread_rng())
                 .to_phrase()
                 .expect("Failed to create mnemonic phrase"),
+            seed: None,
             derivation_path: None,
         }
     }
@@ -747,6 +1433,13 @@ impl AccountGenerator {
         &self.phrase
     }
 
+    /// Derives the accounts from the given BIP32 seed instead of a mnemonic phrase
+    #[must_use]
+    pub fn seed(mut self, seed: impl Into<Vec<u8>>) -> Self {
+        self.seed = Some(seed.into());
+        self
+    }
+
     #[must_use]
     pub fn chain_id(mut self, chain_id: impl Into<u64>) -> Self {
         self.chain_id = chain_id.into();
@@ -770,6 +1463,10 @@ impl AccountGenerator {
 
 impl AccountGenerator {
     pub fn gen(&self) -> Vec<Wallet<SigningKey>> {
+        if let Some(ref seed) = self.seed {
+            return self.gen_from_seed(seed)
+        }
+
         let builder = MnemonicBuilder::<English>::default().phrase(self.phrase.as_str());
 
         // use the
@@ -785,6 +1482,22 @@ impl AccountGenerator {
         }
         wallets
     }
+
+    /// Derives the accounts from the root key of the seed, like a mnemonic's seed
+    fn gen_from_seed(&self, seed: &[u8]) -> Vec<Wallet<SigningKey>> {
+        let root = XPriv::root_from_seed(seed, None).expect("Invalid BIP32 seed");
+        let derivation_path = self.get_derivation_path();
+
+        let mut wallets = Vec::with_capacity(self.amount);
+
+        for idx in 0..self.amount {
+            let child = root.derive_path(format!("{}{}", derivation_path, idx).as_str()).unwrap();
+            let key: &coins_bip32::prelude::SigningKey = child.as_ref();
+            let key = SigningKey::from_bytes(&key.to_bytes()).unwrap();
+            wallets.push(Wallet::from(key).with_chain_id(self.chain_id))
+        }
+        wallets
+    }
 }
 
 /// Finds the latest appropriate block to fork
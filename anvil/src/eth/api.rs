@@ -1,7 +1,13 @@
 use crate::{
     eth::{
         backend,
-        backend::{notifications::NewBlockNotifications, validate::TransactionValidator},
+        backend::{
+            db::Db,
+            fork::{new_fork_provider, redact_url, EthCallCacheKey},
+            genesis::{SerializableFork, SerializableState},
+            notifications::NewBlockNotifications,
+            validate::TransactionValidator,
+        },
         error::{
             BlockchainError, FeeHistoryError, InvalidTransactionError, Result, ToRpcResponseResult,
         },
@@ -15,52 +21,68 @@ use crate::{
             Pool,
         },
         sign,
-        sign::Signer,
+        sign::{KeyStore, Signer},
         util::PRECOMPILES,
     },
     filter::{EthFilter, Filters, LogsFilter},
-    mem::transaction_build,
+    mem::{storage::MinedBlockOutcome, transaction_build},
     revm::TransactOut,
-    ClientFork, LoggingManager, Miner, MiningMode, Provider, StorageInfo,
+    ClientFork, LoggingManager, Miner, MiningMode, StorageInfo,
 };
 use anvil_core::{
     eth::{
         block::BlockInfo,
         call::CallRequest,
         transaction::{
-            EthTransactionRequest, LegacyTransaction, PendingTransaction, TypedTransaction,
-            TypedTransactionRequest,
+            EthTransactionRequest, PendingTransaction, TypedTransaction, TypedTransactionRequest,
         },
         EthRequest,
     },
-    types::{EvmMineOptions, Forking, GethDebugTracingOptions, Index, Work},
+    types::{
+        AccountOverride, AccountStateDiff, AccountUpdate, CheatLogEntry, EvmMineOptions, ForkInfo,
+        ForkStats, Forking, FundedAccount, GethDebugTracingOptions, GethTraceResult, Index,
+        NodeEnvironment, NodeInfo, OptimismTransactionReceipt, OtsBlock, OtsBlockDetails,
+        OtsBlockTransactions, OtsContractCreator, OtsInternalOperation, OtsIssuance, OtsReceipt,
+        OtsSearchTransactions, OtsTrace, SimulatedTransaction, StateOverride, Work,
+    },
 };
 use anvil_rpc::{error::RpcError, response::ResponseResult};
 use ethers::{
-    abi::ethereum_types::H64,
+    abi::{ethereum_types::H64, AbiDecode, Token},
     prelude::TxpoolInspect,
-    providers::{Http, ProviderError, RetryClient},
     types::{
-        transaction::eip2930::{AccessList, AccessListItem, AccessListWithGasUsed},
+        transaction::{
+            eip2930::{AccessList, AccessListItem, AccessListWithGasUsed},
+            eip712::{Eip712, TypedData},
+        },
         Address, Block, BlockId, BlockNumber, Bytes, FeeHistory, Filter, FilteredParams, Log,
         Trace, Transaction, TransactionReceipt, TransactionRequest as EthersTransactionRequest,
         TransactionRequest, TxHash, TxpoolContent, TxpoolInspectSummary, TxpoolStatus, H256, U256,
         U64,
     },
-    utils::rlp,
+    utils::{id, keccak256},
 };
 use foundry_evm::{
     revm::{return_ok, return_revert, Return},
     utils::u256_to_h256_be,
 };
-use futures::channel::mpsc::Receiver;
+use futures::{channel::mpsc::Receiver, StreamExt};
 use parking_lot::RwLock;
-use std::{sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 use tracing::trace;
 
 /// The client version: `anvil/v{major}.{minor}.{patch}`
 pub const CLIENT_VERSION: &str = concat!("anvil/v", env!("CARGO_PKG_VERSION"));
 
+/// How many storage slots `anvil_setErc721Owner` probes for the owner mapping of a token
+///
+/// This covers the upgradeable OpenZeppelin layout, which is preceded by storage gaps.
+const ERC721_MAX_PROBED_SLOTS: u64 = 200;
+
 /// The entry point for executing eth api RPC call - The Eth RPC interface.
 ///
 /// This type is cheap to clone and can be used concurrently
@@ -75,6 +97,8 @@ pub struct EthApi {
     is_mining: bool,
     /// available signers
     signers: Arc<Vec<Box<dyn Signer>>>,
+    /// keys of the accounts created via `personal_newAccount`, also part of the `signers`
+    key_store: KeyStore,
     /// data required for `eth_feeHistory`
     fee_history_cache: FeeHistoryCache,
     /// max number of items kept in fee cache
@@ -90,6 +114,11 @@ pub struct EthApi {
     filters: Filters,
     /// How transactions are ordered in the pool
     transaction_order: Arc<RwLock<TransactionOrder>>,
+    /// Senders whose transactions are always ordered first in the pool
+    priority_senders: Arc<HashSet<Address>>,
+    /// Function selectors whose calls are ordered after priority senders but before all other
+    /// transactions in the pool
+    priority_selectors: Arc<HashSet<[u8; 4]>>,
     /// Whether we're listening for RPC calls
     net_listening: bool,
 }
@@ -103,25 +132,31 @@ impl EthApi {
         pool: Arc<Pool>,
         backend: Arc<backend::mem::Backend>,
         signers: Arc<Vec<Box<dyn Signer>>>,
+        key_store: KeyStore,
         fee_history_cache: FeeHistoryCache,
         fee_history_limit: u64,
         miner: Miner,
         logger: LoggingManager,
         filters: Filters,
-        transactions_order: TransactionOrder,
+        transaction_order: Arc<RwLock<TransactionOrder>>,
+        priority_senders: HashSet<Address>,
+        priority_selectors: HashSet<[u8; 4]>,
     ) -> Self {
         Self {
             pool,
             backend,
             is_mining: true,
             signers,
+            key_store,
             fee_history_cache,
             fee_history_limit,
             miner,
             logger,
             filters,
             net_listening: true,
-            transaction_order: Arc::new(RwLock::new(transactions_order)),
+            transaction_order,
+            priority_senders: Arc::new(priority_senders),
+            priority_selectors: Arc::new(priority_selectors),
         }
     }
 
@@ -143,6 +178,8 @@ impl EthApi {
             EthRequest::EthChainId(_) => self.eth_chain_id().to_rpc_result(),
             EthRequest::EthNetworkId(_) => self.network_id().to_rpc_result(),
             EthRequest::NetListening(_) => self.net_listening().to_rpc_result(),
+            EthRequest::EthProtocolVersion(_) => self.protocol_version().to_rpc_result(),
+            EthRequest::EthSyncing(_) => self.syncing().to_rpc_result(),
             EthRequest::EthGasPrice(_) => self.gas_price().to_rpc_result(),
             EthRequest::EthAccounts(_) => self.accounts().to_rpc_result(),
             EthRequest::EthBlockNumber(_) => self.block_number().to_rpc_result(),
@@ -173,17 +210,22 @@ impl EthApi {
                 self.block_transaction_count_by_number(num).to_rpc_result()
             }
             EthRequest::EthGetUnclesCountByHash(hash) => {
-                self.block_uncles_count_by_hash(hash).to_rpc_result()
+                self.block_uncles_count_by_hash(hash).await.to_rpc_result()
             }
             EthRequest::EthGetUnclesCountByNumber(num) => {
-                self.block_uncles_count_by_number(num).to_rpc_result()
+                self.block_uncles_count_by_number(num).await.to_rpc_result()
             }
             EthRequest::EthGetCodeAt(addr, block) => {
                 self.get_code(addr, block).await.to_rpc_result()
             }
             EthRequest::EthSign(addr, content) => self.sign(addr, content).await.to_rpc_result(),
+            EthRequest::EthSignTypedDataV4(addr, data) => {
+                self.sign_typed_data_v4(addr, &data).await.to_rpc_result()
+            }
             EthRequest::EthSendRawTransaction(tx) => self.send_raw_transaction(tx).to_rpc_result(),
-            EthRequest::EthCall(call, block) => self.call(call, block).await.to_rpc_result(),
+            EthRequest::EthCall(call, block, overrides) => {
+                self.call(call, block, overrides).await.to_rpc_result()
+            }
             EthRequest::EthCreateAccessList(call, block) => {
                 self.create_access_list(call, block).await.to_rpc_result()
             }
@@ -196,9 +238,18 @@ impl EthApi {
             EthRequest::EthGetTransactionByBlockNumberAndIndex(num, index) => {
                 self.transaction_by_block_number_and_index(num, index).await.to_rpc_result()
             }
+            EthRequest::EthGetTransactionReceipt(tx) if self.backend.is_optimism() => {
+                self.optimism_transaction_receipt(tx).await.to_rpc_result()
+            }
             EthRequest::EthGetTransactionReceipt(tx) => {
                 self.transaction_receipt(tx).await.to_rpc_result()
             }
+            EthRequest::EthGetBlockReceipts(block) if self.backend.is_optimism() => {
+                self.optimism_block_receipts(block).await.to_rpc_result()
+            }
+            EthRequest::EthGetBlockReceipts(block) => {
+                self.block_receipts(block).await.to_rpc_result()
+            }
             EthRequest::EthGetUncleByBlockHashAndIndex(hash, index) => {
                 self.uncle_by_block_hash_and_index(hash, index).to_rpc_result()
             }
@@ -221,6 +272,13 @@ impl EthApi {
             EthRequest::DebugTraceTransaction(tx, opts) => {
                 self.debug_trace_transaction(tx, opts).await.to_rpc_result()
             }
+            EthRequest::DebugTraceBlockByNumber(number, opts) => {
+                self.debug_trace_block_by_number(number, opts).await.to_rpc_result()
+            }
+            EthRequest::DebugTraceBlockByHash(hash, opts) => {
+                self.debug_trace_block_by_hash(hash, opts).await.to_rpc_result()
+            }
+            EthRequest::DebugSetHead(num) => self.debug_set_head(num).await.to_rpc_result(),
             EthRequest::TraceTransaction(tx) => self.trace_transaction(tx).await.to_rpc_result(),
             EthRequest::TraceBlock(block) => self.trace_block(block).await.to_rpc_result(),
             EthRequest::ImpersonateAccount(addr) => {
@@ -243,9 +301,27 @@ impl EthApi {
                 self.anvil_drop_transaction(tx).await.to_rpc_result()
             }
             EthRequest::Reset(fork) => self.anvil_reset(fork).await.to_rpc_result(),
+            EthRequest::Rollback(n) => self.anvil_rollback(n).await.to_rpc_result(),
+            EthRequest::GetFork(()) => self.anvil_get_fork().to_rpc_result(),
+            EthRequest::NodeInfo(()) => self.anvil_node_info().to_rpc_result(),
+            EthRequest::SimulateBundle(txs, block) => {
+                self.anvil_simulate_bundle(txs, block).await.to_rpc_result()
+            }
+            EthRequest::DumpStateDiff(()) => self.anvil_dump_state_diff().to_rpc_result(),
+            EthRequest::GetForkStats(reset) => self
+                .anvil_get_fork_stats(reset.and_then(|p| p.params).unwrap_or_default())
+                .to_rpc_result(),
+            EthRequest::GetStateRoot(()) => self.anvil_get_state_root().to_rpc_result(),
+            EthRequest::GetMempoolOrder(()) => self.anvil_get_mempool_order().await.to_rpc_result(),
             EthRequest::SetBalance(addr, val) => {
                 self.anvil_set_balance(addr, val).await.to_rpc_result()
             }
+            EthRequest::AddBalance(addr, val) => {
+                self.anvil_add_balance(addr, val).await.to_rpc_result()
+            }
+            EthRequest::DeployCode(code, from, value) => {
+                self.anvil_deploy_code(code, from, value).await.to_rpc_result()
+            }
             EthRequest::SetCode(addr, code) => {
                 self.anvil_set_code(addr, code).await.to_rpc_result()
             }
@@ -255,20 +331,57 @@ impl EthApi {
             EthRequest::SetStorageAt(addr, slot, val) => {
                 self.anvil_set_storage_at(addr, slot, val).await.to_rpc_result()
             }
+            EthRequest::SetFundedAccounts(accounts) => {
+                self.anvil_set_funded_accounts(accounts).await.to_rpc_result()
+            }
+            EthRequest::SetStorageBatch(addr, slots) => {
+                self.anvil_set_storage_batch(addr, slots).await.to_rpc_result()
+            }
+            EthRequest::SetAccount(addr, account) => {
+                self.anvil_set_account(addr, account).await.to_rpc_result()
+            }
+            EthRequest::GetCheatLog(()) => self.anvil_get_cheat_log().to_rpc_result(),
+            EthRequest::SetErc721Owner(token, token_id, owner) => {
+                self.anvil_set_erc721_owner(token, token_id, owner).await.to_rpc_result()
+            }
             EthRequest::SetCoinbase(addr) => self.anvil_set_coinbase(addr).await.to_rpc_result(),
+            EthRequest::SetNextBlockCoinbase(addr) => {
+                self.anvil_set_next_block_coinbase(addr).await.to_rpc_result()
+            }
+            EthRequest::SetChainId(chain_id) => self.anvil_set_chain_id(chain_id).to_rpc_result(),
             EthRequest::SetLogging(log) => self.anvil_set_logging(log).await.to_rpc_result(),
             EthRequest::SetMinGasPrice(gas) => {
                 self.anvil_set_min_gas_price(gas).await.to_rpc_result()
             }
+            EthRequest::RemoveMinGasPrice(()) => self.anvil_remove_min_gas_price().to_rpc_result(),
+            EthRequest::SetMinSuggestedPriorityFee(fee) => {
+                self.anvil_set_min_suggested_priority_fee(fee).to_rpc_result()
+            }
+            EthRequest::SetBlockGasLimit(gas_limit) => {
+                self.anvil_set_block_gas_limit(gas_limit).to_rpc_result()
+            }
             EthRequest::SetNextBlockBaseFeePerGas(gas) => {
                 self.anvil_set_next_block_base_fee_per_gas(gas).await.to_rpc_result()
             }
+            EthRequest::SetNextTransactionFails(reason) => {
+                self.anvil_set_next_transaction_fails(reason).to_rpc_result()
+            }
+            EthRequest::WaitForTransaction(hash, timeout) => {
+                self.anvil_wait_for_transaction(hash, timeout).await.to_rpc_result()
+            }
+            EthRequest::SetNextBlockGasUsed(gas) => {
+                self.anvil_set_next_block_gas_used(gas).to_rpc_result()
+            }
+            EthRequest::SetBlockNumber(num) => self.anvil_set_block_number(num).to_rpc_result(),
             EthRequest::EvmSnapshot(_) => self.evm_snapshot().await.to_rpc_result(),
             EthRequest::EvmRevert(id) => self.evm_revert(id).await.to_rpc_result(),
             EthRequest::EvmIncreaseTime(time) => self.evm_increase_time(time).await.to_rpc_result(),
             EthRequest::EvmSetNextBlockTimeStamp(time) => {
                 self.evm_set_next_block_timestamp(time).to_rpc_result()
             }
+            EthRequest::MineWithTimestamp(timestamp) => {
+                self.anvil_mine_with_timestamp(timestamp).await.to_rpc_result()
+            }
             EthRequest::EvmSetBlockTimeStampInterval(time) => {
                 self.evm_set_block_timestamp_interval(time).to_rpc_result()
             }
@@ -294,17 +407,55 @@ impl EthApi {
             EthRequest::TxPoolStatus(_) => self.txpool_status().await.to_rpc_result(),
             EthRequest::TxPoolInspect(_) => self.txpool_inspect().await.to_rpc_result(),
             EthRequest::TxPoolContent(_) => self.txpool_content().await.to_rpc_result(),
+            EthRequest::PersonalNewAccount(_) => self.personal_new_account().to_rpc_result(),
+            EthRequest::PersonalUnlockAccount(addr, _, _) => {
+                self.personal_unlock_account(addr).to_rpc_result()
+            }
+            EthRequest::PersonalSendTransaction(request, _) => {
+                self.personal_send_transaction(*request).await.to_rpc_result()
+            }
+            EthRequest::OtsGetApiLevel(_) => self.ots_get_api_level().to_rpc_result(),
+            EthRequest::OtsGetInternalOperations(hash) => {
+                self.ots_get_internal_operations(hash).to_rpc_result()
+            }
+            EthRequest::OtsSearchTransactionsBefore(address, block, page_size) => {
+                self.ots_search_transactions_before(address, block, page_size).to_rpc_result()
+            }
+            EthRequest::OtsSearchTransactionsAfter(address, block, page_size) => {
+                self.ots_search_transactions_after(address, block, page_size).to_rpc_result()
+            }
+            EthRequest::OtsGetTransactionBySenderAndNonce(sender, nonce) => {
+                self.ots_get_transaction_by_sender_and_nonce(sender, nonce).to_rpc_result()
+            }
+            EthRequest::OtsGetBlockDetails(number) => {
+                self.ots_get_block_details(number).await.to_rpc_result()
+            }
+            EthRequest::OtsGetBlockTransactions(number, page, page_size) => {
+                self.ots_get_block_transactions(number, page, page_size).await.to_rpc_result()
+            }
+            EthRequest::OtsHasCode(address, number) => {
+                self.ots_has_code(address, number).await.to_rpc_result()
+            }
+            EthRequest::OtsTraceTransaction(hash) => {
+                self.ots_trace_transaction(hash).to_rpc_result()
+            }
+            EthRequest::OtsGetTransactionError(hash) => {
+                self.ots_get_transaction_error(hash).to_rpc_result()
+            }
+            EthRequest::OtsGetContractCreator(address) => {
+                self.ots_get_contract_creator(address).to_rpc_result()
+            }
         }
     }
 
-    fn sign_request(
+    async fn sign_request(
         &self,
         from: &Address,
         request: TypedTransactionRequest,
     ) -> Result<TypedTransaction> {
         for signer in self.signers.iter() {
             if signer.accounts().contains(from) {
-                return signer.sign_transaction(request, from)
+                return signer.sign_transaction(request, from).await
             }
         }
         Err(BlockchainError::NoSignerAvailable)
@@ -332,12 +483,20 @@ impl EthApi {
         Ok(ethers::utils::hex::encode(&hash[..]))
     }
 
-    /// Returns protocol version encoded as a string (quotes are necessary).
+    /// Returns the version of the `eth` wire protocol, which is `eth/65`.
     ///
     /// Handler for ETH RPC call: `eth_protocolVersion`
-    pub fn protocol_version(&self) -> Result<u64> {
+    pub fn protocol_version(&self) -> Result<U64> {
         node_info!("eth_protocolVersion");
-        Ok(1)
+        Ok(65u64.into())
+    }
+
+    /// Returns `false`, since the node is never syncing.
+    ///
+    /// Handler for ETH RPC call: `eth_syncing`
+    pub fn syncing(&self) -> Result<bool> {
+        node_info!("eth_syncing");
+        Ok(false)
     }
 
     /// Returns the number of hashes per second that the node is mining with.
@@ -374,7 +533,7 @@ impl EthApi {
         Ok(Some(self.backend.chain_id().as_u64().into()))
     }
 
-    /// Returns the same as `chain_id`
+    /// Returns the same as `chain_id`, as a decimal string
     ///
     /// Handler for ETH RPC call: `eth_networkId`
     pub fn network_id(&self) -> Result<Option<String>> {
@@ -426,7 +585,7 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_getBalance`
     pub async fn balance(&self, address: Address, block_number: Option<BlockId>) -> Result<U256> {
         node_info!("eth_getBalance");
-        let number = self.backend.ensure_block_number(block_number)?;
+        let number = self.backend.ensure_block_id(block_number).await?;
         self.backend.get_balance(address, Some(number.into())).await
     }
 
@@ -440,7 +599,7 @@ impl EthApi {
         block_number: Option<BlockId>,
     ) -> Result<H256> {
         node_info!("eth_getStorageAt");
-        let number = self.backend.ensure_block_number(block_number)?;
+        let number = self.backend.ensure_block_id(block_number).await?;
         self.backend.storage_at(address, index, Some(number.into())).await
     }
 
@@ -466,7 +625,7 @@ impl EthApi {
     pub async fn block_by_number(&self, number: BlockNumber) -> Result<Option<Block<TxHash>>> {
         node_info!("eth_getBlockByNumber");
         if number == BlockNumber::Pending {
-            return Ok(Some(self.pending_block()))
+            return Ok(Some(self.pending_block().await))
         }
 
         self.backend.block_by_number(number).await
@@ -481,7 +640,7 @@ impl EthApi {
     ) -> Result<Option<Block<Transaction>>> {
         node_info!("eth_getBlockByNumber");
         if number == BlockNumber::Pending {
-            return Ok(self.pending_block_full())
+            return Ok(self.pending_block_full().await)
         }
         self.backend.block_by_number_full(number).await
     }
@@ -519,18 +678,26 @@ impl EthApi {
 
     /// Returns the number of uncles in a block with given hash.
     ///
+    /// Blocks mined by anvil never have uncles, only forked blocks can. Returns `None` if the
+    /// block doesn't exist.
+    ///
     /// Handler for ETH RPC call: `eth_getUncleCountByBlockHash`
-    pub fn block_uncles_count_by_hash(&self, _: H256) -> Result<U256> {
+    pub async fn block_uncles_count_by_hash(&self, hash: H256) -> Result<Option<U256>> {
         node_info!("eth_getUncleCountByBlockHash");
-        Err(BlockchainError::RpcUnimplemented)
+        let block = self.backend.block_by_hash(hash).await?;
+        Ok(block.map(|block| block.uncles.len().into()))
     }
 
     /// Returns the number of uncles in a block with given block number.
     ///
+    /// Blocks mined by anvil never have uncles, only forked blocks can. Returns `None` if the
+    /// block doesn't exist.
+    ///
     /// Handler for ETH RPC call: `eth_getUncleCountByBlockNumber`
-    pub fn block_uncles_count_by_number(&self, _: BlockNumber) -> Result<U256> {
+    pub async fn block_uncles_count_by_number(&self, number: BlockNumber) -> Result<Option<U256>> {
         node_info!("eth_getUncleCountByBlockNumber");
-        Err(BlockchainError::RpcUnimplemented)
+        let block = self.backend.block_by_number(number).await?;
+        Ok(block.map(|block| block.uncles.len().into()))
     }
 
     /// Returns the code at given address at given time (block number).
@@ -538,7 +705,7 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_getCode`
     pub async fn get_code(&self, address: Address, block_number: Option<BlockId>) -> Result<Bytes> {
         node_info!("eth_getCode");
-        let number = self.backend.ensure_block_number(block_number)?;
+        let number = self.backend.ensure_block_id(block_number).await?;
         self.backend.get_code(address, Some(number.into())).await
     }
 
@@ -552,6 +719,20 @@ impl EthApi {
         Ok(format!("0x{}", signature))
     }
 
+    /// Signs the [EIP-712](https://eips.ethereum.org/EIPS/eip-712) digest of the typed data
+    ///
+    /// Fails if the domain or types of the typed data can't be encoded.
+    ///
+    /// Handler for ETH RPC call: `eth_signTypedData_v4`
+    pub async fn sign_typed_data_v4(&self, address: Address, data: &TypedData) -> Result<String> {
+        node_info!("eth_signTypedData_v4");
+        data.encode_eip712()
+            .map_err(|err| RpcError::invalid_params(format!("Invalid typed data: {}", err)))?;
+        let signer = self.get_signer(address).ok_or(BlockchainError::NoSignerAvailable)?;
+        let signature = signer.sign_typed_data(address, data).await?;
+        Ok(format!("0x{}", signature))
+    }
+
     /// Sends a transaction
     ///
     /// Handler for ETH RPC call: `eth_sendTransaction`
@@ -573,7 +754,7 @@ impl EthApi {
             trace!(target : "node", "eth_sendTransaction: impersonating {:?}", from);
             PendingTransaction::with_sender(transaction, from)
         } else {
-            let transaction = self.sign_request(&from, request)?;
+            let transaction = self.sign_request(&from, request).await?;
             PendingTransaction::new(transaction)?
         };
 
@@ -592,27 +773,19 @@ impl EthApi {
     /// Handler for ETH RPC call: `eth_sendRawTransaction`
     pub fn send_raw_transaction(&self, tx: Bytes) -> Result<TxHash> {
         node_info!("eth_sendRawTransaction");
-        let data = tx.as_ref();
-        if data.is_empty() {
-            return Err(BlockchainError::EmptyRawTransactionData)
-        }
-        let transaction = if data[0] > 0x7f {
-            // legacy transaction
-            match rlp::decode::<LegacyTransaction>(data) {
-                Ok(transaction) => TypedTransaction::Legacy(transaction),
-                Err(_) => return Err(BlockchainError::FailedToDecodeSignedTransaction),
-            }
-        } else {
-            // the [TypedTransaction] requires a valid rlp input,
-            // but EIP-1559 prepends a version byte, so we need to encode the data first to get a
-            // valid rlp and then rlp decode impl of `TypedTransaction` will remove and check the
-            // version byte
-            let extend = rlp::encode(&data);
-            match rlp::decode::<TypedTransaction>(&extend[..]) {
-                Ok(transaction) => transaction,
-                Err(_) => return Err(BlockchainError::FailedToDecodeSignedTransaction),
+        let mut transaction = sign::decode_signed_transaction(tx.as_ref())?;
+
+        if let TypedTransaction::Deposit(ref mut deposit) = transaction {
+            if !self.backend.is_optimism() {
+                return Err(RpcError::invalid_params(
+                    "Deposit transactions are only supported with --optimism",
+                )
+                .into())
             }
-        };
+            // deposits don't have a nonce, they use the sender's next nonce
+            let on_chain_nonce = self.backend.current_nonce(deposit.from);
+            deposit.nonce = self.pending_nonce(deposit.from, on_chain_nonce);
+        }
 
         let pending_transaction = PendingTransaction::new(transaction)?;
 
@@ -624,30 +797,65 @@ impl EthApi {
         let nonce = *pending_transaction.transaction.nonce();
         let requires = required_marker(nonce, on_chain_nonce, from);
 
-        let priority = self.transaction_priority(&pending_transaction.transaction);
-        let pool_transaction = PoolTransaction {
-            requires,
-            provides: vec![to_marker(nonce.as_u64(), *pending_transaction.sender())],
-            pending_transaction,
-            priority,
-        };
-
-        let tx = self.pool.add_transaction(pool_transaction)?;
-        trace!(target: "node", "Added transaction: [{:?}] sender={:?}", tx.hash(), from);
-        Ok(*tx.hash())
+        let provides = vec![to_marker(nonce.as_u64(), from)];
+        self.add_pending_transaction(pending_transaction, requires, provides)
     }
 
     /// Call contract, returning the output data.
     ///
+    /// The optional `overrides` are applied to the state of the block before the call is executed.
+    ///
     /// Handler for ETH RPC call: `eth_call`
-    pub async fn call(&self, request: CallRequest, block_number: Option<BlockId>) -> Result<Bytes> {
+    pub async fn call(
+        &self,
+        request: CallRequest,
+        block_number: Option<BlockId>,
+        overrides: Option<StateOverride>,
+    ) -> Result<Bytes> {
         node_info!("eth_call");
-        let number = self.backend.ensure_block_number(block_number)?;
+        let number = self.backend.ensure_block_id(block_number).await?;
+        let overrides = overrides.unwrap_or_default();
+
+        if let Some((address, _)) = overrides
+            .iter()
+            .find(|(_, account)| account.state.is_some() && account.state_diff.is_some())
+        {
+            return Err(RpcError::invalid_params(format!(
+                "account {:?} has both 'state' and 'stateDiff'",
+                address
+            ))
+            .into())
+        }
+
+        if overrides.is_empty() {
+            if let Some(fork) = self.get_fork().filter(|fork| fork.is_caching_calls()) {
+                let key = EthCallCacheKey::new(&request, number);
+                if let Some(out) = fork.cached_call(&key) {
+                    trace!(target: "node", "eth_call cache hit for block {}", number);
+                    return Ok(out)
+                }
+                let generation = fork.call_cache_generation();
+                let out = self.do_call(request, number, overrides).await?;
+                fork.cache_call(key, generation, out.clone());
+                return Ok(out)
+            }
+        }
+
+        self.do_call(request, number, overrides).await
+    }
+
+    /// Executes the call at the given block number
+    async fn do_call(
+        &self,
+        request: CallRequest,
+        number: u64,
+        overrides: StateOverride,
+    ) -> Result<Bytes> {
         let block_number = Some(number.into());
         // check if the number predates the fork, if in fork mode
         if let Some(fork) = self.get_fork() {
             if fork.predates_fork(number) {
-                return Ok(fork.call(&request, block_number).await?)
+                return Ok(fork.call_with_state(&request, block_number, &overrides).await?)
             }
         }
 
@@ -658,7 +866,8 @@ impl EthApi {
         )?
         .or_zero_fees();
 
-        let (exit, out, gas, _) = self.backend.call(request, fees, block_number).await?;
+        let (exit, out, gas, _) =
+            self.backend.call_with_state(request, fees, block_number, overrides).await?;
 
         trace!(target = "node", "Call status {:?}, gas {}", exit, gas);
 
@@ -684,7 +893,7 @@ impl EthApi {
         block_number: Option<BlockId>,
     ) -> Result<AccessListWithGasUsed> {
         node_info!("eth_createAccessList");
-        let number = self.backend.ensure_block_number(block_number)?;
+        let number = self.backend.ensure_block_id(block_number).await?;
         let block_number = Some(number.into());
         // check if the number predates the fork, if in fork mode
         if let Some(fork) = self.get_fork() {
@@ -750,15 +959,19 @@ impl EthApi {
         node_info!("eth_getTransactionByHash");
         let mut tx = self.backend.transaction_by_hash(hash).await?;
         if tx.is_none() {
-            // no transaction found, check the mempool for a pending transaction
+            // no transaction found, check the mempool for a pending transaction, which has no block
             tx = self.pool.get_transaction(hash).map(|pending| {
-                transaction_build(
+                let from = *pending.sender();
+                let mut tx = transaction_build(
                     pending.transaction,
                     None,
                     None,
                     true,
                     Some(self.backend.base_fee()),
-                )
+                );
+                // the sender of an impersonated transaction can't be recovered from its signature
+                tx.from = from;
+                tx
             });
         }
 
@@ -797,8 +1010,46 @@ impl EthApi {
         self.backend.transaction_receipt(hash).await
     }
 
+    /// Returns all transaction receipts of the given block.
+    ///
+    /// Handler for ETH RPC call: `eth_getBlockReceipts`
+    pub async fn block_receipts(&self, block: BlockId) -> Result<Option<Vec<TransactionReceipt>>> {
+        node_info!("eth_getBlockReceipts");
+        self.backend.block_receipts(block).await
+    }
+
+    /// Returns the transaction receipt with the L1 fee fields of Optimism, see
+    /// [`Backend::mined_transaction_l1_fee`](crate::eth::backend::mem::Backend::mined_transaction_l1_fee)
+    ///
+    /// Handler for ETH RPC call: `eth_getTransactionReceipt` with `--optimism`
+    pub async fn optimism_transaction_receipt(
+        &self,
+        hash: H256,
+    ) -> Result<Option<OptimismTransactionReceipt>> {
+        Ok(self.transaction_receipt(hash).await?.map(|receipt| self.with_l1_fee(receipt)))
+    }
+
+    /// Returns all transaction receipts of the given block with the L1 fee fields of Optimism
+    ///
+    /// Handler for ETH RPC call: `eth_getBlockReceipts` with `--optimism`
+    pub async fn optimism_block_receipts(
+        &self,
+        block: BlockId,
+    ) -> Result<Option<Vec<OptimismTransactionReceipt>>> {
+        Ok(self.block_receipts(block).await?.map(|receipts| {
+            receipts.into_iter().map(|receipt| self.with_l1_fee(receipt)).collect()
+        }))
+    }
+
+    fn with_l1_fee(&self, receipt: TransactionReceipt) -> OptimismTransactionReceipt {
+        let l1_fee = self.backend.mined_transaction_l1_fee(receipt.transaction_hash);
+        OptimismTransactionReceipt { receipt, l1_fee }
+    }
+
     /// Returns an uncles at given block and index.
     ///
+    /// This is always `null`, since blocks mined by anvil don't have uncles.
+    ///
     /// Handler for ETH RPC call: `eth_getUncleByBlockHashAndIndex`
     pub fn uncle_by_block_hash_and_index(
         &self,
@@ -809,11 +1060,17 @@ impl EthApi {
         Ok(None)
     }
 
+    /// Returns an uncles at given block and index.
+    ///
+    /// This is always `null`, since blocks mined by anvil don't have uncles.
+    ///
+    /// Handler for ETH RPC call: `eth_getUncleByBlockNumberAndIndex`
     pub fn uncle_by_block_number_and_index(
         &self,
         _: BlockNumber,
         _: Index,
     ) -> Result<Option<Block<TxHash>>> {
+        node_info!("eth_getUncleByBlockNumberAndIndex");
         Ok(None)
     }
 
@@ -960,12 +1217,13 @@ impl EthApi {
     }
 
     /// Introduced in EIP-1159, a Geth-specific and simplified priority fee oracle.
-    /// Leverages the already existing fee history cache.
+    ///
+    /// Returns the priority fee that is added to the base fee for `eth_gasPrice`.
     ///
     /// Handler for ETH RPC call: `eth_maxPriorityFeePerGas`
     pub fn max_priority_fee_per_gas(&self) -> Result<U256> {
         node_info!("eth_maxPriorityFeePerGas");
-        Err(BlockchainError::RpcUnimplemented)
+        Ok(self.backend.max_priority_fee_per_gas())
     }
 
     /// Creates a filter object, based on filter options, to notify when the state changes (logs).
@@ -1046,6 +1304,55 @@ impl EthApi {
         Err(RpcError::method_not_found().into())
     }
 
+    /// Returns the struct logs of all transactions of the block, one result per transaction, for
+    /// geth's tracing endpoint
+    ///
+    /// Handler for RPC call: `debug_traceBlockByNumber`
+    pub async fn debug_trace_block_by_number(
+        &self,
+        number: BlockNumber,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<GethTraceResult>> {
+        node_info!("debug_traceBlockByNumber");
+        let hash = self
+            .backend
+            .block_by_number(number)
+            .await?
+            .and_then(|block| block.hash)
+            .ok_or(BlockchainError::BlockNotFound)?;
+        self.do_debug_trace_block(hash, opts).await
+    }
+
+    /// Returns the struct logs of all transactions of the block, one result per transaction, for
+    /// geth's tracing endpoint
+    ///
+    /// Handler for RPC call: `debug_traceBlockByHash`
+    pub async fn debug_trace_block_by_hash(
+        &self,
+        hash: H256,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<GethTraceResult>> {
+        node_info!("debug_traceBlockByHash");
+        if self.backend.block_by_hash(hash).await?.is_none() {
+            return Err(BlockchainError::BlockNotFound)
+        }
+        self.do_debug_trace_block(hash, opts).await
+    }
+
+    /// Replays the transactions of the block with geth's default struct logger
+    async fn do_debug_trace_block(
+        &self,
+        hash: H256,
+        opts: GethDebugTracingOptions,
+    ) -> Result<Vec<GethTraceResult>> {
+        if opts.tracer.is_some() {
+            return Err(
+                RpcError::invalid_params("Only the default struct logger is supported").into()
+            )
+        }
+        self.backend.debug_trace_block(hash, &opts).await
+    }
+
     /// Returns traces for the transaction hash via parity's tracing endpoint
     ///
     /// Handler for RPC call: `trace_transaction`
@@ -1113,6 +1420,11 @@ impl EthApi {
 
     /// Mines a series of blocks.
     ///
+    /// The first block gets the timestamp it would get if it was mined on its own. If `interval`
+    /// is set, every following block's timestamp is the previous block's timestamp plus
+    /// `interval`, which overrides the interval set via `anvil_setBlockTimestampInterval` for this
+    /// call only. Otherwise that interval applies to all blocks.
+    ///
     /// Handler for ETH RPC call: `anvil_mine`
     pub async fn anvil_mine(&self, num_blocks: Option<U256>, interval: Option<U256>) -> Result<()> {
         node_info!("anvil_mine");
@@ -1123,12 +1435,13 @@ impl EthApi {
         }
 
         // mine all the blocks
-        for _ in 0..blocks.as_u64() {
-            self.mine_one().await;
-
+        for i in 0..blocks.as_u64() {
             if let Some(interval) = interval {
-                tokio::time::sleep(Duration::from_secs(interval)).await;
+                if i > 0 {
+                    self.backend.time().set_next_block_timestamp_interval(interval);
+                }
             }
+            self.mine_one().await;
         }
 
         Ok(())
@@ -1155,24 +1468,251 @@ impl EthApi {
 
     /// Reset the fork to a fresh forked state, and optionally update the fork config.
     ///
-    /// If `forking` is `None` then this will disable forking entirely.
+    /// If `forking` is `None` and the state was captured right after forking, see
+    /// `--fork-snapshot-on-start`, then that state is restored without any requests to the fork
+    /// endpoint.
     ///
     /// Handler for RPC call: `anvil_reset`
     pub async fn anvil_reset(&self, forking: Option<Forking>) -> Result<()> {
         node_info!("anvil_reset");
         if let Some(forking) = forking {
             self.backend.reset_fork(forking).await
+        } else if self.backend.has_fork_snapshot() {
+            self.backend.reset_to_fork_snapshot()
         } else {
             Err(BlockchainError::RpcUnimplemented)
         }
     }
 
+    /// Moves the chain head back by `n` blocks and restores the state of the new head block.
+    ///
+    /// The transactions of the removed blocks are returned to the mempool. Unlike a reorg, no
+    /// replacement blocks are mined.
+    ///
+    /// Handler for RPC call: `anvil_rollback`
+    pub async fn anvil_rollback(&self, n: U256) -> Result<()> {
+        node_info!("anvil_rollback");
+        if n > u64::MAX.into() {
+            return Err(RpcError::invalid_params("number of blocks to roll back is too large").into())
+        }
+        self.rollback(n.as_u64()).await
+    }
+
+    /// Rewinds the chain head to the given block and restores its state, like
+    /// [anvil_rollback](Self::anvil_rollback).
+    ///
+    /// Only rewinding is supported, moving the head forward errors.
+    ///
+    /// Handler for RPC call: `debug_setHead`
+    pub async fn debug_set_head(&self, block_number: U256) -> Result<()> {
+        node_info!("debug_setHead");
+        let best_number: U256 = self.backend.best_number().as_u64().into();
+        if block_number > best_number {
+            return Err(RpcError::invalid_params(
+                "debug_setHead can not move the chain head forward",
+            )
+            .into())
+        }
+        self.rollback((best_number - block_number).as_u64()).await
+    }
+
+    /// Moves the chain head back by `n` blocks and returns the transactions of the removed blocks
+    /// to the pool
+    async fn rollback(&self, n: u64) -> Result<()> {
+        let transactions = self.backend.rollback(n).await?;
+        for pending_transaction in transactions {
+            let from = *pending_transaction.sender();
+            let nonce = *pending_transaction.nonce();
+            let on_chain_nonce = self.backend.current_nonce(from);
+            let requires = required_marker(nonce, on_chain_nonce, from);
+            let provides = vec![to_marker(nonce.as_u64(), from)];
+            self.add_pending_transaction(pending_transaction, requires, provides)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the origin of the active fork: url, forked block number and chain id.
+    ///
+    /// Returns `None` if not in fork mode.
+    ///
+    /// Handler for RPC call: `anvil_getFork`
+    pub fn anvil_get_fork(&self) -> Result<Option<ForkInfo>> {
+        node_info!("anvil_getFork");
+        Ok(self.get_fork().map(|fork| ForkInfo {
+            json_rpc_url: fork.redacted_eth_rpc_url(),
+            block_number: fork.block_number(),
+            chain_id: fork.chain_id(),
+        }))
+    }
+
+    /// Returns the current block, hardfork, fee settings and fork origin of the node.
+    ///
+    /// Handler for RPC call: `anvil_nodeInfo`
+    pub fn anvil_node_info(&self) -> Result<NodeInfo> {
+        node_info!("anvil_nodeInfo");
+        let current_block_timestamp = self
+            .backend
+            .get_block(BlockNumber::Latest)
+            .map(|block| block.header.timestamp)
+            .unwrap_or_default();
+        Ok(NodeInfo {
+            current_block_number: self.backend.best_number(),
+            current_block_timestamp,
+            current_block_hash: self.backend.best_hash(),
+            hard_fork: format!("{:?}", self.backend.env().read().cfg.spec_id),
+            transaction_order: format!("{:?}", *self.transaction_order.read()),
+            environment: NodeEnvironment {
+                chain_id: self.chain_id(),
+                gas_limit: self.backend.gas_limit(),
+                base_fee: self.backend.base_fee(),
+                gas_price: self.backend.gas_price(),
+                suggested_priority_fee: self.backend.max_priority_fee_per_gas(),
+            },
+            fork: self.get_fork().map(|fork| ForkInfo {
+                json_rpc_url: fork.redacted_eth_rpc_url(),
+                block_number: fork.block_number(),
+                chain_id: fork.chain_id(),
+            }),
+        })
+    }
+
+    /// Executes the transactions in order on top of the state of the given block and returns the
+    /// outcome of each of them.
+    ///
+    /// Every transaction sees the state changes of the transactions before it, but none of the
+    /// changes are committed.
+    ///
+    /// Handler for RPC call: `anvil_simulateBundle`
+    pub async fn anvil_simulate_bundle(
+        &self,
+        requests: Vec<CallRequest>,
+        block_number: Option<BlockId>,
+    ) -> Result<Vec<SimulatedTransaction>> {
+        node_info!("anvil_simulateBundle");
+        let number = self.backend.ensure_block_id(block_number).await?;
+        if self.get_fork().map_or(false, |fork| fork.predates_fork(number)) {
+            return Err(RpcError::invalid_params(
+                "anvil_simulateBundle is not supported for blocks before the fork",
+            )
+            .into())
+        }
+
+        let requests = requests
+            .into_iter()
+            .map(|request| {
+                let fees = FeeDetails::new(
+                    request.gas_price,
+                    request.max_fee_per_gas,
+                    request.max_priority_fee_per_gas,
+                )?
+                .or_zero_fees();
+                Ok((request, fees))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let results = self.backend.simulate_bundle(requests, number).await?;
+        Ok(results
+            .into_iter()
+            .map(|(exit, out, gas, logs)| SimulatedTransaction {
+                gas_used: gas.into(),
+                reverted: !matches!(exit, return_ok!()),
+                return_data: convert_transact_out(&out),
+                logs: logs.into_iter().map(Into::into).collect(),
+            })
+            .collect())
+    }
+
+    /// Returns the state root of the current state, the same root a block mined now would have.
+    ///
+    /// The root is computed on demand from all accounts and storage slots, which is expensive for
+    /// large states. It's not available in fork mode.
+    ///
+    /// Handler for RPC call: `anvil_getStateRoot`
+    pub fn anvil_get_state_root(&self) -> Result<H256> {
+        node_info!("anvil_getStateRoot");
+        self.backend.state_root().ok_or_else(|| {
+            RpcError::invalid_params("The state root is not available in fork mode").into()
+        })
+    }
+
+    /// Returns all accounts that changed since the fork, with only their changed fields and
+    /// storage slots.
+    ///
+    /// Handler for RPC call: `anvil_dumpStateDiff`
+    pub fn anvil_dump_state_diff(&self) -> Result<BTreeMap<Address, AccountStateDiff>> {
+        node_info!("anvil_dumpStateDiff");
+        self.backend.state_diff()
+    }
+
+    /// Returns how many accounts and storage slots were fetched from the fork, how often they were
+    /// served from the cache and how many requests were sent to the fork endpoint.
+    ///
+    /// If `reset` is set, all counters start at zero again after they were returned.
+    ///
+    /// Handler for RPC call: `anvil_getForkStats`
+    pub fn anvil_get_fork_stats(&self, reset: bool) -> Result<ForkStats> {
+        node_info!("anvil_getForkStats");
+        let fork =
+            self.get_fork().ok_or_else(|| RpcError::invalid_params("Forking not enabled"))?;
+        let stats = fork.stats();
+        if reset {
+            fork.reset_stats();
+        }
+        Ok(stats)
+    }
+
+    /// Returns the hashes of the transactions that the next mined block would include, in the
+    /// order of the current `TransactionOrder`.
+    ///
+    /// The transactions are executed against the pending state, so transactions that don't fit
+    /// into the block gas limit or are invalid are not part of the result.
+    ///
+    /// Handler for RPC call: `anvil_getMempoolOrder`
+    pub async fn anvil_get_mempool_order(&self) -> Result<Vec<TxHash>> {
+        node_info!("anvil_getMempoolOrder");
+        let transactions = self.ordered_ready_transactions().await;
+        let info = self.backend.pending_block(transactions);
+        Ok(info.transactions.into_iter().map(|tx| tx.transaction_hash).collect())
+    }
+
     /// Modifies the balance of an account.
     ///
     /// Handler for RPC call: `anvil_setBalance`
     pub async fn anvil_set_balance(&self, address: Address, balance: U256) -> Result<()> {
         node_info!("anvil_setBalance");
-        self.backend.set_balance(address, balance);
+        self.backend
+            .apply_cheat("anvil_setBalance", address, &[], |db| db.set_balance(address, balance));
+        self.on_state_changed().await;
+        Ok(())
+    }
+
+    /// Adds `delta` to the balance of an account and returns the new balance.
+    ///
+    /// The balance saturates at `U256::MAX` instead of overflowing.
+    ///
+    /// Handler for RPC call: `anvil_addBalance`
+    pub async fn anvil_add_balance(&self, address: Address, delta: U256) -> Result<U256> {
+        node_info!("anvil_addBalance");
+        let balance = self
+            .backend
+            .apply_cheat("anvil_addBalance", address, &[], |db| db.add_balance(address, delta));
+        self.on_state_changed().await;
+        Ok(balance)
+    }
+
+    /// Sets the balances of multiple accounts at once.
+    ///
+    /// Useful to restore the funding of the dev accounts after `anvil_reset`.
+    ///
+    /// Handler for RPC call: `anvil_setFundedAccounts`
+    pub async fn anvil_set_funded_accounts(&self, accounts: Vec<FundedAccount>) -> Result<()> {
+        node_info!("anvil_setFundedAccounts");
+        for FundedAccount { address, balance } in accounts {
+            self.backend.apply_cheat("anvil_setFundedAccounts", address, &[], |db| {
+                db.set_balance(address, balance)
+            });
+        }
+        self.on_state_changed().await;
         Ok(())
     }
 
@@ -1181,16 +1721,63 @@ impl EthApi {
     /// Handler for RPC call: `anvil_setCode`
     pub async fn anvil_set_code(&self, address: Address, code: Bytes) -> Result<()> {
         node_info!("anvil_setCode");
-        self.backend.set_code(address, code);
+        self.backend.apply_cheat("anvil_setCode", address, &[], |db| db.set_code(address, code));
+        self.on_state_changed().await;
         Ok(())
     }
 
+    /// Deploys the initcode as a contract creation from `from` (the zero address by default) and
+    /// returns the address of the deployed contract.
+    ///
+    /// Unlike a deployment transaction this doesn't need to be signed or mined and is free, but it
+    /// increases the nonce of `from` and is subject to the same code size limit.
+    ///
+    /// Handler for RPC call: `anvil_deployCode`
+    pub async fn anvil_deploy_code(
+        &self,
+        code: Bytes,
+        from: Option<Address>,
+        value: Option<U256>,
+    ) -> Result<Address> {
+        node_info!("anvil_deployCode");
+        let (exit, out, _) = self
+            .backend
+            .deploy_code(from.unwrap_or_default(), code, value.unwrap_or_default())
+            .await?;
+        ensure_return_ok(exit, &out)?;
+        self.on_state_changed().await;
+        match out {
+            TransactOut::Create(_, Some(address)) => Ok(address),
+            _ => {
+                Err(BlockchainError::Internal("contract creation returned no address".to_string()))
+            }
+        }
+    }
+
     /// Sets the nonce of an address.
     ///
     /// Handler for RPC call: `anvil_setNonce`
     pub async fn anvil_set_nonce(&self, address: Address, nonce: U256) -> Result<()> {
         node_info!("anvil_setNonce");
-        self.backend.set_nonce(address, nonce);
+        self.backend.apply_cheat("anvil_setNonce", address, &[], |db| {
+            db.set_nonce(address, nonce.try_into().unwrap_or(u64::MAX))
+        });
+        self.on_state_changed().await;
+        Ok(())
+    }
+
+    /// Sets the balance, nonce, code and storage of an account in one atomic operation.
+    ///
+    /// Omitted fields are left unchanged, only the given storage slots are written.
+    ///
+    /// Handler for RPC call: `anvil_setAccount`
+    pub async fn anvil_set_account(&self, address: Address, account: AccountUpdate) -> Result<()> {
+        node_info!("anvil_setAccount");
+        let slots = account.storage.keys().copied().collect::<Vec<_>>();
+        self.backend.apply_cheat("anvil_setAccount", address, &slots, |db| {
+            db.set_account(address, account)
+        });
+        self.on_state_changed().await;
         Ok(())
     }
 
@@ -1204,10 +1791,48 @@ impl EthApi {
         val: U256,
     ) -> Result<()> {
         node_info!("anvil_setStorageAt");
-        self.backend.set_storage_at(address, slot, val);
+        self.backend.apply_cheat("anvil_setStorageAt", address, &[slot], |db| {
+            db.set_storage_at(address, slot, val)
+        });
+        self.on_state_changed().await;
         Ok(())
     }
 
+    /// Writes multiple slots of the account's storage in a single call.
+    ///
+    /// All writes are applied at once.
+    ///
+    /// Handler for RPC call: `anvil_setStorageBatch`
+    pub async fn anvil_set_storage_batch(
+        &self,
+        address: Address,
+        slots: Vec<(U256, U256)>,
+    ) -> Result<()> {
+        node_info!("anvil_setStorageBatch");
+        let written = slots.iter().map(|(slot, _)| *slot).collect::<Vec<_>>();
+        self.backend.apply_cheat("anvil_setStorageBatch", address, &written, |db| {
+            for (slot, val) in slots {
+                db.set_storage_at(address, slot, val);
+            }
+        });
+        self.on_state_changed().await;
+        Ok(())
+    }
+
+    /// Returns all changes state cheats like `anvil_setBalance` or `anvil_setStorageAt` made to
+    /// accounts, in order, with their old and new values.
+    ///
+    /// Fails unless cheats are recorded with `--record-cheats`.
+    ///
+    /// Handler for RPC call: `anvil_getCheatLog`
+    pub fn anvil_get_cheat_log(&self) -> Result<Vec<CheatLogEntry>> {
+        node_info!("anvil_getCheatLog");
+        self.backend.cheats().cheat_log().ok_or_else(|| {
+            RpcError::invalid_params("Cheats are not recorded, enable with `--record-cheats`")
+                .into()
+        })
+    }
+
     /// Enable or disable logging.
     ///
     /// Handler for RPC call: `anvil_setLoggingEnabled`
@@ -1219,6 +1844,10 @@ impl EthApi {
 
     /// Set the minimum gas price for the node.
     ///
+    /// This is also the gas price `eth_gasPrice` returns from now on. New transactions with a lower
+    /// gas price are rejected until the minimum is removed with `anvil_removeMinGasPrice`. Fails
+    /// while EIP-1559 is active, where the base fee is the lowest price.
+    ///
     /// Handler for RPC call: `anvil_setMinGasPrice`
     pub async fn anvil_set_min_gas_price(&self, gas: U256) -> Result<()> {
         node_info!("anvil_setMinGasPrice");
@@ -1229,6 +1858,43 @@ impl EthApi {
             .into())
         }
         self.backend.set_gas_price(gas);
+        self.backend.set_min_gas_price(Some(gas));
+        Ok(())
+    }
+
+    /// Removes the minimum gas price, so transactions with any gas price are accepted again.
+    ///
+    /// The gas price that `eth_gasPrice` returns is not changed.
+    ///
+    /// Handler for RPC call: `anvil_removeMinGasPrice`
+    pub fn anvil_remove_min_gas_price(&self) -> Result<()> {
+        node_info!("anvil_removeMinGasPrice");
+        self.backend.set_min_gas_price(None);
+        Ok(())
+    }
+
+    /// Sets the priority fee that `eth_maxPriorityFeePerGas` suggests to wallets.
+    ///
+    /// The fee is kept until it is changed again or the fork is reset with `anvil_reset`.
+    ///
+    /// Handler for RPC call: `anvil_setMinSuggestedPriorityFee`
+    pub fn anvil_set_min_suggested_priority_fee(&self, fee: U256) -> Result<()> {
+        node_info!("anvil_setMinSuggestedPriorityFee");
+        self.backend.set_max_priority_fee_per_gas(fee);
+        Ok(())
+    }
+
+    /// Sets the block gas limit of the next blocks.
+    ///
+    /// `anvil_setGasLimit` is an alias of this call.
+    ///
+    /// Handler for RPC call: `anvil_setBlockGasLimit`
+    pub fn anvil_set_block_gas_limit(&self, gas_limit: U256) -> Result<()> {
+        node_info!("anvil_setBlockGasLimit");
+        if gas_limit > U256::from(u64::MAX) {
+            return Err(RpcError::invalid_params("Gas limit exceeds u64::MAX").into())
+        }
+        self.backend.set_gas_limit(gas_limit);
         Ok(())
     }
 
@@ -1241,6 +1907,121 @@ impl EthApi {
         Ok(())
     }
 
+    /// Sets the gas used that the next mined block reports for the base fee computation of the
+    /// block after it, without affecting the block's header or receipts.
+    ///
+    /// This only applies to the next block. Fails if the base fee is static or if the gas used
+    /// exceeds the block gas limit.
+    ///
+    /// Handler for RPC call: `anvil_setNextBlockGasUsed`
+    pub fn anvil_set_next_block_gas_used(&self, gas_used: U256) -> Result<()> {
+        node_info!("anvil_setNextBlockGasUsed");
+        self.backend.set_next_block_gas_used(gas_used)
+    }
+
+    /// Makes the next submitted transaction revert with the given reason once it's mined,
+    /// regardless of its actual outcome.
+    ///
+    /// This is a testing cheat that doesn't correspond to real EVM behavior: the transaction's
+    /// code is not executed, so it only uses the intrinsic gas and leaves all state unchanged apart
+    /// from the sender's nonce and balance.
+    ///
+    /// Handler for RPC call: `anvil_setNextTransactionFails`
+    pub fn anvil_set_next_transaction_fails(&self, reason: String) -> Result<()> {
+        node_info!("anvil_setNextTransactionFails");
+        self.backend.cheats().set_next_transaction_fails(reason);
+        Ok(())
+    }
+
+    /// Waits until the transaction with the given hash is mined and returns its receipt, or `None`
+    /// if it wasn't mined within `timeout_ms` milliseconds.
+    ///
+    /// This only waits on new block notifications, so other requests are served in the meantime.
+    ///
+    /// Handler for RPC call: `anvil_waitForTransaction`
+    pub async fn anvil_wait_for_transaction(
+        &self,
+        hash: H256,
+        timeout_ms: u64,
+    ) -> Result<Option<TransactionReceipt>> {
+        node_info!("anvil_waitForTransaction");
+        // subscribe before checking the receipt, so a block mined in between isn't missed
+        let mut blocks = self.new_block_notifications();
+        if let Some(receipt) = self.backend.transaction_receipt(hash).await? {
+            return Ok(Some(receipt))
+        }
+
+        let wait = async {
+            while blocks.next().await.is_some() {
+                if let Some(receipt) = self.backend.transaction_receipt(hash).await? {
+                    return Ok(Some(receipt))
+                }
+            }
+            Ok::<_, BlockchainError>(None)
+        };
+
+        match tokio::time::timeout(Duration::from_millis(timeout_ms), wait).await {
+            Ok(res) => res,
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Makes `owner` the owner of the ERC721 token with the given id, by writing the token's owner
+    /// slot directly.
+    ///
+    /// The owner mapping is located by probing the mapping slots of common ERC721 layouts, like
+    /// OpenZeppelin's and Solmate's: `ownerOf(tokenId)` is called with the owner overriding each
+    /// candidate slot until it returns the new owner. If the balance mapping directly follows the
+    /// owner mapping and `balanceOf` confirms it, the balances of the previous and the new owner
+    /// are adjusted as well. Only the matching layout is written to the state.
+    ///
+    /// Returns an error if no known layout matches.
+    ///
+    /// Handler for RPC call: `anvil_setErc721Owner`
+    pub async fn anvil_set_erc721_owner(
+        &self,
+        token: Address,
+        token_id: U256,
+        owner: Address,
+    ) -> Result<()> {
+        node_info!("anvil_setErc721Owner");
+        let previous_owner = self.erc721_owner_of(token, token_id, Default::default()).await;
+        let owner_value = U256::from_big_endian(H256::from(owner).as_bytes());
+
+        // probes only override the state of their call, the matching layout is then written as a
+        // single cheat
+        let mut writes = None;
+        for mapping_slot in 0..ERC721_MAX_PROBED_SLOTS {
+            let slot = mapping_slot_of(Token::Uint(token_id), mapping_slot);
+            let probe = storage_override(token, &[(slot, owner_value)]);
+            if self.erc721_owner_of(token, token_id, probe).await != Some(owner) {
+                continue
+            }
+
+            let mut found = vec![(slot, owner_value)];
+            if previous_owner != Some(owner) {
+                found.extend(
+                    self.erc721_balance_writes(token, previous_owner, owner, mapping_slot + 1)
+                        .await?,
+                );
+            }
+            writes = Some(found);
+            break
+        }
+
+        let writes = writes.ok_or_else(|| {
+            RpcError::invalid_params("No known ERC721 storage layout matches the token")
+        })?;
+        let slots = writes.iter().map(|(slot, _)| *slot).collect::<Vec<_>>();
+        self.backend.apply_cheat("anvil_setErc721Owner", token, &slots, |db| {
+            for (slot, val) in writes {
+                db.set_storage_at(token, slot, val);
+            }
+        });
+        self.on_state_changed().await;
+        Ok(())
+    }
+
     /// Sets the coinbase address.
     ///
     /// Handler for RPC call: `anvil_setCoinbase`
@@ -1250,6 +2031,33 @@ impl EthApi {
         Ok(())
     }
 
+    /// Sets the chain id.
+    ///
+    /// `eth_chainId` and `net_version` return the new chain id, and transactions must be signed
+    /// for it from now on.
+    ///
+    /// Handler for RPC call: `anvil_setChainId`
+    pub fn anvil_set_chain_id(&self, chain_id: U256) -> Result<()> {
+        node_info!("anvil_setChainId");
+        if chain_id > U256::from(u64::MAX) {
+            return Err(RpcError::invalid_params("chain id exceeds u64::MAX").into())
+        }
+        self.backend.set_chain_id(chain_id.as_u64());
+        Ok(())
+    }
+
+    /// Sets the coinbase of the next mined block only.
+    ///
+    /// The block's priority fees are paid to this address, blocks after it use the coinbase set
+    /// via `anvil_setCoinbase` again.
+    ///
+    /// Handler for RPC call: `anvil_setNextBlockCoinbase`
+    pub async fn anvil_set_next_block_coinbase(&self, address: Address) -> Result<()> {
+        node_info!("anvil_setNextBlockCoinbase");
+        self.backend.set_next_block_coinbase(address);
+        Ok(())
+    }
+
     /// Snapshot the state of the blockchain at the current block.
     ///
     /// Handler for RPC call: `evm_snapshot`
@@ -1273,6 +2081,7 @@ impl EthApi {
     pub async fn evm_increase_time(&self, seconds: U256) -> Result<()> {
         node_info!("evm_increaseTime");
         self.backend.time().increase_time(seconds.try_into().unwrap_or(u64::MAX));
+        self.backend.clear_cached_calls();
         Ok(())
     }
 
@@ -1282,9 +2091,29 @@ impl EthApi {
     pub fn evm_set_next_block_timestamp(&self, seconds: u64) -> Result<()> {
         node_info!("evm_setNextBlockTimestamp");
         self.backend.time().set_next_block_timestamp(seconds);
+        self.backend.clear_cached_calls();
         Ok(())
     }
 
+    /// Mines a single block with the exact `timestamp` and returns it
+    ///
+    /// Unlike `evm_setNextBlockTimestamp` followed by `evm_mine`, the timestamp can't be used by
+    /// another block that's mined in between. The timestamp must be later than the timestamp of
+    /// the latest block. A timestamp set via `evm_setNextBlockTimestamp` is kept for the next
+    /// block.
+    ///
+    /// Handler for RPC call: `anvil_mineWithTimestamp`
+    pub async fn anvil_mine_with_timestamp(&self, timestamp: u64) -> Result<Block<TxHash>> {
+        node_info!("anvil_mineWithTimestamp");
+        let transactions = self.ordered_ready_transactions().await;
+        let outcome = self.backend.mine_block_with_timestamp(transactions, timestamp).await?;
+        let number = self.on_mined_block(outcome);
+        self.backend
+            .block_by_number(BlockNumber::Number(number))
+            .await?
+            .ok_or(BlockchainError::BlockNotFound)
+    }
+
     /// Sets an interval for the block timestamp
     ///
     /// Handler for RPC call: `anvil_setBlockTimestampInterval`
@@ -1294,7 +2123,10 @@ impl EthApi {
         Ok(())
     }
 
-    /// Sets an interval for the block timestamp
+    /// Removes the interval for the block timestamp, if one was set via
+    /// `anvil_setBlockTimestampInterval`
+    ///
+    /// Returns `true` if an interval was removed
     ///
     /// Handler for RPC call: `anvil_removeBlockTimestampInterval`
     pub fn evm_remove_block_timestamp_interval(&self) -> Result<bool> {
@@ -1336,11 +2168,23 @@ impl EthApi {
         Ok("0x0".to_string())
     }
 
-    /// Sets the reported block number
+    /// Sets the number of the current head block.
     ///
-    /// Handler for ETH RPC call: `anvil_setBlock`
-    pub fn anvil_set_block(&self, block_number: U256) -> Result<()> {
-        node_info!("anvil_setBlock");
+    /// The chain jumps to the given height without mining the blocks in between, those blocks are
+    /// absent and looking them up returns nothing. `eth_blockNumber` reports the new height
+    /// immediately and the next mined block will be `block_number + 1`.
+    ///
+    /// Moving the head backwards is not supported.
+    ///
+    /// Handler for RPC call: `anvil_setBlockNumber`
+    pub fn anvil_set_block_number(&self, block_number: U256) -> Result<()> {
+        node_info!("anvil_setBlockNumber");
+        if block_number < self.backend.best_number().as_u64().into() {
+            return Err(RpcError::invalid_params(
+                "anvil_setBlockNumber can not move the chain head backwards",
+            )
+            .into())
+        }
         self.backend.set_block_number(block_number);
         Ok(())
     }
@@ -1351,12 +2195,19 @@ impl EthApi {
     pub fn anvil_set_rpc_url(&self, url: String) -> Result<()> {
         node_info!("anvil_setRpcUrl");
         if let Some(fork) = self.backend.get_fork() {
-            let new_provider =
-                Arc::new(Provider::<RetryClient<Http>>::new_client(&url, 10, 1000).map_err(
-                    |_| ProviderError::CustomError(format!("Failed to parse invalid url {}", url)),
-                )?);
             let mut config = fork.config.write();
-            trace!(target: "backend", "Updated fork rpc from \"{}\" to \"{}\"", config.eth_rpc_url, url);
+            let new_provider = Arc::new(new_fork_provider(
+                &url,
+                config.max_concurrency,
+                config.batch_size,
+                config.retry_backoff,
+                config.request_log.clone(),
+                config.health.clone(),
+            )?);
+            if let Some(ref health) = config.health {
+                health.reset();
+            }
+            trace!(target: "backend", "Updated fork rpc from \"{}\" to \"{}\"", redact_url(&config.eth_rpc_url), redact_url(&url));
             config.eth_rpc_url = url;
             config.provider = new_provider;
         }
@@ -1497,6 +2348,237 @@ impl EthApi {
     }
 }
 
+// == impl EthApi personal endpoints ==
+
+impl EthApi {
+    /// Generates a new account and returns its address.
+    ///
+    /// The key is only kept in memory, so a password is not required to unlock the account.
+    ///
+    /// Handler for RPC call: `personal_newAccount`
+    pub fn personal_new_account(&self) -> Result<Address> {
+        node_info!("personal_newAccount");
+        Ok(self.key_store.new_account(self.chain_id()))
+    }
+
+    /// Unlocks an account, all accounts of the node are unlocked so this only checks that the
+    /// account can be signed for.
+    ///
+    /// Handler for RPC call: `personal_unlockAccount`
+    pub fn personal_unlock_account(&self, address: Address) -> Result<bool> {
+        node_info!("personal_unlockAccount");
+        self.get_signer(address).ok_or(BlockchainError::NoSignerAvailable)?;
+        Ok(true)
+    }
+
+    /// Sends a transaction, like `eth_sendTransaction`.
+    ///
+    /// Handler for RPC call: `personal_sendTransaction`
+    pub async fn personal_send_transaction(
+        &self,
+        request: EthTransactionRequest,
+    ) -> Result<TxHash> {
+        node_info!("personal_sendTransaction");
+        self.send_transaction(request).await
+    }
+}
+
+// == impl EthApi otterscan endpoints ==
+
+/// The version of the Otterscan API that is implemented
+pub const OTS_API_LEVEL: u64 = 8;
+
+impl EthApi {
+    /// Returns the version of the Otterscan API that is supported.
+    ///
+    /// Handler for RPC call: `ots_getApiLevel`
+    pub fn ots_get_api_level(&self) -> Result<u64> {
+        node_info!("ots_getApiLevel");
+        Ok(OTS_API_LEVEL)
+    }
+
+    /// Returns the value transfers, contract creations and self destructs of the calls made by
+    /// a mined transaction.
+    ///
+    /// Handler for RPC call: `ots_getInternalOperations`
+    pub fn ots_get_internal_operations(&self, hash: H256) -> Result<Vec<OtsInternalOperation>> {
+        node_info!("ots_getInternalOperations");
+        self.backend
+            .mined_ots_internal_operations(hash)
+            .ok_or_else(|| RpcError::invalid_params("Unknown transaction").into())
+    }
+
+    /// Returns the transactions involving `address` in the blocks before `block_number`, most
+    /// recent first. A `block_number` of 0 searches from the latest block.
+    ///
+    /// At least `page_size` transactions are returned, unless the search reached the oldest block,
+    /// the transactions of the last searched block are always returned completely.
+    ///
+    /// **Note**: only blocks mined by this node are searched, not the blocks of a forked chain.
+    ///
+    /// Handler for RPC call: `ots_searchTransactionsBefore`
+    pub fn ots_search_transactions_before(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> Result<OtsSearchTransactions> {
+        node_info!("ots_searchTransactionsBefore");
+        let best = self.backend.best_number().as_u64();
+        let last = if block_number == 0 { best } else { (block_number - 1).min(best) };
+        let first = self.backend.first_local_block_number();
+        let (found, done) =
+            self.backend.ots_search_transactions(address, first..=last, true, page_size);
+        Ok(ots_search_result(found, block_number == 0, done))
+    }
+
+    /// Returns the transactions involving `address` in the blocks after `block_number`, most
+    /// recent first.
+    ///
+    /// At least `page_size` transactions are returned, unless the search reached the latest block,
+    /// the transactions of the last searched block are always returned completely.
+    ///
+    /// **Note**: only blocks mined by this node are searched, not the blocks of a forked chain.
+    ///
+    /// Handler for RPC call: `ots_searchTransactionsAfter`
+    pub fn ots_search_transactions_after(
+        &self,
+        address: Address,
+        block_number: u64,
+        page_size: usize,
+    ) -> Result<OtsSearchTransactions> {
+        node_info!("ots_searchTransactionsAfter");
+        let best = self.backend.best_number().as_u64();
+        let first = block_number.saturating_add(1).max(self.backend.first_local_block_number());
+        let (found, done) =
+            self.backend.ots_search_transactions(address, first..=best, false, page_size);
+        Ok(ots_search_result(found, done, block_number == 0))
+    }
+
+    /// Returns the hash of the mined transaction that `sender` sent with `nonce`.
+    ///
+    /// Handler for RPC call: `ots_getTransactionBySenderAndNonce`
+    pub fn ots_get_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: U256,
+    ) -> Result<Option<TxHash>> {
+        node_info!("ots_getTransactionBySenderAndNonce");
+        Ok(self.backend.mined_transaction_by_sender_and_nonce(sender, nonce))
+    }
+
+    /// Returns the block without its transactions, the newly issued ether, which is always zero,
+    /// and the fees paid by its transactions.
+    ///
+    /// Handler for RPC call: `ots_getBlockDetails`
+    pub async fn ots_get_block_details(&self, number: BlockNumber) -> Result<OtsBlockDetails> {
+        node_info!("ots_getBlockDetails");
+        let mut block =
+            self.backend.block_by_number(number).await?.ok_or(BlockchainError::BlockNotFound)?;
+        let receipts = match block.hash {
+            Some(hash) => self.backend.block_receipts(BlockId::Hash(hash)).await?,
+            None => None,
+        }
+        .unwrap_or_default();
+        let total_fees = receipts.iter().fold(U256::zero(), |fees, receipt| {
+            fees.saturating_add(
+                receipt
+                    .gas_used
+                    .unwrap_or_default()
+                    .saturating_mul(receipt.effective_gas_price.unwrap_or_default()),
+            )
+        });
+        let transaction_count = block.transactions.len();
+        block.transactions.clear();
+        Ok(OtsBlockDetails {
+            block: OtsBlock { block, transaction_count },
+            issuance: OtsIssuance::default(),
+            total_fees,
+        })
+    }
+
+    /// Returns the transactions of page `page` of the block with their receipts, the first page
+    /// contains the first `page_size` transactions of the block.
+    ///
+    /// The input of the transactions is truncated to the function selector and the logs of the
+    /// receipts are omitted.
+    ///
+    /// Handler for RPC call: `ots_getBlockTransactions`
+    pub async fn ots_get_block_transactions(
+        &self,
+        number: BlockNumber,
+        page: usize,
+        page_size: usize,
+    ) -> Result<OtsBlockTransactions> {
+        node_info!("ots_getBlockTransactions");
+        let mut block = self
+            .backend
+            .block_by_number_full(number)
+            .await?
+            .ok_or(BlockchainError::BlockNotFound)?;
+        let mut receipts = match block.hash {
+            Some(hash) => self.backend.block_receipts(BlockId::Hash(hash)).await?,
+            None => None,
+        }
+        .unwrap_or_default();
+
+        let transaction_count = block.transactions.len();
+        let start = page.saturating_mul(page_size).min(transaction_count);
+        let end = start.saturating_add(page_size).min(transaction_count);
+        block.transactions = block.transactions.drain(start..end).collect();
+        for tx in block.transactions.iter_mut() {
+            let selector = tx.input.as_ref().len().min(4);
+            tx.input = tx.input.as_ref()[..selector].to_vec().into();
+        }
+        receipts.retain(|receipt| (start..end).contains(&receipt.transaction_index.as_usize()));
+        for receipt in receipts.iter_mut() {
+            receipt.logs.clear();
+        }
+
+        Ok(OtsBlockTransactions { fullblock: OtsBlock { block, transaction_count }, receipts })
+    }
+
+    /// Returns whether `address` has code at the block.
+    ///
+    /// Handler for RPC call: `ots_hasCode`
+    pub async fn ots_has_code(&self, address: Address, number: BlockNumber) -> Result<bool> {
+        node_info!("ots_hasCode");
+        let number = self.backend.ensure_block_id(Some(number.into())).await?;
+        let code = self.backend.get_code(address, Some(number.into())).await?;
+        Ok(!code.as_ref().is_empty())
+    }
+
+    /// Returns all calls made by a mined transaction, including the transaction itself.
+    ///
+    /// Handler for RPC call: `ots_traceTransaction`
+    pub fn ots_trace_transaction(&self, hash: H256) -> Result<Vec<OtsTrace>> {
+        node_info!("ots_traceTransaction");
+        self.backend
+            .mined_ots_traces(hash)
+            .ok_or_else(|| RpcError::invalid_params("Unknown transaction").into())
+    }
+
+    /// Returns the revert data of a mined transaction, which is empty if the transaction
+    /// succeeded.
+    ///
+    /// Handler for RPC call: `ots_getTransactionError`
+    pub fn ots_get_transaction_error(&self, hash: H256) -> Result<Bytes> {
+        node_info!("ots_getTransactionError");
+        self.backend
+            .mined_transaction_error(hash)
+            .ok_or_else(|| RpcError::invalid_params("Unknown transaction").into())
+    }
+
+    /// Returns the transaction that created the contract at `address` and the address that
+    /// deployed it, or `None` if the contract was not created by a transaction of this node.
+    ///
+    /// Handler for RPC call: `ots_getContractCreator`
+    pub fn ots_get_contract_creator(&self, address: Address) -> Result<Option<OtsContractCreator>> {
+        node_info!("ots_getContractCreator");
+        Ok(self.backend.mined_contract_creator(address))
+    }
+}
+
 // === impl EthApi utility functions ===
 
 impl EthApi {
@@ -1505,7 +2587,7 @@ impl EthApi {
         mut request: CallRequest,
         block_number: Option<BlockId>,
     ) -> Result<U256> {
-        let number = self.backend.ensure_block_number(block_number)?;
+        let number = self.backend.ensure_block_id(block_number).await?;
         let block_number = Some(number.into());
         // check if the number predates the fork, if in fork mode
         if let Some(fork) = self.get_fork() {
@@ -1537,9 +2619,19 @@ impl EthApi {
         )?
         .or_zero_fees();
 
-        // get the highest possible gas limit, either the request's set value or the currently
-        // configured gas limit
-        let mut highest_gas_limit = request.gas.unwrap_or_else(|| self.backend.gas_limit());
+        // get the highest possible gas limit, either the request's set value or the gas estimation
+        // cap, which keeps the search fast on nodes with a very high block gas limit
+        let gas_estimation_cap = self.backend.gas_estimation_cap();
+        if let Some(gas) = request.gas {
+            if gas > gas_estimation_cap {
+                return Err(RpcError::invalid_params(format!(
+                    "gas {} exceeds the gas estimation cap {}",
+                    gas, gas_estimation_cap
+                ))
+                .into())
+            }
+        }
+        let mut highest_gas_limit = request.gas.unwrap_or(gas_estimation_cap);
 
         // check with the funds of the sender
         if let Some(from) = request.from {
@@ -1582,7 +2674,7 @@ impl EthApi {
                 // if price or limit was included in the request then we can execute the request
                 // again with the max gas limit to check if revert is gas related or not
                 return if request.gas.is_some() || request.gas_price.is_some() {
-                    request.gas = Some(self.backend.gas_limit());
+                    request.gas = Some(gas_estimation_cap);
                     let (exit, out, _, _) =
                         self.backend.call(request.clone(), fees, block_number).await?;
                     match exit {
@@ -1665,8 +2757,20 @@ impl EthApi {
     }
 
     /// Returns the priority of the transaction based on the current `TransactionOrder`
-    fn transaction_priority(&self, tx: &TypedTransaction) -> TransactionPriority {
-        self.transaction_order.read().priority(tx)
+    ///
+    /// Transactions of configured priority senders are ranked ahead of all other transactions,
+    /// followed by transactions that call one of the configured priority selectors
+    fn transaction_priority(&self, tx: &PendingTransaction) -> TransactionPriority {
+        let priority_selector = tx
+            .transaction
+            .data()
+            .get(..4)
+            .map_or(false, |selector| self.priority_selectors.contains(selector));
+        self.transaction_order
+            .read()
+            .priority(&tx.transaction, self.backend.base_fee())
+            .with_priority_sender(self.priority_senders.contains(tx.sender()))
+            .with_priority_selector(priority_selector)
     }
 
     /// Returns the chain ID used for transaction
@@ -1678,6 +2782,19 @@ impl EthApi {
         self.backend.get_fork()
     }
 
+    /// Returns all accounts with their storage and the blocks of the chain, in the format
+    /// `--load-state` reads
+    ///
+    /// In fork mode the state also records the fork, so that `--fork-state` can fork off it again.
+    pub fn dump_state(&self) -> Result<SerializableState> {
+        let accounts = self.backend.dump_state()?;
+        let fork = self.get_fork().map(|fork| SerializableFork {
+            json_rpc_url: fork.eth_rpc_url(),
+            block_number: fork.block_number(),
+        });
+        Ok(SerializableState { accounts, history: self.backend.serializable_history(), fork })
+    }
+
     /// Returns the first signer that can sign for the given address
     #[allow(clippy::borrowed_box)]
     pub fn get_signer(&self, address: Address) -> Option<&Box<dyn Signer>> {
@@ -1704,25 +2821,122 @@ impl EthApi {
         self.backend.is_fork()
     }
 
+    /// Mines a block after the state was modified by a cheat, if enabled, so that subscribers to
+    /// new blocks observe the change
+    async fn on_state_changed(&self) {
+        if self.backend.mine_on_state_change() {
+            // the block is empty, pending transactions are left to the configured mining mode
+            let outcome = self.backend.mine_block(Vec::new()).await;
+            trace!(target: "node", "mined block {} after state change", outcome.block_number);
+            self.pool.on_mined_block(outcome);
+        }
+    }
+
     /// Mines exactly one block
     pub async fn mine_one(&self) {
-        let transactions = self.pool.ready_transactions().collect::<Vec<_>>();
+        let transactions = self.ordered_ready_transactions().await;
         let outcome = self.backend.mine_block(transactions).await;
+        self.on_mined_block(outcome);
+    }
 
-        trace!(target: "node", "mined block {}", outcome.block_number);
+    /// Updates the pool with the `outcome` of a mined block and returns the block's number
+    fn on_mined_block(&self, outcome: MinedBlockOutcome) -> U64 {
+        let block_number = outcome.block_number;
+        trace!(target: "node", "mined block {}", block_number);
         self.pool.on_mined_block(outcome);
+        block_number
     }
 
-    /// Returns the pending block with tx hashes
-    fn pending_block(&self) -> Block<TxHash> {
+    /// Executes a call to `to` against the current state with the `overrides` applied and returns
+    /// its output, if successful
+    async fn call_view(
+        &self,
+        to: Address,
+        data: Vec<u8>,
+        overrides: StateOverride,
+    ) -> Option<Bytes> {
+        let request = CallRequest { to: Some(to), data: Some(data.into()), ..Default::default() };
+        let (exit, out, _, _) = self
+            .backend
+            .call_with_state(request, FeeDetails::zero(), None, overrides)
+            .await
+            .ok()?;
+        ensure_return_ok(exit, &out).ok()
+    }
+
+    /// Returns the result of the ERC721 `ownerOf(tokenId)` call, with the `overrides` applied
+    async fn erc721_owner_of(
+        &self,
+        token: Address,
+        token_id: U256,
+        overrides: StateOverride,
+    ) -> Option<Address> {
+        let data =
+            [&id("ownerOf(uint256)")[..], &ethers::abi::encode(&[Token::Uint(token_id)])].concat();
+        Address::decode(self.call_view(token, data, overrides).await?).ok()
+    }
+
+    /// Returns the result of the ERC721 `balanceOf(owner)` call, with the `overrides` applied
+    async fn erc721_balance_of(
+        &self,
+        token: Address,
+        owner: Address,
+        overrides: StateOverride,
+    ) -> Option<U256> {
+        let data = [&id("balanceOf(address)")[..], &ethers::abi::encode(&[Token::Address(owner)])]
+            .concat();
+        U256::decode(self.call_view(token, data, overrides).await?).ok()
+    }
+
+    /// Returns the storage writes that move one token from the balance of `from` to the balance
+    /// of `to`, if `balanceOf` confirms that `mapping_slot` is the balance mapping of the token
+    ///
+    /// The balance mapping is probed by overriding the balance of `to` for the `balanceOf` call.
+    async fn erc721_balance_writes(
+        &self,
+        token: Address,
+        from: Option<Address>,
+        to: Address,
+        mapping_slot: u64,
+    ) -> Result<Vec<(U256, U256)>> {
+        let to_slot = mapping_slot_of(Token::Address(to), mapping_slot);
+        let to_balance =
+            U256::from_big_endian(self.backend.storage_at(token, to_slot, None).await?.as_bytes());
+        let expected = to_balance.saturating_add(U256::one());
+        let probe = storage_override(token, &[(to_slot, expected)]);
+        if self.erc721_balance_of(token, to, probe).await != Some(expected) {
+            return Ok(Vec::new())
+        }
+
+        let mut writes = vec![(to_slot, expected)];
+        if let Some(from) = from.filter(|from| !from.is_zero()) {
+            let from_slot = mapping_slot_of(Token::Address(from), mapping_slot);
+            let from_balance = U256::from_big_endian(
+                self.backend.storage_at(token, from_slot, None).await?.as_bytes(),
+            );
+            writes.push((from_slot, from_balance.saturating_sub(U256::one())));
+        }
+        Ok(writes)
+    }
+
+    /// Returns the ready transactions of the pool in the order of the current `TransactionOrder`,
+    /// like they're passed to the miner
+    async fn ordered_ready_transactions(&self) -> Vec<Arc<PoolTransaction>> {
         let transactions = self.pool.ready_transactions().collect::<Vec<_>>();
+        let order = self.transaction_order.read().clone();
+        order.order_transactions(transactions, self.backend.base_fee()).await
+    }
+
+    /// Returns the pending block with tx hashes
+    async fn pending_block(&self) -> Block<TxHash> {
+        let transactions = self.ordered_ready_transactions().await;
         let info = self.backend.pending_block(transactions);
         self.backend.convert_block(info.block)
     }
 
     /// Returns the full pending block with `Transaction` objects
-    fn pending_block_full(&self) -> Option<Block<Transaction>> {
-        let transactions = self.pool.ready_transactions().collect::<Vec<_>>();
+    async fn pending_block_full(&self) -> Option<Block<Transaction>> {
+        let transactions = self.ordered_ready_transactions().await;
         let BlockInfo { block, transactions, receipts: _ } =
             self.backend.pending_block(transactions);
 
@@ -1796,26 +3010,37 @@ impl EthApi {
         address: Address,
         block_number: Option<BlockId>,
     ) -> Result<U256> {
-        let number = self.backend.ensure_block_number(block_number)?;
+        let number = self.backend.ensure_block_id(block_number).await?;
         let mut current_nonce = self.backend.get_nonce(address, Some(number.into())).await?;
 
         // if pending, also check the transaction pool for pending tx from the `address`
         if let Some(BlockId::Number(BlockNumber::Pending)) = block_number {
-            let mut current_marker = to_marker(current_nonce.as_u64(), address);
-
-            // check the tx pool for pending tx of the sender
-            for tx in self.pool.ready_transactions() {
-                // tx are already ordered by nonce here
-                if tx.provides.get(0) == Some(&current_marker) {
-                    current_nonce = current_nonce.saturating_add(1.into());
-                    current_marker = to_marker(current_nonce.as_u64(), address);
-                }
-            }
+            current_nonce = self.pending_nonce(address, current_nonce);
         }
 
         Ok(current_nonce)
     }
 
+    /// Returns the next nonce of the `address`, after its ready transactions in the pool that
+    /// follow the given on chain `nonce`
+    fn pending_nonce(&self, address: Address, mut nonce: U256) -> U256 {
+        // the ready transactions are yielded in the order they'd be mined, which is not
+        // necessarily the nonce order of the sender's transactions, so collect them first
+        let provided = self
+            .pool
+            .ready_transactions()
+            .filter(|tx| *tx.pending_transaction.sender() == address)
+            .flat_map(|tx| tx.provides.clone())
+            .collect::<HashSet<_>>();
+
+        // only count the sender's transactions up to the first nonce gap, transactions after
+        // the gap can't be mined yet
+        while provided.contains(&to_marker(nonce.as_u64(), address)) {
+            nonce = nonce.saturating_add(1.into());
+        }
+        nonce
+    }
+
     /// Returns the nonce for this request
     ///
     /// This returns a tuple of `(request nonce, highest nonce)`
@@ -1843,10 +3068,17 @@ impl EthApi {
         provides: Vec<TxMarker>,
     ) -> Result<TxHash> {
         let from = *pending_transaction.sender();
-        let priority = self.transaction_priority(&pending_transaction.transaction);
+        let priority = self.transaction_priority(&pending_transaction);
         let pool_transaction =
             PoolTransaction { requires, provides, pending_transaction, priority };
-        let tx = self.pool.add_transaction(pool_transaction)?;
+        // needs to happen before the transaction is added, which may mine it right away, and is
+        // undone if the pool rejects the transaction
+        let hash = *pool_transaction.hash();
+        self.backend.cheats().on_transaction_submitted(hash);
+        let tx = self.pool.add_transaction(pool_transaction).map_err(|err| {
+            self.backend.cheats().on_transaction_rejected(&hash);
+            err
+        })?;
         trace!(target: "node", "Added transaction: [{:?}] sender={:?}", tx.hash(), from);
         Ok(*tx.hash())
     }
@@ -1872,6 +3104,30 @@ fn convert_transact_out(out: &TransactOut) -> Bytes {
     }
 }
 
+/// Returns the storage slot of `key` in a solidity mapping stored at `mapping_slot`
+fn mapping_slot_of(key: Token, mapping_slot: u64) -> U256 {
+    let encoded = ethers::abi::encode(&[key, Token::Uint(mapping_slot.into())]);
+    U256::from_big_endian(&keccak256(encoded))
+}
+
+/// Returns the state override that only replaces the given storage `slots` of `address`
+fn storage_override(address: Address, slots: &[(U256, U256)]) -> StateOverride {
+    let state_diff = slots.iter().map(|(slot, val)| (H256::from_uint(slot), H256::from_uint(val)));
+    let account = AccountOverride { state_diff: Some(state_diff.collect()), ..Default::default() };
+    StateOverride::from([(address, account)])
+}
+
+/// Assembles an `ots_search*` page from the found transactions, most recent first
+fn ots_search_result(
+    mut found: Vec<(Transaction, OtsReceipt)>,
+    first_page: bool,
+    last_page: bool,
+) -> OtsSearchTransactions {
+    found.sort_by_key(|(tx, _)| std::cmp::Reverse((tx.block_number, tx.transaction_index)));
+    let (txs, receipts) = found.into_iter().unzip();
+    OtsSearchTransactions { txs, receipts, first_page, last_page }
+}
+
 /// Returns an error if the `exit` code is _not_ ok
 fn ensure_return_ok(exit: Return, out: &TransactOut) -> Result<Bytes> {
     let out = convert_transact_out(out);
@@ -1,9 +1,16 @@
 //! Support for "cheat codes" / bypass functions
 
-use anvil_core::eth::transaction::TypedTransaction;
-use ethers::types::{Address, Signature, H256, U256};
+use anvil_core::{
+    eth::transaction::TypedTransaction,
+    types::{CheatChange, CheatLogEntry},
+};
+use ethers::types::{Address, Bytes, Signature, H256, U256};
 use parking_lot::RwLock;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 use tracing::trace;
 
 /// The signature used to bypass signing via the `eth_sendUnsignedTransaction` cheat RPC
@@ -52,6 +59,116 @@ impl CheatsManager {
     pub fn bypass_signature(&self) -> Signature {
         self.state.read().bypass_signature
     }
+
+    /// Makes the next submitted transaction revert with the given reason when it's executed
+    pub fn set_next_transaction_fails(&self, reason: String) {
+        trace!(target: "cheats", "Next transaction fails with {:?}", reason);
+        self.state.write().next_transaction_failure = Some(reason);
+    }
+
+    /// Assigns the pending failure set via
+    /// [set_next_transaction_fails](Self::set_next_transaction_fails), if any, to the submitted
+    /// transaction
+    pub fn on_transaction_submitted(&self, hash: H256) {
+        let mut state = self.state.write();
+        if let Some(reason) = state.next_transaction_failure.take() {
+            trace!(target: "cheats", "Transaction {:?} fails with {:?}", hash, reason);
+            state.failing_transactions.insert(hash, reason);
+        }
+    }
+
+    /// Reverts [on_transaction_submitted](Self::on_transaction_submitted) for a transaction that
+    /// was not accepted by the pool, so the pending failure applies to the next transaction
+    pub fn on_transaction_rejected(&self, hash: &H256) {
+        let mut state = self.state.write();
+        if let Some(reason) = state.failing_transactions.remove(hash) {
+            trace!(target: "cheats", "Transaction {:?} rejected, next transaction fails", hash);
+            state.next_transaction_failure.get_or_insert(reason);
+        }
+    }
+
+    /// Removes the forced failure of the transaction once it was mined
+    pub fn on_transaction_mined(&self, hash: &H256) {
+        self.state.write().failing_transactions.remove(hash);
+    }
+
+    /// Returns the reason the transaction is forced to revert with, if any
+    pub fn forced_failure(&self, hash: &H256) -> Option<String> {
+        self.state.read().failing_transactions.get(hash).cloned()
+    }
+
+    /// Sets whether the changes made by state cheats are recorded
+    pub fn set_record_cheats(&self, record: bool) {
+        let mut state = self.state.write();
+        if record {
+            state.cheat_log.get_or_insert_with(Vec::new);
+        } else {
+            state.cheat_log = None;
+        }
+    }
+
+    /// Returns true if the changes made by state cheats are recorded
+    pub fn is_recording_cheats(&self) -> bool {
+        self.state.read().cheat_log.is_some()
+    }
+
+    /// Records the changes the cheat `method` made to the account, if cheats are recorded
+    pub fn record_cheat(
+        &self,
+        method: &str,
+        address: Address,
+        before: CheatedAccount,
+        after: CheatedAccount,
+    ) {
+        let mut state = self.state.write();
+        if let Some(ref mut log) = state.cheat_log {
+            let timestamp =
+                SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            for change in before.changes(after) {
+                trace!(target: "cheats", "{} changed {:?}: {:?}", method, address, change);
+                log.push(CheatLogEntry { method: method.to_string(), address, change, timestamp });
+            }
+        }
+    }
+
+    /// Returns all recorded changes, or `None` if cheats are not recorded
+    pub fn cheat_log(&self) -> Option<Vec<CheatLogEntry>> {
+        self.state.read().cheat_log.clone()
+    }
+}
+
+/// The fields of an account that state cheats modify, used to record their changes
+#[derive(Debug, Clone, Default)]
+pub struct CheatedAccount {
+    pub balance: U256,
+    pub nonce: U256,
+    pub code: Bytes,
+    /// The values of the storage slots the cheat writes
+    pub storage: Vec<(U256, U256)>,
+}
+
+// === impl CheatedAccount ===
+
+impl CheatedAccount {
+    /// Returns the differences to the account `after` the cheat was applied
+    fn changes(self, after: CheatedAccount) -> Vec<CheatChange> {
+        let mut changes = Vec::new();
+        if self.balance != after.balance {
+            changes.push(CheatChange::Balance { old_value: self.balance, new_value: after.balance })
+        }
+        if self.nonce != after.nonce {
+            changes.push(CheatChange::Nonce { old_value: self.nonce, new_value: after.nonce })
+        }
+        if self.code != after.code {
+            changes.push(CheatChange::Code { old_value: self.code, new_value: after.code })
+        }
+        for ((slot, old_value), (_, new_value)) in self.storage.into_iter().zip(after.storage) {
+            if old_value != new_value {
+                changes.push(CheatChange::Storage { slot, old_value, new_value })
+            }
+        }
+        changes
+    }
 }
 
 /// Container type for all the state variables
@@ -65,10 +182,23 @@ pub struct CheatsState {
     pub impersonated_account: HashMap<Address, Option<H256>>,
     /// The signature used for the `eth_sendUnsignedTransaction` cheat code
     pub bypass_signature: Signature,
+    /// The revert reason of the next submitted transaction
+    pub next_transaction_failure: Option<String>,
+    /// Transactions that revert with the given reason when they're executed, regardless of their
+    /// actual outcome
+    pub failing_transactions: HashMap<H256, String>,
+    /// The changes made by state cheats, `None` if they are not recorded
+    pub cheat_log: Option<Vec<CheatLogEntry>>,
 }
 
 impl Default for CheatsState {
     fn default() -> Self {
-        Self { impersonated_account: Default::default(), bypass_signature: BYPASS_SIGNATURE }
+        Self {
+            impersonated_account: Default::default(),
+            bypass_signature: BYPASS_SIGNATURE,
+            next_transaction_failure: None,
+            failing_transactions: Default::default(),
+            cheat_log: None,
+        }
     }
 }
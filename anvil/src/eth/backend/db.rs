@@ -1,6 +1,7 @@
 //! Helper types for working with [revm](foundry_evm::revm)
 
 use crate::{revm::AccountInfo, U256};
+use anvil_core::types::{AccountStateDiff, AccountUpdate, StateOverride};
 use ethers::{
     prelude::{Address, Bytes, H160},
     types::H256,
@@ -10,7 +11,9 @@ use forge::revm::KECCAK_EMPTY;
 use foundry_evm::{
     executor::DatabaseRef,
     revm::{db::CacheDB, Database, DatabaseCommit, InMemoryDB},
+    utils::{h256_to_u256_be, u256_to_h256_be},
 };
+use std::{any::Any, collections::BTreeMap};
 
 /// This bundles all required revm traits
 pub trait Db: DatabaseRef + Database + DatabaseCommit + Send + Sync {
@@ -47,6 +50,32 @@ pub trait Db: DatabaseRef + Database + DatabaseCommit + Send + Sync {
     /// Sets the balance of the given address
     fn set_storage_at(&mut self, address: Address, slot: U256, val: U256);
 
+    /// Adds `delta` to the balance of the given address, saturating at `U256::MAX`
+    ///
+    /// Returns the new balance
+    fn add_balance(&mut self, address: Address, delta: U256) -> U256 {
+        let balance = self.basic(address).balance.saturating_add(delta);
+        self.set_balance(address, balance);
+        balance
+    }
+
+    /// Applies all given fields of the account
+    fn set_account(&mut self, address: Address, account: AccountUpdate) {
+        let AccountUpdate { balance, nonce, code, storage } = account;
+        if let Some(balance) = balance {
+            self.set_balance(address, balance);
+        }
+        if let Some(nonce) = nonce {
+            self.set_nonce(address, nonce.try_into().unwrap_or(u64::MAX));
+        }
+        if let Some(code) = code {
+            self.set_code(address, code);
+        }
+        for (slot, val) in storage {
+            self.set_storage_at(address, slot, val);
+        }
+    }
+
     /// Creates a new snapshot
     fn snapshot(&mut self) -> U256;
 
@@ -62,6 +91,36 @@ pub trait Db: DatabaseRef + Database + DatabaseCommit + Send + Sync {
 
     /// Returns the current, standalone state of the Db
     fn current_state(&self) -> StateDb;
+
+    /// Replaces the state of the Db with a `state` previously returned by
+    /// [current_state](Db::current_state)
+    ///
+    /// Returns `false` if the state can't be restored
+    fn restore_state(&mut self, _state: &StateDb) -> bool {
+        false
+    }
+
+    /// Fetches the account, or the given storage `slot` of it, from the remote endpoint if it's
+    /// not cached yet
+    ///
+    /// Returns an error if it couldn't be fetched, in which case nothing is cached.
+    fn try_fetch(&self, _address: Address, _slot: Option<U256>) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Returns all accounts that changed since the state was forked
+    ///
+    /// Returns `None` if the Db is not a fork
+    fn state_diff(&self) -> Option<BTreeMap<Address, AccountStateDiff>> {
+        None
+    }
+
+    /// Returns all accounts with their storage, in the format `--load-state` reads
+    ///
+    /// For a fork these are only the accounts that changed since the state was forked
+    fn dump_state(&self) -> Option<BTreeMap<Address, AccountStateDiff>> {
+        self.state_diff()
+    }
 }
 
 /// Convenience impl only used to use any `Db` on the fly as the db layer for revm's CacheDB
@@ -90,8 +149,19 @@ impl<T: DatabaseRef + Send + Sync + Clone> Db for CacheDB<T> {
     }
 }
 
+/// A [DatabaseRef] that can be downcast to its concrete type
+pub trait AnyDatabaseRef: DatabaseRef + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: DatabaseRef + Send + Sync + 'static> AnyDatabaseRef for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
 /// Represents a state at certain point
-pub struct StateDb(Box<dyn DatabaseRef + Send + Sync>);
+pub struct StateDb(Box<dyn AnyDatabaseRef>);
 
 // === impl StateDB ===
 
@@ -99,6 +169,11 @@ impl StateDb {
     pub fn new(db: impl DatabaseRef + Send + Sync + 'static) -> Self {
         Self(Box::new(db))
     }
+
+    /// Returns the underlying database if it is of type `T`
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.0.as_any().downcast_ref()
+    }
 }
 
 impl DatabaseRef for StateDb {
@@ -118,3 +193,67 @@ impl DatabaseRef for StateDb {
         self.0.block_hash(number)
     }
 }
+
+/// A [DatabaseRef] that applies the state overrides of an `eth_call` on top of another database
+pub struct StateOverrideDb<DB> {
+    db: DB,
+    overrides: StateOverride,
+}
+
+// === impl StateOverrideDb ===
+
+impl<DB: DatabaseRef> StateOverrideDb<DB> {
+    pub fn new(db: DB, overrides: StateOverride) -> Self {
+        Self { db, overrides }
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for StateOverrideDb<DB> {
+    fn basic(&self, address: H160) -> AccountInfo {
+        let mut info = self.db.basic(address);
+        if let Some(account) = self.overrides.get(&address) {
+            if let Some(balance) = account.balance {
+                info.balance = balance;
+            }
+            if let Some(nonce) = account.nonce {
+                info.nonce = nonce.as_u64();
+            }
+            if let Some(code) = account.code.as_ref() {
+                info.code_hash = if code.as_ref().is_empty() {
+                    KECCAK_EMPTY
+                } else {
+                    H256::from_slice(&keccak256(code.as_ref())[..])
+                };
+                info.code = Some(code.to_vec().into());
+            }
+        }
+        info
+    }
+
+    fn code_by_hash(&self, code_hash: H256) -> bytes::Bytes {
+        self.overrides
+            .values()
+            .filter_map(|account| account.code.as_ref())
+            .find(|code| H256::from_slice(&keccak256(code.as_ref())[..]) == code_hash)
+            .map(|code| code.to_vec().into())
+            .unwrap_or_else(|| self.db.code_by_hash(code_hash))
+    }
+
+    fn storage(&self, address: H160, index: U256) -> U256 {
+        if let Some(account) = self.overrides.get(&address) {
+            let slot = u256_to_h256_be(index);
+            if let Some(state) = account.state.as_ref() {
+                // the entire storage is replaced
+                return state.get(&slot).copied().map(h256_to_u256_be).unwrap_or_default()
+            }
+            if let Some(val) = account.state_diff.as_ref().and_then(|diff| diff.get(&slot)) {
+                return h256_to_u256_be(*val)
+            }
+        }
+        self.db.storage(address, index)
+    }
+
+    fn block_hash(&self, number: U256) -> H256 {
+        self.db.block_hash(number)
+    }
+}
@@ -1,11 +1,14 @@
 use crate::eth::{
-    backend::{db::Db, validate::TransactionValidator},
+    backend::{
+        cheats::CheatsManager, db::Db, inspector::ForcedRevert, validate::TransactionValidator,
+    },
     error::InvalidTransactionError,
+    macros::node_info,
     pool::transactions::PoolTransaction,
 };
 use anvil_core::eth::{
     block::{Block, BlockInfo, Header, PartialHeader},
-    receipt::{EIP1559Receipt, EIP2930Receipt, EIP658Receipt, Log, TypedReceipt},
+    receipt::{DepositReceipt, EIP1559Receipt, EIP2930Receipt, EIP658Receipt, Log, TypedReceipt},
     transaction::{PendingTransaction, TransactionInfo, TypedTransaction},
     trie,
 };
@@ -15,9 +18,14 @@ use ethers::{
     utils::rlp,
 };
 use foundry_evm::{
-    executor::inspector::Tracer,
+    debug::DebugArena,
+    decode::decode_console_logs,
+    executor::{
+        fork::FailedFetches,
+        inspector::{Debugger, InspectorData, InspectorStack, LogCollector, Tracer},
+    },
     revm,
-    revm::{BlockEnv, CfgEnv, Env, Return, SpecId, TransactOut},
+    revm::{BlockEnv, CfgEnv, DatabaseCommit, Env, Return, TransactOut},
     trace::node::CallTraceNode,
 };
 use parking_lot::RwLock;
@@ -88,6 +96,8 @@ pub struct ExecutedTransaction {
     gas: u64,
     logs: Vec<Log>,
     traces: Vec<CallTraceNode>,
+    /// The executed steps, if steps tracing is enabled
+    steps: Option<DebugArena>,
 }
 
 // == impl ExecutedTransaction ==
@@ -121,6 +131,12 @@ impl ExecutedTransaction {
                 logs_bloom: bloom,
                 logs,
             }),
+            TypedTransaction::Deposit(_) => TypedReceipt::Deposit(DepositReceipt {
+                status_code,
+                gas_used: used_gas,
+                logs_bloom: bloom,
+                logs,
+            }),
         }
     }
 }
@@ -135,6 +151,8 @@ pub struct ExecutedTransactions {
     /// All transactions that were invalid at the point of their execution and were not included in
     /// the block
     pub invalid: Vec<Arc<PoolTransaction>>,
+    /// The executed steps of the `included` transactions, if steps tracing is enabled
+    pub steps: Vec<(H256, DebugArena)>,
 }
 
 /// An executor for a series of transactions
@@ -150,6 +168,16 @@ pub struct TransactionExecutor<'a, Db: ?Sized, Validator: TransactionValidator>
     pub parent_hash: H256,
     /// Cumulative gas used by all executed transactions
     pub gas_used: U256,
+    /// Whether to print the `console.log` output of the executed transactions
+    pub enable_console_log: bool,
+    /// Whether to record every executed opcode, including the stack and memory before it
+    pub enable_steps_tracing: bool,
+    /// Whether the block has a base fee
+    pub is_eip1559: bool,
+    /// Decides which transactions are forced to revert
+    pub cheats: CheatsManager,
+    /// The failed state fetches of the fork, transactions that hit one aren't included
+    pub failed_fetches: FailedFetches,
 }
 
 impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'a, DB, Validator> {
@@ -162,17 +190,14 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
         let mut cumulative_gas_used = U256::zero();
         let mut invalid = Vec::new();
         let mut included = Vec::new();
+        let mut steps = Vec::new();
         let gas_limit = self.block_env.gas_limit;
         let parent_hash = self.parent_hash;
         let block_number = self.block_env.number;
         let difficulty = self.block_env.difficulty;
         let beneficiary = self.block_env.coinbase;
         let timestamp = self.block_env.timestamp.as_u64();
-        let base_fee = if (self.cfg_env.spec_id as u8) >= (SpecId::LONDON as u8) {
-            Some(self.block_env.basefee)
-        } else {
-            None
-        };
+        let base_fee = self.is_eip1559.then(|| self.block_env.basefee);
 
         for (idx, tx) in self.enumerate() {
             let tx = match tx {
@@ -180,7 +205,8 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
                     included.push(tx.transaction.clone());
                     tx
                 }
-                TransactionExecutionOutcome::Exhausted(_) => continue,
+                TransactionExecutionOutcome::Exhausted(_) |
+                TransactionExecutionOutcome::Skipped(_) => continue,
                 TransactionExecutionOutcome::Invalid(tx, _) => {
                     invalid.push(tx);
                     continue
@@ -188,14 +214,19 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
             };
             let receipt = tx.create_receipt();
             cumulative_gas_used = cumulative_gas_used.saturating_add(receipt.gas_used());
-            let ExecutedTransaction { transaction, logs, out, traces, .. } = tx;
+            let ExecutedTransaction { transaction, logs, out, traces, steps: tx_steps, .. } = tx;
             logs_bloom(logs.clone(), &mut bloom);
-
-            let contract_address = if let TransactOut::Create(_, contract_address) = out {
-                trace!(target: "backend", "New contract deployed: at {:?}", contract_address);
-                contract_address
-            } else {
-                None
+            if let Some(tx_steps) = tx_steps {
+                steps.push((*transaction.hash(), tx_steps));
+            }
+
+            let (contract_address, out) = match out {
+                TransactOut::Create(out, contract_address) => {
+                    trace!(target: "backend::executor", "New contract deployed: at {:?}", contract_address);
+                    (contract_address, Some(out.to_vec().into()))
+                }
+                TransactOut::Call(out) => (None, Some(out.to_vec().into())),
+                TransactOut::None => (None, None),
             };
             let info = TransactionInfo {
                 transaction_hash: *transaction.hash(),
@@ -206,6 +237,7 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
                 logs,
                 logs_bloom: *receipt.logs_bloom(),
                 traces,
+                out,
             };
 
             transaction_infos.push(info);
@@ -235,11 +267,20 @@ impl<'a, DB: Db + ?Sized, Validator: TransactionValidator> TransactionExecutor<'
 
         let block = Block::new(partial_header, transactions.clone(), ommers);
         let block = BlockInfo { block, transactions: transaction_infos, receipts };
-        ExecutedTransactions { block, included, invalid }
+        ExecutedTransactions { block, included, invalid, steps }
     }
 
     fn env_for(&self, tx: &PendingTransaction) -> Env {
-        Env { cfg: self.cfg_env.clone(), block: self.block_env.clone(), tx: tx.to_revm_tx_env() }
+        let mut env = Env {
+            cfg: self.cfg_env.clone(),
+            block: self.block_env.clone(),
+            tx: tx.to_revm_tx_env(),
+        };
+        if tx.transaction.is_deposit() {
+            // deposits have a gas price of zero, since their gas is paid on L1
+            env.block.basefee = U256::zero();
+        }
+        env
     }
 }
 
@@ -251,6 +292,9 @@ pub enum TransactionExecutionOutcome {
     Invalid(Arc<PoolTransaction>, InvalidTransactionError),
     /// Execution skipped because could exceed gas limit
     Exhausted(Arc<PoolTransaction>),
+    /// Execution skipped because the transaction can't be included in this block, but may be in
+    /// a later one, so it stays in the pool
+    Skipped(Arc<PoolTransaction>),
 }
 
 impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
@@ -260,7 +304,12 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
 
     fn next(&mut self) -> Option<Self::Item> {
         let transaction = self.pending.next()?;
+        let failed_fetches = self.failed_fetches.count();
         let account = self.db.basic(*transaction.pending_transaction.sender());
+        if self.failed_fetches.count() != failed_fetches {
+            warn!(target: "backend::executor", "[{:?}] sender couldn't be fetched, skipping", transaction.hash());
+            return Some(TransactionExecutionOutcome::Skipped(transaction))
+        }
         let env = self.env_for(&transaction.pending_transaction);
         // check that we comply with the block's gas limit
         let max_gas = self.gas_used.saturating_add(U256::from(env.tx.gas_limit));
@@ -274,27 +323,86 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
             &account,
             &env,
         ) {
-            warn!(target: "backend", "Skipping invalid tx execution [{:?}] {}", transaction.hash(), err);
+            if let InvalidTransactionError::FeeTooLow = err {
+                // the base fee may drop again, until then the transaction stays in the pool
+                trace!(target: "backend::executor", "[{:?}] fee below the base fee, skipping", transaction.hash());
+                return Some(TransactionExecutionOutcome::Skipped(transaction))
+            }
+            warn!(target: "backend::executor", "Skipping invalid tx execution [{:?}] {}", transaction.hash(), err);
             return Some(TransactionExecutionOutcome::Invalid(transaction, err))
         }
 
+        // a previous transaction of the sender was skipped, so this one can't be executed yet
+        if transaction.pending_transaction.nonce().as_u64() > account.nonce {
+            trace!(target: "backend::executor", "[{:?}] nonce gap, skipping", transaction.hash());
+            return Some(TransactionExecutionOutcome::Skipped(transaction))
+        }
+
+        // deposits mint to the sender before they're executed, even if they revert
+        if let TypedTransaction::Deposit(ref deposit) = transaction.pending_transaction.transaction
+        {
+            if !deposit.mint.is_zero() {
+                let sender = *transaction.pending_transaction.sender();
+                self.db.set_balance(sender, account.balance.saturating_add(deposit.mint));
+            }
+        }
+
+        // every transaction is executed in its own EVM, so the accounts and storage slots accessed
+        // by earlier transactions of the block are cold again, like on chain
         let mut evm = revm::EVM::new();
         evm.env = env;
         evm.database(&mut self.db);
 
-        // records all call traces
-        let mut tracer = Tracer::default();
+        // records all call traces and, if enabled, the `console.log` calls and executed steps
+        let mut inspector = InspectorStack {
+            tracer: Some(Tracer::default()),
+            logs: self.enable_console_log.then(LogCollector::default),
+            debugger: self.enable_steps_tracing.then(Debugger::default),
+            ..Default::default()
+        };
 
-        trace!(target: "backend", "[{:?}] executing", transaction.hash());
-        // transact and commit the transaction
-        let (exit, out, gas, logs) = evm.inspect_commit(&mut tracer);
+        trace!(target: "backend::executor", "[{:?}] executing", transaction.hash());
+        // transact the transaction, unless it's forced to revert
+        let (exit, out, gas, state, logs) = match self.cheats.forced_failure(transaction.hash()) {
+            Some(reason) => {
+                trace!(target: "backend::executor", "[{:?}] forced to revert", transaction.hash());
+                evm.inspect(&mut ForcedRevert::new(&mut inspector, &reason))
+            }
+            None => evm.inspect(&mut inspector),
+        };
+        drop(evm);
+
+        // the execution read a default value for state that couldn't be fetched from the fork, so
+        // its outcome is discarded and the transaction stays in the pool
+        if self.failed_fetches.count() != failed_fetches {
+            warn!(target: "backend::executor", "[{:?}] state couldn't be fetched, skipping", transaction.hash());
+            if let TypedTransaction::Deposit(ref deposit) =
+                transaction.pending_transaction.transaction
+            {
+                if !deposit.mint.is_zero() {
+                    self.db.set_balance(*transaction.pending_transaction.sender(), account.balance);
+                }
+            }
+            return Some(TransactionExecutionOutcome::Skipped(transaction))
+        }
+        self.db.commit(state);
+        let InspectorData { logs: console_logs, traces, debug, .. } =
+            inspector.collect_inspector_states();
+
+        let console_logs = decode_console_logs(&console_logs);
+        if !console_logs.is_empty() {
+            node_info!("console.log:");
+            for log in console_logs {
+                node_info!("  {}", log);
+            }
+        }
 
         if exit == Return::OutOfGas {
             // this currently useful for debugging estimations
-            warn!(target: "backend", "[{:?}] executed with out of gas", transaction.hash())
+            warn!(target: "backend::executor", "[{:?}] executed with out of gas", transaction.hash())
         }
 
-        trace!(target: "backend", "[{:?}] executed with out={:?}, gas ={}", transaction.hash(), out, gas);
+        trace!(target: "backend::executor", "[{:?}] executed with out={:?}, gas ={}", transaction.hash(), out, gas);
 
         self.gas_used.saturating_add(U256::from(gas));
 
@@ -306,7 +414,8 @@ impl<'a, 'b, DB: Db + ?Sized, Validator: TransactionValidator> Iterator
             out,
             gas,
             logs: logs.into_iter().map(Into::into).collect(),
-            traces: tracer.traces.arena,
+            traces: traces.map(|traces| traces.arena).unwrap_or_default(),
+            steps: debug,
         };
 
         Some(TransactionExecutionOutcome::Executed(tx))
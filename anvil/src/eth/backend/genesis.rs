@@ -1,8 +1,26 @@
 //! Genesis settings
 
-use ethers::types::{Address, U256};
+use crate::eth::backend::mem::storage::SerializableHistory;
+use anvil_core::types::AccountStateDiff;
+use ethers::{
+    core::k256::ecdsa::SigningKey,
+    signers::{Signer, Wallet},
+    types::{Address, Bytes, H256, U256},
+};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use forge::revm::KECCAK_EMPTY;
 use foundry_evm::revm::AccountInfo;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+/// The first two bytes of a gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
 
 /// Genesis settings
 #[derive(Debug, Clone, Default)]
@@ -11,6 +29,8 @@ pub struct GenesisConfig {
     pub balance: U256,
     /// All accounts that should be initialised at genesis
     pub accounts: Vec<Address>,
+    /// Accounts with custom settings that are applied after `accounts` were initialised
+    pub custom_accounts: Vec<GenesisAccount>,
 }
 
 // === impl GenesisConfig ===
@@ -30,3 +50,190 @@ impl GenesisConfig {
         })
     }
 }
+
+/// An account definition of an accounts file, see `--accounts-file`
+///
+/// Either the `address` or the `privateKey` of the account must be set. Accounts with a private
+/// key can sign transactions, like the dev accounts. All other settings are optional and only
+/// applied if present.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct GenesisAccount {
+    #[serde(default)]
+    pub address: Option<Address>,
+    #[serde(default)]
+    pub private_key: Option<H256>,
+    #[serde(default)]
+    pub balance: Option<U256>,
+    #[serde(default)]
+    pub nonce: Option<u64>,
+    #[serde(default)]
+    pub code: Option<Bytes>,
+    #[serde(default)]
+    pub storage: BTreeMap<U256, U256>,
+}
+
+// === impl GenesisAccount ===
+
+impl GenesisAccount {
+    /// Reads all account definitions from the JSON file at `path`, which contains an array of
+    /// accounts
+    pub fn read_file(path: impl AsRef<Path>) -> Result<Vec<Self>, AccountsFileError> {
+        let content = fs::read_to_string(path)?;
+        let accounts: Vec<Self> = serde_json::from_str(&content)?;
+        for (idx, account) in accounts.iter().enumerate() {
+            match (account.address, account.private_key) {
+                (None, None) => return Err(AccountsFileError::MissingAddress(idx)),
+                (address, Some(_)) => {
+                    let wallet = account.wallet().ok_or(AccountsFileError::InvalidKey(idx))?;
+                    if address.is_some() && address != Some(wallet.address()) {
+                        return Err(AccountsFileError::AddressMismatch(idx))
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Reads all accounts from a state file at `path`, see [SerializableState::read]
+    pub fn read_state_file(path: impl AsRef<Path>) -> Result<Vec<Self>, AccountsFileError> {
+        Ok(SerializableState::read(path)?.genesis_accounts())
+    }
+
+    /// Returns the address of the account, derived from the private key if no address is set
+    pub fn address(&self) -> Address {
+        self.address.or_else(|| self.wallet().map(|wallet| wallet.address())).unwrap_or_default()
+    }
+
+    /// Returns the wallet of the account if it has a valid private key
+    pub fn wallet(&self) -> Option<Wallet<SigningKey>> {
+        let key = SigningKey::from_bytes(self.private_key?.as_bytes()).ok()?;
+        Some(key.into())
+    }
+}
+
+/// The contents of a state file, as written by `--dump-state`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SerializableState {
+    pub accounts: BTreeMap<Address, AccountStateDiff>,
+    #[serde(flatten)]
+    pub history: SerializableHistory,
+    /// The fork the state was dumped from, in which case the accounts are the changes since the
+    /// fork
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fork: Option<SerializableFork>,
+}
+
+/// The endpoint and block a state file was forked off, see [SerializableState::fork]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializableFork {
+    pub json_rpc_url: String,
+    pub block_number: u64,
+}
+
+// === impl SerializableState ===
+
+impl SerializableState {
+    /// Reads the state file at `path`
+    ///
+    /// The file may also only contain the accounts, as returned by `anvil_dumpStateDiff`. Gzip
+    /// compressed files are detected by their magic bytes and decompressed.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, AccountsFileError> {
+        let content = fs::read(path)?;
+        let content = if content.starts_with(&GZIP_MAGIC) {
+            let mut decompressed = Vec::new();
+            GzDecoder::new(content.as_slice()).read_to_end(&mut decompressed)?;
+            decompressed
+        } else {
+            content
+        };
+        let state: serde_json::Value = serde_json::from_slice(&content)?;
+        if state.get("accounts").is_some() {
+            Ok(serde_json::from_value(state)?)
+        } else {
+            Ok(Self {
+                accounts: serde_json::from_value(state)?,
+                history: Default::default(),
+                fork: None,
+            })
+        }
+    }
+
+    /// Writes the state to `path`, as read by [SerializableState::read]
+    pub fn write(
+        &self,
+        path: impl AsRef<Path>,
+        compression: StateCompression,
+    ) -> Result<(), AccountsFileError> {
+        match compression {
+            StateCompression::None => fs::write(path, serde_json::to_string_pretty(self)?)?,
+            StateCompression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&serde_json::to_vec(self)?)?;
+                fs::write(path, encoder.finish()?)?
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the accounts, as they're set at genesis
+    pub fn genesis_accounts(&self) -> Vec<GenesisAccount> {
+        self.accounts
+            .iter()
+            .map(|(address, account)| GenesisAccount {
+                address: Some(*address),
+                private_key: None,
+                balance: account.balance,
+                nonce: account.nonce,
+                code: account.code.clone(),
+                storage: account.storage.clone(),
+            })
+            .collect()
+    }
+}
+
+/// How a state file is compressed when it's written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateCompression {
+    /// Write the state as pretty printed json
+    None,
+    /// Write the state as gzip compressed json
+    Gzip,
+}
+
+impl FromStr for StateCompression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        let compression = match s.as_str() {
+            "none" => StateCompression::None,
+            "gzip" => StateCompression::Gzip,
+            _ => return Err(format!("Unknown StateCompression: `{}`", s)),
+        };
+        Ok(compression)
+    }
+}
+
+impl Default for StateCompression {
+    fn default() -> Self {
+        StateCompression::None
+    }
+}
+
+/// Errors that can occur when reading an accounts file
+#[derive(thiserror::Error, Debug)]
+pub enum AccountsFileError {
+    #[error("Failed to read accounts file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse accounts file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Account #{0} has neither an address nor a private key")]
+    MissingAddress(usize),
+    #[error("Account #{0} has an invalid private key")]
+    InvalidKey(usize),
+    #[error("Address of account #{0} does not match its private key")]
+    AddressMismatch(usize),
+}
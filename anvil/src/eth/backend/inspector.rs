@@ -0,0 +1,133 @@
+//! Inspectors used when executing transactions
+
+use bytes::Bytes;
+use ethers::{
+    abi::{encode, Token},
+    types::{Address, H256},
+};
+use foundry_evm::revm::{
+    db::Database, CallInputs, CreateInputs, EVMData, Gas, Inspector, Interpreter, Return,
+};
+
+/// `keccak(Error(string))[..4]`
+const REVERT_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Wraps another inspector and makes the transaction revert with a reason before its call or
+/// create is executed
+///
+/// Since the transaction's code is never run, it only uses the intrinsic gas and doesn't change
+/// any state apart from the sender's nonce and balance.
+pub struct ForcedRevert<'a, I> {
+    inner: &'a mut I,
+    /// The `Error(string)` encoded revert reason
+    output: Bytes,
+}
+
+// === impl ForcedRevert ===
+
+impl<'a, I> ForcedRevert<'a, I> {
+    pub fn new(inner: &'a mut I, reason: &str) -> Self {
+        let mut output = REVERT_SELECTOR.to_vec();
+        output.extend(encode(&[Token::String(reason.to_string())]));
+        Self { inner, output: output.into() }
+    }
+}
+
+impl<'a, I, DB> Inspector<DB> for ForcedRevert<'a, I>
+where
+    I: Inspector<DB>,
+    DB: Database,
+{
+    fn initialize_interp(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> Return {
+        self.inner.initialize_interp(interpreter, data, is_static)
+    }
+
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+    ) -> Return {
+        self.inner.step(interpreter, data, is_static)
+    }
+
+    fn log(
+        &mut self,
+        evm_data: &mut EVMData<'_, DB>,
+        address: &Address,
+        topics: &[H256],
+        data: &Bytes,
+    ) {
+        self.inner.log(evm_data, address, topics, data)
+    }
+
+    fn step_end(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        is_static: bool,
+        status: Return,
+    ) -> Return {
+        self.inner.step_end(interpreter, data, is_static, status)
+    }
+
+    fn call(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &mut CallInputs,
+        is_static: bool,
+    ) -> (Return, Gas, Bytes) {
+        let (status, gas, retdata) = self.inner.call(data, call, is_static);
+        if status != Return::Continue || data.subroutine.depth() > 0 {
+            return (status, gas, retdata)
+        }
+        (Return::Revert, Gas::new(call.gas_limit), self.output.clone())
+    }
+
+    fn call_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CallInputs,
+        remaining_gas: Gas,
+        status: Return,
+        retdata: Bytes,
+        is_static: bool,
+    ) -> (Return, Gas, Bytes) {
+        self.inner.call_end(data, call, remaining_gas, status, retdata, is_static)
+    }
+
+    fn create(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &mut CreateInputs,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        let (status, address, gas, retdata) = self.inner.create(data, call);
+        if status != Return::Continue || data.subroutine.depth() > 0 {
+            return (status, address, gas, retdata)
+        }
+        // the sender's nonce of a create transaction is only increased when the create is executed
+        data.subroutine.inc_nonce(call.caller);
+        (Return::Revert, None, Gas::new(call.gas_limit), self.output.clone())
+    }
+
+    fn create_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CreateInputs,
+        status: Return,
+        address: Option<Address>,
+        remaining_gas: Gas,
+        retdata: Bytes,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        self.inner.create_end(data, call, status, address, remaining_gas, retdata)
+    }
+
+    fn selfdestruct(&mut self) {
+        Inspector::<DB>::selfdestruct(self.inner)
+    }
+}
@@ -3,7 +3,10 @@ use crate::{
     revm::AccountInfo,
     Address, U256,
 };
-pub use foundry_evm::executor::fork::database::ForkedDatabase;
+use anvil_core::types::AccountStateDiff;
+pub use foundry_evm::executor::fork::database::{ForkDbSnapshot, ForkedDatabase};
+use foundry_evm::{executor::DatabaseRef, revm::KECCAK_EMPTY};
+use std::collections::BTreeMap;
 
 /// Implement the helper for the fork database
 impl Db for ForkedDatabase {
@@ -26,4 +29,60 @@ impl Db for ForkedDatabase {
     fn current_state(&self) -> StateDb {
         StateDb::new(self.create_snapshot())
     }
+
+    fn restore_state(&mut self, state: &StateDb) -> bool {
+        if let Some(snapshot) = state.downcast_ref::<ForkDbSnapshot>() {
+            self.restore_snapshot(snapshot);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn try_fetch(&self, address: Address, slot: Option<U256>) -> Result<(), String> {
+        let res = match slot {
+            Some(slot) => self.try_storage(address, slot).map(drop),
+            None => self.try_basic(address).map(drop),
+        };
+        res.map_err(|err| err.to_string())
+    }
+
+    /// Compares the local layer of the fork, which holds all modified but also all accessed
+    /// accounts, with the state fetched from the remote endpoint
+    fn state_diff(&self) -> Option<BTreeMap<Address, AccountStateDiff>> {
+        let local = self.database();
+        let remote = self.inner().db();
+        let remote_accounts = remote.accounts.read();
+        let remote_storage = remote.storage.read();
+
+        let mut diff = BTreeMap::<Address, AccountStateDiff>::new();
+        for (address, info) in local.cache() {
+            let account = diff.entry(*address).or_default();
+            let (balance, nonce, code_hash) = remote_accounts
+                .get(address)
+                .map(|remote| (remote.balance, remote.nonce, remote.code_hash))
+                .unwrap_or((U256::zero(), 0, KECCAK_EMPTY));
+            if info.balance != balance {
+                account.balance = Some(info.balance);
+            }
+            if info.nonce != nonce {
+                account.nonce = Some(info.nonce);
+            }
+            if info.code_hash != code_hash {
+                account.code = Some(local.code_by_hash(info.code_hash).to_vec().into());
+            }
+        }
+        for (address, slots) in local.storage() {
+            let remote_slots = remote_storage.get(address);
+            for (slot, value) in slots {
+                let remote_value =
+                    remote_slots.and_then(|slots| slots.get(slot)).copied().unwrap_or_default();
+                if *value != remote_value {
+                    diff.entry(*address).or_default().storage.insert(*slot, *value);
+                }
+            }
+        }
+        diff.retain(|_, account| !account.is_empty());
+        Some(diff)
+    }
 }
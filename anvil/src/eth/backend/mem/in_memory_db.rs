@@ -6,7 +6,13 @@ use crate::{
     revm::AccountInfo,
     Address, U256,
 };
+use anvil_core::types::AccountStateDiff;
 use ethers::prelude::H256;
+use foundry_evm::{
+    executor::DatabaseRef,
+    revm::{InMemoryDB, KECCAK_EMPTY},
+};
+use std::collections::BTreeMap;
 use tracing::{trace, warn};
 // reexport for convenience
 pub use foundry_evm::executor::backend::MemDb;
@@ -45,4 +51,30 @@ impl Db for MemDb {
     fn current_state(&self) -> StateDb {
         StateDb::new(self.inner.clone())
     }
+
+    fn restore_state(&mut self, state: &StateDb) -> bool {
+        if let Some(state) = state.downcast_ref::<InMemoryDB>() {
+            self.inner = state.clone();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn dump_state(&self) -> Option<BTreeMap<Address, AccountStateDiff>> {
+        let mut state = BTreeMap::<Address, AccountStateDiff>::new();
+        for (address, info) in self.inner.cache() {
+            let account = state.entry(*address).or_default();
+            account.balance = Some(info.balance);
+            account.nonce = Some(info.nonce);
+            if info.code_hash != KECCAK_EMPTY {
+                account.code = Some(self.inner.code_by_hash(info.code_hash).to_vec().into());
+            }
+        }
+        for (address, slots) in self.inner.storage() {
+            let account = state.entry(*address).or_default();
+            account.storage.extend(slots.iter().filter(|(_, value)| !value.is_zero()));
+        }
+        Some(state)
+    }
 }
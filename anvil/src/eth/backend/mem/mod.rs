@@ -4,23 +4,23 @@ use crate::{
     eth::{
         backend::{
             cheats,
-            cheats::CheatsManager,
-            db::Db,
+            cheats::{CheatedAccount, CheatsManager},
+            db::{Db, StateDb, StateOverrideDb},
             executor::{EvmExecutorLock, ExecutedTransactions, TransactionExecutor},
             fork::ClientFork,
             genesis::GenesisConfig,
             notifications::{NewBlockNotification, NewBlockNotifications},
-            time::{utc_from_secs, TimeManager},
+            time::{duration_since_unix_epoch, utc_from_secs, TimeManager},
             validate::TransactionValidator,
         },
-        error::{BlockchainError, InvalidTransactionError},
-        fees::{FeeDetails, FeeManager},
+        error::{decode_revert_reason, BlockchainError, InvalidTransactionError},
+        fees::{FeeDetails, FeeManager, DEFAULT_PRIORITY_FEE, GAS_PRICE_ORACLE_BLOCKS},
         macros::node_info,
         pool::transactions::PoolTransaction,
     },
     mem::{
         in_memory_db::MemDb,
-        storage::{BlockchainStorage, InMemoryBlockStates, MinedBlockOutcome},
+        storage::{BlockchainStorage, InMemoryBlockStates, MinedBlockOutcome, SerializableHistory},
     },
     revm::{db::DatabaseRef, AccountInfo},
 };
@@ -32,38 +32,64 @@ use anvil_core::{
         transaction::{PendingTransaction, TransactionInfo, TypedTransaction},
         utils::to_access_list,
     },
-    types::{Forking, Index},
+    types::{
+        AccountStateDiff, AccountUpdate, Forking, GethDebugTracingOptions, GethStructLog,
+        GethTrace, GethTraceResult, Index, L1FeeInfo, OtsContractCreator, OtsInternalOperation,
+        OtsReceipt, OtsTrace, StateOverride,
+    },
 };
 use anvil_rpc::error::RpcError;
 use ethers::{
-    prelude::{BlockNumber, TxHash, H256, U256, U64},
+    abi::ethereum_types::BloomInput,
+    prelude::{BlockNumber, TxHash, H160, H256, U256, U64},
     types::{
-        Address, Block as EthersBlock, BlockId, Bytes, Filter, FilteredParams, Log, Trace,
-        Transaction, TransactionReceipt,
+        Address, Block as EthersBlock, BlockId, Bloom, Bytes, Filter, FilteredParams, Log, Trace,
+        Transaction, TransactionReceipt, ValueOrArray,
     },
-    utils::{keccak256, rlp},
+    utils::{hex, keccak256, rlp},
 };
+use foundry_config::Config;
 use foundry_evm::{
+    debug::{DebugArena, Instruction},
+    executor::fork::FailedFetches,
     revm,
     revm::{
-        db::CacheDB, Account, CreateScheme, Env, Return, SpecId, TransactOut, TransactTo, TxEnv,
-        KECCAK_EMPTY,
+        db::CacheDB, opcode, Account, CreateScheme, DatabaseCommit, Env, Return, SpecId,
+        TransactOut, TransactTo, TxEnv, KECCAK_EMPTY,
     },
+    trace::{identifier::SignaturesIdentifier, CallTraceDecoder},
     utils::u256_to_h256_be,
+    CallKind,
 };
 use futures::channel::mpsc::{unbounded, UnboundedSender};
 use parking_lot::{Mutex, RwLock};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::RangeInclusive,
+    sync::Arc,
+};
 use storage::{Blockchain, MinedTransaction};
+use traces::{call_traces, format_steps};
 use tracing::{trace, warn};
 
 pub mod fork_db;
 pub mod in_memory_db;
 pub mod state;
 pub mod storage;
+mod traces;
 
 pub type State = foundry_evm::HashMap<Address, Account>;
 
+/// The `L1Block` predeploy of Optimism that holds the L1 fee parameters
+pub const L1_BLOCK_ADDRESS: Address =
+    H160([0x42, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x15]);
+/// The storage slot of the L1 base fee in the `L1Block` predeploy
+const L1_BASE_FEE_SLOT: u64 = 1;
+/// The storage slot of the fixed L1 gas overhead in the `L1Block` predeploy
+const L1_FEE_OVERHEAD_SLOT: u64 = 5;
+/// The storage slot of the L1 fee scalar in the `L1Block` predeploy, which has 6 decimals
+const L1_FEE_SCALAR_SLOT: u64 = 6;
+
 /// Gives access to the [revm::Database]
 #[derive(Clone)]
 pub struct Backend {
@@ -93,6 +119,33 @@ pub struct Backend {
     active_snapshots: Arc<Mutex<HashMap<U256, (u64, H256)>>>,
     /// A lock used to sync evm executor access
     executor_lock: EvmExecutorLock,
+    /// Decodes the call traces of mined transactions, set if traces should be printed
+    trace_decoder: Option<Arc<CallTraceDecoder>>,
+    /// How much of the execution of mined transactions is printed, see
+    /// [with_trace_verbosity](Self::with_trace_verbosity)
+    trace_verbosity: u8,
+    /// Whether to print the `console.log` output of mined transactions
+    enable_console_log: bool,
+    /// Gas used of the next block that the base fee of the block after it is computed with
+    next_block_gas_used: Arc<Mutex<Option<U256>>>,
+    /// The coinbase of the next block, replaces the configured coinbase for that block only
+    next_block_coinbase: Arc<Mutex<Option<Address>>>,
+    /// The hardforks that become active at a block, sorted by block number
+    ///
+    /// Empty if the hardfork doesn't change over time
+    hardfork_transitions: Vec<(U256, SpecId)>,
+    /// Upper bound for the gas `eth_estimateGas` searches up to, in addition to the block gas
+    /// limit
+    gas_estimation_cap: Option<U256>,
+    /// Whether Optimism deposit transactions are accepted
+    optimism: bool,
+    /// Whether a block is mined after the state was modified by a cheat like `anvil_setBalance`
+    mine_on_state_change: bool,
+    /// The state of the fork right after forking, set if `anvil_reset` without params should
+    /// restore it
+    fork_snapshot: Arc<RwLock<Option<StateDb>>>,
+    /// Whether the suggested gas price is derived from the transactions of recent blocks
+    gas_price_oracle: bool,
 }
 
 impl Backend {
@@ -112,6 +165,17 @@ impl Backend {
             genesis: Default::default(),
             active_snapshots: Arc::new(Mutex::new(Default::default())),
             executor_lock: EvmExecutorLock::new(false),
+            trace_decoder: None,
+            trace_verbosity: 0,
+            enable_console_log: true,
+            next_block_gas_used: Default::default(),
+            next_block_coinbase: Default::default(),
+            hardfork_transitions: Default::default(),
+            gas_estimation_cap: None,
+            optimism: false,
+            mine_on_state_change: false,
+            fork_snapshot: Default::default(),
+            gas_price_oracle: false,
         }
     }
 
@@ -151,12 +215,213 @@ impl Backend {
             fees,
             genesis,
             active_snapshots: Arc::new(Mutex::new(Default::default())),
+            trace_decoder: None,
+            trace_verbosity: 0,
+            enable_console_log: true,
+            next_block_gas_used: Default::default(),
+            next_block_coinbase: Default::default(),
+            hardfork_transitions: Default::default(),
+            gas_estimation_cap: None,
+            optimism: false,
+            mine_on_state_change: false,
+            fork_snapshot: Default::default(),
+            gas_price_oracle: false,
         };
 
         backend.apply_genesis();
         backend
     }
 
+    /// Sets whether the decoded call traces of all mined transactions should be printed
+    ///
+    /// This is the same as a [trace verbosity](Self::with_trace_verbosity) of 2.
+    #[must_use]
+    pub fn with_print_traces(self, print_traces: bool) -> Self {
+        self.with_trace_verbosity(if print_traces { 2 } else { 0 })
+    }
+
+    /// Sets how much of the execution of mined transactions should be printed
+    ///
+    /// - 0: nothing
+    /// - 1: the decoded top-level calls
+    /// - 2: the decoded calls, including internal calls
+    /// - 3: additionally the storage reads and writes
+    /// - 4: additionally every executed opcode
+    ///
+    /// Levels 3 and 4 record every executed opcode, which slows down mining considerably.
+    ///
+    /// Function selectors of contracts that are unknown locally, like forked contracts, are
+    /// decoded on a best-effort basis via the signature identifier.
+    #[must_use]
+    pub fn with_trace_verbosity(mut self, trace_verbosity: u8) -> Self {
+        self.trace_verbosity = trace_verbosity;
+        self.trace_decoder = (trace_verbosity > 0).then(|| {
+            let mut decoder = CallTraceDecoder::new();
+            match SignaturesIdentifier::new(Config::foundry_cache_dir()) {
+                Ok(identifier) => decoder.add_signature_identifier(identifier),
+                Err(err) => {
+                    warn!(target: "backend", "failed to create signature identifier: {}", err)
+                }
+            }
+            Arc::new(decoder)
+        });
+        self
+    }
+
+    /// Sets whether the `console.log` output of mined transactions should be printed
+    #[must_use]
+    pub fn with_console_log(mut self, enable_console_log: bool) -> Self {
+        self.enable_console_log = enable_console_log;
+        self
+    }
+
+    /// Sets an upper bound for the gas `eth_estimateGas` searches up to
+    #[must_use]
+    pub fn with_gas_estimation_cap(mut self, gas_estimation_cap: Option<U256>) -> Self {
+        self.gas_estimation_cap = gas_estimation_cap;
+        self
+    }
+
+    /// Sets whether Optimism deposit transactions are accepted
+    #[must_use]
+    pub fn with_optimism(mut self, optimism: bool) -> Self {
+        self.optimism = optimism;
+        self
+    }
+
+    /// Returns true if Optimism deposit transactions are accepted
+    pub fn is_optimism(&self) -> bool {
+        self.optimism
+    }
+
+    /// Returns the L1 fee the mined transaction pays on Optimism, or `None` if it's a deposit
+    /// transaction
+    ///
+    /// Like on chain, the fee parameters are read from the `L1Block` predeploy at the block of the
+    /// transaction, they're zero unless they were set or fetched from a forked Optimism chain.
+    pub fn mined_transaction_l1_fee(&self, hash: H256) -> Option<L1FeeInfo> {
+        let (block_number, transaction) = {
+            let storage = self.blockchain.storage.read();
+            let mined = storage.transactions.get(&hash)?;
+            let block = storage.blocks.get(&mined.block_hash)?;
+            let transaction =
+                block.transactions.get(mined.info.transaction_index as usize)?.clone();
+            (mined.block_number, transaction)
+        };
+        let raw = match transaction {
+            TypedTransaction::Legacy(tx) => rlp::encode(&tx).to_vec(),
+            TypedTransaction::EIP2930(tx) => [&[1u8][..], &rlp::encode(&tx)[..]].concat(),
+            TypedTransaction::EIP1559(tx) => [&[2u8][..], &rlp::encode(&tx)[..]].concat(),
+            TypedTransaction::Deposit(_) => return None,
+        };
+
+        let (l1_gas_price, overhead, scalar) =
+            self.with_database_at(Some(BlockNumber::Number(block_number.into())), |db| {
+                (
+                    db.storage(L1_BLOCK_ADDRESS, L1_BASE_FEE_SLOT.into()),
+                    db.storage(L1_BLOCK_ADDRESS, L1_FEE_OVERHEAD_SLOT.into()),
+                    db.storage(L1_BLOCK_ADDRESS, L1_FEE_SCALAR_SLOT.into()),
+                )
+            });
+
+        // the data gas of the signed transaction, plus the padding for its signature
+        let zeros = raw.iter().filter(|byte| **byte == 0).count() as u64;
+        let data_gas = zeros * 4 + (raw.len() as u64 - zeros) * 16 + 68 * 16;
+        let l1_gas_used = overhead.saturating_add(data_gas.into());
+        let l1_fee = l1_gas_used.saturating_mul(l1_gas_price).saturating_mul(scalar) / 1_000_000u64;
+
+        let (int, frac) = scalar.div_mod(1_000_000u64.into());
+        let frac = format!("{:06}", frac.as_u64());
+        let frac = frac.trim_end_matches('0');
+        let l1_fee_scalar =
+            if frac.is_empty() { int.to_string() } else { format!("{}.{}", int, frac) };
+
+        Some(L1FeeInfo { l1_fee, l1_gas_used, l1_gas_price, l1_fee_scalar })
+    }
+
+    /// Sets whether a block is mined after the state was modified by a cheat
+    #[must_use]
+    pub fn with_mine_on_state_change(mut self, mine_on_state_change: bool) -> Self {
+        self.mine_on_state_change = mine_on_state_change;
+        self
+    }
+
+    /// Returns true if a block is mined after the state was modified by a cheat
+    pub fn mine_on_state_change(&self) -> bool {
+        self.mine_on_state_change
+    }
+
+    /// Captures the current state of the fork, which [Self::reset_to_fork_snapshot] restores
+    ///
+    /// This has no effect if this is not a fork.
+    #[must_use]
+    pub fn with_fork_snapshot(self, fork_snapshot: bool) -> Self {
+        if fork_snapshot && self.fork.is_some() {
+            *self.fork_snapshot.write() = Some(self.db.read().current_state());
+        }
+        self
+    }
+
+    /// Sets whether the suggested gas price is the median effective gas price of the transactions
+    /// in recent blocks, see [Self::gas_price]
+    #[must_use]
+    pub fn with_gas_price_oracle(mut self, gas_price_oracle: bool) -> Self {
+        self.gas_price_oracle = gas_price_oracle;
+        self
+    }
+
+    /// Returns true if the state of the fork was captured right after forking
+    pub fn has_fork_snapshot(&self) -> bool {
+        self.fork_snapshot.read().is_some()
+    }
+
+    /// Schedules hardforks that become active at the given block numbers
+    ///
+    /// Blocks before the first transition use the hardfork the backend was created with.
+    #[must_use]
+    pub fn with_hardfork_transitions(
+        mut self,
+        transitions: impl IntoIterator<Item = (u64, SpecId)>,
+    ) -> Self {
+        let mut hardfork_transitions: Vec<(U256, SpecId)> =
+            transitions.into_iter().map(|(block, spec_id)| (block.into(), spec_id)).collect();
+        if hardfork_transitions.is_empty() {
+            return self
+        }
+        hardfork_transitions.insert(0, (U256::zero(), self.env.read().cfg.spec_id));
+        // stable sort, so a transition at genesis replaces the initial hardfork
+        hardfork_transitions.sort_by_key(|(block, _)| *block);
+        self.hardfork_transitions = hardfork_transitions;
+
+        self.activate_hardfork(&mut self.env.write());
+        self
+    }
+
+    /// Returns the hardfork that is scheduled for the given block, if transitions are configured
+    fn scheduled_hardfork(&self, number: U256) -> Option<SpecId> {
+        self.hardfork_transitions
+            .iter()
+            .rev()
+            .find(|(block, _)| *block <= number)
+            .map(|(_, spec_id)| *spec_id)
+    }
+
+    /// Activates the hardfork that is scheduled for the block of the given env
+    fn activate_hardfork(&self, env: &mut Env) {
+        if let Some(spec_id) = self.scheduled_hardfork(env.block.number) {
+            if env.cfg.spec_id != spec_id {
+                trace!(
+                    target: "backend",
+                    "activating hardfork {:?} at block {}",
+                    spec_id,
+                    env.block.number
+                );
+            }
+            env.cfg.spec_id = spec_id;
+            self.fees.set_spec_id(spec_id);
+        }
+    }
+
     /// Applies the configured genesis settings
     ///
     /// This will fund, create the genesis accounts
@@ -176,6 +441,22 @@ impl Backend {
                 db.insert_account(account, info);
             }
         }
+
+        for account in self.genesis.custom_accounts.iter() {
+            let address = account.address();
+            if let Some(balance) = account.balance {
+                db.set_balance(address, balance);
+            }
+            if let Some(nonce) = account.nonce {
+                db.set_nonce(address, nonce);
+            }
+            if let Some(ref code) = account.code {
+                db.set_code(address, code.clone());
+            }
+            for (slot, value) in account.storage.iter() {
+                db.set_storage_at(address, *slot, *value);
+            }
+        }
     }
 
     /// Sets the account to impersonate
@@ -187,11 +468,18 @@ impl Backend {
         }
         // need to bypass EIP-3607: Reject transactions from senders with deployed code by setting
         // the code hash to `KECCAK_EMPTY` temporarily
-        let mut account = self.db.read().basic(addr);
+        let mut db = self.db.write();
+        let mut account = db.basic(addr);
         let mut code_hash = None;
         if account.code_hash != KECCAK_EMPTY {
+            // the code can no longer be looked up by its hash, so it must be kept in the account
+            // itself, otherwise the contract would behave like an EOA while it's impersonated
+            if account.code.is_none() {
+                account.code = Some(db.code_by_hash(account.code_hash));
+            }
             code_hash = Some(std::mem::replace(&mut account.code_hash, KECCAK_EMPTY));
-            self.db.write().insert_account(addr, account);
+            db.insert_account(addr, account);
+            self.clear_cached_calls();
         }
         self.cheats.impersonate(addr, code_hash)
     }
@@ -201,8 +489,23 @@ impl Backend {
         if let Some(code_hash) = self.cheats.stop_impersonating(&addr) {
             let mut db = self.db.write();
             let mut account = db.basic(addr);
-            account.code_hash = code_hash;
-            db.insert_account(addr, account)
+            // the code may have been replaced while the account was impersonated
+            account.code_hash = match account.code.as_ref().filter(|code| !code.is_empty()) {
+                Some(code) => H256::from_slice(&keccak256(code)),
+                None if account.code.is_some() => KECCAK_EMPTY,
+                None => code_hash,
+            };
+            db.insert_account(addr, account);
+            self.clear_cached_calls();
+        }
+    }
+
+    /// Invalidates the memoized `eth_call` results of the fork, if any
+    ///
+    /// This must be called whenever the state or the block env changes
+    pub fn clear_cached_calls(&self) {
+        if let Some(ref fork) = self.fork {
+            fork.clear_cached_calls();
         }
     }
 
@@ -221,29 +524,96 @@ impl Backend {
         if let Some(fork) = self.get_fork() {
             // reset the fork entirely and reapply the genesis config
             fork.reset(forking.json_rpc_url.clone(), forking.block_number).await?;
-            // update all settings related to the forked block
-            {
-                let mut env = self.env.write();
-                env.cfg.chain_id = fork.chain_id().into();
-                env.block.number = fork.block_number().into();
-                self.time.set_start_timestamp(fork.timestamp());
-                let base_fee = fork.base_fee().unwrap_or_default();
-                self.fees.set_base_fee(base_fee);
-                env.block.basefee = base_fee;
+            self.reset_to_fork_block(fork);
+            if fork.gas_price_passthrough() {
+                fork.apply_gas_price_passthrough(&self.fees).await;
             }
-
-            // reset storage
-            *self.blockchain.storage.write() =
-                BlockchainStorage::forked(fork.block_number(), fork.block_hash());
-            self.states.write().clear();
-
             self.apply_genesis();
+
+            // the previous snapshot belongs to the old fork
+            let mut fork_snapshot = self.fork_snapshot.write();
+            if fork_snapshot.is_some() {
+                *fork_snapshot = Some(self.db.read().current_state());
+            }
             Ok(())
         } else {
             Err(RpcError::invalid_params("Forking not enabled").into())
         }
     }
 
+    /// Advances a fork that follows the head of the fork endpoint to its latest block, see
+    /// [ClientFork::advance_to_latest]
+    ///
+    /// The local chain continues after the new head, unless it's already ahead of it.
+    pub async fn follow_fork_head(&self) -> Result<(), BlockchainError> {
+        let fork = match self.get_fork() {
+            Some(fork) => fork,
+            None => return Ok(()),
+        };
+        // no block can be mined while the fork moves
+        let _lock = self.executor_lock.write().await;
+        let previous_fork_block = fork.block_number();
+        let block_number = match fork.advance_to_latest().await? {
+            Some(block_number) => block_number,
+            None => return Ok(()),
+        };
+        trace!(target: "backend", "fork head advanced to {}", block_number);
+
+        let mut env = self.env.write();
+        if env.block.number < block_number.into() {
+            env.block.number = block_number.into();
+            let mut storage = self.blockchain.storage.write();
+            if storage.best_number.as_u64() == previous_fork_block {
+                // nothing was mined locally yet, so the new head is the parent of the next block
+                storage.best_hash = fork.block_hash();
+                storage.hashes.insert(block_number.into(), fork.block_hash());
+            }
+            storage.best_number = block_number.into();
+        }
+        Ok(())
+    }
+
+    /// Restores the state of the fork that was captured right after forking, see
+    /// [Self::with_fork_snapshot]
+    ///
+    /// Unlike [Self::reset_fork] this doesn't send any requests to the fork endpoint, the data
+    /// that was already fetched from it is kept.
+    pub fn reset_to_fork_snapshot(&self) -> Result<(), BlockchainError> {
+        let fork =
+            self.get_fork().ok_or_else(|| RpcError::invalid_params("Forking not enabled"))?;
+        let fork_snapshot = self.fork_snapshot.read();
+        let snapshot =
+            fork_snapshot.as_ref().ok_or_else(|| RpcError::invalid_params("No fork snapshot"))?;
+        if !self.db.write().restore_state(snapshot) {
+            return Err(BlockchainError::Internal("Failed to restore the fork snapshot".to_string()))
+        }
+        self.reset_to_fork_block(fork);
+        Ok(())
+    }
+
+    /// Resets the chain to the forked block and updates all settings related to it
+    fn reset_to_fork_block(&self, fork: &ClientFork) {
+        {
+            let mut env = self.env.write();
+            env.cfg.chain_id = fork.chain_id().into();
+            env.block.number = fork.block_number().into();
+            self.activate_hardfork(&mut env);
+            self.time.set_start_timestamp(fork.timestamp());
+            let base_fee = fork.base_fee().unwrap_or_default();
+            self.fees.set_base_fee(base_fee);
+            // the fees of the fork block are fetched again by `reset_fork`
+            if !fork.gas_price_passthrough() {
+                self.fees.set_suggested_priority_fee(DEFAULT_PRIORITY_FEE.into());
+            }
+            env.block.basefee = base_fee;
+        }
+
+        // reset storage
+        *self.blockchain.storage.write() =
+            BlockchainStorage::forked(fork.block_number(), fork.block_hash());
+        self.states.write().clear();
+    }
+
     /// Returns the `TimeManager` responsible for timestamps
     pub fn time(&self) -> &TimeManager {
         &self.time
@@ -254,6 +624,41 @@ impl Backend {
         &self.cheats
     }
 
+    /// Sets whether the changes made by state cheats are recorded, see `anvil_getCheatLog`
+    #[must_use]
+    pub fn with_record_cheats(self, record_cheats: bool) -> Self {
+        self.cheats.set_record_cheats(record_cheats);
+        self
+    }
+
+    /// Applies the state `cheat` to the db and records the changes it made to the account, if
+    /// cheats are recorded, see `anvil_getCheatLog`
+    ///
+    /// `slots` are the storage slots the cheat writes. The db lock is held from reading the account
+    /// before the cheat until reading it after, so no concurrent write is attributed to the cheat.
+    pub fn apply_cheat<T>(
+        &self,
+        method: &str,
+        address: Address,
+        slots: &[U256],
+        cheat: impl FnOnce(&mut dyn Db) -> T,
+    ) -> T {
+        let out = {
+            let mut db = self.db.write();
+            if self.cheats.is_recording_cheats() {
+                let before = cheated_account(&*db, address, slots);
+                let out = cheat(&mut *db);
+                let after = cheated_account(&*db, address, slots);
+                self.cheats.record_cheat(method, address, before, after);
+                out
+            } else {
+                cheat(&mut *db)
+            }
+        };
+        self.clear_cached_calls();
+        out
+    }
+
     /// Returns the `FeeManager` that manages fee/pricings
     pub fn fees(&self) -> &FeeManager {
         &self.fees
@@ -284,6 +689,8 @@ impl Backend {
     pub fn set_block_number(&self, number: U256) {
         let mut env = self.env.write();
         env.block.number = number;
+        self.activate_hardfork(&mut env);
+        self.clear_cached_calls();
     }
 
     /// Returns the client coinbase address.
@@ -296,6 +703,12 @@ impl Backend {
         self.env.read().cfg.chain_id
     }
 
+    /// Sets the chain id, transactions must be signed for the new chain id from now on
+    pub fn set_chain_id(&self, chain_id: u64) {
+        self.env.write().cfg.chain_id = chain_id.into();
+        self.clear_cached_calls();
+    }
+
     /// Returns balance of the given account.
     pub fn current_balance(&self, address: Address) -> U256 {
         self.db.read().basic(address).balance
@@ -309,31 +722,186 @@ impl Backend {
     /// Sets the coinbase address
     pub fn set_coinbase(&self, address: Address) {
         self.env.write().block.coinbase = address;
+        self.clear_cached_calls();
+    }
+
+    /// Sets the coinbase of the next mined block only, later blocks use the configured coinbase
+    /// again
+    pub fn set_next_block_coinbase(&self, address: Address) {
+        *self.next_block_coinbase.lock() = Some(address);
     }
 
     /// Sets the nonce of the given address
     pub fn set_nonce(&self, address: Address, nonce: U256) {
         self.db.write().set_nonce(address, nonce.try_into().unwrap_or(u64::MAX));
+        self.clear_cached_calls();
     }
 
     /// Sets the balance of the given address
     pub fn set_balance(&self, address: Address, balance: U256) {
         self.db.write().set_balance(address, balance);
+        self.clear_cached_calls();
+    }
+
+    /// Adds `delta` to the balance of the given address, saturating at `U256::MAX`
+    ///
+    /// Returns the new balance
+    pub fn add_balance(&self, address: Address, delta: U256) -> U256 {
+        let balance = self.db.write().add_balance(address, delta);
+        self.clear_cached_calls();
+        balance
+    }
+
+    /// Returns the state root of the current state, if the database can compute it
+    ///
+    /// This is computed on demand by building the state trie of all accounts and their storage,
+    /// so the cost grows with the size of the state. It's not available in fork mode, since the
+    /// full state of the forked chain isn't known locally.
+    pub fn state_root(&self) -> Option<H256> {
+        self.db.read().maybe_state_root()
+    }
+
+    /// Returns all accounts that changed since the fork
+    pub fn state_diff(&self) -> Result<BTreeMap<Address, AccountStateDiff>, BlockchainError> {
+        self.db
+            .read()
+            .state_diff()
+            .ok_or_else(|| RpcError::invalid_params("Forking not enabled").into())
+    }
+
+    /// Returns all accounts with their storage, in the format `--load-state` reads
+    ///
+    /// In fork mode these are only the accounts that changed since the fork
+    pub fn dump_state(&self) -> Result<BTreeMap<Address, AccountStateDiff>, BlockchainError> {
+        self.db
+            .read()
+            .dump_state()
+            .ok_or_else(|| RpcError::invalid_params("Dumping the state is not supported").into())
+    }
+
+    /// Returns all blocks and transactions, in the format `--load-state` reads
+    ///
+    /// This is empty in fork mode, since the blocks of a fork are not loaded
+    pub fn serializable_history(&self) -> SerializableHistory {
+        if self.is_fork() {
+            return Default::default()
+        }
+        self.blockchain.storage.read().serializable_history()
+    }
+
+    /// Replaces the blocks and transactions of the chain with the ones of the `history`, so that
+    /// they can be queried after a restart
+    ///
+    /// The state of the chain is not affected, it's set by the accounts of the state file. The
+    /// `history` is ignored in fork mode.
+    pub fn load_history(&self, history: SerializableHistory) {
+        if self.is_fork() {
+            return
+        }
+        let (best_number, best_timestamp) = {
+            let mut storage = self.blockchain.storage.write();
+            if !storage.load_history(history) {
+                return
+            }
+            let timestamp = storage.blocks.get(&storage.best_hash).map(|b| b.header.timestamp);
+            (storage.best_number, timestamp.unwrap_or_default())
+        };
+        self.states.write().clear();
+        // the next block must not be older than the loaded blocks
+        if best_timestamp >= duration_since_unix_epoch().as_secs() {
+            self.time.set_start_timestamp(best_timestamp + 1);
+        }
+        self.set_block_number(best_number.as_u64().into());
     }
 
     /// Sets the code of the given address
     pub fn set_code(&self, address: Address, code: Bytes) {
         self.db.write().set_code(address, code);
+        self.clear_cached_calls();
+    }
+
+    /// Executes the `initcode` as a contract creation from `from` and commits the resulting state,
+    /// without a transaction or a new block
+    ///
+    /// The creation doesn't pay for gas, but it increases the nonce of `from` like a deployment
+    /// transaction. The changes to all touched accounts are recorded like those of the
+    /// `anvil_deployCode` cheat.
+    ///
+    /// Nothing is committed if state the creation read couldn't be fetched from the fork.
+    pub async fn deploy_code(
+        &self,
+        from: Address,
+        initcode: Bytes,
+        value: U256,
+    ) -> Result<(Return, TransactOut, u64), BlockchainError> {
+        let _lock = self.executor_lock.write().await;
+        let failed_fetches = self.failed_fetches().count();
+
+        let request = CallRequest {
+            from: Some(from),
+            data: Some(initcode),
+            value: Some(value),
+            ..Default::default()
+        };
+        let env = self.build_call_env(request, FeeDetails::zero());
+
+        let (exit, out, gas) = {
+            let mut db = self.db.write();
+            let (exit, out, gas, state) = {
+                let mut db = &mut *db;
+                let mut evm = revm::EVM::new();
+                evm.env = env;
+                evm.database(&mut db);
+                let (exit, out, gas, state, _) = evm.transact();
+                (exit, out, gas, state)
+            };
+            self.ensure_no_failed_fetches(failed_fetches)?;
+
+            let mut touched = Vec::new();
+            if self.cheats.is_recording_cheats() {
+                for (address, account) in &state {
+                    let slots = account.storage.keys().copied().collect::<Vec<_>>();
+                    let before = cheated_account(&*db, *address, &slots);
+                    touched.push((*address, slots, before));
+                }
+            }
+            db.commit(state);
+            for (address, slots, before) in touched {
+                let after = cheated_account(&*db, address, &slots);
+                self.cheats.record_cheat("anvil_deployCode", address, before, after);
+            }
+            (exit, out, gas)
+        };
+        trace!(target: "backend", "deployed code from {:?}: {:?} out: {:?} gas {}", from, exit, out, gas);
+        self.clear_cached_calls();
+
+        Ok((exit, out, gas))
     }
 
     /// Sets the value for the given slot of the given address
     pub fn set_storage_at(&self, address: Address, slot: U256, val: U256) {
         self.db.write().set_storage_at(address, slot, val);
+        self.clear_cached_calls();
+    }
+
+    /// Sets multiple storage slots of the given account while holding the db lock
+    pub fn set_storage_batch(&self, address: Address, slots: Vec<(U256, U256)>) {
+        let mut db = self.db.write();
+        for (slot, val) in slots {
+            db.set_storage_at(address, slot, val);
+        }
+        self.clear_cached_calls();
+    }
+
+    /// Applies all given fields of the account while holding the db lock
+    pub fn set_account(&self, address: Address, account: AccountUpdate) {
+        self.db.write().set_account(address, account);
+        self.clear_cached_calls();
     }
 
-    /// Returns true for post London
+    /// Returns true for post London, unless EIP-1559 was forced on or off
     pub fn is_eip1559(&self) -> bool {
-        (self.env().read().cfg.spec_id as u8) >= (SpecId::LONDON as u8)
+        self.fees.is_eip1559()
     }
 
     /// Returns the block gas limit
@@ -341,6 +909,19 @@ impl Backend {
         self.env().read().block.gas_limit
     }
 
+    /// Sets the block gas limit
+    pub fn set_gas_limit(&self, gas_limit: U256) {
+        self.env().write().block.gas_limit = gas_limit;
+    }
+
+    /// Returns the highest gas `eth_estimateGas` searches up to
+    ///
+    /// This is the block gas limit, lowered to the configured gas estimation cap if any.
+    pub fn gas_estimation_cap(&self) -> U256 {
+        let gas_limit = self.gas_limit();
+        self.gas_estimation_cap.map(|cap| cap.min(gas_limit)).unwrap_or(gas_limit)
+    }
+
     /// Returns the current base fee
     pub fn base_fee(&self) -> U256 {
         self.fees.base_fee()
@@ -351,16 +932,95 @@ impl Backend {
         self.fees.set_base_fee(basefee)
     }
 
+    /// Sets the gas used that the next block reports for the base fee computation of its successor
+    pub fn set_next_block_gas_used(&self, gas_used: U256) -> Result<(), BlockchainError> {
+        if !self.fees.is_dynamic_base_fee() {
+            return Err(RpcError::invalid_params(
+                "Setting the next block's gas used requires a dynamic base fee",
+            )
+            .into())
+        }
+        let gas_limit = self.gas_limit();
+        if gas_used > gas_limit {
+            return Err(RpcError::invalid_params(format!(
+                "Gas used {} exceeds the block gas limit {}",
+                gas_used, gas_limit
+            ))
+            .into())
+        }
+        *self.next_block_gas_used.lock() = Some(gas_used);
+        Ok(())
+    }
+
     /// Returns the current gas price
+    ///
+    /// If the gas price oracle is enabled, this is derived from the transactions of the last
+    /// [GAS_PRICE_ORACLE_BLOCKS] blocks, like geth's oracle does. Without any transactions in these
+    /// blocks the configured gas price is returned.
     pub fn gas_price(&self) -> U256 {
+        if self.gas_price_oracle {
+            if let Some(gas_price) = self.gas_price_from_history() {
+                return gas_price
+            }
+        }
         self.fees.gas_price()
     }
 
+    /// Returns the median effective tip of the transactions in recent blocks on top of the current
+    /// base fee, or `None` if there are no transactions in these blocks
+    fn gas_price_from_history(&self) -> Option<U256> {
+        let mut tips = Vec::new();
+        {
+            let storage = self.blockchain.storage.read();
+            let best_number = storage.best_number.as_u64();
+            let first = best_number.saturating_sub(GAS_PRICE_ORACLE_BLOCKS - 1);
+            for number in first..=best_number {
+                let block =
+                    storage.hashes.get(&number.into()).and_then(|hash| storage.blocks.get(hash));
+                if let Some(block) = block {
+                    let base_fee = block.header.base_fee_per_gas.unwrap_or_default();
+                    tips.extend(
+                        block
+                            .transactions
+                            .iter()
+                            .filter(|tx| !matches!(tx, TypedTransaction::Deposit(_)))
+                            .map(|tx| tx.effective_tip(base_fee)),
+                    );
+                }
+            }
+        }
+        if tips.is_empty() {
+            return None
+        }
+        tips.sort_unstable();
+        let gas_price = self.fees.base_fee().saturating_add(tips[tips.len() / 2]);
+        Some(
+            self.fees
+                .min_gas_price()
+                .map_or(gas_price, |min_gas_price| gas_price.max(min_gas_price)),
+        )
+    }
+
+    /// Returns the priority fee that is suggested on top of the base fee
+    pub fn max_priority_fee_per_gas(&self) -> U256 {
+        self.fees.suggested_priority_fee()
+    }
+
+    /// Sets the priority fee that is suggested on top of the base fee
+    pub fn set_max_priority_fee_per_gas(&self, fee: U256) {
+        self.fees.set_suggested_priority_fee(fee)
+    }
+
     /// Sets the gas price
     pub fn set_gas_price(&self, price: U256) {
         self.fees.set_gas_price(price)
     }
 
+    /// Sets the lowest gas price of accepted transactions, `None` removes it
+    pub fn set_min_gas_price(&self, min_gas_price: Option<U256>) {
+        self.fees.set_min_gas_price(min_gas_price)
+    }
+
     pub fn elasticity(&self) -> f64 {
         self.fees.elasticity()
     }
@@ -392,7 +1052,7 @@ impl Backend {
                     if let Some(hash) = storage.hashes.remove(&n) {
                         if let Some(block) = storage.blocks.remove(&hash) {
                             for tx in block.transactions {
-                                let _ = storage.transactions.remove(&tx.hash());
+                                let _ = storage.remove_transaction(&tx.hash());
                             }
                         }
                     }
@@ -403,7 +1063,111 @@ impl Backend {
             }
             self.set_block_number(num.into());
         }
-        self.db.write().revert(id)
+        let reverted = self.db.write().revert(id);
+        self.clear_cached_calls();
+        reverted
+    }
+
+    /// Moves the chain head back by `n` blocks and restores the state of the new head block
+    ///
+    /// The timestamps and, if it's adjusted per block, the base fee of the next blocks continue
+    /// from the new head block. Returns the transactions of the removed blocks in the order they
+    /// were mined.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if this would roll back past the genesis block (or the fork block in fork
+    /// mode), or if the state of the new head block is not available.
+    pub async fn rollback(&self, n: u64) -> Result<Vec<PendingTransaction>, BlockchainError> {
+        let _lock = self.executor_lock.write().await;
+
+        let best_number = self.best_number().as_u64();
+        let first_number = self.get_fork().map(|fork| fork.block_number()).unwrap_or_default();
+        let number = match best_number.checked_sub(n).filter(|number| *number >= first_number) {
+            Some(number) => number,
+            None => {
+                return Err(RpcError::invalid_params(format!(
+                    "can not roll back {} blocks from block {}, the chain starts at block {}",
+                    n, best_number, first_number
+                ))
+                .into())
+            }
+        };
+        if number == best_number {
+            return Ok(Vec::new())
+        }
+
+        let (hash, header) = {
+            let storage = self.blockchain.storage.read();
+            let hash = storage
+                .hashes
+                .get(&U64::from(number))
+                .copied()
+                .ok_or(BlockchainError::BlockNotFound)?;
+            (hash, storage.blocks.get(&hash).map(|block| block.header.clone()))
+        };
+
+        // the db is always locked before the storage, like `pending_block` does
+        {
+            let states = self.states.read();
+            let restored =
+                states.get(&hash).map_or(false, |state| self.db.write().restore_state(state));
+            if !restored {
+                return Err(RpcError::invalid_params(format!(
+                    "the state of block {} is not available",
+                    number
+                ))
+                .into())
+            }
+        }
+
+        // the fork block is not stored locally
+        let (timestamp, base_fee) = match header {
+            Some(header) => {
+                let base_fee = header.base_fee_per_gas.unwrap_or_default();
+                let base_fee = if self.fees.is_dynamic_base_fee() {
+                    self.fees
+                        .get_next_block_base_fee_per_gas(
+                            header.gas_used,
+                            header.gas_limit,
+                            base_fee,
+                        )
+                        .into()
+                } else {
+                    base_fee
+                };
+                (header.timestamp, base_fee)
+            }
+            None => {
+                let fork = self.get_fork().ok_or(BlockchainError::BlockNotFound)?;
+                (fork.timestamp(), fork.base_fee().unwrap_or_default())
+            }
+        };
+        self.time.rewind_to(timestamp);
+        self.fees.set_base_fee(base_fee);
+
+        let mut storage = self.blockchain.storage.write();
+        let mut transactions = Vec::new();
+        for n in (number + 1)..=best_number {
+            trace!(target: "backend", "rolling back block {}", n);
+            if let Some(hash) = storage.hashes.remove(&U64::from(n)) {
+                self.states.write().remove(&hash);
+                if let Some(block) = storage.blocks.remove(&hash) {
+                    for tx in block.transactions {
+                        if let Some(mined) = storage.remove_transaction(&tx.hash()) {
+                            transactions.push(PendingTransaction::with_sender(tx, mined.info.from));
+                        }
+                    }
+                }
+            }
+        }
+        storage.best_number = number.into();
+        storage.best_hash = hash;
+        drop(storage);
+
+        self.set_block_number(number.into());
+        self.clear_cached_calls();
+        Ok(transactions)
     }
 
     /// Returns the environment for the next block
@@ -411,6 +1175,9 @@ impl Backend {
         let mut env = self.env.read().clone();
         // increase block number for this block
         env.block.number = env.block.number.saturating_add(U256::one());
+        if let Some(spec_id) = self.scheduled_hardfork(env.block.number) {
+            env.cfg.spec_id = spec_id;
+        }
         env.block.basefee = self.base_fee();
         env.block.timestamp = self.time.current_call_timestamp().into();
         env
@@ -450,6 +1217,11 @@ impl Backend {
             cfg_env: env.cfg,
             parent_hash: storage.best_hash,
             gas_used: U256::zero(),
+            enable_console_log: false,
+            enable_steps_tracing: false,
+            is_eip1559: self.is_eip1559(),
+            cheats: self.cheats.clone(),
+            failed_fetches: self.failed_fetches(),
         };
 
         // create a new pending block
@@ -457,6 +1229,127 @@ impl Backend {
         executed.block
     }
 
+    /// Replays the transactions of the mined block on top of the state of its parent and returns
+    /// geth's struct logs of every transaction, see `debug_traceBlockByHash`
+    pub async fn debug_trace_block(
+        &self,
+        hash: H256,
+        opts: &GethDebugTracingOptions,
+    ) -> Result<Vec<GethTraceResult>, BlockchainError> {
+        let _lock = self.executor_lock.read().await;
+
+        let (block, senders) = {
+            let storage = self.blockchain.storage.read();
+            let block = match storage.blocks.get(&hash) {
+                Some(block) => block.clone(),
+                None if self.get_fork().is_some() => {
+                    return Err(RpcError::invalid_params(
+                        "Only blocks mined after the fork can be traced",
+                    )
+                    .into())
+                }
+                None => return Err(BlockchainError::BlockNotFound),
+            };
+            let senders = block
+                .transactions
+                .iter()
+                .map(|tx| storage.transactions.get(&tx.hash()).map(|tx| tx.info.from))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(BlockchainError::BlockNotFound)?;
+            (block, senders)
+        };
+        if block.transactions.is_empty() {
+            return Ok(Vec::new())
+        }
+        let Block { header, transactions, .. } = block;
+        let hashes = transactions.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+
+        let mut env = self.env.read().clone();
+        env.block.number = header.number;
+        env.block.coinbase = header.beneficiary;
+        env.block.timestamp = header.timestamp.into();
+        env.block.difficulty = header.difficulty;
+        env.block.gas_limit = header.gas_limit;
+        env.block.basefee = header.base_fee_per_gas.unwrap_or_default();
+        if let Some(spec_id) = self.scheduled_hardfork(header.number) {
+            env.cfg.spec_id = spec_id;
+        }
+
+        let pool_transactions = transactions
+            .into_iter()
+            .zip(senders)
+            .map(|(tx, sender)| {
+                Arc::new(PoolTransaction {
+                    pending_transaction: PendingTransaction::with_sender(tx, sender),
+                    requires: Vec::new(),
+                    provides: Vec::new(),
+                    priority: Default::default(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let states = self.states.read();
+        let state = states.get(&header.parent_hash).ok_or_else(|| {
+            BlockchainError::BlockOutOfRange(
+                self.env.read().block.number.as_u64(),
+                header.number.as_u64(),
+            )
+        })?;
+        let mut cache_db = CacheDB::new(state);
+        let executor = TransactionExecutor {
+            db: &mut cache_db,
+            validator: self,
+            pending: pool_transactions.into_iter(),
+            block_env: env.block,
+            cfg_env: env.cfg,
+            parent_hash: header.parent_hash,
+            gas_used: U256::zero(),
+            enable_console_log: false,
+            enable_steps_tracing: true,
+            is_eip1559: header.base_fee_per_gas.is_some(),
+            cheats: self.cheats.clone(),
+            failed_fetches: self.failed_fetches(),
+        };
+        let failed_fetches = self.failed_fetches().count();
+        let ExecutedTransactions { block, mut steps, .. } = executor.execute();
+        self.ensure_no_failed_fetches(failed_fetches)?;
+        let BlockInfo { transactions: infos, receipts, .. } = block;
+        let mut executed = infos
+            .into_iter()
+            .zip(receipts)
+            .map(|(info, receipt)| (info.transaction_hash, (info, receipt)))
+            .collect::<HashMap<_, _>>();
+
+        Ok(hashes
+            .into_iter()
+            .map(|hash| {
+                let (info, receipt) = match executed.remove(&hash) {
+                    Some(executed) => executed,
+                    None => {
+                        return GethTraceResult {
+                            result: None,
+                            error: Some(format!("transaction {:?} could not be replayed", hash)),
+                        }
+                    }
+                };
+                let struct_logs = steps
+                    .iter()
+                    .position(|(tx_hash, _)| *tx_hash == hash)
+                    .map(|idx| geth_struct_logs(&steps.swap_remove(idx).1, opts))
+                    .unwrap_or_default();
+                GethTraceResult {
+                    result: Some(GethTrace {
+                        gas: receipt.gas_used().as_u64(),
+                        failed: !receipt.status(),
+                        return_value: hex::encode(info.out.unwrap_or_default()),
+                        struct_logs,
+                    }),
+                    error: None,
+                }
+            })
+            .collect())
+    }
+
     /// Mines a new block and stores it.
     ///
     /// this will execute all transaction in the order they come in and return all the markers they
@@ -465,44 +1358,93 @@ impl Backend {
         &self,
         pool_transactions: Vec<Arc<PoolTransaction>>,
     ) -> MinedBlockOutcome {
-        self.do_mine_block(pool_transactions).await
+        self.do_mine_block(pool_transactions, None)
+            .await
+            .expect("only an exact timestamp can be rejected")
+    }
+
+    /// Mines a new block with the given `timestamp` and stores it.
+    ///
+    /// The timestamp is checked and applied while the executor is locked, so no other block can be
+    /// mined in between. A timestamp set via `evm_setNextBlockTimestamp` is kept for the next
+    /// block.
+    ///
+    /// Returns an error if the `timestamp` isn't later than the timestamp of the latest block.
+    pub async fn mine_block_with_timestamp(
+        &self,
+        pool_transactions: Vec<Arc<PoolTransaction>>,
+        timestamp: u64,
+    ) -> Result<MinedBlockOutcome, BlockchainError> {
+        self.do_mine_block(pool_transactions, Some(timestamp)).await
     }
 
     async fn do_mine_block(
         &self,
         pool_transactions: Vec<Arc<PoolTransaction>>,
-    ) -> MinedBlockOutcome {
-        trace!(target: "backend", "creating new block with {} transactions", pool_transactions.len());
+        timestamp: Option<u64>,
+    ) -> Result<MinedBlockOutcome, BlockchainError> {
+        trace!(target: "backend::mining", "creating new block with {} transactions", pool_transactions.len());
 
-        let (outcome, header, block_hash) = {
+        let (outcome, header, block_hash, traces, steps) = {
             let _lock = self.executor_lock.write().await;
 
-            let current_base_fee = self.base_fee();
             // acquire all locks
             let mut env = self.env.write();
             let mut db = self.db.write();
             let mut storage = self.blockchain.storage.write();
 
+            if let Some(timestamp) = timestamp {
+                let latest = storage
+                    .blocks
+                    .get(&storage.best_hash)
+                    .map(|block| block.header.timestamp)
+                    .unwrap_or_default();
+                if timestamp <= latest {
+                    return Err(RpcError::invalid_params(format!(
+                        "Timestamp {} is lower than or equal to the latest block's timestamp {}",
+                        timestamp, latest
+                    ))
+                    .into())
+                }
+            }
+
             // store current state
             self.states.write().insert(storage.best_hash, db.current_state());
 
             // increase block number for this block
             env.block.number = env.block.number.saturating_add(U256::one());
-            env.block.basefee = current_base_fee;
-            env.block.timestamp = self.time.next_timestamp().into();
+            // the hardfork of the block determines whether it has a base fee
+            self.activate_hardfork(&mut env);
+            env.block.basefee = self.base_fee();
+            env.block.timestamp = match timestamp {
+                Some(timestamp) => self.time.use_timestamp(timestamp),
+                None => self.time.next_timestamp(),
+            }
+            .into();
+
+            let mut block_env = env.block.clone();
+            // the one-shot coinbase only applies to this block
+            if let Some(coinbase) = self.next_block_coinbase.lock().take() {
+                block_env.coinbase = coinbase;
+            }
 
             let executor = TransactionExecutor {
                 db: &mut *db,
                 validator: self,
                 pending: pool_transactions.into_iter(),
-                block_env: env.block.clone(),
+                block_env,
                 cfg_env: env.cfg.clone(),
                 parent_hash: storage.best_hash,
                 gas_used: U256::zero(),
+                enable_console_log: self.enable_console_log,
+                enable_steps_tracing: self.trace_verbosity >= 3,
+                is_eip1559: self.is_eip1559(),
+                cheats: self.cheats.clone(),
+                failed_fetches: self.failed_fetches(),
             };
 
             // create the new block with the current timestamp
-            let ExecutedTransactions { block, included, invalid } = executor.execute();
+            let ExecutedTransactions { block, included, invalid, steps } = executor.execute();
             let BlockInfo { block, transactions, receipts } = block;
 
             let header = block.header.clone();
@@ -511,7 +1453,7 @@ impl Backend {
             let block_number: U64 = env.block.number.as_u64().into();
 
             trace!(
-                target: "backend",
+                target: "backend::mining",
                 "Mined block {} with {} tx {:?}",
                 block_number,
                 transactions.len(),
@@ -526,6 +1468,7 @@ impl Backend {
             storage.hashes.insert(block_number, block_hash);
 
             node_info!("");
+            let mut traces = Vec::new();
             // insert all transactions
             for (info, receipt) in transactions.into_iter().zip(receipts) {
                 // log some tx info
@@ -535,7 +1478,18 @@ impl Backend {
                         node_info!("    Contract created: {:?}", contract);
                     }
                     node_info!("    Gas used: {}", receipt.gas_used());
+                    if !receipt.status() {
+                        match info.out.as_ref().and_then(decode_revert_reason) {
+                            Some(reason) => node_info!("    Error: reverted with {}", reason),
+                            None => node_info!("    Error: reverted"),
+                        }
+                    }
+                }
+
+                if self.trace_decoder.is_some() {
+                    traces.push((info.transaction_hash, info.traces.clone()));
                 }
+                self.cheats.on_transaction_mined(&info.transaction_hash);
 
                 let mined_tx = MinedTransaction {
                     info,
@@ -543,7 +1497,7 @@ impl Backend {
                     block_hash,
                     block_number: block_number.as_u64(),
                 };
-                storage.transactions.insert(mined_tx.info.transaction_hash, mined_tx);
+                storage.insert_transaction(mined_tx);
             }
             let timestamp = utc_from_secs(header.timestamp);
 
@@ -553,21 +1507,47 @@ impl Backend {
 
             let outcome = MinedBlockOutcome { block_number, included, invalid };
 
-            (outcome, header, block_hash)
+            (outcome, header, block_hash, traces, steps)
         };
-        let next_block_base_fee = self.fees.get_next_block_base_fee_per_gas(
-            header.gas_used,
-            header.gas_limit,
-            header.base_fee_per_gas.unwrap_or_default(),
-        );
+
+        if let Some(ref decoder) = self.trace_decoder {
+            // decoding may require lookups, so this is done after all locks are released
+            for (tx_hash, traces) in traces {
+                let mut arena = call_traces(traces, self.trace_verbosity);
+                decoder.decode(&mut arena).await;
+                node_info!("    Traces for {:?}:\n{}", tx_hash, arena);
+            }
+        }
+        for (tx_hash, steps) in steps {
+            node_info!(
+                "    Steps for {:?}:\n{}",
+                tx_hash,
+                format_steps(&steps, self.trace_verbosity)
+            );
+        }
+
+        // the configured gas used only applies to this block
+        let gas_used = self.next_block_gas_used.lock().take().unwrap_or(header.gas_used);
+        let next_block_base_fee = self.fees.is_dynamic_base_fee().then(|| {
+            self.fees.get_next_block_base_fee_per_gas(
+                gas_used,
+                header.gas_limit,
+                header.base_fee_per_gas.unwrap_or_default(),
+            )
+        });
+
+        // the state changed, so previous call results are stale
+        self.clear_cached_calls();
 
         // notify all listeners
         self.notify_on_new_block(header, block_hash);
 
-        // update next base fee
-        self.fees.set_base_fee(next_block_base_fee.into());
+        // update next base fee, if the base fee is adjusted per block
+        if let Some(next_block_base_fee) = next_block_base_fee {
+            self.fees.set_base_fee(next_block_base_fee.into());
+        }
 
-        outcome
+        Ok(outcome)
     }
 
     /// Executes the `CallRequest` without writing to the DB
@@ -580,42 +1560,29 @@ impl Backend {
         request: CallRequest,
         fee_details: FeeDetails,
         block_number: Option<BlockNumber>,
+    ) -> Result<(Return, TransactOut, u64, State), BlockchainError> {
+        self.call_with_state(request, fee_details, block_number, Default::default()).await
+    }
+
+    /// Executes the `CallRequest` without writing to the DB, with the given `overrides` applied to
+    /// the state at the `block_number`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `block_number` is greater than the current height
+    pub async fn call_with_state(
+        &self,
+        request: CallRequest,
+        fee_details: FeeDetails,
+        block_number: Option<BlockNumber>,
+        overrides: StateOverride,
     ) -> Result<(Return, TransactOut, u64, State), BlockchainError> {
         trace!(target: "backend", "calling from [{:?}] fees={:?}", request.from, fee_details);
 
         let _lock = self.executor_lock.read().await;
+        let failed_fetches = self.failed_fetches().count();
 
-        let CallRequest { from, to, gas, value, data, nonce, access_list, .. } = request;
-
-        let FeeDetails { gas_price, max_fee_per_gas, max_priority_fee_per_gas } = fee_details;
-
-        let gas_limit = gas.unwrap_or_else(|| self.gas_limit());
-        let mut env = self.env.read().clone();
-        env.block.timestamp = self.time.current_call_timestamp().into();
-
-        if let Some(base) = max_fee_per_gas {
-            env.block.basefee = base;
-        }
-
-        let gas_price = gas_price.or(max_fee_per_gas).unwrap_or_else(|| self.gas_price());
-
-        env.tx = TxEnv {
-            caller: from.unwrap_or_default(),
-            gas_limit: gas_limit.as_u64(),
-            gas_price,
-            gas_priority_fee: max_priority_fee_per_gas,
-            transact_to: match to {
-                Some(addr) => TransactTo::Call(addr),
-                None => TransactTo::Create(CreateScheme::Create),
-            },
-            value: value.unwrap_or_default(),
-            data: data.unwrap_or_else(|| vec![].into()).to_vec().into(),
-            chain_id: None,
-            nonce: nonce.map(|n| n.as_u64()),
-            access_list: to_access_list(access_list.unwrap_or_default().0),
-        };
-
-        trace!(target: "backend", "calling with tx env from={:?} gas-limit={:?}, gas-price={:?}", env.tx.caller,  env.tx.gas_limit, env.tx.gas_limit);
+        let mut env = self.build_call_env(request, fee_details);
 
         let block_number =
             U256::from(self.convert_block_number(block_number)).min(env.block.number);
@@ -630,9 +1597,10 @@ impl Backend {
                 let mut evm = revm::EVM::new();
                 env.block.number = block_number;
                 evm.env = env;
-                evm.database(state);
+                evm.database(StateOverrideDb::new(state, overrides));
 
                 let (exit, out, gas, state, _) = evm.transact_ref();
+                self.ensure_no_failed_fetches(failed_fetches)?;
 
                 trace!(target: "backend", "call return {:?} out: {:?} gas {} on block {}", exit, out, gas, block_number);
 
@@ -649,14 +1617,104 @@ impl Backend {
         let db = self.db.read();
         let mut evm = revm::EVM::new();
         evm.env = env;
-        evm.database(&*db);
+        evm.database(StateOverrideDb::new(&*db, overrides));
 
         let (exit, out, gas, state, _) = evm.transact_ref();
+        self.ensure_no_failed_fetches(failed_fetches)?;
         trace!(target: "backend", "call return {:?} out: {:?} gas {}", exit, out, gas);
 
         Ok((exit, out, gas, state))
     }
 
+    /// Executes the `CallRequest`s one after another on top of the state at the given block
+    /// without writing to the DB
+    ///
+    /// Every call sees the state changes of the calls before it, which are discarded afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there's no state for the `block_number`
+    pub async fn simulate_bundle(
+        &self,
+        requests: Vec<(CallRequest, FeeDetails)>,
+        block_number: u64,
+    ) -> Result<Vec<(Return, TransactOut, u64, Vec<revm::Log>)>, BlockchainError> {
+        trace!(target: "backend", "simulating bundle of {} calls", requests.len());
+
+        let _lock = self.executor_lock.read().await;
+
+        let best_number = self.best_number().as_u64();
+        let has_state = block_number >= best_number ||
+            self.hash_for_block_number(block_number)
+                .map_or(false, |hash| self.states.read().get(&hash).is_some());
+        if !has_state {
+            warn!(target: "backend", "Not historic state found for block={}", block_number);
+            return Err(BlockchainError::BlockOutOfRange(best_number, block_number))
+        }
+
+        let envs: Vec<_> = requests
+            .into_iter()
+            .map(|(request, fee_details)| {
+                let mut env = self.build_call_env(request, fee_details);
+                env.block.number = block_number.into();
+                env
+            })
+            .collect();
+
+        let failed_fetches = self.failed_fetches().count();
+        let results = self.with_database_at(Some(block_number.into()), |db| {
+            // all changes are applied to this overlay only
+            let mut cache_db = CacheDB::new(&*db);
+            envs.into_iter()
+                .map(|env| {
+                    let mut evm = revm::EVM::new();
+                    evm.env = env;
+                    evm.database(&mut cache_db);
+                    evm.transact_commit()
+                })
+                .collect()
+        });
+        self.ensure_no_failed_fetches(failed_fetches)?;
+        Ok(results)
+    }
+
+    /// Returns the env to execute the `CallRequest` with at the current block
+    fn build_call_env(&self, request: CallRequest, fee_details: FeeDetails) -> Env {
+        let CallRequest { from, to, gas, value, data, nonce, access_list, .. } = request;
+
+        let FeeDetails { gas_price, max_fee_per_gas, max_priority_fee_per_gas } = fee_details;
+
+        let gas_limit = gas.unwrap_or_else(|| self.gas_limit());
+        let mut env = self.env.read().clone();
+        env.block.timestamp = self.time.current_call_timestamp().into();
+
+        if let Some(base) = max_fee_per_gas {
+            env.block.basefee = base;
+        }
+
+        let gas_price = gas_price.or(max_fee_per_gas).unwrap_or_else(|| self.gas_price());
+
+        env.tx = TxEnv {
+            caller: from.unwrap_or_default(),
+            gas_limit: gas_limit.as_u64(),
+            gas_price,
+            gas_priority_fee: max_priority_fee_per_gas,
+            transact_to: match to {
+                Some(addr) => TransactTo::Call(addr),
+                None => TransactTo::Create(CreateScheme::Create),
+            },
+            value: value.unwrap_or_default(),
+            data: data.unwrap_or_else(|| vec![].into()).to_vec().into(),
+            chain_id: None,
+            nonce: nonce.map(|n| n.as_u64()),
+            access_list: to_access_list(access_list.unwrap_or_default().0),
+        };
+
+        trace!(target: "backend", "calling with tx env from={:?} gas-limit={:?}, gas-price={:?}", env.tx.caller,  env.tx.gas_limit, env.tx.gas_limit);
+
+        env
+    }
+
     /// returns all receipts for the given transactions
     fn get_receipts(&self, tx_hashes: impl IntoIterator<Item = TxHash>) -> Vec<TypedReceipt> {
         let storage = self.blockchain.storage.read();
@@ -691,8 +1749,11 @@ impl Backend {
 
     /// Returns all `Log`s mined by the node that were emitted in the `block` and match the `Filter`
     fn mined_logs_for_block(&self, filter: Filter, block: Block) -> Vec<Log> {
-        let params = FilteredParams::new(Some(filter.clone()));
         let mut all_logs = Vec::new();
+        if !bloom_matches_filter(&block.header.logs_bloom, &filter) {
+            return all_logs
+        }
+        let params = FilteredParams::new(Some(filter.clone()));
         let block_hash = block.header.hash();
         let mut block_log_index = 0u32;
 
@@ -781,7 +1842,17 @@ impl Backend {
         }
 
         for number in from..=to {
-            if let Some(block) = self.get_block(number) {
+            // skip blocks without matching logs before the block is cloned
+            let block = {
+                let storage = self.blockchain.storage.read();
+                storage
+                    .hashes
+                    .get(&number)
+                    .and_then(|hash| storage.blocks.get(hash))
+                    .filter(|block| bloom_matches_filter(&block.header.logs_bloom, filter))
+                    .cloned()
+            };
+            if let Some(block) = block {
                 all_logs.extend(self.mined_logs_for_block(filter.clone(), block));
             }
         }
@@ -842,15 +1913,27 @@ impl Backend {
     }
 
     /// Returns all transactions given a block
+    ///
+    /// The transactions are in the same order as their hashes in the block. The stored info of a
+    /// transaction is only used if it was mined in this block, a transaction can be mined again in
+    /// another block after a revert.
     fn mined_transactions_in_block(&self, block: &Block) -> Option<Vec<Transaction>> {
         let mut transactions = Vec::with_capacity(block.transactions.len());
         let base_fee = block.header.base_fee_per_gas;
+        let block_hash = block.header.hash();
         let storage = self.blockchain.storage.read();
-        for hash in block.transactions.iter().map(|tx| tx.hash()) {
-            let info = storage.transactions.get(&hash)?.info.clone();
-            let tx = block.transactions.get(info.transaction_index as usize)?.clone();
-
-            let tx = transaction_build(tx, Some(block), Some(info), true, base_fee);
+        for (idx, tx) in block.transactions.iter().enumerate() {
+            let info = storage
+                .transactions
+                .get(&tx.hash())
+                .filter(|mined| mined.block_hash == block_hash)
+                .map(|mined| mined.info.clone());
+            let has_info = info.is_some();
+
+            let mut tx = transaction_build(tx.clone(), Some(block), info, true, base_fee);
+            if !has_info {
+                tx.transaction_index = Some((idx as u64).into());
+            }
             transactions.push(tx);
         }
         Some(transactions)
@@ -1009,6 +2092,39 @@ impl Backend {
         }
     }
 
+    /// Same as [Self::ensure_block_number] but also resolves the hashes of blocks that predate the
+    /// fork, if in fork mode.
+    ///
+    /// Since state is looked up by block number, a block hash is only accepted if it's the hash
+    /// of the canonical block at that number.
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the block is unknown, not canonical or larger than the current height
+    pub async fn ensure_block_id(&self, block_id: Option<BlockId>) -> Result<u64, BlockchainError> {
+        if let (Some(BlockId::Hash(hash)), Some(fork)) = (block_id, self.get_fork()) {
+            if !self.blockchain.storage.read().blocks.contains_key(&hash) {
+                let number = fork
+                    .block_by_hash(hash)
+                    .await?
+                    .and_then(|block| block.number)
+                    .ok_or(BlockchainError::BlockNotFound)?
+                    .as_u64();
+                let canonical = fork.block_by_number(number).await?.and_then(|block| block.hash);
+                // blocks after the fork block on the remote chain are not part of this chain
+                if canonical != Some(hash) || number > fork.block_number() {
+                    return Err(RpcError::invalid_params(format!(
+                        "block {:?} is not canonical",
+                        hash
+                    ))
+                    .into())
+                }
+                return Ok(number)
+            }
+        }
+        self.ensure_block_number(block_id)
+    }
+
     pub fn convert_block_number(&self, block: Option<BlockNumber>) -> u64 {
         match block.unwrap_or(BlockNumber::Latest) {
             BlockNumber::Latest | BlockNumber::Pending => self.best_number().as_u64(),
@@ -1042,6 +2158,13 @@ impl Backend {
         index: U256,
         number: Option<BlockNumber>,
     ) -> Result<H256, BlockchainError> {
+        if let Some(fork) = self.get_fork() {
+            let number = self.convert_block_number(number);
+            if fork.predates_fork(number) {
+                return Ok(fork.storage_at(address, index, Some(number.into())).await?)
+            }
+        }
+        self.ensure_fork_state_available(address, Some(index))?;
         self.with_database_at(number, |db| {
             trace!(target: "backend", "get storage for {:?} at {:?}", address, index);
             let val = db.storage(address, index);
@@ -1058,6 +2181,13 @@ impl Backend {
         address: Address,
         number: Option<BlockNumber>,
     ) -> Result<Bytes, BlockchainError> {
+        if let Some(fork) = self.get_fork() {
+            let number = self.convert_block_number(number);
+            if fork.predates_fork(number) {
+                return Ok(fork.get_code(address, number).await?)
+            }
+        }
+        self.ensure_fork_state_available(address, None)?;
         self.with_database_at(number, |db| {
             trace!(target: "backend", "get code for {:?}", address);
             let account = db.basic(address);
@@ -1078,6 +2208,13 @@ impl Backend {
         address: Address,
         number: Option<BlockNumber>,
     ) -> Result<U256, BlockchainError> {
+        if let Some(fork) = self.get_fork() {
+            let number = self.convert_block_number(number);
+            if fork.predates_fork(number) {
+                return Ok(fork.get_balance(address, number).await?)
+            }
+        }
+        self.ensure_fork_state_available(address, None)?;
         self.with_database_at(number, |db| {
             trace!(target: "backend", "get balance for {:?}", address);
             Ok(db.basic(address).balance)
@@ -1092,12 +2229,66 @@ impl Backend {
         address: Address,
         number: Option<BlockNumber>,
     ) -> Result<U256, BlockchainError> {
+        if let Some(fork) = self.get_fork() {
+            let number = self.convert_block_number(number);
+            if fork.predates_fork(number) {
+                return Ok(fork.get_nonce(address, number).await?)
+            }
+        }
+        self.ensure_fork_state_available(address, None)?;
         self.with_database_at(number, |db| {
             trace!(target: "backend", "get nonce for {:?}", address);
             Ok(db.basic(address).nonce.into())
         })
     }
 
+    /// Returns an error if the account, or its storage `slot`, couldn't be fetched from the fork
+    /// endpoint
+    ///
+    /// This is checked before the state is read, which would return an empty value instead. The
+    /// failed value isn't cached, so it's fetched again by the next read, until the fork went
+    /// offline.
+    fn ensure_fork_state_available(
+        &self,
+        address: Address,
+        slot: Option<U256>,
+    ) -> Result<(), BlockchainError> {
+        if let Some(fork) = self.get_fork() {
+            if let Err(err) = self.db.read().try_fetch(address, slot) {
+                if fork.is_offline() {
+                    return Err(BlockchainError::ForkOffline)
+                }
+                return Err(BlockchainError::ForkFetchFailed(err))
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the state fetches of the fork that failed during executions
+    ///
+    /// A new counter that's never incremented is returned if not in fork mode.
+    fn failed_fetches(&self) -> FailedFetches {
+        self.get_fork().map(|fork| fork.failed_fetches().clone()).unwrap_or_default()
+    }
+
+    /// Returns an error if a state fetch of the fork failed since the `failed_fetches` were counted
+    ///
+    /// An execution that ran in between read a default value instead of the state that couldn't be
+    /// fetched, so its result must not be used.
+    fn ensure_no_failed_fetches(&self, failed_fetches: usize) -> Result<(), BlockchainError> {
+        match self.get_fork() {
+            Some(fork) if fork.failed_fetches().count() != failed_fetches => {
+                if fork.is_offline() {
+                    return Err(BlockchainError::ForkOffline)
+                }
+                Err(BlockchainError::ForkFetchFailed(
+                    "the state read by the execution couldn't be fetched".to_string(),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
     /// Returns the traces for the given transaction
     pub async fn trace_transaction(&self, hash: H256) -> Result<Vec<Trace>, BlockchainError> {
         if let Some(traces) =
@@ -1118,6 +2309,140 @@ impl Backend {
         self.blockchain.storage.read().transactions.get(&hash).map(|tx| tx.parity_traces())
     }
 
+    /// Returns the internal operations of the given transaction, see `ots_getInternalOperations`
+    pub fn mined_ots_internal_operations(&self, hash: H256) -> Option<Vec<OtsInternalOperation>> {
+        self.blockchain
+            .storage
+            .read()
+            .transactions
+            .get(&hash)
+            .map(|tx| tx.ots_internal_operations())
+    }
+
+    /// Returns the calls of the given transaction, see `ots_traceTransaction`
+    pub fn mined_ots_traces(&self, hash: H256) -> Option<Vec<OtsTrace>> {
+        self.blockchain.storage.read().transactions.get(&hash).map(|tx| tx.ots_traces())
+    }
+
+    /// Returns the revert data of the given transaction, which is empty if the transaction
+    /// succeeded, see `ots_getTransactionError`
+    pub fn mined_transaction_error(&self, hash: H256) -> Option<Bytes> {
+        let storage = self.blockchain.storage.read();
+        let tx = storage.transactions.get(&hash)?;
+        if tx.receipt.status() {
+            return Some(Bytes::default())
+        }
+        Some(tx.info.out.clone().unwrap_or_default())
+    }
+
+    /// Returns the hash of the mined transaction that `sender` sent with `nonce`
+    pub fn mined_transaction_by_sender_and_nonce(
+        &self,
+        sender: Address,
+        nonce: U256,
+    ) -> Option<H256> {
+        let storage = self.blockchain.storage.read();
+        storage
+            .address_transactions
+            .get(&sender)?
+            .values()
+            .find(|hash| {
+                storage
+                    .transactions
+                    .get(hash)
+                    .filter(|tx| tx.info.from == sender)
+                    .and_then(|tx| {
+                        storage
+                            .blocks
+                            .get(&tx.block_hash)?
+                            .transactions
+                            .get(tx.info.transaction_index as usize)
+                    })
+                    .map(|tx| *tx.nonce() == nonce)
+                    .unwrap_or_default()
+            })
+            .copied()
+    }
+
+    /// Returns the mined transaction that created the contract at `address` and the address that
+    /// deployed it, see `ots_getContractCreator`
+    pub fn mined_contract_creator(&self, address: Address) -> Option<OtsContractCreator> {
+        let storage = self.blockchain.storage.read();
+        storage.address_transactions.get(&address)?.values().find_map(|hash| {
+            let tx = storage.transactions.get(hash)?;
+            let creator = tx
+                .info
+                .traces
+                .iter()
+                .find(|node| {
+                    node.kind() == CallKind::Create &&
+                        node.trace.success &&
+                        node.trace.address == address
+                })
+                .map(|node| node.trace.caller)
+                // transactions loaded from a state file have no traces
+                .or_else(|| (tx.info.contract_address == Some(address)).then(|| tx.info.from))?;
+            Some(OtsContractCreator { hash: *hash, creator })
+        })
+    }
+
+    /// Returns the number of the oldest block that is stored locally
+    pub fn first_local_block_number(&self) -> u64 {
+        self.get_fork().map(|fork| fork.block_number()).unwrap_or_default()
+    }
+
+    /// Searches the given range of mined blocks for transactions that involve `address`, most
+    /// recent block first if `reverse` is set, see `ots_searchTransactionsBefore`
+    ///
+    /// The search stops after the block in which the number of found transactions reached
+    /// `page_size`. Returns the transactions with their receipts and whether no more transactions
+    /// involving `address` are left in the range.
+    pub fn ots_search_transactions(
+        &self,
+        address: Address,
+        blocks: RangeInclusive<u64>,
+        reverse: bool,
+        page_size: usize,
+    ) -> (Vec<(Transaction, OtsReceipt)>, bool) {
+        if blocks.is_empty() {
+            return (vec![], true)
+        }
+        let (hashes, done) = {
+            let storage = self.blockchain.storage.read();
+            let index = match storage.address_transactions.get(&address) {
+                Some(index) => index,
+                None => return (vec![], true),
+            };
+            let range = index.range((*blocks.start(), 0)..=(*blocks.end(), u32::MAX));
+            let mut positions: Box<dyn Iterator<Item = (&(u64, u32), &TxHash)>> =
+                if reverse { Box::new(range.rev()) } else { Box::new(range) };
+            let mut hashes: Vec<(u64, TxHash)> = vec![];
+            let mut done = true;
+            for ((number, _), hash) in positions.by_ref() {
+                // the transactions of the last block are always returned completely
+                if hashes.len() >= page_size && hashes.last().map(|(last, _)| last) != Some(number)
+                {
+                    done = false;
+                    break
+                }
+                hashes.push((*number, *hash));
+            }
+            (hashes, done)
+        };
+
+        let mut found = Vec::with_capacity(hashes.len());
+        for (number, hash) in hashes {
+            if let (Some(tx), Some(receipt), Some(block)) = (
+                self.mined_transaction_by_hash(hash),
+                self.mined_transaction_receipt(hash),
+                self.get_block(number),
+            ) {
+                found.push((tx, OtsReceipt { receipt, timestamp: block.header.timestamp.into() }));
+            }
+        }
+        (found, done)
+    }
+
     /// Returns the traces for the given transaction
     pub fn mined_parity_trace_block(&self, block: u64) -> Option<Vec<Trace>> {
         let block = self.get_block(block)?;
@@ -1155,12 +2480,63 @@ impl Backend {
         }
 
         if let Some(fork) = self.get_fork() {
-            return Ok(fork.transaction_receipt(hash).await?)
+            if fork.passthrough_receipts() {
+                return Ok(fork.transaction_receipt(hash).await?)
+            }
         }
 
         Ok(None)
     }
 
+    /// Returns all transaction receipts of the block
+    pub async fn block_receipts(
+        &self,
+        number: BlockId,
+    ) -> Result<Option<Vec<TransactionReceipt>>, BlockchainError> {
+        if let receipts @ Some(_) =
+            tokio::task::block_in_place(|| self.mined_block_receipts(number))
+        {
+            return Ok(receipts)
+        }
+
+        if let Some(fork) = self.get_fork() {
+            if !fork.passthrough_receipts() {
+                return Ok(None)
+            }
+            let block = match number {
+                BlockId::Hash(hash) => fork.block_by_hash(hash).await?,
+                BlockId::Number(number) => {
+                    fork.block_by_number(self.convert_block_number(Some(number))).await?
+                }
+            };
+            if let Some(block) = block {
+                let mut receipts = Vec::with_capacity(block.transactions.len());
+                for tx in block.transactions {
+                    match fork.transaction_receipt(tx).await? {
+                        Some(receipt) => receipts.push(receipt),
+                        None => return Ok(None),
+                    }
+                }
+                return Ok(Some(receipts))
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the transaction receipts of all transactions in the mined block
+    ///
+    /// These are identical to the receipts returned by
+    /// [Self::mined_transaction_receipt()] for each transaction
+    pub fn mined_block_receipts(&self, id: impl Into<BlockId>) -> Option<Vec<TransactionReceipt>> {
+        let block = self.get_block(id)?;
+        let mut receipts = Vec::with_capacity(block.transactions.len());
+        for tx in block.transactions.iter() {
+            receipts.push(self.mined_transaction_receipt(tx.hash())?);
+        }
+        Some(receipts)
+    }
+
     /// Returns all receipts of the block
     pub fn mined_receipts(&self, hash: H256) -> Option<Vec<TypedReceipt>> {
         let block = self.mined_block_by_hash(hash)?;
@@ -1199,14 +2575,7 @@ impl Backend {
 
         let transaction = block.transactions[index].clone();
 
-        let effective_gas_price = match transaction {
-            TypedTransaction::Legacy(t) => t.gas_price,
-            TypedTransaction::EIP2930(t) => t.gas_price,
-            TypedTransaction::EIP1559(t) => self
-                .base_fee()
-                .checked_add(t.max_priority_fee_per_gas)
-                .unwrap_or_else(U256::max_value),
-        };
+        let effective_gas_price = transaction.effective_gas_price(block.header.base_fee_per_gas);
 
         Some(TransactionReceipt {
             transaction_hash: info.transaction_hash,
@@ -1284,6 +2653,11 @@ impl Backend {
             return Ok(tx)
         }
 
+        // the block is known locally, so the index is out of range
+        if self.blockchain.storage.read().blocks.contains_key(&hash) {
+            return Ok(None)
+        }
+
         if let Some(fork) = self.get_fork() {
             return Ok(fork.transaction_by_block_hash_and_index(hash, index.into()).await?)
         }
@@ -1364,6 +2738,12 @@ impl TransactionValidator for Backend {
         &self,
         tx: &PendingTransaction,
     ) -> Result<(), InvalidTransactionError> {
+        if let Some(min_gas_price) = self.fees.min_gas_price() {
+            if tx.transaction.gas_price() < min_gas_price && !tx.transaction.is_deposit() {
+                warn!(target: "backend", "[{:?}] gas price below the minimum", tx.hash());
+                return Err(InvalidTransactionError::Underpriced)
+            }
+        }
         let account = self.db.read().basic(*tx.sender());
         self.validate_pool_transaction_for(tx, &account, &*self.env().read())
     }
@@ -1375,11 +2755,28 @@ impl TransactionValidator for Backend {
         env: &Env,
     ) -> Result<(), InvalidTransactionError> {
         let tx = &pending.transaction;
+
+        // transactions without a chain id were signed before EIP-155 and are valid on any chain
+        if let Some(chain_id) = tx.chain_id() {
+            if U256::from(chain_id) != env.cfg.chain_id {
+                warn!(target: "backend", "[{:?}] invalid chain id {}", tx.hash(), chain_id);
+                return Err(InvalidTransactionError::InvalidChainId {
+                    expected: env.cfg.chain_id,
+                    got: chain_id,
+                })
+            }
+        }
+
         if tx.gas_limit() > env.block.gas_limit {
             warn!(target: "backend", "[{:?}] gas too high", tx.hash());
             return Err(InvalidTransactionError::GasTooHigh)
         }
 
+        if tx.gas_limit() < tx.intrinsic_gas() {
+            warn!(target: "backend", "[{:?}] gas too low", tx.hash());
+            return Err(InvalidTransactionError::GasTooLow)
+        }
+
         // check nonce
         let nonce: u64 = (*tx.nonce()).try_into().map_err(|_| InvalidTransactionError::NonceMax)?;
         if nonce < account.nonce {
@@ -1387,8 +2784,15 @@ impl TransactionValidator for Backend {
             return Err(InvalidTransactionError::NonceTooLow)
         }
 
-        if (env.cfg.spec_id as u8) >= (SpecId::LONDON as u8) && tx.gas_price() < env.block.basefee {
-            return Err(InvalidTransactionError::FeeTooLow)
+        if self.is_eip1559() {
+            // deposits don't pay for gas, their gas was bought on L1
+            if tx.gas_price() < env.block.basefee && !tx.is_deposit() {
+                warn!(target: "backend", "[{:?}] max fee per gas below the base fee", tx.hash());
+                return Err(InvalidTransactionError::FeeTooLow)
+            }
+        } else if let TypedTransaction::EIP1559(_) = tx {
+            warn!(target: "backend", "[{:?}] EIP-1559 is disabled", tx.hash());
+            return Err(InvalidTransactionError::Eip1559Disabled)
         }
 
         let max_cost = tx.max_cost();
@@ -1400,7 +2804,12 @@ impl TransactionValidator for Backend {
             InvalidTransactionError::Payment
         })?;
 
-        if account.balance < req_funds {
+        // deposits mint to the sender before they're executed
+        let balance = match tx {
+            TypedTransaction::Deposit(deposit) => account.balance.saturating_add(deposit.mint),
+            _ => account.balance,
+        };
+        if balance < req_funds {
             warn!(target: "backend", "[{:?}] insufficient allowance={}, required={} account={:?}", tx.hash(), account.balance, req_funds, *pending.sender());
             return Err(InvalidTransactionError::Payment)
         }
@@ -1421,6 +2830,105 @@ impl TransactionValidator for Backend {
     }
 }
 
+/// Returns the fields of the account that state cheats modify, including the given `slots`
+fn cheated_account(db: &dyn Db, address: Address, slots: &[U256]) -> CheatedAccount {
+    let account = db.basic(address);
+    let code = match account.code {
+        Some(code) => code.into(),
+        None => db.code_by_hash(account.code_hash).into(),
+    };
+    CheatedAccount {
+        balance: account.balance,
+        nonce: account.nonce.into(),
+        code,
+        storage: slots.iter().map(|slot| (*slot, db.storage(address, *slot))).collect(),
+    }
+}
+
+/// Converts the executed `steps` of a transaction into geth's struct logs
+///
+/// The steps of the arena are in the order of their execution, a call continues in a new node
+/// after its subcalls returned.
+fn geth_struct_logs(steps: &DebugArena, opts: &GethDebugTracingOptions) -> Vec<GethStructLog> {
+    let mut struct_logs = Vec::new();
+    // the storage slots accessed so far, per executed code
+    let mut storage = HashMap::<Address, BTreeMap<H256, H256>>::new();
+    for node in &steps.arena {
+        for (idx, step) in node.steps.iter().enumerate() {
+            let op = match step.instruction {
+                Instruction::OpCode(op) => op,
+                Instruction::Cheatcode(_) => continue,
+            };
+            let next = node.steps.get(idx + 1);
+            let top = |n: usize| step.stack.iter().rev().nth(n).copied().unwrap_or_default();
+
+            let slots = storage.entry(node.address).or_default();
+            match op {
+                opcode::SLOAD => {
+                    // the loaded value is on top of the stack of the next step
+                    let value =
+                        next.and_then(|next| next.stack.last().copied()).unwrap_or_default();
+                    slots.insert(u256_to_h256_be(top(0)), u256_to_h256_be(value));
+                }
+                opcode::SSTORE => {
+                    slots.insert(u256_to_h256_be(top(0)), u256_to_h256_be(top(1)));
+                }
+                _ => {}
+            }
+            let accesses_storage = matches!(op, opcode::SLOAD | opcode::SSTORE);
+
+            struct_logs.push(GethStructLog {
+                pc: step.pc as u64,
+                op: step.instruction.to_string(),
+                gas: step.gas_remaining,
+                gas_cost: next
+                    .map(|next| step.gas_remaining.saturating_sub(next.gas_remaining))
+                    .unwrap_or_default(),
+                depth: node.depth as u64 + 1,
+                stack: (!opts.disable_stack.unwrap_or_default()).then(|| step.stack.clone()),
+                memory: opts
+                    .enable_memory
+                    .unwrap_or_default()
+                    .then(|| step.memory.data().chunks(32).map(hex::encode).collect()),
+                storage: (accesses_storage && !opts.disable_storage.unwrap_or_default()).then(
+                    || {
+                        slots
+                            .iter()
+                            .map(|(slot, value)| (hex::encode(slot), hex::encode(value)))
+                            .collect()
+                    },
+                ),
+            });
+        }
+    }
+    struct_logs
+}
+
+/// Returns true if the block with the `bloom` may contain logs that match the `filter`
+///
+/// The bloom of a block contains the addresses and topics of all its logs, so a block can be
+/// skipped if it's missing all of the filter's addresses or all of the topics at any position.
+fn bloom_matches_filter(bloom: &Bloom, filter: &Filter) -> bool {
+    let contains = |input: &[u8]| bloom.contains_input(BloomInput::Raw(input));
+    let address_matches = match &filter.address {
+        None => true,
+        Some(ValueOrArray::Value(address)) => contains(address.as_bytes()),
+        Some(ValueOrArray::Array(addresses)) => {
+            addresses.is_empty() || addresses.iter().any(|address| contains(address.as_bytes()))
+        }
+    };
+    address_matches &&
+        filter.topics.iter().flatten().all(|topic| match topic {
+            ValueOrArray::Value(topic) => topic.map_or(true, |topic| contains(topic.as_bytes())),
+            ValueOrArray::Array(topics) => {
+                topics.is_empty() ||
+                    topics
+                        .iter()
+                        .any(|topic| topic.map_or(true, |topic| contains(topic.as_bytes())))
+            }
+        })
+}
+
 /// Creates a `Transaction` as it's expected for the `eth` RPC api from storage data
 pub fn transaction_build(
     eth_transaction: TypedTransaction,
@@ -1436,16 +2944,11 @@ pub fn transaction_build(
             // transaction is not mined yet, gas price is considered just `max_fee_per_gas`
             transaction.gas_price = transaction.max_fee_per_gas;
         } else {
-            // if transaction is already mined, gas price is considered base fee + priority fee: the
-            // effective gas price.
-            let base_fee = base_fee.unwrap_or(U256::zero());
-            let max_priority_fee_per_gas =
-                transaction.max_priority_fee_per_gas.unwrap_or(U256::zero());
-            transaction.gas_price = Some(
-                base_fee.checked_add(max_priority_fee_per_gas).unwrap_or_else(U256::max_value),
-            );
+            // if transaction is already mined, gas price is considered the effective gas price:
+            // base fee + priority fee, capped by the max fee
+            transaction.gas_price = Some(eth_transaction.effective_gas_price(base_fee));
         }
-    } else if !is_eip1559 {
+    } else if !is_eip1559 && !eth_transaction.is_deposit() {
         transaction.max_fee_per_gas = None;
         transaction.max_priority_fee_per_gas = None;
         transaction.transaction_type = None;
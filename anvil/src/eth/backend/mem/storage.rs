@@ -3,19 +3,24 @@ use crate::eth::{
     backend::{db::StateDb, time::duration_since_unix_epoch},
     pool::transactions::PoolTransaction,
 };
-use anvil_core::eth::{
-    block::{Block, PartialHeader},
-    receipt::TypedReceipt,
-    transaction::TransactionInfo,
+use anvil_core::{
+    eth::{
+        block::{Block, PartialHeader},
+        receipt::{Log, TypedReceipt},
+        transaction::TransactionInfo,
+    },
+    types::{OtsInternalOperation, OtsInternalOperationType, OtsTrace, OtsTraceType},
 };
 use ethers::{
     prelude::{BlockId, BlockNumber, Trace, H256, H256 as TxHash, U64},
-    types::{ActionType, U256},
+    types::{ActionType, Address, Bloom, Bytes, U256},
 };
 use forge::revm::{Env, Return};
+use foundry_evm::CallKind;
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     fmt,
     sync::Arc,
 };
@@ -56,6 +61,12 @@ impl InMemoryBlockStates {
         self.states.get(hash)
     }
 
+    /// Removes the state for the given `hash`
+    pub fn remove(&mut self, hash: &H256) -> Option<StateDb> {
+        self.present.retain(|present| present != hash);
+        self.states.remove(hash)
+    }
+
     /// Clears all entries
     pub fn clear(&mut self) {
         self.states.clear();
@@ -95,6 +106,9 @@ pub struct BlockchainStorage {
     /// Mapping from the transaction hash to a tuple containing the transaction as well as the
     /// transaction receipt
     pub transactions: HashMap<TxHash, MinedTransaction>,
+    /// Mapping from an address to the hashes of the mined transactions that involve it, ordered
+    /// by block number and transaction index
+    pub address_transactions: HashMap<Address, BTreeMap<(u64, u32), TxHash>>,
 }
 
 impl BlockchainStorage {
@@ -121,6 +135,7 @@ impl BlockchainStorage {
             best_number,
             genesis_hash,
             transactions: Default::default(),
+            address_transactions: Default::default(),
         }
     }
 
@@ -132,6 +147,7 @@ impl BlockchainStorage {
             best_number: block_number.into(),
             genesis_hash: Default::default(),
             transactions: Default::default(),
+            address_transactions: Default::default(),
         }
     }
 
@@ -144,6 +160,7 @@ impl BlockchainStorage {
             best_number: Default::default(),
             genesis_hash: Default::default(),
             transactions: Default::default(),
+            address_transactions: Default::default(),
         }
     }
 }
@@ -160,6 +177,85 @@ impl BlockchainStorage {
             BlockNumber::Number(num) => self.hashes.get(&num).copied(),
         }
     }
+
+    /// Inserts a mined transaction and indexes the addresses it involves
+    pub fn insert_transaction(&mut self, tx: MinedTransaction) {
+        let hash = tx.info.transaction_hash;
+        self.remove_transaction(&hash);
+        let position = (tx.block_number, tx.info.transaction_index);
+        for address in tx.involved_addresses() {
+            self.address_transactions.entry(address).or_default().insert(position, hash);
+        }
+        self.transactions.insert(hash, tx);
+    }
+
+    /// Removes a mined transaction and its index entries
+    pub fn remove_transaction(&mut self, hash: &TxHash) -> Option<MinedTransaction> {
+        let tx = self.transactions.remove(hash)?;
+        self.unindex_transaction(&tx);
+        Some(tx)
+    }
+
+    /// Removes the index entries of the transaction
+    fn unindex_transaction(&mut self, tx: &MinedTransaction) {
+        let position = (tx.block_number, tx.info.transaction_index);
+        for address in tx.involved_addresses() {
+            if let Some(index) = self.address_transactions.get_mut(&address) {
+                if index.get(&position) == Some(&tx.info.transaction_hash) {
+                    index.remove(&position);
+                }
+                if index.is_empty() {
+                    self.address_transactions.remove(&address);
+                }
+            }
+        }
+    }
+
+    /// Returns all blocks and transactions, ordered by block number
+    pub fn serializable_history(&self) -> SerializableHistory {
+        let mut blocks: Vec<_> = self.blocks.values().cloned().collect();
+        blocks.sort_by_key(|block| block.header.number);
+        let mut transactions: Vec<SerializableTransaction> =
+            self.transactions.values().cloned().map(Into::into).collect();
+        transactions.sort_by_key(|tx| (tx.block_number, tx.transaction_index));
+        SerializableHistory { blocks, transactions }
+    }
+
+    /// Replaces all blocks and transactions with the ones of the `history`, the block with the
+    /// highest number becomes the best block
+    ///
+    /// Returns `false` and leaves the storage untouched if the `history` contains no blocks
+    pub fn load_history(&mut self, history: SerializableHistory) -> bool {
+        let (best_number, best_hash) = match history
+            .blocks
+            .iter()
+            .map(|block| (block.header.number.as_u64(), block.header.hash()))
+            .max_by_key(|(number, _)| *number)
+        {
+            Some(best) => best,
+            None => return false,
+        };
+
+        self.blocks.clear();
+        self.hashes.clear();
+        for block in history.blocks {
+            let number = block.header.number.as_u64();
+            let hash = block.header.hash();
+            if number == 0 {
+                self.genesis_hash = hash;
+            }
+            self.hashes.insert(number.into(), hash);
+            self.blocks.insert(hash, block);
+        }
+        self.transactions.clear();
+        self.address_transactions.clear();
+        for tx in history.transactions {
+            self.insert_transaction(tx.into());
+        }
+        self.best_number = best_number.into();
+        self.best_hash = best_hash;
+        true
+    }
 }
 
 /// A simple in-memory blockchain
@@ -249,4 +345,159 @@ impl MinedTransaction {
 
         traces
     }
+
+    /// Returns the value transfers, contract creations and self destructs of the calls made by
+    /// the transaction for `ots_getInternalOperations`
+    ///
+    /// **Note**: the traces don't distinguish `CREATE2` from `CREATE` and don't record the
+    /// beneficiary of a self destruct, which is reported as the zero address.
+    pub fn ots_internal_operations(&self) -> Vec<OtsInternalOperation> {
+        let mut operations = vec![];
+        for node in self.info.traces.iter() {
+            let trace = &node.trace;
+            // the top level call is the transaction itself
+            if node.parent.is_some() {
+                let r#type = match node.kind() {
+                    CallKind::Create => Some(OtsInternalOperationType::Create),
+                    CallKind::Call if !trace.value.is_zero() => {
+                        Some(OtsInternalOperationType::Transfer)
+                    }
+                    _ => None,
+                };
+                if let Some(r#type) = r#type {
+                    operations.push(OtsInternalOperation {
+                        r#type,
+                        from: trace.caller,
+                        to: trace.address,
+                        value: trace.value,
+                    });
+                }
+            }
+            if node.status() == Return::SelfDestruct {
+                operations.push(OtsInternalOperation {
+                    r#type: OtsInternalOperationType::SelfDestruct,
+                    from: trace.address,
+                    to: Address::zero(),
+                    value: trace.value,
+                });
+            }
+        }
+        operations
+    }
+
+    /// Returns the calls and self destructs of the transaction for `ots_traceTransaction`
+    ///
+    /// **Note**: like [Self::ots_internal_operations], `CREATE2` is reported as `CREATE`.
+    pub fn ots_traces(&self) -> Vec<OtsTrace> {
+        let mut traces = vec![];
+        for node in self.info.traces.iter() {
+            let trace = &node.trace;
+            let r#type = match node.kind() {
+                CallKind::Call => OtsTraceType::Call,
+                CallKind::StaticCall => OtsTraceType::StaticCall,
+                CallKind::CallCode => OtsTraceType::CallCode,
+                CallKind::DelegateCall => OtsTraceType::DelegateCall,
+                CallKind::Create => OtsTraceType::Create,
+            };
+            // static and delegate calls can't transfer value
+            let value = match node.kind() {
+                CallKind::StaticCall | CallKind::DelegateCall => None,
+                _ => Some(trace.value),
+            };
+            traces.push(OtsTrace {
+                r#type,
+                depth: trace.depth,
+                from: trace.caller,
+                to: trace.address,
+                value,
+                input: trace.data.to_raw().into(),
+            });
+            if node.status() == Return::SelfDestruct {
+                traces.push(OtsTrace {
+                    r#type: OtsTraceType::SelfDestruct,
+                    depth: trace.depth + 1,
+                    from: trace.address,
+                    to: Address::zero(),
+                    value: Some(trace.value),
+                    input: Default::default(),
+                });
+            }
+        }
+        traces
+    }
+
+    /// Returns all addresses the transaction involves: the sender, the receiver or created
+    /// contract, and the callers and callees of all its calls
+    pub fn involved_addresses(&self) -> HashSet<Address> {
+        let mut addresses = HashSet::from([self.info.from]);
+        addresses.extend(self.info.to);
+        addresses.extend(self.info.contract_address);
+        for node in self.info.traces.iter() {
+            addresses.insert(node.trace.caller);
+            addresses.insert(node.trace.address);
+        }
+        addresses
+    }
+}
+
+/// A mined transaction as it's written to a state file, the traces are not included
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializableTransaction {
+    pub transaction_hash: H256,
+    pub transaction_index: u32,
+    pub from: Address,
+    pub to: Option<Address>,
+    pub contract_address: Option<Address>,
+    pub logs: Vec<Log>,
+    pub logs_bloom: Bloom,
+    pub out: Option<Bytes>,
+    pub receipt: TypedReceipt,
+    pub block_hash: H256,
+    pub block_number: u64,
+}
+
+impl From<MinedTransaction> for SerializableTransaction {
+    fn from(tx: MinedTransaction) -> Self {
+        let MinedTransaction { info, receipt, block_hash, block_number } = tx;
+        Self {
+            transaction_hash: info.transaction_hash,
+            transaction_index: info.transaction_index,
+            from: info.from,
+            to: info.to,
+            contract_address: info.contract_address,
+            logs: info.logs,
+            logs_bloom: info.logs_bloom,
+            out: info.out,
+            receipt,
+            block_hash,
+            block_number,
+        }
+    }
+}
+
+impl From<SerializableTransaction> for MinedTransaction {
+    fn from(tx: SerializableTransaction) -> Self {
+        let info = TransactionInfo {
+            transaction_hash: tx.transaction_hash,
+            transaction_index: tx.transaction_index,
+            from: tx.from,
+            to: tx.to,
+            contract_address: tx.contract_address,
+            logs: tx.logs,
+            logs_bloom: tx.logs_bloom,
+            traces: vec![],
+            out: tx.out,
+        };
+        Self { info, receipt: tx.receipt, block_hash: tx.block_hash, block_number: tx.block_number }
+    }
+}
+
+/// The blocks and transactions of a chain as they're written to a state file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SerializableHistory {
+    #[serde(default)]
+    pub blocks: Vec<Block>,
+    #[serde(default)]
+    pub transactions: Vec<SerializableTransaction>,
 }
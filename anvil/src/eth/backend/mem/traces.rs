@@ -0,0 +1,137 @@
+//! Formatting of the traces of mined transactions, see `--trace-verbosity`
+
+use ethers::utils::hex;
+use foundry_evm::{
+    debug::{DebugArena, Instruction},
+    revm::opcode,
+    trace::{node::CallTraceNode, CallTraceArena, LogCallOrder},
+};
+
+/// Returns the call traces of a transaction that are printed at the `trace_verbosity`
+///
+/// At verbosity 1 only the top-level call and its logs are kept, all calls are kept otherwise.
+pub fn call_traces(mut traces: Vec<CallTraceNode>, trace_verbosity: u8) -> CallTraceArena {
+    if trace_verbosity == 1 {
+        traces.truncate(1);
+        if let Some(node) = traces.first_mut() {
+            node.children.clear();
+            node.ordering.retain(|item| matches!(item, LogCallOrder::Log(_)));
+        }
+    }
+    CallTraceArena { arena: traces }
+}
+
+/// Formats the storage reads and writes of the executed `steps`, or every step if the
+/// `trace_verbosity` is 4 or higher
+///
+/// Each call is introduced by its depth and the address whose code is executed, which is not the
+/// address of the accessed storage for delegate calls.
+pub fn format_steps(steps: &DebugArena, trace_verbosity: u8) -> String {
+    let mut out = String::new();
+    for node in &steps.arena {
+        out.push_str(&format!("      [{}] {:?} {:?}\n", node.depth, node.kind, node.address));
+        for (idx, step) in node.steps.iter().enumerate() {
+            let top = |n: usize| step.stack.iter().rev().nth(n).copied().unwrap_or_default();
+            let line = match step.instruction {
+                Instruction::OpCode(opcode::SLOAD) => {
+                    // the loaded value is on top of the stack of the next step
+                    let value = node
+                        .steps
+                        .get(idx + 1)
+                        .and_then(|next| next.stack.last().copied())
+                        .unwrap_or_default();
+                    format!("SLOAD  slot {:#x} => {:#x}", top(0), value)
+                }
+                Instruction::OpCode(opcode::SSTORE) => {
+                    format!("SSTORE slot {:#x} <= {:#x}", top(0), top(1))
+                }
+                _ if trace_verbosity >= 4 => match step.push_bytes {
+                    Some(ref bytes) => format!("{} 0x{}", step.instruction, hex::encode(bytes)),
+                    None => step.instruction.to_string(),
+                },
+                _ => continue,
+            };
+            out.push_str(&format!(
+                "        {:>5} {} (gas used {})\n",
+                step.pc, line, step.total_gas_used
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Address, U256};
+    use foundry_evm::debug::{DebugNode, DebugStep};
+
+    fn call_trace_nodes() -> Vec<CallTraceNode> {
+        let root = CallTraceNode {
+            children: vec![1],
+            ordering: vec![LogCallOrder::Call(0), LogCallOrder::Log(0)],
+            ..Default::default()
+        };
+        let child = CallTraceNode { parent: Some(0), idx: 1, ..Default::default() };
+        vec![root, child]
+    }
+
+    // stores 0x2a in slot 0 and loads it again
+    fn steps() -> DebugArena {
+        let step = |pc: usize, op: u8, stack: Vec<u64>, total_gas_used: u64| DebugStep {
+            pc,
+            stack: stack.into_iter().map(U256::from).collect(),
+            instruction: Instruction::OpCode(op),
+            push_bytes: (op == opcode::PUSH1).then(|| vec![0]),
+            total_gas_used,
+            ..Default::default()
+        };
+        let steps = vec![
+            step(0, opcode::SSTORE, vec![0x2a, 0], 20_000),
+            step(1, opcode::PUSH1, vec![], 20_003),
+            step(3, opcode::SLOAD, vec![0], 20_103),
+            step(4, opcode::STOP, vec![0x2a], 20_103),
+        ];
+        DebugArena { arena: vec![DebugNode::new(Address::zero(), 0, steps)] }
+    }
+
+    #[test]
+    fn keeps_top_level_call_at_verbosity_1() {
+        let arena = call_traces(call_trace_nodes(), 1);
+        assert_eq!(arena.arena.len(), 1);
+        assert!(arena.arena[0].children.is_empty());
+        assert_eq!(arena.arena[0].ordering, vec![LogCallOrder::Log(0)]);
+    }
+
+    #[test]
+    fn keeps_all_calls_at_verbosity_2() {
+        let arena = call_traces(call_trace_nodes(), 2);
+        assert_eq!(arena.arena, call_trace_nodes());
+    }
+
+    #[test]
+    fn formats_storage_steps_at_verbosity_3() {
+        assert_eq!(
+            format_steps(&steps(), 3),
+            concat!(
+                "      [0] Call 0x0000000000000000000000000000000000000000\n",
+                "            0 SSTORE slot 0x0 <= 0x2a (gas used 20000)\n",
+                "            3 SLOAD  slot 0x0 => 0x2a (gas used 20103)\n",
+            )
+        );
+    }
+
+    #[test]
+    fn formats_all_steps_at_verbosity_4() {
+        assert_eq!(
+            format_steps(&steps(), 4),
+            concat!(
+                "      [0] Call 0x0000000000000000000000000000000000000000\n",
+                "            0 SSTORE slot 0x0 <= 0x2a (gas used 20000)\n",
+                "            1 PUSH1 0x00 (gas used 20003)\n",
+                "            3 SLOAD  slot 0x0 => 0x2a (gas used 20103)\n",
+                "            4 STOP (gas used 20103)\n",
+            )
+        );
+    }
+}
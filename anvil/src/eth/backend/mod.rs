@@ -12,5 +12,6 @@ pub mod executor;
 pub mod fork;
 pub mod genesis;
 pub mod info;
+pub mod inspector;
 pub mod notifications;
 pub mod validate;
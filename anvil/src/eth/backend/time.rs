@@ -11,6 +11,15 @@ pub fn utc_from_secs(secs: u64) -> DateTime<Utc> {
 }
 
 /// Manages block time
+///
+/// The timestamp of the next block is determined by the following rules, in order of precedence:
+///   1. an exact timestamp set via `evm_setNextBlockTimestamp` or derived from the interval of an
+///      `anvil_mine` call, used once
+///   2. the previous block's timestamp plus the interval set via `anvil_setBlockTimestampInterval`
+///   3. the current wall clock time plus the accumulated offset (`evm_increaseTime`)
+///
+/// Jumping forward in time via `evm_increaseTime` always applies exactly once, regardless of
+/// whether an interval is currently active.
 #[derive(Debug, Clone, Default)]
 pub struct TimeManager {
     /// tracks the overall applied timestamp offset
@@ -48,9 +57,18 @@ impl TimeManager {
 
     /// Jumps forward in time by the given seconds
     ///
-    /// This will apply a permanent offset to the natural UNIX Epoch timestamp
+    /// This will apply a permanent offset to the natural UNIX Epoch timestamp.
+    ///
+    /// If an interval is active, the next timestamp is derived from the last timestamp instead, so
+    /// the jump is applied to the last timestamp as well. The offset is recomputed from the last
+    /// timestamp once the interval is removed, so the jump is not counted twice.
     pub fn increase_time(&self, seconds: u64) {
-        self.add_offset(seconds as i128)
+        self.add_offset(seconds as i128);
+        if self.interval.read().is_some() {
+            if let Some(last) = self.last_timestamp.write().as_mut() {
+                *last = last.saturating_add(seconds);
+            }
+        }
     }
 
     /// Sets the exact timestamp to use in the next block
@@ -59,6 +77,14 @@ impl TimeManager {
         self.next_exact_timestamp.write().replace(timestamp);
     }
 
+    /// Sets the exact timestamp to use in the next block to the last timestamp plus `interval`
+    ///
+    /// This takes precedence over the interval set via
+    /// [`set_block_timestamp_interval`](Self::set_block_timestamp_interval).
+    pub fn set_next_block_timestamp_interval(&self, interval: u64) {
+        self.set_next_block_timestamp(self.last_timestamp().saturating_add(interval))
+    }
+
     /// Sets an interval to use when computing the next timestamp
     ///
     /// If an interval already exists, this will update the interval, otherwise a new interval will
@@ -69,6 +95,10 @@ impl TimeManager {
     }
 
     /// Removes the interval if it exists
+    ///
+    /// Subsequent timestamps continue from the last timestamp based on the wall clock.
+    ///
+    /// Returns `true` if an interval was removed
     pub fn remove_block_timestamp_interval(&self) -> bool {
         if self.interval.write().take().is_some() {
             trace!(target: "time", "removed interval");
@@ -83,6 +113,16 @@ impl TimeManager {
         }
     }
 
+    /// Continues the timestamps from `timestamp`, the timestamp of the new chain head after the
+    /// chain was rolled back
+    ///
+    /// A timestamp that was set for the next block is kept.
+    pub fn rewind_to(&self, timestamp: u64) {
+        trace!(target: "time", "rewind to {}", timestamp);
+        self.set_start_timestamp(timestamp);
+        self.set_last_timestamp(timestamp);
+    }
+
     fn set_last_timestamp(&self, last_timestamp: u64) {
         self.last_timestamp.write().replace(last_timestamp);
     }
@@ -100,13 +140,7 @@ impl TimeManager {
         let current = duration_since_unix_epoch().as_secs() as i128;
 
         let next = if let Some(next) = self.next_exact_timestamp.write().take() {
-            // return the custom block timestamp and adjust the offset accordingly
-            // the offset will be negative if the `next` timestamp is in the past
-            let offset = (next as i128) - current;
-            let mut current_offset = self.offset.write();
-            // increase the offset by one second, so that we don't yield the same timestamp twice if
-            // it's set manually
-            *current_offset = offset.saturating_add(1);
+            self.apply_exact_timestamp(next, current);
             next
         } else if let Some(interval) = *self.interval.read() {
             self.last_timestamp().saturating_add(interval)
@@ -118,6 +152,27 @@ impl TimeManager {
         next
     }
 
+    /// Returns the exact `timestamp` as the current timestamp and updates the underlying offset
+    /// accordingly
+    ///
+    /// Unlike [`next_timestamp`](Self::next_timestamp), a timestamp set via
+    /// [`set_next_block_timestamp`](Self::set_next_block_timestamp) is kept for the next block.
+    pub fn use_timestamp(&self, timestamp: u64) -> u64 {
+        let current = duration_since_unix_epoch().as_secs() as i128;
+        self.apply_exact_timestamp(timestamp, current);
+        self.set_last_timestamp(timestamp);
+        timestamp
+    }
+
+    /// Adjusts the offset so that subsequent timestamps continue after the exact `timestamp`
+    fn apply_exact_timestamp(&self, timestamp: u64, current: i128) {
+        // the offset will be negative if the `timestamp` is in the past
+        let offset = (timestamp as i128) - current;
+        // increase the offset by one second, so that we don't yield the same timestamp twice if
+        // it's set manually
+        *self.offset.write() = offset.saturating_add(1);
+    }
+
     /// Returns the current timestamp for a call that does _not_ update the value
     pub fn current_call_timestamp(&self) -> u64 {
         let current = duration_since_unix_epoch().as_secs() as i128;
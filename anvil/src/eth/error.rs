@@ -10,6 +10,7 @@ use ethers::{
     providers::ProviderError,
     signers::WalletError,
     types::{Bytes, SignatureError, U256},
+    utils::hex,
 };
 use foundry_evm::revm::Return;
 use serde::Serialize;
@@ -57,6 +58,12 @@ pub enum BlockchainError {
     BlockOutOfRange(u64, u64),
     #[error("Resource not found")]
     BlockNotFound,
+    #[error(
+        "Fork offline: the requested state is not cached and the fork endpoint is unreachable"
+    )]
+    ForkOffline,
+    #[error("Failed to fetch the requested state from the fork endpoint: {0}")]
+    ForkFetchFailed(String),
 }
 
 impl From<RpcError> for BlockchainError {
@@ -75,6 +82,9 @@ pub enum PoolError {
     ReplacementUnderpriced(Box<PoolTransaction>),
     #[error("Tx: [{0:?}] already Imported")]
     AlreadyImported(Box<PoolTransaction>),
+    /// Thrown if the pool reached its limit and the transaction can't replace another one
+    #[error("Tx: [{0:?}] rejected because the pool is full")]
+    PoolFull(Box<PoolTransaction>),
 }
 
 /// Errors that can occur with `eth_feeHistory`
@@ -124,17 +134,41 @@ pub enum InvalidTransactionError {
     /// Thrown post London if the transaction's fee is less than the base fee of the block
     #[error("max fee per gas less than block base fee")]
     FeeTooLow,
+    /// Thrown pre London if the transaction's gas price is less than the configured minimum
+    #[error("transaction underpriced")]
+    Underpriced,
+    /// Thrown if an EIP-1559 transaction is sent while EIP-1559 is disabled
+    #[error("EIP-1559 transactions are not supported, EIP-1559 is disabled")]
+    Eip1559Disabled,
+    /// Thrown if the transaction was signed for a different chain
+    #[error("invalid chain id for signer: have {got} want {expected}")]
+    InvalidChainId { expected: U256, got: u64 },
 }
 
-/// Returns the revert reason from the `revm::TransactOut` data, if it's an abi encoded String.
+/// Returns the revert reason from the `revm::TransactOut` data.
+///
+/// `Error(string)` reverts are decoded to their message and `Panic(uint256)` reverts to their
+/// panic code. Any other revert data, like a custom error, is returned as its 4-byte selector.
 ///
 /// **Note:** it's assumed the `out` buffer starts with the call's signature
-fn decode_revert_reason(out: impl AsRef<[u8]>) -> Option<String> {
+pub(crate) fn decode_revert_reason(out: impl AsRef<[u8]>) -> Option<String> {
     let out = out.as_ref();
     if out.len() < 4 {
         return None
     }
-    String::decode(&out[4..]).ok()
+    match out[..4] {
+        // keccak(Error(string))
+        [0x08, 0xc3, 0x79, 0xa0] => String::decode(&out[4..]).ok(),
+        // keccak(Panic(uint256))
+        [0x4e, 0x48, 0x7b, 0x71] => {
+            let code = U256::decode(&out[4..]).ok()?;
+            match foundry_utils::decode_revert(out, None) {
+                Ok(reason) => Some(format!("panic code {:#x} ({})", code, reason)),
+                Err(_) => Some(format!("panic code {:#x}", code)),
+            }
+        }
+        _ => Some(format!("custom error 0x{}", hex::encode(&out[..4]))),
+    }
 }
 
 /// Helper trait to easily convert results to rpc results
@@ -170,6 +204,7 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                         PoolError::AlreadyImported(_) => {
                             RpcError::transaction_rejected("transaction already imported")
                         }
+                        PoolError::PoolFull(_) => RpcError::transaction_rejected("txpool is full"),
                     }
                 }
                 BlockchainError::NoSignerAvailable => {
@@ -225,6 +260,9 @@ impl<T: Serialize> ToRpcResponseResult for Result<T> {
                 err @ BlockchainError::BlockOutOfRange(_, _) => {
                     RpcError::invalid_params(err.to_string())
                 }
+                err @ (BlockchainError::ForkOffline | BlockchainError::ForkFetchFailed(_)) => {
+                    RpcError::internal_error_with(err.to_string())
+                }
                 err @ BlockchainError::BlockNotFound => RpcError {
                     // <https://eips.ethereum.org/EIPS/eip-1898>
                     code: ErrorCode::ServerError(-32001),
@@ -2,7 +2,6 @@ use crate::eth::{
     backend::{info::StorageInfo, notifications::NewBlockNotifications},
     error::BlockchainError,
 };
-use anvil_core::eth::transaction::TypedTransaction;
 use ethers::types::{H256, U256};
 use foundry_evm::revm::SpecId;
 use futures::StreamExt;
@@ -25,6 +24,12 @@ pub const INITIAL_BASE_FEE: u64 = 1_000_000_000;
 /// Initial default gas price for the first block
 pub const INITIAL_GAS_PRICE: u64 = 1_875_000_000;
 
+/// Default priority fee that is suggested on top of the base fee
+pub const DEFAULT_PRIORITY_FEE: u64 = 1_000_000_000;
+
+/// Number of recent blocks the gas price oracle takes the transactions of into account
+pub const GAS_PRICE_ORACLE_BLOCKS: u64 = 20;
+
 /// Bounds the amount the base fee can change between blocks.
 pub const BASE_FEE_CHANGE_DENOMINATOR: u64 = 8;
 
@@ -36,7 +41,9 @@ pub fn default_elasticity() -> f64 {
 #[derive(Debug, Clone)]
 pub struct FeeManager {
     /// Hardfork identifier
-    spec_id: SpecId,
+    ///
+    /// This changes when a scheduled hardfork transition is reached
+    spec_id: Arc<RwLock<SpecId>>,
     /// Tracks the base fee for the next block post London
     ///
     /// This value will be updated after a new block was mined
@@ -45,7 +52,17 @@ pub struct FeeManager {
     ///
     /// This will be constant value unless changed manually
     gas_price: Arc<RwLock<U256>>,
+    /// The lowest gas price Pre London, see [FeeManager::min_gas_price]
+    min_gas_price: Arc<RwLock<Option<U256>>>,
+    /// The priority fee that is suggested on top of the base fee Post London
+    priority_fee: Arc<RwLock<U256>>,
     elasticity: Arc<RwLock<f64>>,
+    /// Whether the base fee should be recomputed after every block based on the parent's gas usage
+    ///
+    /// Enabled by default, if disabled the base fee stays constant unless changed manually
+    dynamic_base_fee: bool,
+    /// Forces EIP-1559 on or off, regardless of the hardfork
+    eip1559: Option<bool>,
 }
 
 // === impl FeeManager ===
@@ -53,20 +70,55 @@ pub struct FeeManager {
 impl FeeManager {
     pub fn new(spec_id: SpecId, base_fee: U256, gas_price: U256) -> Self {
         Self {
-            spec_id,
+            spec_id: Arc::new(RwLock::new(spec_id)),
             base_fee: Arc::new(RwLock::new(base_fee)),
             gas_price: Arc::new(RwLock::new(gas_price)),
+            min_gas_price: Default::default(),
+            priority_fee: Arc::new(RwLock::new(DEFAULT_PRIORITY_FEE.into())),
             elasticity: Arc::new(RwLock::new(default_elasticity())),
+            dynamic_base_fee: true,
+            eip1559: None,
         }
     }
 
+    /// Enables or disables the EIP-1559 base fee adjustment between blocks
+    #[must_use]
+    pub fn with_dynamic_base_fee(mut self, dynamic_base_fee: bool) -> Self {
+        self.dynamic_base_fee = dynamic_base_fee;
+        self
+    }
+
+    /// Forces EIP-1559 on or off, `None` activates it with the London hardfork
+    #[must_use]
+    pub fn with_eip1559(mut self, eip1559: Option<bool>) -> Self {
+        self.eip1559 = eip1559;
+        self
+    }
+
+    /// Sets the lowest gas price, see [FeeManager::min_gas_price]
+    #[must_use]
+    pub fn with_min_gas_price(self, min_gas_price: Option<U256>) -> Self {
+        self.set_min_gas_price(min_gas_price);
+        self
+    }
+
+    /// Returns true if the base fee is recomputed after every block
+    pub fn is_dynamic_base_fee(&self) -> bool {
+        self.dynamic_base_fee
+    }
+
     pub fn elasticity(&self) -> f64 {
         *self.elasticity.read()
     }
 
-    /// Returns true for post London
+    /// Returns true for post London, unless EIP-1559 was forced on or off
     pub fn is_eip1559(&self) -> bool {
-        (self.spec_id as u8) >= (SpecId::LONDON as u8)
+        self.eip1559.unwrap_or_else(|| (*self.spec_id.read() as u8) >= (SpecId::LONDON as u8))
+    }
+
+    /// Sets the active hardfork
+    pub fn set_spec_id(&self, spec_id: SpecId) {
+        *self.spec_id.write() = spec_id;
     }
 
     /// Calculates the current gas price
@@ -74,13 +126,36 @@ impl FeeManager {
         if self.is_eip1559() {
             self.base_fee().saturating_add(self.suggested_priority_fee())
         } else {
-            *self.gas_price.read()
+            let gas_price = *self.gas_price.read();
+            self.min_gas_price().map_or(gas_price, |min_gas_price| gas_price.max(min_gas_price))
+        }
+    }
+
+    /// Returns the lowest gas price of transactions that are accepted, which is also the lowest
+    /// gas price that is suggested
+    ///
+    /// This only applies Pre London, while EIP-1559 is active the base fee is the lowest price and
+    /// this returns `None`.
+    pub fn min_gas_price(&self) -> Option<U256> {
+        if self.is_eip1559() {
+            return None
         }
+        *self.min_gas_price.read()
+    }
+
+    /// Sets the lowest gas price, `None` removes it
+    pub fn set_min_gas_price(&self, min_gas_price: Option<U256>) {
+        *self.min_gas_price.write() = min_gas_price;
     }
 
     /// Suggested priority fee to add to the base fee
     pub fn suggested_priority_fee(&self) -> U256 {
-        U256::from(1e9 as u64)
+        *self.priority_fee.read()
+    }
+
+    /// Sets the priority fee that is suggested on top of the base fee
+    pub fn set_suggested_priority_fee(&self, priority_fee: U256) {
+        *self.priority_fee.write() = priority_fee;
     }
 
     pub fn base_fee(&self) -> U256 {
@@ -227,19 +302,11 @@ impl FeeHistoryService {
                 .enumerate()
                 .map(|(i, receipt)| {
                     let gas_used = receipt.gas_used().as_u64();
-                    let effective_reward = match block.transactions.get(i) {
-                        Some(&TypedTransaction::Legacy(ref t)) => {
-                            t.gas_price.saturating_sub(base_fee).as_u64()
-                        }
-                        Some(&TypedTransaction::EIP2930(ref t)) => {
-                            t.gas_price.saturating_sub(base_fee).as_u64()
-                        }
-                        Some(&TypedTransaction::EIP1559(ref t)) => t
-                            .max_priority_fee_per_gas
-                            .min(t.max_fee_per_gas.saturating_sub(base_fee))
-                            .as_u64(),
-                        None => 0,
-                    };
+                    let effective_reward = block
+                        .transactions
+                        .get(i)
+                        .map(|tx| tx.effective_tip(base_fee).as_u64())
+                        .unwrap_or_default();
 
                     (gas_used, effective_reward)
                 })
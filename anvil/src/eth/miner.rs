@@ -19,7 +19,7 @@ use std::{
     task::{Context, Poll},
     time::Duration,
 };
-use tokio::time::Interval;
+use tokio::time::{Interval, MissedTickBehavior};
 use tracing::trace;
 
 #[derive(Debug, Clone)]
@@ -167,9 +167,14 @@ pub struct FixedBlockTimeMiner {
 
 impl FixedBlockTimeMiner {
     /// Creates a new instance with an interval of `duration`
+    ///
+    /// If mining a block takes longer than the interval, the next tick is delayed instead of
+    /// firing the missed ticks in a burst, so short intervals don't mine back to back.
     pub fn new(duration: Duration) -> Self {
         let start = tokio::time::Instant::now() + duration;
-        Self { interval: tokio::time::interval_at(start, duration) }
+        let mut interval = tokio::time::interval_at(start, duration);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self { interval }
     }
 
     fn poll(&mut self, pool: &Arc<Pool>, cx: &mut Context<'_>) -> Poll<Vec<Arc<PoolTransaction>>> {
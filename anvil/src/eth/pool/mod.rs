@@ -31,7 +31,7 @@ use crate::{
         error::PoolError,
         pool::transactions::{
             PendingPoolTransaction, PendingTransactions, PoolTransaction, ReadyTransactions,
-            TransactionsIterator, TxMarker,
+            TransactionsIterator, TxMarker, DEFAULT_PRICE_BUMP,
         },
     },
     mem::storage::MinedBlockOutcome,
@@ -43,7 +43,7 @@ use ethers::{
 };
 use futures::channel::mpsc::{channel, Receiver, Sender};
 use parking_lot::{Mutex, RwLock};
-use std::{collections::VecDeque, fmt, sync::Arc};
+use std::{collections::VecDeque, fmt, str::FromStr, sync::Arc};
 use tracing::{debug, trace, warn};
 
 pub mod transactions;
@@ -60,6 +60,28 @@ pub struct Pool {
 // == impl Pool ==
 
 impl Pool {
+    /// Sets the maximum number of transactions the pool holds, `None` for no limit
+    #[must_use]
+    pub fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.inner.get_mut().limit = limit;
+        self
+    }
+
+    /// Sets what happens when a transaction is added to a full pool
+    #[must_use]
+    pub fn with_full_behavior(mut self, full_behavior: PoolFullBehavior) -> Self {
+        self.inner.get_mut().full_behavior = full_behavior;
+        self
+    }
+
+    /// Sets the percentage by which a replacement transaction's gas price must exceed the gas price
+    /// of the transaction it replaces
+    #[must_use]
+    pub fn with_price_bump(mut self, price_bump: u64) -> Self {
+        self.inner.get_mut().price_bump = price_bump;
+        self
+    }
+
     /// Returns an iterator that yields all transactions that are currently ready
     pub fn ready_transactions(&self) -> TransactionsIterator {
         self.inner.read().ready_transactions()
@@ -192,13 +214,61 @@ impl Pool {
     }
 }
 
+/// Determines what happens when a transaction is added to a pool that reached its limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolFullBehavior {
+    /// Reject the new transaction
+    Reject,
+    /// Evict the transaction with the lowest priority to make room for the new transaction.
+    ///
+    /// The new transaction is rejected if no transaction in the pool has a lower priority.
+    Evict,
+}
+
+impl FromStr for PoolFullBehavior {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        let behavior = match s.as_str() {
+            "reject" => PoolFullBehavior::Reject,
+            "evict" => PoolFullBehavior::Evict,
+            _ => return Err(format!("Unknown PoolFullBehavior: `{}`", s)),
+        };
+        Ok(behavior)
+    }
+}
+
+impl Default for PoolFullBehavior {
+    fn default() -> Self {
+        PoolFullBehavior::Reject
+    }
+}
+
 /// A Transaction Pool
 ///
 /// Contains all transactions that are ready to be executed
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct PoolInner {
     ready_transactions: ReadyTransactions,
     pending_transactions: PendingTransactions,
+    /// the maximum number of transactions in the pool, if limited
+    limit: Option<usize>,
+    full_behavior: PoolFullBehavior,
+    /// the percentage by which a replacement transaction's gas price must be higher
+    price_bump: u64,
+}
+
+impl Default for PoolInner {
+    fn default() -> Self {
+        Self {
+            ready_transactions: Default::default(),
+            pending_transactions: Default::default(),
+            limit: None,
+            full_behavior: Default::default(),
+            price_bump: DEFAULT_PRICE_BUMP,
+        }
+    }
 }
 
 // == impl PoolInner ==
@@ -226,22 +296,87 @@ impl PoolInner {
         self.pending_transactions.contains(tx_hash) || self.ready_transactions.contains(tx_hash)
     }
 
+    /// Returns the number of transactions in the pool
+    fn len(&self) -> usize {
+        self.ready_transactions.len() + self.pending_transactions.len()
+    }
+
+    /// Returns true if the pool reached its limit
+    fn is_full(&self) -> bool {
+        matches!(self.limit, Some(limit) if self.len() >= limit)
+    }
+
+    /// Returns true if the transaction would replace a transaction in the pool, because it
+    /// provides the same markers
+    fn is_replacement(&self, tx: &PoolTransaction) -> bool {
+        let provided = self.ready_transactions.provided_markers();
+        tx.provides.iter().any(|marker| provided.contains_key(marker)) ||
+            self.pending_transactions.transactions().any(|pending| {
+                pending.provides.iter().any(|marker| tx.provides.contains(marker))
+            })
+    }
+
+    /// Returns the transaction with the lowest priority that is not sent by the sender of `tx`,
+    /// if any
+    ///
+    /// Transactions of the same sender are skipped, because evicting one of them could also
+    /// remove `tx`, which depends on the transactions with lower nonces.
+    fn lowest_priority_transaction(&self, tx: &PoolTransaction) -> Option<Arc<PoolTransaction>> {
+        let sender = tx.pending_transaction.sender();
+        self.ready_transactions
+            .get_transactions()
+            .chain(self.pending_transactions.transactions())
+            .filter(|other| other.pending_transaction.sender() != sender)
+            .min_by_key(|other| other.priority)
+    }
+
     fn add_transaction(&mut self, tx: PoolTransaction) -> Result<AddedTransaction, PoolError> {
         if self.contains(tx.hash()) {
             warn!(target: "txpool", "[{:?}] Already imported", tx.hash());
             return Err(PoolError::AlreadyImported(Box::new(tx)))
         }
 
+        // the transaction to evict once `tx` was added
+        let mut evict = None;
+        if self.is_full() && !self.is_replacement(&tx) {
+            let lowest = match self.full_behavior {
+                PoolFullBehavior::Reject => None,
+                PoolFullBehavior::Evict => self
+                    .lowest_priority_transaction(&tx)
+                    .filter(|lowest| lowest.priority < tx.priority),
+            };
+            match lowest {
+                Some(lowest) => evict = Some(*lowest.hash()),
+                None => {
+                    warn!(target: "txpool", "[{:?}] Pool is full", tx.hash());
+                    return Err(PoolError::PoolFull(Box::new(tx)))
+                }
+            }
+        }
+
+        let hash = *tx.hash();
         let tx = PendingPoolTransaction::new(tx, self.ready_transactions.provided_markers());
         trace!(target: "txpool", "[{:?}] {:?}", tx.transaction.hash(), tx);
 
         // If all markers are not satisfied import to future
-        if !tx.is_ready() {
-            let hash = *tx.transaction.hash();
-            self.pending_transactions.add_transaction(tx)?;
-            return Ok(AddedTransaction::Pending { hash })
+        let added = if !tx.is_ready() {
+            let replaced = self.pending_transactions.add_transaction(tx, self.price_bump)?;
+            if !replaced.is_empty() {
+                debug!(target: "txpool", "[{:?}] Replaced pending transactions: {:?}", hash, replaced);
+            }
+            AddedTransaction::Pending { hash }
+        } else {
+            self.add_ready_transaction(tx)?
+        };
+
+        // only make room after the transaction was accepted, so a rejected transaction never
+        // evicts another one
+        if let Some(lowest) = evict {
+            let evicted = self.remove_invalid(vec![lowest]);
+            debug!(target: "txpool", "[{:?}] Evicted transactions to make room: {:?}", hash, evicted);
         }
-        self.add_ready_transaction(tx)
+
+        Ok(added)
     }
 
     /// Adds the transaction to the ready queue
@@ -266,7 +401,7 @@ impl PoolInner {
 
             let current_hash = *current_tx.transaction.hash();
             // try to add the transaction to the ready pool
-            match self.ready_transactions.add_transaction(current_tx) {
+            match self.ready_transactions.add_transaction(current_tx, self.price_bump) {
                 Ok(replaced_transactions) => {
                     if !is_new_tx {
                         ready.promoted.push(current_hash);
@@ -2,14 +2,18 @@ use crate::eth::{error::PoolError, util::hex_fmt_many};
 use anvil_core::eth::transaction::{PendingTransaction, TypedTransaction};
 use ethers::types::{Address, TxHash, U256};
 use parking_lot::RwLock;
+use serde::Serialize;
 use std::{
-    cmp::Ordering,
+    cmp::{Ordering, Reverse},
     collections::{BTreeSet, HashMap, HashSet},
     fmt,
+    path::PathBuf,
+    process::Stdio,
     str::FromStr,
     sync::Arc,
-    time::Instant,
+    time::{Duration, Instant},
 };
+use tokio::{io::AsyncWriteExt, process::Command};
 use tracing::{trace, warn};
 
 /// A unique identifying marker for a transaction
@@ -23,10 +27,44 @@ pub fn to_marker(nonce: u64, from: Address) -> TxMarker {
     data.to_vec()
 }
 
+/// The default percentage by which the gas price of a transaction must exceed the gas price of the
+/// transaction it replaces
+pub const DEFAULT_PRICE_BUMP: u64 = 10;
+
+/// Returns true if `tx` pays enough to replace `existing`, which has the same sender and nonce
+///
+/// The gas price of the replacement must be higher and exceed the gas price of the replaced
+/// transaction by at least `price_bump` percent. If both are EIP-1559 transactions, the max
+/// priority fee must be bumped as well.
+pub fn is_priced_replacement(
+    tx: &PoolTransaction,
+    existing: &PoolTransaction,
+    price_bump: u64,
+) -> bool {
+    let min_bumped = |price: U256| {
+        price.saturating_add(price.saturating_mul(price_bump.into()) / U256::from(100u64))
+    };
+    let max_priority_fee = |tx: &PoolTransaction| match tx.pending_transaction.transaction {
+        TypedTransaction::EIP1559(ref tx) => Some(tx.max_priority_fee_per_gas),
+        _ => None,
+    };
+    if tx.gas_price() <= existing.gas_price() || tx.gas_price() < min_bumped(existing.gas_price()) {
+        return false
+    }
+    match (max_priority_fee(tx), max_priority_fee(existing)) {
+        (Some(new), Some(old)) => new >= min_bumped(old),
+        _ => true,
+    }
+}
+
+/// How long the external ordering command may take before the default order is used
+pub const EXTERNAL_ORDER_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Modes that determine the transaction ordering of the mempool
 ///
-/// This type controls the transaction order via the priority metric of a transaction
-#[derive(Debug, Clone, Eq, PartialEq, Copy)]
+/// This type controls the transaction order via the priority metric of a transaction, ties are
+/// broken as described in [PoolTransactionRef::order_key()]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum TransactionOrder {
     /// Keep the pool transaction transactions sorted in the order they arrive.
     ///
@@ -34,19 +72,119 @@ pub enum TransactionOrder {
     /// determined by their internal id
     Fifo,
     /// This means that it prioritizes transactions based on the fees paid to the miner.
+    ///
+    /// The fee of a transaction is its effective tip at the current base fee, so that EIP-1559
+    /// and legacy transactions are ranked by the same metric.
     Fees,
+    /// The transactions of every block are ordered by an external command.
+    ///
+    /// The pool itself is ordered by fees. The candidate transactions of a block are written as
+    /// a JSON array to the stdin of the command, which replies with a JSON array of the hashes of
+    /// the transactions to include, in order, on its stdout.
+    External(PathBuf),
 }
 
 // === impl TransactionOrder ===
 
 impl TransactionOrder {
-    /// Returns the priority of the transactions
-    pub fn priority(&self, tx: &TypedTransaction) -> TransactionPriority {
+    /// Returns the priority of the transactions, given the current `base_fee`
+    ///
+    /// Deposit transactions don't pay a tip, but are ordered before all other transactions when
+    /// ordering by fees, like on an Optimism L2.
+    pub fn priority(&self, tx: &TypedTransaction, base_fee: U256) -> TransactionPriority {
         match self {
             TransactionOrder::Fifo => TransactionPriority::default(),
-            TransactionOrder::Fees => TransactionPriority(tx.gas_price()),
+            TransactionOrder::Fees | TransactionOrder::External(_) if tx.is_deposit() => {
+                TransactionPriority::new(U256::MAX)
+            }
+            TransactionOrder::Fees | TransactionOrder::External(_) => {
+                TransactionPriority::new(tx.effective_tip(base_fee))
+            }
         }
     }
+
+    /// Returns the candidate `transactions` of a block with the given `base_fee` in the order they
+    /// should be included
+    ///
+    /// The priority of a pool transaction is based on the base fee at the time it was added, so
+    /// when ordering by fees the transactions are ranked by their priority at the `base_fee` of the
+    /// block instead. With [TransactionOrder::External] this is the default order, which is kept
+    /// if the command fails, times out or replies with malformed output. The transactions of every
+    /// sender are always included in nonce order, the command only decides at which positions of
+    /// the block they're included.
+    pub async fn order_transactions(
+        &self,
+        mut transactions: Vec<Arc<PoolTransaction>>,
+        base_fee: U256,
+    ) -> Vec<Arc<PoolTransaction>> {
+        self.sort_by_priority(&mut transactions, base_fee);
+
+        let command = match self {
+            TransactionOrder::External(command) if !transactions.is_empty() => command,
+            _ => return transactions,
+        };
+
+        let input = match order_command_input(&transactions) {
+            Ok(input) => input,
+            Err(err) => {
+                warn!(target: "txpool", "failed to encode transactions for order command: {}", err);
+                return transactions
+            }
+        };
+
+        // the command runs on its own task, so that it is killed if it times out
+        let task = {
+            let command = command.clone();
+            tokio::task::spawn(async move {
+                tokio::time::timeout(EXTERNAL_ORDER_TIMEOUT, run_order_command(command, input))
+                    .await
+            })
+        };
+        let hashes = match task.await {
+            Ok(Ok(Ok(hashes))) => hashes,
+            Ok(Ok(Err(err))) => {
+                warn!(target: "txpool", "order command {:?} failed, using default order: {}", command, err);
+                return transactions
+            }
+            Ok(Err(_)) => {
+                warn!(target: "txpool", "order command {:?} timed out, using default order", command);
+                return transactions
+            }
+            Err(err) => {
+                warn!(target: "txpool", "order command {:?} failed, using default order: {}", command, err);
+                return transactions
+            }
+        };
+
+        let mut by_hash: HashMap<TxHash, Arc<PoolTransaction>> =
+            transactions.iter().map(|tx| (*tx.hash(), Arc::clone(tx))).collect();
+        let mut ordered = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            match by_hash.remove(&hash) {
+                Some(tx) => ordered.push(tx),
+                None => {
+                    warn!(target: "txpool", "order command {:?} returned unknown or duplicate transaction {:?}, using default order", command, hash);
+                    return transactions
+                }
+            }
+        }
+        sort_nonces_per_sender(&mut ordered);
+        trace!(target: "txpool", "order command selected {} of {} transactions", ordered.len(), transactions.len());
+        ordered
+    }
+
+    /// Sorts the `transactions` by their priority at the given `base_fee`, highest first
+    fn sort_by_priority(&self, transactions: &mut [Arc<PoolTransaction>], base_fee: U256) {
+        if *self == TransactionOrder::Fifo {
+            return
+        }
+        // the sort is stable, so transactions of equal priority keep the order of the pool
+        transactions.sort_by_key(|tx| {
+            let value = self.priority(&tx.pending_transaction.transaction, base_fee).value;
+            Reverse(TransactionPriority { value, ..tx.priority })
+        });
+        sort_nonces_per_sender(transactions);
+    }
 }
 
 impl FromStr for TransactionOrder {
@@ -69,12 +207,111 @@ impl Default for TransactionOrder {
     }
 }
 
+/// Sorts the transactions of every sender by nonce, the positions that the transactions of a
+/// sender take in `transactions` are kept
+fn sort_nonces_per_sender(transactions: &mut [Arc<PoolTransaction>]) {
+    let mut positions: HashMap<Address, Vec<usize>> = HashMap::new();
+    for (idx, tx) in transactions.iter().enumerate() {
+        positions.entry(*tx.pending_transaction.sender()).or_default().push(idx);
+    }
+    for positions in positions.into_values() {
+        let mut sender_transactions: Vec<_> =
+            positions.iter().map(|idx| Arc::clone(&transactions[*idx])).collect();
+        sender_transactions.sort_by_key(|tx| *tx.pending_transaction.nonce());
+        for (idx, tx) in positions.into_iter().zip(sender_transactions) {
+            transactions[idx] = tx;
+        }
+    }
+}
+
+/// A candidate transaction as it is passed to the external ordering command
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OrderCandidate<'a> {
+    hash: TxHash,
+    from: Address,
+    nonce: U256,
+    gas_price: U256,
+    transaction: &'a TypedTransaction,
+}
+
+/// Returns the JSON input of the external ordering command
+fn order_command_input(transactions: &[Arc<PoolTransaction>]) -> serde_json::Result<Vec<u8>> {
+    let candidates = transactions
+        .iter()
+        .map(|tx| OrderCandidate {
+            hash: *tx.hash(),
+            from: *tx.pending_transaction.sender(),
+            nonce: *tx.pending_transaction.nonce(),
+            gas_price: tx.gas_price(),
+            transaction: &tx.pending_transaction.transaction,
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_vec(&candidates)
+}
+
+/// Pipes the `input` to the `command` and returns the hashes it replied with
+async fn run_order_command(command: PathBuf, input: Vec<u8>) -> Result<Vec<TxHash>, String> {
+    let mut child = Command::new(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| err.to_string())?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped; qed");
+    stdin.write_all(&input).await.map_err(|err| err.to_string())?;
+    // close stdin so the command sees the end of the input
+    drop(stdin);
+
+    let output = child.wait_with_output().await.map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        return Err(format!("exited with {}", output.status))
+    }
+    serde_json::from_slice(&output.stdout).map_err(|err| format!("malformed output: {}", err))
+}
+
 /// Metric value for the priority of a transaction.
 ///
 /// The `TransactionPriority` determines the ordering of two transactions that have all  their
 /// markers satisfied.
+///
+/// Transactions of priority senders are always ordered before all other transactions, followed by
+/// transactions that call a priority selector. Within each group the `value` of the configured
+/// [TransactionOrder] applies.
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Default)]
-pub struct TransactionPriority(pub U256);
+pub struct TransactionPriority {
+    /// Whether the transaction was sent by a priority sender
+    pub priority_sender: bool,
+    /// Whether the calldata of the transaction starts with a priority selector
+    pub priority_selector: bool,
+    /// The priority metric determined by the [TransactionOrder]
+    pub value: U256,
+}
+
+// === impl TransactionPriority ===
+
+impl TransactionPriority {
+    /// Creates a new priority with the given metric value
+    pub fn new(value: U256) -> Self {
+        Self { priority_sender: false, priority_selector: false, value }
+    }
+
+    /// Marks the transaction as sent by a priority sender
+    #[must_use]
+    pub fn with_priority_sender(mut self, priority_sender: bool) -> Self {
+        self.priority_sender = priority_sender;
+        self
+    }
+
+    /// Marks the transaction as calling a priority selector
+    #[must_use]
+    pub fn with_priority_selector(mut self, priority_selector: bool) -> Self {
+        self.priority_selector = priority_selector;
+        self
+    }
+}
 
 /// Internal Transaction type
 #[derive(Clone, PartialEq, Eq)]
@@ -121,7 +358,10 @@ impl fmt::Debug for PoolTransaction {
 #[derive(Debug, Clone, Default)]
 pub struct PendingTransactions {
     /// markers that aren't yet provided by any transaction
-    required_markers: HashMap<TxMarker, HashSet<TxHash>>,
+    ///
+    /// The waiting transactions are sorted, so that transactions unlocked by the same marker are
+    /// promoted in a deterministic order
+    required_markers: HashMap<TxMarker, BTreeSet<TxHash>>,
     /// mapping of the markers of a transaction to the hash of the transaction
     waiting_markers: HashMap<Vec<TxMarker>, TxHash>,
     /// the transactions that are not ready yet are waiting for another tx to finish
@@ -146,25 +386,37 @@ impl PendingTransactions {
     }
 
     /// Adds a transaction to Pending queue of transactions
-    pub fn add_transaction(&mut self, tx: PendingPoolTransaction) -> Result<(), PoolError> {
+    ///
+    /// A waiting transaction with the same markers is replaced if the new transaction's gas price
+    /// is at least `price_bump` percent higher, see [is_priced_replacement]. Returns the replaced
+    /// transactions.
+    pub fn add_transaction(
+        &mut self,
+        tx: PendingPoolTransaction,
+        price_bump: u64,
+    ) -> Result<Vec<Arc<PoolTransaction>>, PoolError> {
         assert!(!tx.is_ready(), "transaction must not be ready");
         assert!(
             !self.waiting_queue.contains_key(tx.transaction.hash()),
             "transaction is already added"
         );
 
+        let mut replaced = Vec::new();
         if let Some(replace) = self
             .waiting_markers
             .get(&tx.transaction.provides)
             .and_then(|hash| self.waiting_queue.get(hash))
         {
             // check if underpriced
-            if tx.transaction.gas_price() < replace.transaction.gas_price() {
+            if !is_priced_replacement(&tx.transaction, &replace.transaction, price_bump) {
                 warn!(target: "txpool", "pending replacement transaction underpriced [{:?}]", tx.transaction.hash());
                 return Err(PoolError::ReplacementUnderpriced(Box::new(
                     tx.transaction.as_ref().clone(),
                 )))
             }
+            trace!(target: "txpool", "replacing pending transaction [{:?}] with higher gas price [{:?}]", replace.transaction.hash(), tx.transaction.hash());
+            let replaced_hash = *replace.transaction.hash();
+            replaced = self.remove(vec![replaced_hash]);
         }
 
         // add all missing markers
@@ -177,7 +429,7 @@ impl PendingTransactions {
         // add tx to the queue
         self.waiting_queue.insert(*tx.transaction.hash(), tx);
 
-        Ok(())
+        Ok(replaced)
     }
 
     /// Returns true if given transaction is part of the queue
@@ -366,7 +618,7 @@ pub struct ReadyTransactions {
     /// transactions that are ready
     ready_tx: Arc<RwLock<HashMap<TxHash, ReadyTransaction>>>,
     /// independent transactions that can be included directly and don't require other transactions
-    /// Sorted by their id
+    /// Sorted by their [PoolTransactionRef::order_key()]
     independent_transactions: BTreeSet<PoolTransactionRef>,
 }
 
@@ -383,6 +635,16 @@ impl ReadyTransactions {
         }
     }
 
+    /// Returns the number of ready transactions
+    pub fn len(&self) -> usize {
+        self.ready_tx.read().len()
+    }
+
+    /// Whether there are no ready transactions
+    pub fn is_empty(&self) -> bool {
+        self.ready_tx.read().is_empty()
+    }
+
     /// Returns true if the transaction is part of the queue.
     pub fn contains(&self, hash: &TxHash) -> bool {
         self.ready_tx.read().contains_key(hash)
@@ -409,9 +671,12 @@ impl ReadyTransactions {
     ///
     /// if the pending transaction is not ready: [PendingTransaction::is_ready()]
     /// or the transaction is already included
+    /// A ready transaction with the same markers is replaced if the new transaction's gas price is
+    /// at least `price_bump` percent higher, see [is_priced_replacement].
     pub fn add_transaction(
         &mut self,
         tx: PendingPoolTransaction,
+        price_bump: u64,
     ) -> Result<Vec<Arc<PoolTransaction>>, PoolError> {
         assert!(tx.is_ready(), "transaction must be ready",);
         assert!(
@@ -419,7 +684,7 @@ impl ReadyTransactions {
             "transaction already included"
         );
 
-        let (replaced_tx, unlocks) = self.replaced_transactions(&tx.transaction)?;
+        let (replaced_tx, unlocks) = self.replaced_transactions(&tx.transaction, price_bump)?;
 
         let id = self.next_id();
         let hash = *tx.transaction.hash();
@@ -462,6 +727,7 @@ impl ReadyTransactions {
     fn replaced_transactions(
         &mut self,
         tx: &PoolTransaction,
+        price_bump: u64,
     ) -> Result<(Vec<Arc<PoolTransaction>>, Vec<TxHash>), PoolError> {
         // check if we are replacing transactions
         let remove_hashes: HashSet<_> =
@@ -484,7 +750,7 @@ impl ReadyTransactions {
                 // (addr + nonce) then we check for gas price
                 if to_remove.provides() == tx.provides {
                     // check if underpriced
-                    if tx.pending_transaction.transaction.gas_price() <= to_remove.gas_price() {
+                    if !is_priced_replacement(tx, &to_remove.transaction.transaction, price_bump) {
                         warn!(target: "txpool", "ready replacement transaction underpriced [{:?}]", tx.hash());
                         return Err(PoolError::ReplacementUnderpriced(Box::new(tx.clone())))
                     } else {
@@ -645,6 +911,24 @@ pub struct PoolTransactionRef {
     pub id: u64,
 }
 
+// === impl PoolTransactionRef ===
+
+impl PoolTransactionRef {
+    /// Returns the key that orders the transactions in the pool, the transaction with the highest
+    /// key is mined first.
+    ///
+    /// The key compares, in this order:
+    ///  1. the [TransactionPriority] of the transaction, higher first
+    ///  2. the `id`, which increases in the order the transactions became ready, lower first
+    ///  3. the hash of the transaction, lower first
+    ///
+    /// This is a total order, so identical inputs always result in identical blocks, regardless of
+    /// the [TransactionOrder].
+    pub fn order_key(&self) -> (TransactionPriority, Reverse<u64>, Reverse<&TxHash>) {
+        (self.transaction.priority, Reverse(self.id), Reverse(self.transaction.hash()))
+    }
+}
+
 impl Eq for PoolTransactionRef {}
 
 impl PartialEq<Self> for PoolTransactionRef {
@@ -661,10 +945,7 @@ impl PartialOrd<Self> for PoolTransactionRef {
 
 impl Ord for PoolTransactionRef {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.transaction
-            .priority
-            .cmp(&other.transaction.priority)
-            .then_with(|| other.id.cmp(&self.id))
+        self.order_key().cmp(&other.order_key())
     }
 }
 
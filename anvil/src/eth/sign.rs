@@ -5,13 +5,20 @@ use anvil_core::eth::transaction::{
 };
 use ethers::{
     core::k256::ecdsa::SigningKey,
-    prelude::{Address, Wallet},
-    signers::Signer as EthersSigner,
+    prelude::{rand::thread_rng, Address, Wallet},
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer as EthersSigner},
     types::{
-        transaction::eip2718::TypedTransaction as EthersTypedTransactionRequest, Signature, H256,
+        transaction::{
+            eip2718::TypedTransaction as EthersTypedTransactionRequest, eip712::TypedData,
+        },
+        Bytes, Signature, H256,
     },
+    utils::rlp,
 };
-use std::collections::HashMap;
+use parking_lot::RwLock;
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Arc};
 
 /// A transaction signer
 #[async_trait::async_trait]
@@ -27,8 +34,15 @@ pub trait Signer: Send + Sync {
     /// Returns the signature
     async fn sign(&self, address: Address, message: &[u8]) -> Result<Signature, BlockchainError>;
 
+    /// Returns the signature of the EIP-712 digest of the typed data
+    async fn sign_typed_data(
+        &self,
+        address: Address,
+        payload: &TypedData,
+    ) -> Result<Signature, BlockchainError>;
+
     /// signs a transaction request using the given account in request
-    fn sign_transaction(
+    async fn sign_transaction(
         &self,
         request: TypedTransactionRequest,
         address: &Address,
@@ -65,7 +79,17 @@ impl Signer for DevSigner {
         Ok(signer.sign_message(message).await?)
     }
 
-    fn sign_transaction(
+    async fn sign_typed_data(
+        &self,
+        address: Address,
+        payload: &TypedData,
+    ) -> Result<Signature, BlockchainError> {
+        let signer = self.accounts.get(&address).ok_or(BlockchainError::NoSignerAvailable)?;
+
+        Ok(signer.sign_typed_data(payload).await?)
+    }
+
+    async fn sign_transaction(
         &self,
         request: TypedTransactionRequest,
         address: &Address,
@@ -79,6 +103,146 @@ impl Signer for DevSigner {
     }
 }
 
+/// Holds the keys of accounts that were created at runtime via `personal_newAccount`
+///
+/// This is a cheap handle, all clones share the same keys.
+#[derive(Clone, Default)]
+pub struct KeyStore {
+    accounts: Arc<RwLock<Vec<LocalWallet>>>,
+}
+
+impl KeyStore {
+    /// Generates a new key for the given chain and returns its address
+    pub fn new_account(&self, chain_id: u64) -> Address {
+        let wallet = LocalWallet::new(&mut thread_rng()).with_chain_id(chain_id);
+        let address = wallet.address();
+        self.accounts.write().push(wallet);
+        address
+    }
+
+    fn wallet(&self, address: &Address) -> Option<LocalWallet> {
+        self.accounts.read().iter().find(|wallet| wallet.address() == *address).cloned()
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for KeyStore {
+    fn accounts(&self) -> Vec<Address> {
+        self.accounts.read().iter().map(|wallet| wallet.address()).collect()
+    }
+
+    async fn sign(&self, address: Address, message: &[u8]) -> Result<Signature, BlockchainError> {
+        let signer = self.wallet(&address).ok_or(BlockchainError::NoSignerAvailable)?;
+
+        Ok(signer.sign_message(message).await?)
+    }
+
+    async fn sign_typed_data(
+        &self,
+        address: Address,
+        payload: &TypedData,
+    ) -> Result<Signature, BlockchainError> {
+        let signer = self.wallet(&address).ok_or(BlockchainError::NoSignerAvailable)?;
+
+        Ok(signer.sign_typed_data(payload).await?)
+    }
+
+    async fn sign_transaction(
+        &self,
+        request: TypedTransactionRequest,
+        address: &Address,
+    ) -> Result<TypedTransaction, BlockchainError> {
+        let signer = self.wallet(address).ok_or(BlockchainError::NoSignerAvailable)?;
+        let ethers_tx: EthersTypedTransactionRequest = request.clone().into();
+
+        let signature = signer.sign_transaction_sync(&ethers_tx);
+
+        build_typed_transaction(request, signature)
+    }
+}
+
+/// Forwards signing requests for a set of accounts to an external signer, like a hardware wallet
+/// bridge, via `eth_sign` and `eth_signTransaction`
+pub struct ExternalSigner {
+    addresses: Vec<Address>,
+    provider: Provider<Http>,
+}
+
+impl ExternalSigner {
+    pub fn new(provider: Provider<Http>, addresses: Vec<Address>) -> Self {
+        Self { addresses, provider }
+    }
+}
+
+/// The response of `eth_signTransaction`
+///
+/// Geth and clef return the raw transaction together with its json representation, other signers
+/// only return the raw transaction.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SignTransactionResponse {
+    Raw(Bytes),
+    WithTx { raw: Bytes },
+}
+
+#[async_trait::async_trait]
+impl Signer for ExternalSigner {
+    fn accounts(&self) -> Vec<Address> {
+        self.addresses.clone()
+    }
+
+    async fn sign(&self, address: Address, message: &[u8]) -> Result<Signature, BlockchainError> {
+        Ok(self.provider.sign(message.to_vec(), &address).await?)
+    }
+
+    async fn sign_typed_data(
+        &self,
+        address: Address,
+        payload: &TypedData,
+    ) -> Result<Signature, BlockchainError> {
+        let signature: Bytes =
+            self.provider.request("eth_signTypedData_v4", (address, payload)).await?;
+        Ok(Signature::try_from(signature.as_ref())?)
+    }
+
+    async fn sign_transaction(
+        &self,
+        request: TypedTransactionRequest,
+        address: &Address,
+    ) -> Result<TypedTransaction, BlockchainError> {
+        let mut ethers_tx: EthersTypedTransactionRequest = request.into();
+        ethers_tx.set_from(*address);
+
+        let raw = match self.provider.request("eth_signTransaction", [ethers_tx]).await? {
+            SignTransactionResponse::Raw(raw) => raw,
+            SignTransactionResponse::WithTx { raw } => raw,
+        };
+        decode_signed_transaction(raw.as_ref())
+    }
+}
+
+/// Decodes a raw, signed transaction as it is sent via `eth_sendRawTransaction`
+pub fn decode_signed_transaction(data: &[u8]) -> Result<TypedTransaction, BlockchainError> {
+    if data.is_empty() {
+        return Err(BlockchainError::EmptyRawTransactionData)
+    }
+    if data[0] > 0x7f {
+        // legacy transaction
+        match rlp::decode::<LegacyTransaction>(data) {
+            Ok(transaction) => Ok(TypedTransaction::Legacy(transaction)),
+            Err(_) => Err(BlockchainError::FailedToDecodeSignedTransaction),
+        }
+    } else {
+        // the [TypedTransaction] requires a valid rlp input,
+        // but EIP-1559 prepends a version byte, so we need to encode the data first to get a
+        // valid rlp and then rlp decode impl of `TypedTransaction` will remove and check the
+        // version byte
+        let extend = rlp::encode(&data);
+        rlp::decode::<TypedTransaction>(&extend[..])
+            .map_err(|_| BlockchainError::FailedToDecodeSignedTransaction)
+    }
+}
+
 /// converts the `request` into a [`TypedTransactionRequest`] with the given signature
 ///
 /// # Errors
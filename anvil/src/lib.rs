@@ -4,11 +4,11 @@ use crate::{
         fees::{FeeHistoryService, FeeManager},
         miner::{Miner, MiningMode},
         pool::Pool,
-        sign::{DevSigner, Signer as EthSigner},
+        sign::{DevSigner, ExternalSigner, KeyStore, Signer as EthSigner},
         EthApi,
     },
     filter::Filters,
-    logging::{LoggingManager, NodeLogLayer},
+    logging::{LogCategory, LoggingManager, NodeLogLayer},
     service::NodeService,
 };
 use eth::backend::fork::ClientFork;
@@ -21,7 +21,7 @@ use ethers::{
 };
 use foundry_evm::revm;
 use futures::FutureExt;
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use std::{
     future::Future,
     net::{IpAddr, Ipv4Addr, SocketAddr},
@@ -31,12 +31,15 @@ use std::{
     time::Duration,
 };
 use tokio::task::JoinError;
+use tracing::trace;
 
 /// contains the background service that drives the node
 mod service;
 
 mod config;
-pub use config::{AccountGenerator, Hardfork, NodeConfig, CHAIN_ID, VERSION_MESSAGE};
+pub use config::{
+    AccountGenerator, Hardfork, HardforkTransition, NodeConfig, CHAIN_ID, VERSION_MESSAGE,
+};
 
 /// ethereum related implementations
 pub mod eth;
@@ -53,6 +56,9 @@ pub mod server;
 #[cfg(feature = "cmd")]
 pub mod cmd;
 
+/// The interval at which a fork that follows the head of the fork endpoint checks for new blocks
+const FORK_HEAD_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Creates the node and runs the server
 ///
 /// Returns the [EthApi] that can be used to interact with the node and the [JoinHandle] of the
@@ -73,7 +79,11 @@ pub mod cmd;
 /// # }
 /// ```
 pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
-    let logger = if config.enable_tracing { init_tracing() } else { Default::default() };
+    let logger = if config.enable_tracing {
+        init_tracing(config.get_log_categories())
+    } else {
+        Default::default()
+    };
 
     let backend = Arc::new(config.setup().await);
 
@@ -87,10 +97,38 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
         server_config,
         no_mining,
         transaction_order,
+        priority_senders,
+        priority_selectors,
+        mempool_limit,
+        mempool_full_behavior,
+        price_bump,
+        external_signer_url,
+        external_signer_accounts,
+        fork_follow_head,
         ..
     } = config.clone();
 
-    let pool = Arc::new(Pool::default());
+    if fork_follow_head && fork.is_some() {
+        // keep the fork at the head of the fork endpoint
+        let backend = Arc::clone(&backend);
+        tokio::task::spawn(async move {
+            let mut interval = tokio::time::interval(FORK_HEAD_POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(err) = backend.follow_fork_head().await {
+                    trace!(target: "node", "failed to follow the fork head: {:?}", err);
+                }
+            }
+        });
+    }
+
+    let pool = Arc::new(
+        Pool::default()
+            .with_limit(mempool_limit)
+            .with_full_behavior(mempool_full_behavior)
+            .with_price_bump(price_bump),
+    );
+    let transaction_order = Arc::new(RwLock::new(transaction_order));
 
     let mode = if let Some(block_time) = block_time {
         MiningMode::interval(block_time)
@@ -103,7 +141,16 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
     };
     let miner = Miner::new(mode);
 
-    let dev_signer: Box<dyn EthSigner> = Box::new(DevSigner::new(signer_accounts));
+    let mut signers: Vec<Box<dyn EthSigner>> = Vec::new();
+    if let Some(url) = external_signer_url {
+        let provider =
+            Provider::<Http>::try_from(url.as_str()).expect("Failed to parse external signer url");
+        // the external signer takes precedence for its accounts
+        signers.push(Box::new(ExternalSigner::new(provider, external_signer_accounts)));
+    }
+    signers.push(Box::new(DevSigner::new(signer_accounts)));
+    let key_store = KeyStore::default();
+    signers.push(Box::new(key_store.clone()));
     let fees = backend.fees().clone();
     let fee_history_cache = Arc::new(Mutex::new(Default::default()));
     let fee_history_service = FeeHistoryService::new(
@@ -119,18 +166,27 @@ pub async fn spawn(mut config: NodeConfig) -> (EthApi, NodeHandle) {
     let api = EthApi::new(
         Arc::clone(&pool),
         Arc::clone(&backend),
-        Arc::new(vec![dev_signer]),
+        Arc::new(signers),
+        key_store,
         fee_history_cache,
         fee_history_service.fee_history_limit(),
         miner.clone(),
         logger,
         filters.clone(),
-        transaction_order,
+        Arc::clone(&transaction_order),
+        priority_senders.into_iter().collect(),
+        priority_selectors.into_iter().collect(),
     );
 
     // spawn the node service
-    let node_service =
-        tokio::task::spawn(NodeService::new(pool, backend, miner, fee_history_service, filters));
+    let node_service = tokio::task::spawn(NodeService::new(
+        pool,
+        backend,
+        miner,
+        fee_history_service,
+        filters,
+        transaction_order,
+    ));
 
     let host = config.host.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST));
     let mut addr = SocketAddr::new(host, port);
@@ -180,10 +236,27 @@ impl NodeHandle {
     pub(crate) fn print(&self, fork: Option<&ClientFork>) {
         self.config.print(fork);
         if !self.config.silent {
-            println!("Listening on {}", self.socket_address())
+            println!("{}", self.listening_message())
         }
     }
 
+    /// Returns the startup message that announces the endpoints of the node
+    ///
+    /// There is one `Listening on <url>` line per endpoint, the http endpoint comes first. This is
+    /// printed on launch, unless the node is silent.
+    pub fn listening_message(&self) -> String {
+        self.endpoints()
+            .iter()
+            .map(|url| format!("Listening on {}", url))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Returns the urls of all endpoints the node is serving, the http endpoint first
+    pub fn endpoints(&self) -> Vec<String> {
+        vec![self.http_endpoint(), self.ws_endpoint()]
+    }
+
     /// The address of the launched server
     ///
     /// **N.B.** this may not necessarily be the same `host + port` as configured in the
@@ -253,7 +326,7 @@ impl Future for NodeHandle {
 
 #[allow(unused)]
 #[doc(hidden)]
-pub fn init_tracing() -> LoggingManager {
+pub fn init_tracing(categories: Vec<LogCategory>) -> LoggingManager {
     use tracing_subscriber::prelude::*;
 
     let manager = LoggingManager::default();
@@ -265,7 +338,7 @@ pub fn init_tracing() -> LoggingManager {
             .init();
     } else {
         tracing_subscriber::Registry::default()
-            .with(NodeLogLayer::new(manager.clone()))
+            .with(NodeLogLayer::new(manager.clone()).with_categories(categories))
             .with(
                 tracing_subscriber::fmt::layer()
                     .without_time()
@@ -1,20 +1,80 @@
 //! User facing Logger
 
 use parking_lot::RwLock;
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 use tracing::{subscriber::Interest, Metadata};
 use tracing_subscriber::{layer::Context, Layer};
 
 /// The target that identifies the events intended to be logged to stdout
 pub(crate) const NODE_USER_LOG_TARGET: &str = "node::user";
 
+/// A subsystem whose internal events can be logged in addition to the user facing events
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogCategory {
+    /// Block production and transaction execution
+    Mining,
+    /// Transactions entering and leaving the pool
+    Txpool,
+    /// Requests to the forked endpoint and the RPC cache
+    Fork,
+    /// Incoming RPC requests
+    Rpc,
+}
+
+// === impl LogCategory ===
+
+impl LogCategory {
+    pub const VARIANTS: &'static [&'static str] = &["mining", "txpool", "fork", "rpc"];
+
+    /// Returns the `tracing` targets of the category, nested targets are included
+    pub fn targets(&self) -> &'static [&'static str] {
+        match self {
+            LogCategory::Mining => &["miner", "backend::mining", "backend::executor"],
+            LogCategory::Txpool => &["txpool"],
+            LogCategory::Fork => &[
+                "fork",
+                "backend::fork",
+                "backend::forkdb",
+                "backendhandler",
+                "sharedbackend",
+                "cache",
+            ],
+            LogCategory::Rpc => &["rpc"],
+        }
+    }
+
+    /// Returns true if events of the `target` belong to this category
+    pub fn contains(&self, target: &str) -> bool {
+        self.targets().iter().any(|t| {
+            target.strip_prefix(t).map_or(false, |rest| rest.is_empty() || rest.starts_with("::"))
+        })
+    }
+}
+
+impl FromStr for LogCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+        let category = match s.as_str() {
+            "mining" => LogCategory::Mining,
+            "txpool" => LogCategory::Txpool,
+            "fork" => LogCategory::Fork,
+            "rpc" => LogCategory::Rpc,
+            _ => return Err(format!("Unknown LogCategory: `{}`", s)),
+        };
+        Ok(category)
+    }
+}
+
 /// A logger that listens for node related events and displays them.
 ///
 /// This layer is intended to be used as filter for `NODE_USER_LOG_TARGET` events that will
-/// eventually be logged to stdout
+/// eventually be logged to stdout, as well as all events of the enabled [LogCategory]s
 #[derive(Debug, Clone, Default)]
 pub struct NodeLogLayer {
     state: LoggingManager,
+    categories: Vec<LogCategory>,
 }
 
 // === impl NodeLogLayer ===
@@ -22,7 +82,19 @@ pub struct NodeLogLayer {
 impl NodeLogLayer {
     /// Returns a new instance of this layer
     pub fn new(state: LoggingManager) -> Self {
-        Self { state }
+        Self { state, categories: Vec::new() }
+    }
+
+    /// Also logs all events of the given categories
+    #[must_use]
+    pub fn with_categories(mut self, categories: Vec<LogCategory>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// Returns true if events of the `target` are logged
+    fn is_logged(&self, target: &str) -> bool {
+        target == NODE_USER_LOG_TARGET || self.categories.iter().any(|c| c.contains(target))
     }
 }
 
@@ -32,7 +104,7 @@ where
     S: tracing::Subscriber,
 {
     fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
-        if self.state.is_enabled() && metadata.target() == NODE_USER_LOG_TARGET {
+        if self.state.is_enabled() && self.is_logged(metadata.target()) {
             Interest::always()
         } else {
             Interest::never()
@@ -40,7 +112,7 @@ where
     }
 
     fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
-        self.state.is_enabled() && metadata.target() == NODE_USER_LOG_TARGET
+        self.state.is_enabled() && self.is_logged(metadata.target())
     }
 }
 
@@ -71,3 +143,31 @@ impl Default for LoggingManager {
         Self { enabled: Arc::new(RwLock::new(true)) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_category_matches_nested_targets() {
+        let fork: LogCategory = "fork".parse().unwrap();
+        assert!(fork.contains("backend::fork"));
+        assert!(fork.contains("fork::cache"));
+        assert!(!fork.contains("forkchoice"));
+        assert!(!fork.contains("miner"));
+
+        let layer = NodeLogLayer::default().with_categories(vec![fork]);
+        assert!(layer.is_logged(NODE_USER_LOG_TARGET));
+        assert!(layer.is_logged("sharedbackend"));
+        assert!(!layer.is_logged("txpool"));
+    }
+
+    #[test]
+    fn mining_category_includes_block_and_transaction_events() {
+        let mining: LogCategory = "mining".parse().unwrap();
+        assert!(mining.contains("miner"));
+        assert!(mining.contains("backend::mining"));
+        assert!(mining.contains("backend::executor"));
+        assert!(!mining.contains("backend"));
+    }
+}
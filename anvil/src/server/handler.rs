@@ -13,6 +13,33 @@ use anvil_server::{RpcHandler, WsContext, WsRpcHandler};
 use ethers::types::FilteredParams;
 use tracing::trace;
 
+/// Methods that only read state, consecutive calls of these in a batch request are executed in
+/// parallel
+const READ_ONLY_METHODS: &[&str] = &[
+    "eth_blockNumber",
+    "eth_call",
+    "eth_chainId",
+    "eth_createAccessList",
+    "eth_estimateGas",
+    "eth_gasPrice",
+    "eth_getBalance",
+    "eth_getBlockByHash",
+    "eth_getBlockByNumber",
+    "eth_getCode",
+    "eth_getLogs",
+    "eth_getStorageAt",
+    "eth_getTransactionByHash",
+    "eth_getTransactionCount",
+    "eth_getTransactionReceipt",
+];
+
+/// Returns true if the `method` is in [READ_ONLY_METHODS]
+///
+/// Shared by the http and websocket handlers so that batches are executed the same way over both.
+fn is_read_only_method(method: &str) -> bool {
+    READ_ONLY_METHODS.contains(&method)
+}
+
 /// A `RpcHandler` that expects `EthRequest` rpc calls via http
 #[derive(Clone)]
 pub struct HttpEthRpcHandler {
@@ -36,6 +63,14 @@ impl RpcHandler for HttpEthRpcHandler {
     async fn on_request(&self, request: Self::Request) -> ResponseResult {
         self.api.execute(request).await
     }
+
+    fn orders_batch_calls(&self) -> bool {
+        true
+    }
+
+    fn is_read_only(&self, method: &str) -> bool {
+        is_read_only_method(method)
+    }
 }
 
 /// A `RpcHandler` that expects `EthRequest` rpc calls and `EthPubSub` via websocket
@@ -117,4 +152,12 @@ impl WsRpcHandler for WsEthRpcHandler {
             EthRpcCall::PubSub(pubsub) => self.on_pub_sub(pubsub, cx).await,
         }
     }
+
+    fn orders_batch_calls(&self) -> bool {
+        true
+    }
+
+    fn is_read_only(&self, method: &str) -> bool {
+        is_read_only_method(method)
+    }
 }
@@ -4,12 +4,16 @@ use crate::{
     eth::{
         fees::FeeHistoryService,
         miner::Miner,
-        pool::{transactions::PoolTransaction, Pool},
+        pool::{
+            transactions::{PoolTransaction, TransactionOrder},
+            Pool,
+        },
     },
     filter::Filters,
     mem::{storage::MinedBlockOutcome, Backend},
 };
 use futures::{FutureExt, Stream, StreamExt};
+use parking_lot::RwLock;
 use std::{
     collections::VecDeque,
     future::Future,
@@ -48,10 +52,11 @@ impl NodeService {
         miner: Miner,
         fee_history: FeeHistoryService,
         filters: Filters,
+        transaction_order: Arc<RwLock<TransactionOrder>>,
     ) -> Self {
         Self {
             pool,
-            block_producer: BlockProducer::new(backend),
+            block_producer: BlockProducer::new(backend, transaction_order),
             miner,
             fee_history,
             filter_eviction_interval: tokio::time::interval(filters.keep_alive()),
@@ -111,13 +116,20 @@ struct BlockProducer {
     block_mining: Option<BlockMiningFuture>,
     /// backlog of sets of transactions ready to be mined
     queued: VecDeque<Vec<Arc<PoolTransaction>>>,
+    /// How the transactions of a block are ordered
+    transaction_order: Arc<RwLock<TransactionOrder>>,
 }
 
 // === impl BlockProducer ===
 
 impl BlockProducer {
-    fn new(backend: Arc<Backend>) -> Self {
-        Self { idle_backend: Some(backend), block_mining: None, queued: Default::default() }
+    fn new(backend: Arc<Backend>, transaction_order: Arc<RwLock<TransactionOrder>>) -> Self {
+        Self {
+            idle_backend: Some(backend),
+            block_mining: None,
+            queued: Default::default(),
+            transaction_order,
+        }
     }
 }
 
@@ -130,7 +142,10 @@ impl Stream for BlockProducer {
         if !pin.queued.is_empty() {
             if let Some(backend) = pin.idle_backend.take() {
                 let transactions = pin.queued.pop_front().expect("not empty; qed");
+                let order = pin.transaction_order.read().clone();
                 pin.block_mining = Some(Box::pin(async move {
+                    let transactions =
+                        order.order_transactions(transactions, backend.base_fee()).await;
                     trace!(target: "miner", "creating new block");
                     let block = backend.mine_block(transactions).await;
                     trace!(target: "miner", "created new block: {}", block.block_number);
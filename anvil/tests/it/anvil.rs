@@ -1,7 +1,13 @@
 //! tests for anvil specific logic
 
-use anvil::{spawn, NodeConfig};
-use ethers::{prelude::Middleware, types::Address};
+use anvil::{
+    eth::backend::genesis::{GenesisAccount, SerializableState, StateCompression},
+    spawn, AccountGenerator, Hardfork, HardforkTransition, NodeConfig,
+};
+use ethers::{
+    prelude::{Middleware, Signer},
+    types::{Address, BlockNumber, Bytes, Eip1559TransactionRequest, TransactionRequest, H256},
+};
 
 #[tokio::test(flavor = "multi_thread")]
 async fn test_can_change_mining_mode() {
@@ -25,6 +31,23 @@ async fn test_can_change_mining_mode() {
     assert_eq!(num.as_u64(), 1);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_listening_endpoints() {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+
+    let addr = handle.socket_address();
+    assert_ne!(addr.port(), 0);
+    assert_eq!(handle.endpoints(), vec![format!("http://{}", addr), format!("ws://{}", addr)]);
+    assert_eq!(
+        handle.listening_message(),
+        format!("Listening on http://{}\nListening on ws://{}", addr, addr)
+    );
+
+    // the announced endpoint serves requests
+    let provider = handle.http_provider();
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 0);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_get_default_dev_keys() {
     let (_api, handle) = spawn(NodeConfig::test()).await;
@@ -35,6 +58,24 @@ async fn can_get_default_dev_keys() {
     assert_eq!(dev_accounts, accounts);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_generate_dev_accounts_from_seed() {
+    let seed = [7u8; 32];
+    let gen = AccountGenerator::new(3).seed(seed);
+    let (_api, handle) = spawn(NodeConfig::test().with_account_generator(gen.clone())).await;
+    let provider = handle.http_provider();
+
+    let dev_accounts = handle.dev_accounts().collect::<Vec<_>>();
+    assert_eq!(dev_accounts.len(), 3);
+    assert_eq!(dev_accounts, provider.get_accounts().await.unwrap());
+
+    // the same seed derives the same accounts, which differ from the default mnemonic's
+    let accounts = gen.gen().into_iter().map(|wallet| wallet.address()).collect::<Vec<_>>();
+    assert_eq!(dev_accounts, accounts);
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    assert!(!dev_accounts.contains(&handle.dev_accounts().next().unwrap()));
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_set_empty_code() {
     let (api, _handle) = spawn(NodeConfig::test()).await;
@@ -43,3 +84,176 @@ async fn can_set_empty_code() {
     let code = api.get_code(addr, None).await.unwrap();
     assert!(code.as_ref().is_empty());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_mine_with_sub_second_block_time() {
+    use futures::StreamExt;
+
+    let (api, _handle) =
+        spawn(NodeConfig::test().with_blocktime(Some(std::time::Duration::from_millis(100)))).await;
+
+    // with a block time of a second or more this would take at least 5 seconds
+    let blocks = api.new_block_notifications().take(5).collect::<Vec<_>>();
+    let blocks = tokio::time::timeout(std::time::Duration::from_secs(2), blocks).await.unwrap();
+    assert_eq!(blocks.len(), 5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_schedule_hardfork_transition() {
+    let transition = HardforkTransition { hardfork: Hardfork::London, block: 2 };
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_hardfork(Hardfork::Berlin)
+            .with_hardfork_transitions(vec![transition]),
+    )
+    .await;
+    let provider = handle.http_provider();
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(1u64).await.unwrap().unwrap();
+    assert!(block.base_fee_per_gas.is_none());
+    assert_eq!(api.anvil_node_info().unwrap().hard_fork, "BERLIN");
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(2u64).await.unwrap().unwrap();
+    assert!(block.base_fee_per_gas.is_some());
+    assert_eq!(api.anvil_node_info().unwrap().hard_fork, "LONDON");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_disable_eip1559() {
+    let (_api, handle) =
+        spawn(NodeConfig::test().with_hardfork(Hardfork::London).with_eip1559(Some(false))).await;
+    let provider = handle.http_provider();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+
+    let tx = Eip1559TransactionRequest::new().to(to).value(1337u64).from(from);
+    assert!(provider.send_transaction(tx, None).await.is_err());
+
+    let tx = TransactionRequest::new().to(to).value(1337u64).from(from);
+    provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.transactions.len(), 1);
+    assert!(block.base_fee_per_gas.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_enable_eip1559() {
+    let (api, handle) =
+        spawn(NodeConfig::test().with_hardfork(Hardfork::Berlin).with_eip1559(Some(true))).await;
+    let provider = handle.http_provider();
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert!(block.base_fee_per_gas.is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_dump_and_load_state() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let address = Address::random();
+    let code = Bytes::from(vec![0x60, 0x00]);
+    api.anvil_set_balance(address, 1337u64.into()).await.unwrap();
+    api.anvil_set_code(address, code.clone()).await.unwrap();
+    api.anvil_set_storage_at(address, 1u64.into(), 42u64.into()).await.unwrap();
+
+    let state = api.dump_state().unwrap();
+    let state_file = std::env::temp_dir().join(format!("anvil-state-{:?}.json", address));
+    std::fs::write(&state_file, serde_json::to_string(&state).unwrap()).unwrap();
+    let accounts = GenesisAccount::read_state_file(&state_file).unwrap();
+    std::fs::remove_file(state_file).unwrap();
+
+    let (api, _handle) = spawn(NodeConfig::test().with_custom_genesis_accounts(accounts)).await;
+    assert_eq!(api.balance(address, None).await.unwrap(), 1337u64.into());
+    assert_eq!(api.get_code(address, None).await.unwrap(), code);
+    assert_eq!(
+        api.storage_at(address, 1u64.into(), None).await.unwrap(),
+        H256::from_low_u64_be(42)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_load_compressed_state() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let address = Address::random();
+    api.anvil_set_balance(address, 1337u64.into()).await.unwrap();
+
+    let state = api.dump_state().unwrap();
+    let state_file = std::env::temp_dir().join(format!("anvil-state-{:?}.json.gz", address));
+    state.write(&state_file, StateCompression::Gzip).unwrap();
+    let content = std::fs::read(&state_file).unwrap();
+    assert_eq!(&content[..2], &[0x1f, 0x8b]);
+    assert!(content.len() < serde_json::to_vec(&state).unwrap().len());
+
+    let loaded = SerializableState::read(&state_file).unwrap();
+    std::fs::remove_file(state_file).unwrap();
+    assert_eq!(loaded.accounts, state.accounts);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_load_block_history() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+    let tx = TransactionRequest::new().from(from).to(Address::random()).value(1337u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    let block = provider.get_block(1u64).await.unwrap().unwrap();
+
+    let state = api.dump_state().unwrap();
+    let state_file = std::env::temp_dir().join(format!("anvil-state-{:?}.json", from));
+    std::fs::write(&state_file, serde_json::to_string(&state).unwrap()).unwrap();
+    let state = SerializableState::read(&state_file).unwrap();
+    std::fs::remove_file(state_file).unwrap();
+
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_custom_genesis_accounts(state.genesis_accounts())
+            .with_state_history(state.history),
+    )
+    .await;
+    let provider = handle.http_provider();
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 1);
+    assert_eq!(provider.get_block(1u64).await.unwrap().unwrap().hash, block.hash);
+    let loaded = provider.get_transaction_receipt(receipt.transaction_hash).await.unwrap().unwrap();
+    assert_eq!(loaded.block_hash, receipt.block_hash);
+    assert_eq!(loaded.gas_used, receipt.gas_used);
+
+    api.evm_mine(None).await.unwrap();
+    let next = provider.get_block(2u64).await.unwrap().unwrap();
+    assert_eq!(Some(next.parent_hash), block.hash);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_mine_with_trace_verbosity() {
+    let (_api, handle) = spawn(NodeConfig::test().with_trace_verbosity(4)).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+
+    // writes and reads storage slot 0 of the created contract
+    let initcode =
+        Bytes::from(vec![0x60, 0x00, 0x35, 0x60, 0x00, 0x55, 0x60, 0x00, 0x54, 0x50, 0x00]);
+    let tx = TransactionRequest::new().from(from).data(initcode);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()));
+}
+
+#[test]
+fn hardfork_schedules_of_known_chains() {
+    assert_eq!(Hardfork::from_chain_and_number(1, 12_000_000), Some(Hardfork::Muirglacier));
+    assert_eq!(Hardfork::from_chain_and_number(1, 15_000_000), Some(Hardfork::Latest));
+    assert_eq!(Hardfork::from_chain_and_number(5, 0), Some(Hardfork::Petersburg));
+    assert_eq!(Hardfork::from_chain_and_number(5, 4_500_000), Some(Hardfork::Berlin));
+    assert_eq!(Hardfork::from_chain_and_number(5, 5_062_605), Some(Hardfork::Latest));
+    assert_eq!(Hardfork::from_chain_and_number(11155111, 0), Some(Hardfork::Latest));
+    assert_eq!(Hardfork::from_chain_and_number(1337, 0), None);
+}
+
+#[test]
+fn rejects_unsupported_hardforks() {
+    assert_eq!("london".parse::<Hardfork>(), Ok(Hardfork::London));
+    let err = "cancun".parse::<Hardfork>().unwrap_err();
+    assert!(err.contains("not supported"));
+}
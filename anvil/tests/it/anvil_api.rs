@@ -1,10 +1,15 @@
 //! tests for custom anvil endpoints
 use crate::abi::*;
 use anvil::{spawn, Hardfork, NodeConfig};
+use anvil_core::{
+    eth::call::CallRequest,
+    types::{AccountUpdate, CheatChange, FundedAccount},
+};
 use ethers::{
-    prelude::{Middleware, SignerMiddleware},
-    types::{Address, BlockNumber, TransactionRequest, U256},
+    prelude::{ContractFactory, Middleware, Signer, SignerMiddleware},
+    types::{Address, BlockNumber, Bytes, TransactionRequest, H256, U256},
 };
+use ethers_solc::{project_util::TempProject, Artifact};
 use std::{
     sync::Arc,
     time::{Duration, SystemTime},
@@ -20,6 +25,136 @@ async fn can_set_gas_price() {
     assert_eq!(gas_price, provider.get_gas_price().await.unwrap());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_and_remove_min_gas_price() {
+    let min_gas_price = U256::from(5_000_000_000u64);
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_hardfork(Hardfork::Berlin)
+            .with_gas_price(Some(1_000_000_000u64))
+            .with_min_gas_price(Some(min_gas_price)),
+    )
+    .await;
+    let provider = handle.http_provider();
+    assert_eq!(provider.get_gas_price().await.unwrap(), min_gas_price);
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let tx = TransactionRequest::new()
+        .from(accounts[0].address())
+        .to(accounts[1].address())
+        .value(1337u64)
+        .gas_price(min_gas_price - 1u64);
+    let err = provider.send_transaction(tx.clone(), None).await.unwrap_err();
+    assert!(err.to_string().contains("transaction underpriced"));
+
+    api.anvil_remove_min_gas_price().unwrap();
+    assert_eq!(provider.get_gas_price().await.unwrap(), U256::from(1_000_000_000u64));
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_block_gas_limit() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let gas_limit = U256::from(1_000_000u64);
+    let _: () = provider.request("anvil_setGasLimit", [gas_limit]).await.unwrap();
+    assert_eq!(api.gas_limit(), gas_limit);
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let tx = TransactionRequest::new()
+        .from(accounts[0].address())
+        .to(accounts[1].address())
+        .gas(gas_limit + 1u64);
+    assert!(provider.send_transaction(tx, None).await.is_err());
+
+    api.mine_one().await;
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.gas_limit, gas_limit);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_disable_block_gas_limit() {
+    let (api, _handle) = spawn(NodeConfig::test().with_disable_block_gas_limit(true)).await;
+    assert_eq!(api.gas_limit(), U256::from(u64::MAX));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_record_cheats() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+    let address = Address::random();
+    api.anvil_set_balance(address, 1u64.into()).await.unwrap();
+    assert!(api.anvil_get_cheat_log().is_err());
+
+    let (api, _handle) = spawn(NodeConfig::test().with_record_cheats(true)).await;
+    api.anvil_set_balance(address, 1337u64.into()).await.unwrap();
+    api.anvil_set_storage_at(address, 1u64.into(), 2u64.into()).await.unwrap();
+    // unchanged values are not recorded
+    api.anvil_set_balance(address, 1337u64.into()).await.unwrap();
+
+    let log = api.anvil_get_cheat_log().unwrap();
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].method, "anvil_setBalance");
+    assert_eq!(log[0].address, address);
+    assert_eq!(
+        log[0].change,
+        CheatChange::Balance { old_value: U256::zero(), new_value: 1337u64.into() }
+    );
+    assert_eq!(log[1].method, "anvil_setStorageAt");
+    assert_eq!(
+        log[1].change,
+        CheatChange::Storage { slot: 1u64.into(), old_value: U256::zero(), new_value: 2u64.into() }
+    );
+
+    let json = serde_json::to_value(&log[1]).unwrap();
+    assert_eq!(json["field"], "storage");
+    assert_eq!(json["newValue"], "0x2");
+
+    // PUSH1 0x2a PUSH1 0x00 SSTORE STOP, the changes of all touched accounts are recorded
+    let initcode = Bytes::from(vec![0x60, 0x2a, 0x60, 0x00, 0x55, 0x00]);
+    let created = api.anvil_deploy_code(initcode, Some(address), None).await.unwrap();
+    let log = api.anvil_get_cheat_log().unwrap();
+    let deployed = log
+        .iter()
+        .filter(|entry| entry.method == "anvil_deployCode")
+        .map(|entry| (entry.address, entry.change.clone()))
+        .collect::<Vec<_>>();
+    let nonce = CheatChange::Nonce { old_value: U256::zero(), new_value: U256::one() };
+    let storage = CheatChange::Storage {
+        slot: U256::zero(),
+        old_value: U256::zero(),
+        new_value: 42u64.into(),
+    };
+    assert!(deployed.contains(&(address, nonce)));
+    assert!(deployed.contains(&(created, storage)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chain_id_and_net_version_are_consistent() {
+    let (api, handle) = spawn(NodeConfig::test().with_chain_id(1337u64)).await;
+    let provider = handle.http_provider();
+
+    let chain_id: U256 = provider.request("eth_chainId", ()).await.unwrap();
+    let net_version: String = provider.request("net_version", ()).await.unwrap();
+    assert_eq!(chain_id, 1337u64.into());
+    assert_eq!(net_version, "1337");
+
+    let _: () = provider.request("anvil_setChainId", [U256::from(31337u64)]).await.unwrap();
+    let chain_id: String = provider.request("eth_chainId", ()).await.unwrap();
+    let net_version: String = provider.request("net_version", ()).await.unwrap();
+    assert_eq!(chain_id, "0x7a69");
+    assert_eq!(net_version, "31337");
+
+    // transactions are signed for the new chain id
+    let wallet = handle.dev_wallets().next().unwrap().with_chain_id(31337u64);
+    let client = SignerMiddleware::new(provider, wallet);
+    let tx = TransactionRequest::new().to(Address::random()).value(1u64);
+    let receipt = client.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()));
+    assert_eq!(api.chain_id(), 31337);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_impersonate_account() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -89,6 +224,12 @@ async fn can_impersonate_contract() {
     let balance = provider.get_balance(to, None).await.unwrap();
     assert_eq!(balance, val.into());
 
+    // the contract keeps its code and storage while it's impersonated
+    let code = provider.get_code(impersonate, None).await.unwrap();
+    assert!(!code.as_ref().is_empty());
+    let greeting = greeter_contract.greet().call().await.unwrap();
+    assert_eq!("Hello World!", greeting);
+
     api.anvil_stop_impersonating_account(impersonate).await.unwrap();
     let res = provider.send_transaction(tx, None).await;
     assert!(res.is_err());
@@ -186,3 +327,569 @@ async fn test_timestamp_interval() {
     // check interval is disabled
     assert!(another_block.timestamp - new_block.timestamp < U256::from(interval));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_timestamp_interval_with_increase_time() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    api.evm_mine(None).await.unwrap();
+    let interval = 10;
+    let jump = 1_000u64;
+
+    api.evm_set_block_timestamp_interval(interval).unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+
+    // increasing the time while an interval is active applies the jump exactly once
+    api.evm_increase_time(jump.into()).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+    let new_block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(new_block.timestamp, block.timestamp + interval + jump);
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.timestamp, new_block.timestamp + interval);
+
+    // an explicit next timestamp takes precedence over the interval
+    let next_timestamp = block.timestamp + 5;
+    api.evm_set_next_block_timestamp(next_timestamp.as_u64()).unwrap();
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.timestamp, next_timestamp);
+
+    // removing the interval continues from the last timestamp without counting the jump again
+    assert!(api.evm_remove_block_timestamp_interval().unwrap());
+    assert!(!api.evm_remove_block_timestamp_interval().unwrap());
+    api.evm_mine(None).await.unwrap();
+    let new_block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert!(new_block.timestamp > block.timestamp);
+    assert!(new_block.timestamp - block.timestamp < U256::from(interval));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mine_with_timestamp() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let latest = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    let timestamp = latest.timestamp.as_u64() + 1337;
+
+    let block = api.anvil_mine_with_timestamp(timestamp).await.unwrap();
+    assert_eq!(block.number, Some(1u64.into()));
+    assert_eq!(block.timestamp.as_u64(), timestamp);
+    assert_eq!(provider.get_block(1u64).await.unwrap().unwrap().hash, block.hash);
+
+    // timestamps can't go back in time
+    assert!(api.anvil_mine_with_timestamp(timestamp).await.is_err());
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 1);
+
+    // a pending next block timestamp is kept for the block after
+    let next_timestamp = timestamp + 1337;
+    api.evm_set_next_block_timestamp(next_timestamp).unwrap();
+    let block = api.anvil_mine_with_timestamp(timestamp + 1).await.unwrap();
+    assert_eq!(block.timestamp.as_u64(), timestamp + 1);
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.timestamp.as_u64(), next_timestamp);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_mine_with_timestamp_interval() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    api.evm_mine(None).await.unwrap();
+    let interval = 10u64;
+    api.evm_set_block_timestamp_interval(interval).unwrap();
+
+    // returns the differences between the timestamps of the last `n` blocks and their parents
+    let last_intervals = |n: u64| {
+        let provider = provider.clone();
+        async move {
+            let latest = provider.get_block_number().await.unwrap().as_u64();
+            let mut timestamps = Vec::new();
+            for number in latest - n..=latest {
+                let block = provider.get_block(number).await.unwrap().unwrap();
+                timestamps.push(block.timestamp.as_u64());
+            }
+            timestamps.windows(2).map(|pair| pair[1] - pair[0]).collect::<Vec<_>>()
+        }
+    };
+
+    // the global interval applies to every block
+    api.anvil_mine(Some(3u64.into()), None).await.unwrap();
+    assert_eq!(last_intervals(3).await, vec![interval; 3]);
+
+    // the interval of the call applies between the mined blocks
+    api.anvil_mine(Some(3u64.into()), Some(100u64.into())).await.unwrap();
+    assert_eq!(last_intervals(3).await, vec![interval, 100, 100]);
+
+    // and only for that call
+    api.anvil_mine(Some(2u64.into()), None).await.unwrap();
+    assert_eq!(last_intervals(2).await, vec![interval; 2]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_storage_batch() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let address = Address::random();
+    let slots: Vec<(U256, U256)> =
+        (0..10u64).map(|slot| (slot.into(), U256::from(slot) + 1337u64)).collect();
+    api.anvil_set_storage_batch(address, slots.clone()).await.unwrap();
+
+    for (slot, value) in slots {
+        let slot = H256::from_low_u64_be(slot.as_u64());
+        let stored = provider.get_storage_at(address, slot, None).await.unwrap();
+        assert_eq!(U256::from_big_endian(stored.as_bytes()), value);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_add_balance() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let address = Address::random();
+    api.anvil_set_balance(address, 1000u64.into()).await.unwrap();
+
+    let balance = api.anvil_add_balance(address, 337u64.into()).await.unwrap();
+    assert_eq!(balance, 1337u64.into());
+    assert_eq!(provider.get_balance(address, None).await.unwrap(), 1337u64.into());
+
+    // saturates instead of overflowing
+    let balance = api.anvil_add_balance(address, U256::MAX).await.unwrap();
+    assert_eq!(balance, U256::MAX);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_funded_accounts() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let accounts: Vec<_> = (0..5u64)
+        .map(|i| FundedAccount { address: Address::random(), balance: U256::from(1000 + i) })
+        .collect();
+    api.anvil_set_funded_accounts(accounts.clone()).await.unwrap();
+
+    for account in accounts {
+        assert_eq!(provider.get_balance(account.address, None).await.unwrap(), account.balance);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_min_suggested_priority_fee() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let fee = U256::from(3_000_000_000u64);
+    api.anvil_set_min_suggested_priority_fee(fee).unwrap();
+
+    let suggested: U256 = provider.request("eth_maxPriorityFeePerGas", ()).await.unwrap();
+    assert_eq!(suggested, fee);
+    assert_eq!(api.anvil_node_info().unwrap().environment.suggested_priority_fee, fee);
+
+    // the fee persists across blocks
+    api.evm_mine(None).await.unwrap();
+    let suggested: U256 = provider.request("eth_maxPriorityFeePerGas", ()).await.unwrap();
+    assert_eq!(suggested, fee);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_node_info() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+
+    let info = api.anvil_node_info().unwrap();
+    assert_eq!(info.current_block_number, block.number.unwrap());
+    assert_eq!(info.current_block_hash, block.hash.unwrap());
+    assert_eq!(info.current_block_timestamp, block.timestamp.as_u64());
+    assert_eq!(info.environment.chain_id, api.chain_id());
+    assert!(info.fork.is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_simulate_bundle() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let relay = Address::random();
+    let to = Address::random();
+    let amount = U256::from(1_000_000u64);
+
+    let bundle = vec![
+        CallRequest {
+            from: Some(from),
+            to: Some(relay),
+            value: Some(amount),
+            ..Default::default()
+        },
+        // only succeeds with the funds of the previous transaction
+        CallRequest { from: Some(relay), to: Some(to), value: Some(amount), ..Default::default() },
+        CallRequest { from: Some(relay), to: Some(to), value: Some(amount), ..Default::default() },
+    ];
+    let results = api.anvil_simulate_bundle(bundle, None).await.unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert!(!results[0].reverted);
+    assert_eq!(results[0].gas_used, 21_000u64.into());
+    assert!(!results[1].reverted);
+    assert!(results[2].reverted);
+
+    // nothing was committed
+    assert!(provider.get_balance(relay, None).await.unwrap().is_zero());
+    assert!(provider.get_balance(to, None).await.unwrap().is_zero());
+    assert_eq!(provider.get_block_number().await.unwrap(), 0u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_block_number() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    api.anvil_set_block_number(100u64.into()).unwrap();
+    assert_eq!(provider.get_block_number().await.unwrap(), 100u64.into());
+
+    // the skipped blocks are absent
+    assert!(provider.get_block(50u64).await.unwrap().is_none());
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.number.unwrap(), 101u64.into());
+
+    // can't move backwards
+    assert!(api.anvil_set_block_number(50u64.into()).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_erc721_owner() {
+    let prj = TempProject::dapptools().unwrap();
+    prj.add_source(
+        "Token",
+        r#"
+pragma solidity 0.8.13;
+contract Token {
+    string public name = "Token";
+    string public symbol = "TKN";
+    mapping(uint256 => address) internal _ownerOf;
+    mapping(address => uint256) internal _balanceOf;
+
+    function ownerOf(uint256 id) public view returns (address owner) {
+        require((owner = _ownerOf[id]) != address(0), "NOT_MINTED");
+    }
+
+    function balanceOf(address owner) public view returns (uint256) {
+        return _balanceOf[owner];
+    }
+
+    function mint(address to, uint256 id) public {
+        _ownerOf[id] = to;
+        _balanceOf[to]++;
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let mut compiled = prj.compile().unwrap();
+    assert!(!compiled.has_compiler_errors());
+    let contract = compiled.remove("Token").unwrap();
+    let (abi, bytecode, _) = contract.into_contract_bytecode().into_parts();
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet.clone()));
+
+    let factory = ContractFactory::new(abi.unwrap(), bytecode.unwrap(), client);
+    let token = factory.deploy(()).unwrap().send().await.unwrap();
+    token.method::<_, ()>("mint", (wallet.address(), U256::one())).unwrap().send().await.unwrap();
+
+    let new_owner = Address::random();
+    api.anvil_set_erc721_owner(token.address(), U256::one(), new_owner).await.unwrap();
+
+    let owner: Address = token.method("ownerOf", U256::one()).unwrap().call().await.unwrap();
+    assert_eq!(owner, new_owner);
+    let balance: U256 = token.method("balanceOf", new_owner).unwrap().call().await.unwrap();
+    assert_eq!(balance, U256::one());
+    let balance: U256 = token.method("balanceOf", wallet.address()).unwrap().call().await.unwrap();
+    assert_eq!(balance, U256::zero());
+
+    // not an ERC721 token
+    assert!(api.anvil_set_erc721_owner(Address::random(), U256::one(), new_owner).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_rollback() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let from = handle.dev_accounts().next().unwrap();
+    let to = Address::random();
+    let tx = TransactionRequest::new().to(to).value(1337u64).from(from);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.block_number.unwrap().as_u64(), 1);
+    api.evm_increase_time(1000u64.into()).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 2);
+    let removed = provider.get_block(1u64).await.unwrap().unwrap();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+    api.anvil_rollback(2u64.into()).await.unwrap();
+
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 0);
+    assert!(provider.get_block(1u64).await.unwrap().is_none());
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), U256::zero());
+    // the transaction is back in the mempool
+    assert_eq!(api.txpool_status().await.unwrap().pending, 1u64.into());
+
+    api.evm_mine(None).await.unwrap();
+    let receipt =
+        provider.get_transaction_receipt(receipt.transaction_hash).await.unwrap().unwrap();
+    assert_eq!(receipt.block_number.unwrap().as_u64(), 1);
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 1337u64.into());
+    // the block is mined with the base fee the removed block had
+    let block = provider.get_block(1u64).await.unwrap().unwrap();
+    assert_eq!(block.base_fee_per_gas, removed.base_fee_per_gas);
+
+    // can't roll back past genesis
+    assert!(api.anvil_rollback(2u64.into()).await.is_err());
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_account() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let address = Address::random();
+    let code: ethers::types::Bytes = "0x6000".parse().unwrap();
+    let account = AccountUpdate {
+        balance: Some(1337u64.into()),
+        nonce: Some(5u64.into()),
+        code: Some(code.clone()),
+        storage: [(U256::zero(), U256::from(42u64))].into_iter().collect(),
+    };
+    api.anvil_set_account(address, account).await.unwrap();
+
+    assert_eq!(provider.get_balance(address, None).await.unwrap(), 1337u64.into());
+    assert_eq!(provider.get_transaction_count(address, None).await.unwrap(), 5u64.into());
+    assert_eq!(provider.get_code(address, None).await.unwrap(), code);
+    let slot = provider.get_storage_at(address, H256::zero(), None).await.unwrap();
+    assert_eq!(slot, H256::from_low_u64_be(42));
+
+    // omitted fields are left unchanged
+    let account = AccountUpdate { balance: Some(1u64.into()), ..Default::default() };
+    api.anvil_set_account(address, account).await.unwrap();
+    assert_eq!(provider.get_balance(address, None).await.unwrap(), 1u64.into());
+    assert_eq!(provider.get_transaction_count(address, None).await.unwrap(), 5u64.into());
+    assert_eq!(provider.get_code(address, None).await.unwrap(), code);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_debug_set_head() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    for _ in 0..3 {
+        api.evm_increase_time(1000u64.into()).await.unwrap();
+        api.evm_mine(None).await.unwrap();
+    }
+    let head = provider.get_block(1u64).await.unwrap().unwrap();
+    let removed = provider.get_block(2u64).await.unwrap().unwrap();
+
+    api.debug_set_head(1u64.into()).await.unwrap();
+    assert_eq!(provider.get_block_number().await.unwrap().as_u64(), 1);
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.hash, head.hash);
+
+    // the next block continues from the timestamp and base fee of the new head
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(2u64).await.unwrap().unwrap();
+    assert!(block.timestamp >= head.timestamp);
+    assert!(block.timestamp < removed.timestamp);
+    assert_eq!(block.base_fee_per_gas, removed.base_fee_per_gas);
+
+    // can't move the head forward
+    assert!(api.debug_set_head(3u64.into()).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_set_next_block_coinbase() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+
+    let coinbase = Address::random();
+    let next_coinbase = Address::random();
+    api.anvil_set_coinbase(coinbase).await.unwrap();
+    api.anvil_set_next_block_coinbase(next_coinbase).await.unwrap();
+
+    let tx = TransactionRequest::new().from(from).to(Address::random()).value(1337u64);
+    provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.author, Some(next_coinbase));
+    assert!(!provider.get_balance(next_coinbase, None).await.unwrap().is_zero());
+
+    // only applies to a single block
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.author, Some(coinbase));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_wait_for_transaction() {
+    let (api, handle) = spawn(NodeConfig::test().with_no_mining(true)).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let tx =
+        TransactionRequest::new().from(accounts[0].address()).to(Address::random()).value(1337u64);
+    let hash = *provider.send_transaction(tx, None).await.unwrap();
+
+    // times out while the transaction is pending
+    assert!(api.anvil_wait_for_transaction(hash, 100).await.unwrap().is_none());
+
+    let waiter = {
+        let api = api.clone();
+        tokio::spawn(async move { api.anvil_wait_for_transaction(hash, 10_000).await })
+    };
+    // other requests are served while waiting
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    assert_eq!(provider.get_block_number().await.unwrap(), 0u64.into());
+    api.mine_one().await;
+
+    let receipt = waiter.await.unwrap().unwrap().unwrap();
+    assert_eq!(receipt.transaction_hash, hash);
+    assert_eq!(receipt.block_number, Some(1u64.into()));
+
+    // already mined transactions return immediately
+    assert!(api.anvil_wait_for_transaction(hash, 0).await.unwrap().is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_deploy_code() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let from = wallet.address();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let tx = Greeter::deploy(Arc::clone(&client), "Hello World!".to_string()).unwrap().deployer.tx;
+    let initcode = tx.data().cloned().unwrap();
+
+    let address = api.anvil_deploy_code(initcode, Some(from), None).await.unwrap();
+    let greeter_contract = Greeter::new(address, Arc::clone(&client));
+    assert_eq!(greeter_contract.greet().call().await.unwrap(), "Hello World!");
+
+    // no block was mined, but the deployer's nonce was used
+    assert_eq!(client.get_block_number().await.unwrap(), 0u64.into());
+    assert_eq!(client.get_transaction_count(from, None).await.unwrap(), 1u64.into());
+
+    // PUSH2 0x6001 PUSH1 0x00 RETURN, which exceeds the code size limit
+    let too_large = Bytes::from(vec![0x61, 0x60, 0x01, 0x60, 0x00, 0xf3]);
+    assert!(api.anvil_deploy_code(too_large, None, None).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_state_root() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+
+    let tx = TransactionRequest::new().to(accounts[1].address()).value(1337u64);
+    let tx = provider.send_transaction(tx.from(accounts[0].address()), None).await.unwrap();
+    tx.await.unwrap().unwrap();
+
+    // the current state root is the root of the last mined block
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    let root = api.anvil_get_state_root().unwrap();
+    assert_eq!(root, block.state_root);
+
+    api.anvil_set_balance(Address::random(), 1u64.into()).await.unwrap();
+    assert_ne!(api.anvil_get_state_root().unwrap(), root);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_force_next_transaction_to_fail() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let to = Address::random();
+
+    api.anvil_set_next_transaction_fails("forced failure".to_string()).unwrap();
+
+    let tx = TransactionRequest::new().from(from).to(to).value(1337u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(0u64.into()));
+    assert_eq!(receipt.gas_used, Some(21_000u64.into()));
+    // the value was not transferred but the nonce was used
+    assert!(provider.get_balance(to, None).await.unwrap().is_zero());
+    assert_eq!(provider.get_transaction_count(from, None).await.unwrap(), 1u64.into());
+
+    // only the next transaction fails
+    let tx = TransactionRequest::new().from(from).to(to).value(1337u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()));
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 1337u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn anvil_methods_behave_the_same_over_http_and_ws() {
+    let (_api, http_handle) = spawn(NodeConfig::test()).await;
+    let (_api, ws_handle) = spawn(NodeConfig::test()).await;
+    let http = http_handle.http_provider();
+    let ws = ws_handle.ws_provider().await;
+
+    let address = format!("{:?}", Address::random());
+    let slot = format!("{:?}", H256::from_low_u64_be(42));
+    let calls = [
+        ("anvil_setBalance", serde_json::json!([address, "0x1337"])),
+        ("anvil_setNonce", serde_json::json!([address, "0x5"])),
+        ("anvil_setCode", serde_json::json!([address, "0x6000"])),
+        ("anvil_setStorageAt", serde_json::json!([address, "0x1", slot])),
+        ("eth_getBalance", serde_json::json!([address, "latest"])),
+        ("eth_getTransactionCount", serde_json::json!([address, "latest"])),
+        ("eth_getCode", serde_json::json!([address, "latest"])),
+        ("eth_getStorageAt", serde_json::json!([address, "0x1", "latest"])),
+        ("evm_snapshot", serde_json::json!([])),
+        ("evm_setAutomine", serde_json::json!([false])),
+        ("anvil_getAutomine", serde_json::json!([])),
+        ("evm_mine", serde_json::json!([])),
+        ("eth_blockNumber", serde_json::json!([])),
+        ("evm_revert", serde_json::json!(["0x0"])),
+        ("eth_blockNumber", serde_json::json!([])),
+        ("anvil_impersonateAccount", serde_json::json!([address])),
+        ("anvil_stopImpersonatingAccount", serde_json::json!([address])),
+        ("anvil_dumpStateDiff", serde_json::json!([])),
+        ("anvil_setBalance", serde_json::json!(["0xinvalid", "0x1"])),
+        ("anvil_unknownMethod", serde_json::json!([])),
+    ];
+
+    for (method, params) in calls {
+        let over_http = http.request::<_, serde_json::Value>(method, params.clone()).await;
+        let over_ws = ws.request::<_, serde_json::Value>(method, params).await;
+        match (over_http, over_ws) {
+            (Ok(over_http), Ok(over_ws)) => assert_eq!(over_http, over_ws, "{}", method),
+            (Err(over_http), Err(over_ws)) => {
+                // both transports display the json-rpc error with its code, message and data
+                let (over_http, over_ws) = (over_http.to_string(), over_ws.to_string());
+                assert!(over_http.contains("code:"), "{}: {}", method, over_http);
+                assert_eq!(over_http, over_ws, "{}", method);
+                if method == "anvil_unknownMethod" {
+                    assert!(over_http.contains("Method not found"));
+                }
+            }
+            (over_http, over_ws) => {
+                panic!("{} differs, http: {:?}, ws: {:?}", method, over_http, over_ws)
+            }
+        }
+    }
+}
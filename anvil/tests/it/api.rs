@@ -1,11 +1,23 @@
 //! general eth api tests
 
-use anvil::{eth::api::CLIENT_VERSION, spawn, NodeConfig, CHAIN_ID};
+use crate::abi::*;
+use anvil::{
+    eth::{api::CLIENT_VERSION, backend::genesis::GenesisAccount, error::BlockchainError},
+    spawn, NodeConfig, CHAIN_ID,
+};
+use anvil_core::{
+    eth::{call::CallRequest, transaction::EthTransactionRequest},
+    types::{AccountOverride, StateOverride},
+};
 use ethers::{
-    prelude::Middleware,
-    signers::Signer,
-    types::{Block, BlockNumber, Transaction, TransactionRequest, U256},
+    prelude::{Middleware, SignerMiddleware},
+    signers::{LocalWallet, Signer},
+    types::{
+        transaction::eip712::TypedData, Address, Block, BlockNumber, Transaction,
+        TransactionRequest, H256, U256, U64,
+    },
 };
+use std::sync::Arc;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn can_get_block_number() {
@@ -57,6 +69,18 @@ async fn can_get_client_version() {
     assert_eq!(CLIENT_VERSION, version);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_syncing_and_protocol_version() {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let syncing: bool = provider.request("eth_syncing", ()).await.unwrap();
+    assert!(!syncing);
+
+    let version: U64 = provider.request("eth_protocolVersion", ()).await.unwrap();
+    assert_eq!(version, 65u64.into());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_get_chain_id() {
     let (_api, handle) = spawn(NodeConfig::test()).await;
@@ -96,6 +120,34 @@ async fn can_get_block_by_number() {
     assert_eq!(block.transactions.len(), 1);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_uncles() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+
+    let count: U256 =
+        provider.request("eth_getUncleCountByBlockHash", [block.hash.unwrap()]).await.unwrap();
+    assert_eq!(count, U256::zero());
+    let count: U256 = provider.request("eth_getUncleCountByBlockNumber", ["latest"]).await.unwrap();
+    assert_eq!(count, U256::zero());
+
+    // unknown blocks have no uncle count
+    let count: Option<U256> =
+        provider.request("eth_getUncleCountByBlockHash", [H256::random()]).await.unwrap();
+    assert!(count.is_none());
+    let count: Option<U256> =
+        provider.request("eth_getUncleCountByBlockNumber", ["0x1337"]).await.unwrap();
+    assert!(count.is_none());
+
+    let uncle = provider.get_uncle(block.hash.unwrap(), 0u64.into()).await.unwrap();
+    assert!(uncle.is_none());
+    let uncle = provider.get_uncle(BlockNumber::Latest, 0u64.into()).await.unwrap();
+    assert!(uncle.is_none());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_get_pending_block() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -129,3 +181,337 @@ async fn can_get_pending_block() {
     assert_eq!(block.number.unwrap().as_u64(), 1u64);
     assert_eq!(block.transactions.len(), 1);
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_block_receipts() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    // disable automine so that both transactions are included in the same block
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+
+    let tx = TransactionRequest::new().to(to).from(from).value(1337u64).nonce(0u64);
+    let first = provider.send_transaction(tx, None).await.unwrap();
+    let tx = TransactionRequest::new().to(to).from(from).value(1337u64).nonce(1u64);
+    let second = provider.send_transaction(tx, None).await.unwrap();
+
+    api.mine_one().await;
+
+    let first = first.await.unwrap().unwrap();
+    let second = second.await.unwrap().unwrap();
+
+    let receipts = api.block_receipts(BlockNumber::Latest.into()).await.unwrap().unwrap();
+    assert_eq!(receipts, vec![first, second]);
+
+    let hash = receipts[0].block_hash.unwrap();
+    let by_hash = api.block_receipts(hash.into()).await.unwrap().unwrap();
+    assert_eq!(receipts, by_hash);
+
+    // unknown block
+    let unknown = BlockNumber::Number(100u64.into());
+    assert!(api.block_receipts(unknown.into()).await.unwrap().is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_load_accounts_file() {
+    let key = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+    let contract = Address::random();
+    let accounts_file = std::env::temp_dir().join(format!("anvil-accounts-{:?}.json", contract));
+    let content = format!(
+        r#"[
+            {{ "privateKey": "{}", "balance": "0x64" }},
+            {{ "address": "{:?}", "nonce": 5, "code": "0x6000" }}
+        ]"#,
+        key, contract
+    );
+    std::fs::write(&accounts_file, content).unwrap();
+    let accounts = GenesisAccount::read_file(&accounts_file).unwrap();
+    std::fs::remove_file(accounts_file).unwrap();
+
+    let (api, handle) = spawn(NodeConfig::test().with_custom_genesis_accounts(accounts)).await;
+    let provider = handle.http_provider();
+
+    let wallet: LocalWallet = key.parse().unwrap();
+    assert!(api.accounts().unwrap().contains(&wallet.address()));
+    assert_eq!(provider.get_balance(wallet.address(), None).await.unwrap(), 100u64.into());
+
+    assert_eq!(provider.get_transaction_count(contract, None).await.unwrap(), 5u64.into());
+    assert_eq!(provider.get_code(contract, None).await.unwrap(), vec![0x60, 0x00].into());
+}
+
+#[test]
+fn rejects_malformed_accounts_file() {
+    use anvil::cmd::NodeArgs;
+    use clap::Parser;
+
+    let accounts_file =
+        std::env::temp_dir().join(format!("anvil-accounts-{:?}.json", Address::random()));
+    std::fs::write(&accounts_file, "[{ \"balance\": ").unwrap();
+    let args = NodeArgs::parse_from(["anvil", "--accounts-file", accounts_file.to_str().unwrap()]);
+    let err = args.into_node_config().unwrap_err();
+    std::fs::remove_file(&accounts_file).unwrap();
+
+    assert!(err.to_string().contains("Failed to parse accounts file"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_sign_with_external_signer() {
+    let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+    let account = GenesisAccount {
+        private_key: Some(H256::from_slice(&wallet.signer().to_bytes())),
+        ..Default::default()
+    };
+    let (_signer_api, signer_handle) =
+        spawn(NodeConfig::test().with_custom_genesis_accounts(vec![account])).await;
+
+    let (api, _handle) = spawn(
+        NodeConfig::test()
+            .with_external_signer(Some(signer_handle.http_endpoint()), vec![wallet.address()]),
+    )
+    .await;
+    assert!(api.accounts().unwrap().contains(&wallet.address()));
+
+    let signature = api.sign(wallet.address(), "hello").await.unwrap();
+    let expected = wallet.sign_message("hello").await.unwrap();
+    assert_eq!(signature, format!("0x{}", expected));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_send_transaction_with_external_signer() {
+    use axum::{extract::Extension, routing::post, Json, Router};
+    use ethers::types::transaction::eip2718::TypedTransaction;
+    use std::{
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    /// Signs every `eth_signTransaction` request with the wallet
+    async fn signer(
+        Extension((wallet, requests)): Extension<(LocalWallet, Arc<AtomicUsize>)>,
+        Json(request): Json<serde_json::Value>,
+    ) -> Json<serde_json::Value> {
+        assert_eq!(request["method"], "eth_signTransaction");
+        requests.fetch_add(1, Ordering::SeqCst);
+        let tx: TypedTransaction = serde_json::from_value(request["params"][0].clone()).unwrap();
+        assert_eq!(tx.from(), Some(&wallet.address()));
+        let wallet = wallet.with_chain_id(tx.chain_id().unwrap().as_u64());
+        let signature = wallet.sign_transaction(&tx).await.unwrap();
+        Json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": { "raw": tx.rlp_signed(&signature), "tx": tx }
+        }))
+    }
+
+    let wallet = LocalWallet::new(&mut ethers::core::rand::thread_rng());
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let signer_url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/", post(signer))
+        .layer(Extension((wallet.clone(), Arc::clone(&requests))));
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+    let (api, handle) =
+        spawn(NodeConfig::test().with_external_signer(Some(signer_url), vec![wallet.address()]))
+            .await;
+    let provider = handle.http_provider();
+    api.anvil_set_balance(wallet.address(), U256::exp10(18)).await.unwrap();
+
+    let to = Address::random();
+    let tx = TransactionRequest::new().from(wallet.address()).to(to).value(1337u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert_eq!(receipt.from, wallet.address());
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 1337u64.into());
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_sign_typed_data() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let wallet = handle.dev_wallets().next().unwrap();
+
+    let data: TypedData = serde_json::from_str(
+        r#"{
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "chainId", "type": "uint256"}
+                ],
+                "Mail": [{"name": "contents", "type": "string"}]
+            },
+            "primaryType": "Mail",
+            "domain": {"name": "Ether Mail", "chainId": 1},
+            "message": {"contents": "Hello, Bob!"}
+        }"#,
+    )
+    .unwrap();
+
+    let signature = api.sign_typed_data_v4(wallet.address(), &data).await.unwrap();
+    let expected = wallet.sign_typed_data(&data).await.unwrap();
+    assert_eq!(signature, format!("0x{}", expected));
+
+    // the primary type must be defined
+    let mut invalid = data;
+    invalid.primary_type = "Unknown".to_string();
+    assert!(api.sign_typed_data_v4(wallet.address(), &invalid).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_use_personal_accounts() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let account = api.personal_new_account().unwrap();
+    assert!(api.accounts().unwrap().contains(&account));
+    assert!(api.personal_unlock_account(account).unwrap());
+    assert!(api.personal_unlock_account(Address::random()).is_err());
+
+    let dev = handle.dev_accounts().next().unwrap();
+    let tx = TransactionRequest::new().to(account).value(1_000_000_000_000_000u64).from(dev);
+    provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    let to = Address::random();
+    let tx = EthTransactionRequest {
+        from: Some(account),
+        to: Some(to),
+        value: Some(1337u64.into()),
+        ..Default::default()
+    };
+    let hash = api.personal_send_transaction(tx).await.unwrap();
+    let tx = provider.get_transaction(hash).await.unwrap().unwrap();
+    assert_eq!(tx.from, account);
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 1337u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_call_with_state_override() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+
+    let address = Address::random();
+    // returns the value of storage slot 0
+    let sload: ethers::types::Bytes = "0x60005460005260206000f3".parse().unwrap();
+    let request = CallRequest { to: Some(address), ..Default::default() };
+
+    let mut overrides = StateOverride::default();
+    overrides.insert(
+        address,
+        AccountOverride {
+            code: Some(sload.clone()),
+            state_diff: Some([(H256::zero(), H256::from_low_u64_be(42))].into_iter().collect()),
+            ..Default::default()
+        },
+    );
+    let out = api.call(request.clone(), None, Some(overrides)).await.unwrap();
+    assert_eq!(U256::from_big_endian(&out), 42u64.into());
+
+    // returns its own balance
+    let selfbalance: ethers::types::Bytes = "0x4760005260206000f3".parse().unwrap();
+    let mut overrides = StateOverride::default();
+    overrides.insert(
+        address,
+        AccountOverride {
+            code: Some(selfbalance),
+            balance: Some(1337u64.into()),
+            ..Default::default()
+        },
+    );
+    let out = api.call(request.clone(), None, Some(overrides)).await.unwrap();
+    assert_eq!(U256::from_big_endian(&out), 1337u64.into());
+
+    // nothing is persisted
+    assert!(api.get_code(address, None).await.unwrap().as_ref().is_empty());
+
+    let mut overrides = StateOverride::default();
+    overrides.insert(
+        address,
+        AccountOverride {
+            code: Some(sload),
+            state: Some(Default::default()),
+            state_diff: Some(Default::default()),
+            ..Default::default()
+        },
+    );
+    assert!(api.call(request, None, Some(overrides)).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_handle_batch_with_reads_and_writes() {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let from = handle.dev_wallets().next().unwrap().address();
+    let to = Address::random();
+
+    let send = |id: u64| serde_json::json!({"jsonrpc": "2.0", "id": id, "method": "eth_sendTransaction", "params": [{"from": from, "to": to, "value": "0x539"}]});
+    let balance = |id: u64| serde_json::json!({"jsonrpc": "2.0", "id": id, "method": "eth_getBalance", "params": [to, "latest"]});
+    let batch = vec![send(0), balance(1), balance(2), send(3), balance(4)];
+
+    let responses: Vec<serde_json::Value> = reqwest::Client::new()
+        .post(handle.http_endpoint())
+        .json(&batch)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // the responses are in the order of the batch
+    let ids: Vec<_> = responses.iter().map(|res| res["id"].as_u64().unwrap()).collect();
+    assert_eq!(ids, vec![0, 1, 2, 3, 4]);
+
+    // the writes were executed one after another, in the order of the batch
+    let provider = handle.http_provider();
+    for (res, nonce) in [(&responses[0], 0u64), (&responses[3], 1u64)] {
+        let hash: H256 = serde_json::from_value(res["result"].clone()).unwrap();
+        let tx = provider.get_transaction(hash).await.unwrap().unwrap();
+        assert_eq!(tx.nonce, nonce.into());
+        provider.pending_transaction(hash).await.unwrap().unwrap();
+    }
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 0xa72u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn debug_trace_block_errors_on_unknown_block() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+
+    let res = api.debug_trace_block_by_hash(H256::random(), Default::default()).await;
+    assert!(matches!(res, Err(BlockchainError::BlockNotFound)));
+
+    let res = api
+        .debug_trace_block_by_number(BlockNumber::Number(100u64.into()), Default::default())
+        .await;
+    assert!(matches!(res, Err(BlockchainError::BlockNotFound)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_debug_trace_block() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(handle.http_provider(), wallet));
+
+    Greeter::deploy(client, "Hello World!".to_string()).unwrap().send().await.unwrap();
+    let block = api.block_by_number(BlockNumber::Latest).await.unwrap().unwrap();
+
+    let traces =
+        api.debug_trace_block_by_number(BlockNumber::Latest, Default::default()).await.unwrap();
+    assert_eq!(traces.len(), 1);
+    let trace = traces[0].result.as_ref().unwrap();
+    assert!(!trace.failed);
+    assert_eq!(trace.struct_logs[0].depth, 1);
+    assert!(trace.struct_logs[0].stack.is_some());
+    assert!(trace.struct_logs[0].memory.is_none());
+    // the constructor stores the greeting
+    let sstore = trace.struct_logs.iter().find(|log| log.op == "SSTORE").unwrap();
+    assert_eq!(sstore.storage.as_ref().unwrap().len(), 1);
+
+    let by_hash =
+        api.debug_trace_block_by_hash(block.hash.unwrap(), Default::default()).await.unwrap();
+    assert_eq!(by_hash, traces);
+}
@@ -1,14 +1,18 @@
 //! various fork related test
 
 use crate::{abi::*, utils};
-use anvil::{eth::EthApi, spawn, NodeConfig, NodeHandle};
-use anvil_core::types::Forking;
+use anvil::{eth::EthApi, spawn, Hardfork, NodeConfig, NodeHandle};
+use anvil_core::{eth::call::CallRequest, types::Forking};
 use ethers::{
     core::rand,
     prelude::{Bytes, LocalWallet, Middleware, SignerMiddleware},
     signers::Signer,
     types::{
-        transaction::eip2718::TypedTransaction, Address, BlockNumber, Chain, TransactionRequest,
+        transaction::{
+            eip2718::TypedTransaction,
+            eip2930::{AccessList, AccessListItem},
+        },
+        Address, BlockId, BlockNumber, Chain, Eip1559TransactionRequest, TransactionRequest, H256,
         U256,
     },
 };
@@ -62,6 +66,27 @@ async fn test_spawn_fork() {
     assert_eq!(head, BLOCK_NUMBER.into())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_block_hash() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    for _ in 0..5 {
+        origin_api.evm_mine(None).await.unwrap();
+    }
+    let block = origin_handle.http_provider().get_block(2u64).await.unwrap().unwrap();
+    let block_hash = block.hash.unwrap();
+
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_fork_block_hash(Some(block_hash)),
+    )
+    .await;
+    assert_eq!(api.block_number().unwrap(), 2u64.into());
+
+    let block = handle.http_provider().get_block(2u64).await.unwrap().unwrap();
+    assert_eq!(block.hash, Some(block_hash));
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_fork_eth_get_balance() {
     let (api, handle) = spawn(fork_config()).await;
@@ -199,6 +224,28 @@ async fn test_fork_snapshotting() {
     assert_eq!(block_number, provider.get_block_number().await.unwrap());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_pre_london_block() {
+    // a Berlin block
+    let (api, handle) = spawn(fork_config().with_fork_block_number(Some(12_500_000u64))).await;
+    let provider = handle.http_provider();
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert!(block.base_fee_per_gas.is_none());
+
+    // an explicit hardfork takes precedence
+    let (api, handle) = spawn(
+        fork_config().with_fork_block_number(Some(12_500_000u64)).with_hardfork(Hardfork::London),
+    )
+    .await;
+    let provider = handle.http_provider();
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert!(block.base_fee_per_gas.is_some());
+}
+
 /// tests that the remote state and local state are kept separate.
 /// changes don't make into the read only Database that holds the remote state, which is flushed to
 /// a cache file.
@@ -223,6 +270,74 @@ async fn test_separate_states() {
     assert_eq!(acc.balance, remote_balance)
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_caches_blocks() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    for _ in 0..5 {
+        origin_api.evm_mine(None).await.unwrap();
+    }
+
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_fork_block_number(Some(4u64)),
+    )
+    .await;
+    let provider = handle.http_provider();
+
+    let block = provider.get_block(2u64).await.unwrap().unwrap();
+    let block_hash = block.hash.unwrap();
+
+    let fork = api.get_fork().unwrap();
+    {
+        let fork_db = fork.database.read();
+        let cached = fork_db.inner().blocks().read().get(&block_hash).cloned().unwrap();
+        assert_eq!(cached.number, block.number);
+        assert_eq!(fork_db.inner().block_hashes().read().get(&2), Some(&block_hash));
+    }
+
+    // served from the cache after the in-memory fork storage was cleared
+    fork.clear_cached_storage();
+    let cached = provider.get_block(block_hash).await.unwrap().unwrap();
+    assert_eq!(cached, block);
+
+    // nothing is cached if block caching is disabled
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_fork_block_number(Some(4u64))
+            .with_fork_block_caching(false),
+    )
+    .await;
+    handle.http_provider().get_block(2u64).await.unwrap().unwrap();
+    let fork = api.get_fork().unwrap();
+    assert!(fork.database.read().inner().blocks().read().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_stats() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (api, handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    let provider = handle.http_provider();
+
+    api.anvil_get_fork_stats(true).unwrap();
+
+    let address = Address::random();
+    provider.get_balance(address, None).await.unwrap();
+    provider.get_balance(address, None).await.unwrap();
+
+    let stats = api.anvil_get_fork_stats(false).unwrap();
+    assert_eq!(stats.accounts_fetched, 1);
+    assert_eq!(stats.account_cache_misses, 1);
+    assert!(stats.account_cache_hits >= 1);
+    assert!(stats.provider_requests > 0);
+
+    // the previous call reset nothing, this one does after returning the counters
+    assert_eq!(api.anvil_get_fork_stats(true).unwrap(), stats);
+    assert_eq!(api.anvil_get_fork_stats(false).unwrap(), Default::default());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_deploy_greeter_on_fork() {
     let (_api, handle) = spawn(fork_config().with_fork_block_number(Some(14723772u64))).await;
@@ -460,3 +575,844 @@ async fn test_fork_can_send_opensea_tx() {
     let tx = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
     assert_eq!(tx.status, Some(1u64.into()));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_batched_storage_requests() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let address = Address::random();
+    let num_slots = 10u64;
+    for slot in 0..num_slots {
+        origin_api.anvil_set_storage_at(address, slot.into(), (slot + 1).into()).await.unwrap();
+    }
+
+    let (api, _handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_fork_batch_size(Some(3)),
+    )
+    .await;
+
+    let requests = (0..num_slots).map(|slot| api.storage_at(address, slot.into(), None));
+    let values = futures::future::join_all(requests).await;
+    for (slot, value) in values.into_iter().enumerate() {
+        let value = U256::from_big_endian(value.unwrap().as_bytes());
+        assert_eq!(value, U256::from(slot + 1));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_call_cache_invalidated_on_state_change() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (api, _handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_call_caching(true),
+    )
+    .await;
+
+    // returns the value of storage slot 0
+    let address = Address::random();
+    let code: Bytes = "0x60005460005260206000f3".parse().unwrap();
+    api.anvil_set_code(address, code).await.unwrap();
+    api.anvil_set_storage_at(address, 0u64.into(), 1u64.into()).await.unwrap();
+
+    let request = CallRequest { to: Some(address), ..Default::default() };
+    let first = api.call(request.clone(), None, None).await.unwrap();
+    let second = api.call(request.clone(), None, None).await.unwrap();
+    assert_eq!(first, second);
+    assert_eq!(U256::from_big_endian(&first), 1u64.into());
+
+    api.anvil_set_storage_at(address, 0u64.into(), 2u64.into()).await.unwrap();
+    let out = api.call(request.clone(), None, None).await.unwrap();
+    assert_eq!(U256::from_big_endian(&out), 2u64.into());
+
+    api.evm_mine(None).await.unwrap();
+    api.anvil_set_storage_at(address, 0u64.into(), 3u64.into()).await.unwrap();
+    let out = api.call(request, None, None).await.unwrap();
+    assert_eq!(U256::from_big_endian(&out), 3u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_call_cache_keyed_by_gas_price() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_call_caching(true),
+    )
+    .await;
+    let from = handle.dev_accounts().next().unwrap();
+
+    // returns the gas price of the call
+    let address = Address::random();
+    let code: Bytes = "0x3a60005260206000f3".parse().unwrap();
+    api.anvil_set_code(address, code).await.unwrap();
+
+    for gas_price in [10_000_000_000u64, 20_000_000_000u64] {
+        let request = CallRequest {
+            from: Some(from),
+            to: Some(address),
+            gas_price: Some(gas_price.into()),
+            ..Default::default()
+        };
+        let out = api.call(request, None, None).await.unwrap();
+        assert_eq!(U256::from_big_endian(&out), gas_price.into());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_get_fork() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    assert!(origin_api.anvil_get_fork().unwrap().is_none());
+    origin_api.evm_mine(None).await.unwrap();
+
+    let (api, _handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    let fork = api.anvil_get_fork().unwrap().unwrap();
+    assert_eq!(fork.json_rpc_url, origin_handle.http_endpoint());
+    assert_eq!(fork.block_number, 1);
+    assert_eq!(fork.chain_id, origin_api.chain_id());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dump_state_diff() {
+    let fork = LocalFork::new().await;
+    let address = Address::random();
+    let unchanged = Address::random();
+    fork.origin_api.anvil_set_balance(unchanged, 100u64.into()).await.unwrap();
+
+    fork.fork_api.anvil_set_balance(address, 1337u64.into()).await.unwrap();
+    fork.fork_api.anvil_set_storage_at(address, 1u64.into(), 42u64.into()).await.unwrap();
+    // only read
+    fork.fork_api.balance(unchanged, None).await.unwrap();
+
+    let diff = fork.fork_api.anvil_dump_state_diff().unwrap();
+    let account = &diff[&address];
+    assert_eq!(account.balance, Some(1337u64.into()));
+    assert_eq!(account.nonce, None);
+    assert_eq!(account.storage.get(&1u64.into()), Some(&42u64.into()));
+    assert!(!diff.contains_key(&unchanged));
+
+    assert!(fork.origin_api.anvil_dump_state_diff().is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_start_from_state_file() {
+    use anvil::{cmd::NodeArgs, eth::backend::genesis::StateCompression};
+    use clap::Parser;
+
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (address, unchanged) = (Address::random(), Address::random());
+    origin_api.anvil_set_balance(unchanged, 100u64.into()).await.unwrap();
+    origin_api.evm_mine(None).await.unwrap();
+
+    let (fork_api, _fork_handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    fork_api.anvil_set_balance(address, 1337u64.into()).await.unwrap();
+    fork_api.anvil_set_storage_at(address, 1u64.into(), 42u64.into()).await.unwrap();
+
+    let state = fork_api.dump_state().unwrap();
+    assert_eq!(state.fork.as_ref().unwrap().block_number, 1);
+    let state_file = std::env::temp_dir().join(format!("anvil-state-{:?}.json", address));
+    state.write(&state_file, StateCompression::None).unwrap();
+
+    // the state of the origin changes after the state was dumped
+    origin_api.anvil_set_balance(unchanged, 200u64.into()).await.unwrap();
+    origin_api.evm_mine(None).await.unwrap();
+
+    let args = NodeArgs::parse_from([
+        "anvil",
+        "--port",
+        "0",
+        "--silent",
+        "--fork-state",
+        state_file.to_str().unwrap(),
+    ]);
+    let config = args.into_node_config().unwrap();
+    std::fs::remove_file(state_file).unwrap();
+
+    let (api, _handle) = spawn(config).await;
+    assert!(api.is_fork());
+    assert_eq!(api.get_fork().unwrap().block_number(), 1);
+    assert_eq!(api.balance(address, None).await.unwrap(), 1337u64.into());
+    assert_eq!(
+        api.storage_at(address, 1u64.into(), None).await.unwrap(),
+        H256::from_low_u64_be(42)
+    );
+    // accounts that are not part of the state file are read from the fork block
+    assert_eq!(api.balance(unchanged, None).await.unwrap(), 100u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_max_concurrency() {
+    let fork =
+        LocalFork::setup(NodeConfig::test(), NodeConfig::test().with_fork_max_concurrency(Some(1)))
+            .await;
+    let provider = fork.fork_handle.http_provider();
+
+    // requests beyond the limit are queued and still succeed
+    let balances =
+        futures::future::join_all((0..10).map(|_| provider.get_balance(Address::random(), None)))
+            .await;
+    assert!(balances.into_iter().all(|balance| balance.unwrap().is_zero()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_gas_price_passthrough() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let origin = origin_handle.http_provider();
+
+    let from = origin_handle.dev_wallets().next().unwrap().address();
+    let tip = U256::from(3_000_000_000u64);
+    let tx = Eip1559TransactionRequest::new()
+        .to(Address::random())
+        .from(from)
+        .value(1u64)
+        .max_fee_per_gas(tip * 10)
+        .max_priority_fee_per_gas(tip);
+    origin.send_transaction(tx, None).await.unwrap().await.unwrap();
+
+    let (fork_api, _fork_handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_fork_gas_price_passthrough(true),
+    )
+    .await;
+
+    // the tip paid in the fork block is suggested instead of the default
+    assert_ne!(origin_api.max_priority_fee_per_gas().unwrap(), tip);
+    assert_eq!(fork_api.max_priority_fee_per_gas().unwrap(), tip);
+    let base_fee =
+        origin.get_block(BlockNumber::Latest).await.unwrap().unwrap().base_fee_per_gas.unwrap();
+    assert_eq!(fork_api.gas_price().unwrap(), base_fee + tip);
+
+    // resetting the fork suggests the tip paid in the new fork block
+    let new_tip = tip * 2;
+    let tx = Eip1559TransactionRequest::new()
+        .to(Address::random())
+        .from(from)
+        .value(1u64)
+        .max_fee_per_gas(new_tip * 10)
+        .max_priority_fee_per_gas(new_tip);
+    let receipt = origin.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    fork_api
+        .anvil_reset(Some(Forking {
+            json_rpc_url: None,
+            block_number: Some(receipt.block_number.unwrap().as_u64()),
+        }))
+        .await
+        .unwrap();
+    assert_eq!(fork_api.max_priority_fee_per_gas().unwrap(), new_tip);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_gas_price_passthrough_before_london() {
+    let (_origin_api, origin_handle) =
+        spawn(NodeConfig::test().with_hardfork(Hardfork::Berlin)).await;
+    let origin = origin_handle.http_provider();
+
+    let from = origin_handle.dev_wallets().next().unwrap().address();
+    let gas_price = U256::from(7_000_000_000u64);
+    let tx =
+        TransactionRequest::new().to(Address::random()).from(from).value(1u64).gas_price(gas_price);
+    origin.send_transaction(tx, None).await.unwrap().await.unwrap();
+
+    let (fork_api, _fork_handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_hardfork(Hardfork::Berlin)
+            .with_fork_gas_price_passthrough(true),
+    )
+    .await;
+
+    // blocks before London have no priority fees, the gas price paid in the block is suggested
+    assert_eq!(fork_api.gas_price().unwrap(), gas_price);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_passthrough_receipts() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let origin = origin_handle.http_provider();
+
+    let from = origin_handle.dev_wallets().next().unwrap().address();
+    let tx = TransactionRequest::new().to(Address::random()).from(from).value(1u64);
+    let receipt = origin.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    let (fork_api, _fork_handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_fork_passthrough_receipts(false),
+    )
+    .await;
+    let block = BlockId::Hash(receipt.block_hash.unwrap());
+    assert!(fork_api.transaction_receipt(receipt.transaction_hash).await.unwrap().is_none());
+    assert!(fork_api.block_receipts(block).await.unwrap().is_none());
+
+    let (fork_api, _fork_handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    let fork_receipt =
+        fork_api.transaction_receipt(receipt.transaction_hash).await.unwrap().unwrap();
+    assert_eq!(fork_receipt, receipt);
+    assert_eq!(fork_api.block_receipts(block).await.unwrap().unwrap(), vec![receipt]);
+
+    // transactions mined after the fork are not part of the forked chain
+    let tx = TransactionRequest::new().to(Address::random()).from(from).value(1u64);
+    let receipt = origin.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    assert!(fork_api.transaction_receipt(receipt.transaction_hash).await.unwrap().is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_passthrough_typed_transactions() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let wallet = origin_handle.dev_wallets().next().unwrap();
+    let origin = SignerMiddleware::new(origin_handle.http_provider(), wallet);
+
+    let to = Address::random();
+    let access_list =
+        AccessList(vec![AccessListItem { address: to, storage_keys: vec![H256::zero()] }]);
+    let eip1559: TypedTransaction =
+        Eip1559TransactionRequest::new().to(to).value(1u64).max_priority_fee_per_gas(1u64).into();
+    let eip2930: TypedTransaction =
+        TransactionRequest::new().to(to).value(1u64).with_access_list(access_list.clone()).into();
+
+    let mut hashes = Vec::new();
+    for tx in [eip1559, eip2930] {
+        let receipt = origin.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        hashes.push(receipt.transaction_hash);
+    }
+
+    let (_fork_api, fork_handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    let fork = fork_handle.http_provider();
+
+    for hash in hashes.iter().copied() {
+        let origin_tx = origin.get_transaction(hash).await.unwrap().unwrap();
+        let fork_tx = fork.get_transaction(hash).await.unwrap().unwrap();
+        assert_eq!(fork_tx, origin_tx);
+    }
+
+    let eip1559 = fork.get_transaction(hashes[0]).await.unwrap().unwrap();
+    assert_eq!(eip1559.transaction_type, Some(2u64.into()));
+    assert!(eip1559.max_fee_per_gas.is_some());
+    assert_eq!(eip1559.max_priority_fee_per_gas, Some(1u64.into()));
+
+    let eip2930 = fork.get_transaction(hashes[1]).await.unwrap().unwrap();
+    assert_eq!(eip2930.transaction_type, Some(1u64.into()));
+    assert_eq!(eip2930.access_list, Some(access_list));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_request_log() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let address = Address::random();
+    let log_file = std::env::temp_dir().join(format!("anvil-fork-requests-{:?}.jsonl", address));
+
+    let (api, _handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_fork_request_log(Some(log_file.clone())),
+    )
+    .await;
+    api.balance(address, None).await.unwrap();
+
+    let content = std::fs::read_to_string(&log_file).unwrap();
+    let requests: Vec<serde_json::Value> =
+        content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let balance_request = requests
+        .iter()
+        .find(|request| {
+            request["method"] == "eth_getBalance" &&
+                request["params"][0] == serde_json::json!(address)
+        })
+        .unwrap();
+    assert!(balance_request["block"].is_string());
+    assert_eq!(balance_request["retry"], 0);
+
+    let _ = std::fs::remove_file(log_file);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_preload_storage_falls_back_to_lazy_loading() {
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let address = Address::random();
+    origin_api.anvil_set_storage_at(address, 1u64.into(), 42u64.into()).await.unwrap();
+    origin_api.evm_mine(None).await.unwrap();
+    origin_api.evm_mine(None).await.unwrap();
+
+    // anvil doesn't support `debug_storageRangeAt`, so the storage can't be preloaded
+    let (api, _handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_fork_block_number(Some(1u64))
+            .with_fork_preload_storage(vec![address]),
+    )
+    .await;
+
+    api.anvil_get_fork_stats(true).unwrap();
+    assert_eq!(
+        api.storage_at(address, 1u64.into(), None).await.unwrap(),
+        H256::from_low_u64_be(42)
+    );
+    assert_eq!(api.anvil_get_fork_stats(false).unwrap().storage_slots_fetched, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_preload_storage() {
+    use axum::{
+        body::Bytes as Body,
+        extract::Extension,
+        http::header,
+        response::{IntoResponse, Response},
+        routing::post,
+        Json, Router,
+    };
+    use serde_json::json;
+    use std::{
+        net::TcpListener,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    /// Serves the storage of the preloaded contract in two pages and forwards all other requests
+    /// to the origin node
+    async fn proxy(
+        Extension((origin, pages)): Extension<(String, Arc<AtomicUsize>)>,
+        body: Body,
+    ) -> Response {
+        let request: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+        if request["method"] == "debug_storageRangeAt" {
+            pages.fetch_add(1, Ordering::SeqCst);
+            let next_key = H256::repeat_byte(0x11);
+            let (slot, value, next_key) = if request["params"][3] == json!(H256::zero()) {
+                (1u64, 42u64, Some(next_key))
+            } else {
+                assert_eq!(request["params"][3], json!(next_key));
+                (2u64, 1337u64, None)
+            };
+            return Json(json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": {
+                    "storage": {
+                        format!("{:?}", H256::from_low_u64_be(slot + 0x100)): {
+                            "key": H256::from_low_u64_be(slot),
+                            "value": H256::from_low_u64_be(value),
+                        }
+                    },
+                    "nextKey": next_key,
+                }
+            }))
+            .into_response()
+        }
+        let res = reqwest::Client::new()
+            .post(origin)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec())
+            .send()
+            .await
+            .unwrap();
+        ([(header::CONTENT_TYPE, "application/json")], res.bytes().await.unwrap().to_vec())
+            .into_response()
+    }
+
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    // the storage at the fork block is read at its child block
+    origin_api.evm_mine(None).await.unwrap();
+    origin_api.evm_mine(None).await.unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+    let pages = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/", post(proxy))
+        .layer(Extension((origin_handle.http_endpoint(), Arc::clone(&pages))));
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+    let address = Address::random();
+    let (api, _handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(proxy_url))
+            .with_fork_block_number(Some(1u64))
+            .with_fork_preload_storage(vec![address]),
+    )
+    .await;
+    assert_eq!(pages.load(Ordering::SeqCst), 2);
+
+    // the preloaded slots are served from the cache
+    api.anvil_get_fork_stats(true).unwrap();
+    for (slot, value) in [(1u64, 42u64), (2, 1337)] {
+        assert_eq!(
+            api.storage_at(address, slot.into(), None).await.unwrap(),
+            H256::from_low_u64_be(value)
+        );
+    }
+    assert_eq!(api.anvil_get_fork_stats(false).unwrap().storage_slots_fetched, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_reset_to_snapshot() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_fork_snapshot_on_start(true),
+    )
+    .await;
+    let provider = handle.http_provider();
+    let fork_block_number = provider.get_block_number().await.unwrap();
+    let address = Address::random();
+
+    api.anvil_set_balance(address, 1337u64.into()).await.unwrap();
+    api.evm_mine(None).await.unwrap();
+    assert_eq!(provider.get_balance(address, None).await.unwrap(), 1337u64.into());
+    assert_eq!(provider.get_block_number().await.unwrap(), fork_block_number + 1);
+
+    api.anvil_reset(None).await.unwrap();
+    assert_eq!(provider.get_balance(address, None).await.unwrap(), 0u64.into());
+    assert_eq!(provider.get_block_number().await.unwrap(), fork_block_number);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_retry_after_rate_limit() {
+    use axum::{
+        body::Bytes as Body,
+        extract::Extension,
+        http::{header, StatusCode},
+        response::{IntoResponse, Response},
+        routing::post,
+        Router,
+    };
+    use std::{
+        net::TcpListener,
+        sync::atomic::{AtomicBool, Ordering},
+        time::Instant,
+    };
+
+    /// Rate limits the first request and forwards all others to the origin node
+    async fn proxy(
+        Extension((origin, limited)): Extension<(String, Arc<AtomicBool>)>,
+        body: Body,
+    ) -> Response {
+        if !limited.swap(true, Ordering::SeqCst) {
+            return (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, "1")]).into_response()
+        }
+        let res = reqwest::Client::new()
+            .post(origin)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec())
+            .send()
+            .await
+            .unwrap();
+        ([(header::CONTENT_TYPE, "application/json")], res.bytes().await.unwrap().to_vec())
+            .into_response()
+    }
+
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    origin_api.mine_one().await;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+    let limited = Arc::new(AtomicBool::new(false));
+    let app = Router::new()
+        .route("/", post(proxy))
+        .layer(Extension((origin_handle.http_endpoint(), Arc::clone(&limited))));
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+    // a short backoff ensures that the delay of the `Retry-After` header is used
+    let start = Instant::now();
+    let (_api, handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(proxy_url))
+            .with_fork_retry_backoff(Some(Duration::from_millis(1))),
+    )
+    .await;
+    assert!(limited.load(Ordering::SeqCst));
+    assert!(start.elapsed() >= Duration::from_secs(1));
+
+    let provider = handle.http_provider();
+    assert_eq!(provider.get_block_number().await.unwrap(), 1u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_offline_on_failure() {
+    use axum::{
+        body::Bytes as Body,
+        extract::Extension,
+        http::header,
+        response::{IntoResponse, Response},
+        routing::post,
+        Router,
+    };
+    use std::net::TcpListener;
+
+    /// Forwards all requests to the origin node
+    async fn proxy(Extension(origin): Extension<String>, body: Body) -> Response {
+        let res = reqwest::Client::new()
+            .post(origin)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec())
+            .send()
+            .await
+            .unwrap();
+        ([(header::CONTENT_TYPE, "application/json")], res.bytes().await.unwrap().to_vec())
+            .into_response()
+    }
+
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let (cached, uncached) = (Address::random(), Address::random());
+    for address in [cached, uncached] {
+        origin_api.anvil_set_balance(address, 1337u64.into()).await.unwrap();
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let app = Router::new().route("/", post(proxy)).layer(Extension(origin_handle.http_endpoint()));
+    let server = tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            }),
+    );
+
+    let (api, _handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(proxy_url.clone()))
+            .with_fork_offline_on_failure(Some(1)),
+    )
+    .await;
+    assert_eq!(api.balance(cached, None).await.unwrap(), 1337u64.into());
+    let (tolerant_api, _tolerant_handle) = spawn(
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(proxy_url))
+            .with_fork_offline_on_failure(Some(u32::MAX)),
+    )
+    .await;
+
+    // the fork endpoint becomes unreachable
+    shutdown_tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+
+    // before the fork is offline, failed fetches are errors too and aren't cached
+    for _ in 0..2 {
+        let err = tolerant_api.balance(uncached, None).await.unwrap_err();
+        assert!(err.to_string().contains("Failed to fetch"));
+    }
+    assert!(!tolerant_api.get_fork().unwrap().is_offline());
+
+    let err = api.balance(uncached, None).await.unwrap_err();
+    assert!(err.to_string().contains("Fork offline"));
+    assert!(api.get_fork().unwrap().is_offline());
+
+    // cached state is still served
+    assert_eq!(api.balance(cached, None).await.unwrap(), 1337u64.into());
+
+    // executions that read uncached state fail instead of seeing it as empty
+    let call = CallRequest { from: Some(cached), to: Some(uncached), ..Default::default() };
+    let err = api.call(call, None, None).await.unwrap_err();
+    assert!(err.to_string().contains("Fork offline"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_latest_is_pinned() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let origin = origin_handle.http_provider();
+    let from = origin_handle.dev_wallets().next().unwrap().address();
+    let tx = TransactionRequest::new().to(Address::random()).from(from).value(1u64);
+    origin.send_transaction(tx, None).await.unwrap().await.unwrap();
+
+    let fork_config = || {
+        NodeConfig::test()
+            .with_eth_rpc_url(Some(origin_handle.http_endpoint()))
+            .with_storage_caching(true)
+    };
+    let (pinned_api, _pinned_handle) = spawn(fork_config()).await;
+    let (follow_api, _follow_handle) = spawn(fork_config().with_fork_follow_head(true)).await;
+    assert_eq!(pinned_api.get_fork().unwrap().block_number(), 1);
+
+    // state changes of the origin after the fork are only visible when following the head, also
+    // for state that was already fetched
+    let to = Address::random();
+    assert!(follow_api.balance(to, None).await.unwrap().is_zero());
+    for balance in [1337u64, 2 * 1337] {
+        let tx = TransactionRequest::new().to(to).from(from).value(1337u64);
+        origin.send_transaction(tx, None).await.unwrap().await.unwrap();
+        let head = origin.get_block_number().await.unwrap().as_u64();
+
+        // the fork polls for new blocks
+        for _ in 0..20 {
+            if follow_api.block_number().unwrap() >= head.into() {
+                break
+            }
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+        assert_eq!(follow_api.get_fork().unwrap().block_number(), head);
+        assert_eq!(follow_api.block_number().unwrap(), head.into());
+        assert_eq!(follow_api.balance(to, None).await.unwrap(), balance.into());
+    }
+
+    assert!(pinned_api.balance(to, None).await.unwrap().is_zero());
+    assert_eq!(pinned_api.block_number().unwrap(), 1u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_state_at_block_hash() {
+    let (_origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let origin = origin_handle.http_provider();
+    let from = origin_handle.dev_wallets().next().unwrap().address();
+    let to = Address::random();
+
+    let mut hashes = Vec::new();
+    for _ in 0..2 {
+        let tx = TransactionRequest::new().to(to).from(from).value(1337u64);
+        let receipt = origin.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        hashes.push(receipt.block_hash.unwrap());
+    }
+
+    let (fork_api, fork_handle) =
+        spawn(NodeConfig::test().with_eth_rpc_url(Some(origin_handle.http_endpoint()))).await;
+    let provider = fork_handle.http_provider();
+    let tx = TransactionRequest::new().to(to).from(from).value(1337u64);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    hashes.push(receipt.block_hash.unwrap());
+
+    // the first block predates the fork, the second is the fork block and the last a local block
+    for (i, hash) in hashes.into_iter().enumerate() {
+        let balance = fork_api.balance(to, Some(hash.into())).await.unwrap();
+        assert_eq!(balance, (1337u64 * (i as u64 + 1)).into());
+    }
+
+    assert!(fork_api.balance(to, Some(H256::random().into())).await.is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_sends_storage_requests_in_batches() {
+    use anvil::eth::backend::fork::new_fork_provider;
+    use axum::{
+        body::Bytes as Body,
+        extract::Extension,
+        http::header,
+        response::{IntoResponse, Response},
+        routing::post,
+        Router,
+    };
+    use std::{net::TcpListener, sync::Mutex};
+
+    /// Records the size of every batch and forwards all requests to the origin node
+    async fn proxy(
+        Extension((origin, batches)): Extension<(String, Arc<Mutex<Vec<usize>>>)>,
+        body: Body,
+    ) -> Response {
+        if let Ok(serde_json::Value::Array(requests)) = serde_json::from_slice(&body) {
+            batches.lock().unwrap().push(requests.len());
+        }
+        let res = reqwest::Client::new()
+            .post(origin)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec())
+            .send()
+            .await
+            .unwrap();
+        ([(header::CONTENT_TYPE, "application/json")], res.bytes().await.unwrap().to_vec())
+            .into_response()
+    }
+
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let address = Address::random();
+    let num_slots = 10u64;
+    for slot in 0..num_slots {
+        origin_api.anvil_set_storage_at(address, slot.into(), (slot + 1).into()).await.unwrap();
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route("/", post(proxy))
+        .layer(Extension((origin_handle.http_endpoint(), Arc::clone(&batches))));
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+    let provider = new_fork_provider(&proxy_url, None, Some(4), None, None, None).unwrap();
+    let requests = (0..num_slots)
+        .map(|slot| provider.get_storage_at(address, H256::from_low_u64_be(slot), None));
+    let values = futures::future::join_all(requests).await;
+    for (slot, value) in values.into_iter().enumerate() {
+        assert_eq!(value.unwrap(), H256::from_low_u64_be(slot as u64 + 1));
+    }
+
+    let mut batches = batches.lock().unwrap().clone();
+    batches.sort_unstable();
+    assert_eq!(batches, vec![2, 4, 4]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_fork_falls_back_if_batches_are_rejected() {
+    use anvil::eth::backend::fork::new_fork_provider;
+    use axum::{
+        body::Bytes as Body,
+        extract::Extension,
+        http::header,
+        response::{IntoResponse, Response},
+        routing::post,
+        Json, Router,
+    };
+    use std::{
+        net::TcpListener,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    /// Rejects all batch requests and forwards single requests to the origin node
+    async fn proxy(
+        Extension((origin, rejected)): Extension<(String, Arc<AtomicUsize>)>,
+        body: Body,
+    ) -> Response {
+        if let Ok(serde_json::Value::Array(_)) = serde_json::from_slice(&body) {
+            rejected.fetch_add(1, Ordering::SeqCst);
+            return Json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32600, "message": "batch requests are not supported" }
+            }))
+            .into_response()
+        }
+        let res = reqwest::Client::new()
+            .post(origin)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.to_vec())
+            .send()
+            .await
+            .unwrap();
+        ([(header::CONTENT_TYPE, "application/json")], res.bytes().await.unwrap().to_vec())
+            .into_response()
+    }
+
+    let (origin_api, origin_handle) = spawn(NodeConfig::test()).await;
+    let address = Address::random();
+    let num_slots = 10u64;
+    for slot in 0..num_slots {
+        origin_api.anvil_set_storage_at(address, slot.into(), (slot + 1).into()).await.unwrap();
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+    let rejected = Arc::new(AtomicUsize::new(0));
+    let app = Router::new()
+        .route("/", post(proxy))
+        .layer(Extension((origin_handle.http_endpoint(), Arc::clone(&rejected))));
+    tokio::spawn(axum::Server::from_tcp(listener).unwrap().serve(app.into_make_service()));
+
+    let provider = new_fork_provider(&proxy_url, None, Some(4), None, None, None).unwrap();
+    for _ in 0..2 {
+        let requests = (0..num_slots)
+            .map(|slot| provider.get_storage_at(address, H256::from_low_u64_be(slot), None));
+        let values = futures::future::join_all(requests).await;
+        for (slot, value) in values.into_iter().enumerate() {
+            assert_eq!(value.unwrap(), H256::from_low_u64_be(slot as u64 + 1));
+        }
+    }
+
+    // no batches are sent anymore after the first ones were rejected
+    assert!(rejected.load(Ordering::SeqCst) > 0);
+    assert!(rejected.load(Ordering::SeqCst) <= 3);
+}
@@ -1,6 +1,6 @@
 //! Gas related tests
 
-use anvil::{eth::fees::INITIAL_BASE_FEE, spawn, NodeConfig};
+use anvil::{eth::fees::INITIAL_BASE_FEE, spawn, Hardfork, NodeConfig};
 use ethers::{
     prelude::Middleware,
     types::{transaction::eip2718::TypedTransaction, Address, BlockNumber, TransactionRequest},
@@ -68,6 +68,27 @@ async fn test_basefee_empty_block() {
     assert!(next_base_fee < base_fee);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_basefee_static() {
+    let (api, handle) = spawn(
+        NodeConfig::test()
+            .with_base_fee(Some(INITIAL_BASE_FEE))
+            .with_dynamic_base_fee(false)
+            .with_gas_limit(Some(GAS_TRANSFER)),
+    )
+    .await;
+    let provider = handle.http_provider();
+    let tx = TransactionRequest::new().to(Address::random()).value(1337u64);
+    provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+
+    // full block followed by an empty block
+    api.mine_one().await;
+
+    let base_fee =
+        provider.get_block(BlockNumber::Latest).await.unwrap().unwrap().base_fee_per_gas.unwrap();
+    assert_eq!(base_fee.as_u64(), INITIAL_BASE_FEE);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_respect_base_fee() {
     let base_fee = 50u64;
@@ -87,3 +108,52 @@ async fn test_respect_base_fee() {
     let tx = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
     assert_eq!(tx.status, Some(1u64.into()));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_basefee_set_next_block_gas_used() {
+    let (api, handle) = spawn(NodeConfig::test().with_base_fee(Some(INITIAL_BASE_FEE))).await;
+    let provider = handle.http_provider();
+
+    // an empty block that reports a full block
+    api.anvil_set_next_block_gas_used(api.gas_limit()).unwrap();
+    api.mine_one().await;
+    api.mine_one().await;
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.base_fee_per_gas.unwrap().as_u64(), INITIAL_BASE_FEE + 125_000_000);
+
+    // only applies to a single block
+    api.mine_one().await;
+    let next_block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert!(next_block.base_fee_per_gas.unwrap() < block.base_fee_per_gas.unwrap());
+
+    assert!(api.anvil_set_next_block_gas_used(api.gas_limit() + 1).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_set_next_block_gas_used_requires_dynamic_base_fee() {
+    let (api, _handle) = spawn(NodeConfig::test().with_dynamic_base_fee(false)).await;
+    assert!(api.anvil_set_next_block_gas_used(api.gas_limit()).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_gas_price_oracle() {
+    let gas_price = 1_000_000_000u64;
+    let (_api, handle) = spawn(
+        NodeConfig::test()
+            .with_hardfork(Hardfork::Berlin)
+            .with_gas_price(Some(gas_price))
+            .with_gas_price_oracle(true),
+    )
+    .await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+
+    // falls back to the configured gas price without any transactions
+    assert_eq!(provider.get_gas_price().await.unwrap().as_u64(), gas_price);
+
+    for price in [2 * gas_price, 3 * gas_price, 10 * gas_price] {
+        let tx = TransactionRequest::new().from(from).to(Address::random()).gas_price(price);
+        provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    }
+    assert_eq!(provider.get_gas_price().await.unwrap().as_u64(), 3 * gas_price);
+}
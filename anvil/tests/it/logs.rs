@@ -5,7 +5,7 @@ use anvil::{spawn, NodeConfig};
 use ethers::{
     middleware::SignerMiddleware,
     prelude::{BlockNumber, Filter, FilterKind, Middleware, Signer, H256},
-    types::Log,
+    types::{Address, Log},
 };
 use futures::StreamExt;
 use std::sync::Arc;
@@ -173,3 +173,43 @@ async fn watch_events() {
         assert_eq!(meta.block_hash, hash);
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_logs_skips_blocks_by_bloom() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let address = wallet.address();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let contract = SimpleStorage::deploy(Arc::clone(&client), "initial value".to_string())
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+
+    // blocks without logs in between
+    for _ in 0..10 {
+        api.mine_one().await;
+    }
+
+    let func = contract.method::<_, H256>("setValue", "hi".to_owned()).unwrap();
+    let _receipt = func.send().await.unwrap().await.unwrap();
+
+    let logs: Vec<ValueChanged> =
+        contract.event().from_block(0u64).topic1(address).query().await.unwrap();
+    assert_eq!(logs.len(), 2);
+
+    let filter = Filter::new().from_block(0u64).address(Address::random());
+    assert!(client.get_logs(&filter).await.unwrap().is_empty());
+
+    let filter = Filter::new().from_block(0u64).address(contract.address()).topic1(H256::random());
+    assert!(client.get_logs(&filter).await.unwrap().is_empty());
+
+    let filter = Filter::new()
+        .from_block(0u64)
+        .address(vec![Address::random(), contract.address()])
+        .topic1(vec![H256::random(), H256::from(address)]);
+    assert_eq!(client.get_logs(&filter).await.unwrap().len(), 2);
+}
@@ -7,6 +7,7 @@ mod ganache;
 mod gas;
 mod geth;
 mod logs;
+mod otterscan;
 mod pubsub;
 mod revert;
 mod traces;
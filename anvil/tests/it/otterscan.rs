@@ -0,0 +1,197 @@
+//! tests for otterscan endpoints
+
+use anvil::{spawn, NodeConfig};
+use anvil_core::types::{OtsInternalOperationType, OtsTraceType};
+use ethers::{
+    prelude::{ContractFactory, Middleware, SignerMiddleware, TransactionRequest},
+    signers::Signer,
+    types::{Address, BlockNumber, U256},
+};
+use ethers_solc::{project_util::TempProject, Artifact};
+use std::sync::Arc;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_ots_internal_operations() {
+    let prj = TempProject::dapptools().unwrap();
+    prj.add_source(
+        "Forwarder",
+        r#"
+pragma solidity 0.8.13;
+contract Forwarder {
+    function forward(address payable to) public payable {
+        to.transfer(msg.value);
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let mut compiled = prj.compile().unwrap();
+    assert!(!compiled.has_compiler_errors());
+    let contract = compiled.remove("Forwarder").unwrap();
+    let (abi, bytecode, _) = contract.into_contract_bytecode().into_parts();
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let factory = ContractFactory::new(abi.unwrap(), bytecode.unwrap(), client);
+    let contract = factory.deploy(()).unwrap().send().await.unwrap();
+
+    let to = Address::random();
+    let value = U256::from(1337u64);
+    let call = contract.method::<_, ()>("forward", to).unwrap().value(value);
+    let receipt = call.send().await.unwrap().await.unwrap().unwrap();
+
+    let operations = api.ots_get_internal_operations(receipt.transaction_hash).unwrap();
+    assert_eq!(operations.len(), 1);
+    assert_eq!(operations[0].r#type, OtsInternalOperationType::Transfer);
+    assert_eq!(operations[0].from, contract.address());
+    assert_eq!(operations[0].to, to);
+    assert_eq!(operations[0].value, value);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_search_ots_transactions() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let accounts: Vec<_> = handle.dev_wallets().map(|wallet| wallet.address()).collect();
+    let from = accounts[0];
+    let to = Address::random();
+
+    // mine one transaction of `from` per block, interleaved with unrelated transactions
+    let mut hashes = vec![];
+    for _ in 0..4 {
+        let tx = TransactionRequest::new().to(to).from(from).value(1u64);
+        let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        hashes.push(receipt.transaction_hash);
+        let tx = TransactionRequest::new().to(accounts[2]).from(accounts[1]).value(1u64);
+        provider.send_transaction(tx, None).await.unwrap().await.unwrap();
+    }
+    // most recent first
+    hashes.reverse();
+
+    let page = api.ots_search_transactions_before(to, 0, 3).unwrap();
+    assert!(page.first_page);
+    assert!(!page.last_page);
+    assert_eq!(page.txs.iter().map(|tx| tx.hash).collect::<Vec<_>>(), hashes[..3]);
+    assert_eq!(page.receipts.len(), 3);
+
+    let oldest = page.txs.last().unwrap().block_number.unwrap().as_u64();
+    let page = api.ots_search_transactions_before(to, oldest, 3).unwrap();
+    assert!(!page.first_page);
+    assert!(page.last_page);
+    assert_eq!(page.txs.iter().map(|tx| tx.hash).collect::<Vec<_>>(), hashes[3..]);
+
+    let page = api.ots_search_transactions_after(to, 0, 10).unwrap();
+    assert!(page.first_page);
+    assert!(page.last_page);
+    assert_eq!(page.txs.iter().map(|tx| tx.hash).collect::<Vec<_>>(), hashes);
+
+    let hash = api.ots_get_transaction_by_sender_and_nonce(from, 2u64.into()).unwrap();
+    assert_eq!(hash, Some(hashes[1]));
+    assert_eq!(api.ots_get_transaction_by_sender_and_nonce(from, 4u64.into()).unwrap(), None);
+    assert_eq!(api.ots_get_api_level().unwrap(), 8);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_ots_block_and_contract_details() {
+    let prj = TempProject::dapptools().unwrap();
+    prj.add_source(
+        "Factory",
+        r#"
+pragma solidity 0.8.13;
+contract Child {}
+contract Factory {
+    function create() public returns (address) {
+        return address(new Child());
+    }
+    function fail() public pure {
+        revert("failed");
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let mut compiled = prj.compile().unwrap();
+    assert!(!compiled.has_compiler_errors());
+    let contract = compiled.remove("Factory").unwrap();
+    let (abi, bytecode, _) = contract.into_contract_bytecode().into_parts();
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let sender = wallet.address();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let factory = ContractFactory::new(abi.unwrap(), bytecode.unwrap(), client);
+    let contract = factory.deploy(()).unwrap().send().await.unwrap();
+    let deployment = api.ots_get_transaction_by_sender_and_nonce(sender, 0u64.into()).unwrap();
+    let creator = api.ots_get_contract_creator(contract.address()).unwrap().unwrap();
+    assert_eq!(Some(creator.hash), deployment);
+    assert_eq!(creator.creator, sender);
+
+    let receipt = contract
+        .method::<_, Address>("create", ())
+        .unwrap()
+        .send()
+        .await
+        .unwrap()
+        .await
+        .unwrap()
+        .unwrap();
+
+    let traces = api.ots_trace_transaction(receipt.transaction_hash).unwrap();
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces[0].r#type, OtsTraceType::Call);
+    assert_eq!(traces[0].from, sender);
+    assert_eq!(traces[1].r#type, OtsTraceType::Create);
+    assert_eq!(traces[1].from, contract.address());
+    assert_eq!(traces[1].depth, traces[0].depth + 1);
+    let child = traces[1].to;
+
+    let creator = api.ots_get_contract_creator(child).unwrap().unwrap();
+    assert_eq!(creator.hash, receipt.transaction_hash);
+    assert_eq!(creator.creator, contract.address());
+    assert!(api.ots_has_code(child, BlockNumber::Latest).await.unwrap());
+    assert!(!api.ots_has_code(Address::random(), BlockNumber::Latest).await.unwrap());
+    assert!(api.ots_get_contract_creator(Address::random()).unwrap().is_none());
+    assert!(api.ots_get_transaction_error(receipt.transaction_hash).unwrap().as_ref().is_empty());
+
+    let number = BlockNumber::Number(receipt.block_number.unwrap());
+    let details = api.ots_get_block_details(number).await.unwrap();
+    assert_eq!(details.block.transaction_count, 1);
+    assert!(details.block.block.transactions.is_empty());
+    assert_eq!(
+        details.total_fees,
+        receipt.gas_used.unwrap() * receipt.effective_gas_price.unwrap()
+    );
+
+    let page = api.ots_get_block_transactions(number, 0, 10).await.unwrap();
+    assert_eq!(page.fullblock.transaction_count, 1);
+    assert_eq!(page.fullblock.block.transactions.len(), 1);
+    assert_eq!(page.fullblock.block.transactions[0].input.as_ref().len(), 4);
+    assert_eq!(page.receipts.len(), 1);
+    assert_eq!(page.receipts[0].transaction_hash, receipt.transaction_hash);
+    let page = api.ots_get_block_transactions(number, 1, 10).await.unwrap();
+    assert!(page.fullblock.block.transactions.is_empty());
+    assert!(page.receipts.is_empty());
+
+    let receipt = contract
+        .method::<_, ()>("fail", ())
+        .unwrap()
+        .gas(100_000u64)
+        .send()
+        .await
+        .unwrap()
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(receipt.status, Some(0u64.into()));
+    let error = api.ots_get_transaction_error(receipt.transaction_hash).unwrap();
+    // `Error(string)`
+    assert_eq!(&error.as_ref()[..4], &[0x08, 0xc3, 0x79, 0xa0]);
+}
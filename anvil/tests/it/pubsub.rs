@@ -7,7 +7,7 @@ use ethers::{
     prelude::{Middleware, Ws},
     providers::{JsonRpcClient, PubsubClient},
     signers::Signer,
-    types::{Block, Filter, TxHash, ValueOrArray, U256},
+    types::{Address, Block, Filter, TransactionRequest, TxHash, ValueOrArray, U256},
 };
 use futures::StreamExt;
 use std::sync::Arc;
@@ -29,6 +29,41 @@ async fn test_sub_new_heads() {
     assert_eq!(block_numbers, vec![1, 2, 3]);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sub_new_heads_on_state_change() {
+    let (api, handle) = spawn(NodeConfig::test().with_mine_on_state_change(true)).await;
+
+    let provider = handle.ws_provider().await;
+
+    let blocks = provider.subscribe_blocks().await.unwrap();
+
+    let account = Address::random();
+    api.anvil_set_balance(account, 1337u64.into()).await.unwrap();
+    api.anvil_set_storage_at(account, U256::zero(), U256::one()).await.unwrap();
+
+    let blocks = blocks.take(2).collect::<Vec<_>>().await;
+    let block_numbers = blocks.into_iter().map(|b| b.number.unwrap().as_u64()).collect::<Vec<_>>();
+    assert_eq!(block_numbers, vec![1, 2]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_state_change_does_not_mine_pending_transactions() {
+    let (api, handle) =
+        spawn(NodeConfig::test().with_mine_on_state_change(true).with_no_mining(true)).await;
+    let provider = handle.http_provider();
+
+    let from = handle.dev_wallets().next().unwrap().address();
+    let tx = TransactionRequest::new().from(from).to(Address::random()).value(1337u64);
+    let hash = provider.send_transaction(tx, None).await.unwrap().tx_hash();
+
+    api.anvil_set_balance(Address::random(), 1337u64.into()).await.unwrap();
+
+    let block = provider.get_block(1u64).await.unwrap().unwrap();
+    assert!(block.transactions.is_empty());
+    assert!(provider.get_transaction_receipt(hash).await.unwrap().is_none());
+    assert_eq!(api.txpool_status().await.unwrap().pending, 1u64.into());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_sub_logs_legacy() {
     abigen!(EmitLogs, "test-data/emit_logs.json");
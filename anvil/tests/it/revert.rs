@@ -3,7 +3,7 @@ use ethers::{
     contract::{Contract, ContractFactory},
     middleware::SignerMiddleware,
     types::U256,
-    utils::WEI_IN_ETHER,
+    utils::{hex, id, WEI_IN_ETHER},
 };
 use ethers_solc::{project_util::TempProject, Artifact};
 use std::sync::Arc;
@@ -205,3 +205,50 @@ contract Contract {
     let msg = err.to_string();
     assert!(msg.contains("execution reverted: RevertStringFooBar"));
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_custom_error_and_panic_revert_messages() {
+    let prj = TempProject::dapptools().unwrap();
+    prj.add_source(
+        "Contract",
+        r#"
+pragma solidity 0.8.13;
+contract Contract {
+    error Unauthorized();
+
+    function withdraw() public pure {
+        revert Unauthorized();
+    }
+
+    function divide(uint256 num) public pure returns (uint256) {
+        return 1 / num;
+    }
+}
+"#,
+    )
+    .unwrap();
+
+    let mut compiled = prj.compile().unwrap();
+    assert!(!compiled.has_compiler_errors());
+    let contract = compiled.remove("Contract").unwrap();
+    let (abi, bytecode, _) = contract.into_contract_bytecode().into_parts();
+
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.ws_provider().await;
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let factory = ContractFactory::new(abi.unwrap(), bytecode.unwrap(), client);
+    let contract = factory.deploy(()).unwrap().send().await.unwrap();
+
+    let err = contract.method::<_, ()>("withdraw", ()).unwrap().call().await.unwrap_err();
+    let selector = hex::encode(id("Unauthorized()"));
+    assert!(err.to_string().contains(&format!("execution reverted: custom error 0x{}", selector)));
+
+    let err = contract.method::<_, U256>("divide", U256::zero()).unwrap().call().await.unwrap_err();
+    assert!(err.to_string().contains("execution reverted: panic code 0x12"));
+
+    // the reason is also returned when the revert is caught during gas estimation
+    let err = contract.method::<_, ()>("withdraw", ()).unwrap().send().await.unwrap_err();
+    assert!(err.to_string().contains(&format!("execution reverted: custom error 0x{}", selector)));
+}
@@ -1,17 +1,56 @@
 use crate::abi::*;
-use anvil::{spawn, NodeConfig};
+use anvil::{
+    eth::{backend::mem::L1_BLOCK_ADDRESS, pool::transactions::TransactionOrder},
+    spawn, NodeConfig,
+};
+use anvil_core::eth::transaction::{DepositTransaction, TransactionKind, DEPOSIT_TX_TYPE};
 use ethers::{
     prelude::{
         signer::SignerMiddlewareError, BlockId, Middleware, Signer, SignerMiddleware,
         TransactionRequest,
     },
-    types::{Address, BlockNumber, Transaction, TransactionReceipt, H256, U256},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Eip1559TransactionRequest,
+        Transaction, TransactionReceipt, H256, U256,
+    },
+    utils::rlp,
 };
 
 use futures::{future::join_all, FutureExt, StreamExt};
 use std::{collections::HashSet, sync::Arc, time::Duration};
 use tokio::time::timeout;
 
+#[tokio::test(flavor = "multi_thread")]
+async fn receipts_have_effective_gas_price() {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let from = handle.dev_wallets().next().unwrap().address();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    let base_fee = block.base_fee_per_gas.unwrap();
+
+    let legacy: TypedTransaction =
+        TransactionRequest::new().from(from).to(Address::random()).gas_price(base_fee * 2).into();
+    // the tip is capped by the max fee
+    let eip1559: TypedTransaction = Eip1559TransactionRequest::new()
+        .from(from)
+        .to(Address::random())
+        .max_fee_per_gas(base_fee + 1)
+        .max_priority_fee_per_gas(base_fee)
+        .into();
+
+    for (tx, effective_gas_price) in [(legacy, base_fee * 2), (eip1559, base_fee + 1)] {
+        let balance_before = provider.get_balance(from, None).await.unwrap();
+        let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+        assert_eq!(receipt.effective_gas_price, Some(effective_gas_price));
+
+        let mined = provider.get_transaction(receipt.transaction_hash).await.unwrap().unwrap();
+        assert_eq!(mined.gas_price, Some(effective_gas_price));
+
+        let balance_after = provider.get_balance(from, None).await.unwrap();
+        assert_eq!(balance_before - balance_after, receipt.gas_used.unwrap() * effective_gas_price);
+    }
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_transfer_eth() {
     let (_api, handle) = spawn(NodeConfig::test()).await;
@@ -47,6 +86,47 @@ async fn can_transfer_eth() {
     assert_eq!(balance_before.saturating_add(amount), to_balance);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_transaction_by_block_and_index() {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+
+    let tx = TransactionRequest::new().to(to).value(1000u64).from(from);
+    let receipt = provider.send_transaction(tx, None).await.unwrap().await.unwrap().unwrap();
+    let block_hash = receipt.block_hash.unwrap();
+
+    let by_hash: Option<Transaction> = provider
+        .request("eth_getTransactionByBlockHashAndIndex", (block_hash, "0x0"))
+        .await
+        .unwrap();
+    let by_hash = by_hash.unwrap();
+    assert_eq!(by_hash.hash, receipt.transaction_hash);
+    assert_eq!(by_hash.block_hash, Some(block_hash));
+    assert_eq!(by_hash.transaction_index, Some(0u64.into()));
+
+    let by_number: Option<Transaction> = provider
+        .request("eth_getTransactionByBlockNumberAndIndex", (BlockNumber::Latest, "0x0"))
+        .await
+        .unwrap();
+    assert_eq!(by_number.unwrap().hash, receipt.transaction_hash);
+
+    // out of range indices return null
+    let missing: Option<Transaction> = provider
+        .request("eth_getTransactionByBlockHashAndIndex", (block_hash, "0x1"))
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+    let missing: Option<Transaction> = provider
+        .request("eth_getTransactionByBlockNumberAndIndex", (BlockNumber::Latest, "0x1"))
+        .await
+        .unwrap();
+    assert!(missing.is_none());
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_order_transactions() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -81,6 +161,231 @@ async fn can_order_transactions() {
     assert_eq!(block.transactions, vec![higher_price, lower_price])
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_order_transactions_by_effective_tip() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    // disable automine
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+    let base_fee = U256::from(1_000_000_000u64);
+    api.anvil_set_next_block_base_fee_per_gas(base_fee).await.unwrap();
+
+    // high max fee but a tip of only 1 wei
+    let tx = Eip1559TransactionRequest::new()
+        .to(to)
+        .from(from)
+        .value(1337u64)
+        .max_fee_per_gas(base_fee * 10)
+        .max_priority_fee_per_gas(1u64);
+    let tx_lower = provider.send_transaction(tx, None).await.unwrap();
+
+    // pays a tip of 2 wei
+    let tx = TransactionRequest::new().to(from).from(to).value(1337u64).gas_price(base_fee + 2);
+    let tx_higher = provider.send_transaction(tx, None).await.unwrap();
+
+    api.mine_one().await;
+
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    let lower_tip = tx_lower.await.unwrap().unwrap().transaction_hash;
+    let higher_tip = tx_higher.await.unwrap().unwrap().transaction_hash;
+    assert_eq!(block.transactions, vec![higher_tip, lower_tip])
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn orders_transactions_by_effective_tip_at_current_base_fee() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+    let base_fee = U256::from(1_000_000_000u64);
+    api.anvil_set_next_block_base_fee_per_gas(base_fee).await.unwrap();
+
+    // pays a tip of 10 wei at the initial base fee
+    let tx = TransactionRequest::new().to(to).from(from).value(1337u64).gas_price(base_fee + 10);
+    let tx_legacy = provider.send_transaction(tx, None).await.unwrap();
+
+    // pays a tip of 7 wei, but the tip of the legacy transaction drops to 5 wei
+    let base_fee = base_fee + 5;
+    api.anvil_set_next_block_base_fee_per_gas(base_fee).await.unwrap();
+    let tx = Eip1559TransactionRequest::new()
+        .to(from)
+        .from(to)
+        .value(1337u64)
+        .max_fee_per_gas(base_fee * 10)
+        .max_priority_fee_per_gas(7u64);
+    let tx_eip1559 = provider.send_transaction(tx, None).await.unwrap();
+
+    api.mine_one().await;
+
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    let legacy = tx_legacy.await.unwrap().unwrap().transaction_hash;
+    let eip1559 = tx_eip1559.await.unwrap().unwrap().transaction_hash;
+    assert_eq!(block.transactions, vec![eip1559, legacy])
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keeps_underpriced_transactions_in_pool() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let from = handle.dev_wallets().next().unwrap().address();
+    let base_fee = U256::from(1_000_000_000u64);
+    api.anvil_set_next_block_base_fee_per_gas(base_fee).await.unwrap();
+
+    let mut hashes = vec![];
+    for _ in 0..2 {
+        let tx = Eip1559TransactionRequest::new()
+            .to(Address::random())
+            .from(from)
+            .value(1337u64)
+            .max_fee_per_gas(base_fee * 2)
+            .max_priority_fee_per_gas(1u64);
+        hashes.push(provider.send_transaction(tx, None).await.unwrap().tx_hash());
+    }
+
+    // the transactions can't pay the base fee of the next block
+    api.anvil_set_next_block_base_fee_per_gas(base_fee * 10).await.unwrap();
+    api.mine_one().await;
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert!(block.transactions.is_empty());
+    for hash in hashes.iter().copied() {
+        let pending = provider.get_transaction(hash).await.unwrap().unwrap();
+        assert!(pending.block_number.is_none());
+    }
+
+    api.anvil_set_next_block_base_fee_per_gas(base_fee).await.unwrap();
+    api.mine_one().await;
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.transactions, hashes);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_block_with_mixed_transaction_types() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    // disable automine
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let from = handle.dev_wallets().next().unwrap().address();
+    let gas_price = provider.get_gas_price().await.unwrap();
+    let legacy = TransactionRequest::new()
+        .from(from)
+        .to(Address::random())
+        .value(1337u64)
+        .gas_price(gas_price * 2);
+    let legacy = provider.send_transaction(legacy, None).await.unwrap();
+    let eip1559 = Eip1559TransactionRequest::new()
+        .from(from)
+        .to(Address::random())
+        .value(1337u64)
+        .max_fee_per_gas(gas_price)
+        .max_priority_fee_per_gas(1u64);
+    let eip1559 = provider.send_transaction(eip1559, None).await.unwrap();
+    let hashes = vec![*legacy, *eip1559];
+
+    api.mine_one().await;
+
+    let block: serde_json::Value =
+        provider.request("eth_getBlockByNumber", ("latest", false)).await.unwrap();
+    let block_hashes: Vec<H256> = serde_json::from_value(block["transactions"].clone()).unwrap();
+    assert_eq!(block_hashes, hashes);
+
+    let block: serde_json::Value =
+        provider.request("eth_getBlockByNumber", ("latest", true)).await.unwrap();
+    let txs = block["transactions"].as_array().unwrap();
+    let full_hashes: Vec<H256> =
+        txs.iter().map(|tx| serde_json::from_value(tx["hash"].clone()).unwrap()).collect();
+    assert_eq!(full_hashes, hashes);
+    for (idx, tx) in txs.iter().enumerate() {
+        assert_eq!(tx["transactionIndex"], format!("0x{:x}", idx));
+        assert_eq!(tx["blockHash"], block["hash"]);
+    }
+
+    let (legacy, eip1559) = (&txs[0], &txs[1]);
+    assert_eq!(legacy["type"], "0x0");
+    assert!(legacy.get("maxFeePerGas").is_none());
+    assert!(legacy.get("yParity").is_none());
+    assert_eq!(eip1559["type"], "0x2");
+    assert!(eip1559["accessList"].is_array());
+    assert_eq!(eip1559["yParity"], eip1559["v"]);
+    assert!(eip1559.get("maxPriorityFeePerGas").is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_order_priority_sender_first() {
+    let config = NodeConfig::test();
+    let accounts: Vec<_> = config.genesis_accounts.iter().map(|w| w.address()).collect();
+    let priority = accounts[1];
+    let (api, handle) = spawn(config.with_priority_senders(vec![priority])).await;
+    let provider = handle.http_provider();
+
+    // disable automine
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let from = accounts[0];
+    let to = Address::random();
+    let gas_price = provider.get_gas_price().await.unwrap();
+
+    // the priority sender pays less but is still included first
+    let tx = TransactionRequest::new().to(to).from(from).value(1337u64).gas_price(gas_price + 1);
+    let tx_higher = provider.send_transaction(tx, None).await.unwrap();
+
+    let tx = TransactionRequest::new().to(to).from(priority).value(1337u64).gas_price(gas_price);
+    let tx_priority = provider.send_transaction(tx, None).await.unwrap();
+
+    api.mine_one().await;
+
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    let higher_price = tx_higher.await.unwrap().unwrap().transaction_hash;
+    let priority_sender = tx_priority.await.unwrap().unwrap().transaction_hash;
+    assert_eq!(block.transactions, vec![priority_sender, higher_price])
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_order_priority_selector_first() {
+    let config = NodeConfig::test();
+    let accounts: Vec<_> = config.genesis_accounts.iter().map(|w| w.address()).collect();
+    let selector = [0xde, 0xad, 0xbe, 0xef];
+    let (api, handle) = spawn(config.with_priority_selectors(vec![selector])).await;
+    let provider = handle.http_provider();
+
+    // disable automine
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let to = Address::random();
+    let gas_price = provider.get_gas_price().await.unwrap();
+
+    // the call of the priority selector pays less but is still included first
+    let tx = TransactionRequest::new()
+        .to(to)
+        .from(accounts[0])
+        .data(vec![0xde, 0xad, 0xbe, 0xee])
+        .gas_price(gas_price + 1);
+    let tx_higher = provider.send_transaction(tx, None).await.unwrap();
+
+    let mut data = selector.to_vec();
+    data.extend_from_slice(&[0u8; 32]);
+    let tx = TransactionRequest::new().to(to).from(accounts[1]).data(data).gas_price(gas_price);
+    let tx_priority = provider.send_transaction(tx, None).await.unwrap();
+
+    api.mine_one().await;
+
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    let higher_price = tx_higher.await.unwrap().unwrap().transaction_hash;
+    let priority_selector = tx_priority.await.unwrap().unwrap().transaction_hash;
+    assert_eq!(block.transactions, vec![priority_selector, higher_price])
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_respect_nonces() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -139,9 +444,9 @@ async fn can_replace_transaction() {
     let lower_priced_pending_tx =
         provider.send_transaction(tx.clone().gas_price(gas_price), None).await.unwrap();
 
-    // send the same transaction with higher gas price
+    // send the same transaction with a gas price bumped by at least the default price bump
     let higher_priced_pending_tx =
-        provider.send_transaction(tx.gas_price(gas_price + 1u64), None).await.unwrap();
+        provider.send_transaction(tx.gas_price(gas_price * 2u64), None).await.unwrap();
 
     // mine exactly one block
     api.mine_one().await;
@@ -158,6 +463,46 @@ async fn can_replace_transaction() {
     assert_eq!(vec![higher_priced_receipt.transaction_hash], block.transactions);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_reject_replacement_below_price_bump() {
+    let (api, handle) = spawn(NodeConfig::test().with_price_bump(50)).await;
+
+    // disable auto mining
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let provider = handle.http_provider();
+
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let to = accounts[1].address();
+
+    let nonce = provider.get_transaction_count(from, None).await.unwrap();
+    let gas_price = provider.get_gas_price().await.unwrap();
+
+    let tx = TransactionRequest::new().to(to).value(1337u64).from(from).nonce(nonce);
+
+    let pending_tx =
+        provider.send_transaction(tx.clone().gas_price(gas_price), None).await.unwrap();
+
+    // a higher gas price that's not bumped by 50% is rejected
+    let replacement_err = provider
+        .send_transaction(tx.clone().gas_price(gas_price * 3u64 / 2u64 - 1u64), None)
+        .await
+        .unwrap_err();
+    assert!(replacement_err.to_string().contains("replacement transaction underpriced"));
+
+    // bumping the gas price by 50% replaces the transaction
+    let replacement_tx =
+        provider.send_transaction(tx.gas_price(gas_price * 3u64 / 2u64), None).await.unwrap();
+
+    api.mine_one().await;
+
+    assert!(pending_tx.await.unwrap().is_none());
+    let receipt = replacement_tx.await.unwrap().unwrap();
+    let block = provider.get_block(1u64).await.unwrap().unwrap();
+    assert_eq!(vec![receipt.transaction_hash], block.transactions);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_reject_too_high_gas_limits() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -190,6 +535,32 @@ async fn can_reject_too_high_gas_limits() {
     assert!(pending.is_ok());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn can_cap_gas_estimation() {
+    let (_api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let tx = Greeter::deploy(Arc::clone(&client), "Hello World!".to_string()).unwrap().deployer.tx;
+    let gas = client.estimate_gas(&tx).await.unwrap();
+
+    // the deployment can't succeed within the cap
+    let (_api, handle) = spawn(NodeConfig::test().with_gas_estimation_cap(Some(gas / 2))).await;
+    let provider = handle.http_provider();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let err = client.estimate_gas(&tx).await.unwrap_err();
+    assert!(err.to_string().contains("Out of gas"));
+
+    // an explicit gas limit above the cap is rejected instead of silently lowered
+    let mut tx = tx;
+    tx.set_gas(gas);
+    let err = client.estimate_gas(&tx).await.unwrap_err();
+    assert!(err.to_string().contains("exceeds the gas estimation cap"));
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_reject_underpriced_replacement() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -211,7 +582,7 @@ async fn can_reject_underpriced_replacement() {
 
     // send transaction with higher gas price
     let higher_priced_pending_tx =
-        provider.send_transaction(tx.clone().gas_price(gas_price + 1u64), None).await.unwrap();
+        provider.send_transaction(tx.clone().gas_price(gas_price * 2u64), None).await.unwrap();
 
     // send the same transaction with lower gas price
     let lower_priced_pending_tx = provider.send_transaction(tx.gas_price(gas_price), None).await;
@@ -254,6 +625,32 @@ async fn can_deploy_greeter_http() {
     assert_eq!("Hello World!", greeting);
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn transactions_in_same_block_use_cold_access() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    let wallet = handle.dev_wallets().next().unwrap();
+    let client = Arc::new(SignerMiddleware::new(provider, wallet));
+
+    let greeter_contract = Greeter::deploy(Arc::clone(&client), "Hello World!".to_string())
+        .unwrap()
+        .send()
+        .await
+        .unwrap();
+
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    // both transactions read the same storage, which must not be warm for the second one
+    let first = *greeter_contract.greet().send().await.unwrap();
+    let second = *greeter_contract.greet().send().await.unwrap();
+    api.mine_one().await;
+
+    let first = client.get_transaction_receipt(first).await.unwrap().unwrap();
+    let second = client.get_transaction_receipt(second).await.unwrap().unwrap();
+    assert_eq!(first.block_number, second.block_number);
+    assert_eq!(first.gas_used, second.gas_used);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_deploy_and_mine_manually() {
     let (api, handle) = spawn(NodeConfig::test()).await;
@@ -589,13 +986,35 @@ async fn can_get_pending_transaction() {
     let tx = TransactionRequest::new().from(from).value(1337u64).to(Address::random());
     let tx = provider.send_transaction(tx, None).await.unwrap();
 
-    let pending = provider.get_transaction(tx.tx_hash()).await.unwrap();
-    assert!(pending.is_some());
+    let pending = provider.get_transaction(tx.tx_hash()).await.unwrap().unwrap();
+    assert_eq!(pending.from, from);
+    assert!(pending.block_hash.is_none());
+    assert!(pending.block_number.is_none());
+    assert!(pending.transaction_index.is_none());
 
     api.mine_one().await;
     let mined = provider.get_transaction(tx.tx_hash()).await.unwrap().unwrap();
 
-    assert_eq!(mined.hash, pending.unwrap().hash);
+    assert_eq!(mined.hash, pending.hash);
+    assert!(mined.block_number.is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_get_pending_impersonated_transaction() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    api.anvil_set_auto_mine(false).await.unwrap();
+    let provider = handle.http_provider();
+
+    let impersonate = Address::random();
+    api.anvil_set_balance(impersonate, U256::from(1e18 as u64)).await.unwrap();
+    api.anvil_impersonate_account(impersonate).await.unwrap();
+
+    let tx = TransactionRequest::new().from(impersonate).value(1337u64).to(Address::random());
+    let tx = provider.send_transaction(tx, None).await.unwrap();
+
+    let pending = provider.get_transaction(tx.tx_hash()).await.unwrap().unwrap();
+    assert_eq!(pending.from, impersonate);
+    assert!(pending.block_number.is_none());
 }
 
 #[tokio::test(flavor = "multi_thread")]
@@ -628,6 +1047,82 @@ async fn includes_pending_tx_for_transaction_count() {
     assert_eq!(nonce, tx_count.into());
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn distinguishes_pending_and_latest_transaction_count() {
+    let (api, handle) = spawn(
+        NodeConfig::test().with_no_mining(true).with_transaction_order(TransactionOrder::Fees),
+    )
+    .await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+    let from = accounts[0].address();
+    let other = accounts[1].address();
+
+    // the sender's later transactions pay more, so they're not mined in submission order relative
+    // to the other sender's transaction
+    for (nonce, gas_price) in [(0u64, 1_000_000_000u64), (1, 100_000_000_000)] {
+        let tx = TransactionRequest::new()
+            .from(from)
+            .to(Address::random())
+            .nonce(nonce)
+            .gas_price(gas_price);
+        provider.send_transaction(tx, None).await.unwrap();
+    }
+    let tx =
+        TransactionRequest::new().from(other).to(Address::random()).gas_price(50_000_000_000u64);
+    provider.send_transaction(tx, None).await.unwrap();
+
+    // a transaction after a nonce gap isn't counted
+    let tx = TransactionRequest::new().from(from).to(Address::random()).nonce(3u64);
+    provider.send_transaction(tx, None).await.unwrap();
+
+    let latest = provider.get_transaction_count(from, None).await.unwrap();
+    assert_eq!(latest, 0u64.into());
+    let pending = provider
+        .get_transaction_count(from, Some(BlockId::Number(BlockNumber::Pending)))
+        .await
+        .unwrap();
+    assert_eq!(pending, 2u64.into());
+
+    // the next transaction without a nonce uses the pending count and closes the gap
+    let tx = TransactionRequest::new().from(from).to(Address::random());
+    provider.send_transaction(tx, None).await.unwrap();
+    let pending = provider
+        .get_transaction_count(from, Some(BlockId::Number(BlockNumber::Pending)))
+        .await
+        .unwrap();
+    assert_eq!(pending, 4u64.into());
+
+    api.mine_one().await;
+    let latest = provider.get_transaction_count(from, None).await.unwrap();
+    assert_eq!(latest, 4u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn orders_transactions_with_equal_fees_by_arrival() {
+    let (api, handle) = spawn(
+        NodeConfig::test().with_no_mining(true).with_transaction_order(TransactionOrder::Fees),
+    )
+    .await;
+    let provider = handle.http_provider();
+    let accounts: Vec<_> = handle.dev_wallets().collect();
+
+    let gas_prices = [3_000_000_000u64, 2_000_000_000, 3_000_000_000, 2_000_000_000];
+    let mut hashes = Vec::new();
+    for (account, gas_price) in accounts.iter().zip(gas_prices) {
+        let tx = TransactionRequest::new()
+            .from(account.address())
+            .to(Address::random())
+            .gas_price(gas_price);
+        hashes.push(*provider.send_transaction(tx, None).await.unwrap());
+    }
+    api.mine_one().await;
+
+    // higher fees first, equal fees in the order they arrived
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.transactions, vec![hashes[0], hashes[2], hashes[1], hashes[3]]);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn can_get_historic_info() {
     let (_api, handle) = spawn(NodeConfig::test()).await;
@@ -739,3 +1234,204 @@ async fn can_stream_pending_transactions() {
         }
     }
 }
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread")]
+async fn can_order_transactions_with_external_command() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let accounts: Vec<_> = handle.dev_accounts().collect();
+    let to = Address::random();
+    let mut hashes = Vec::new();
+    for from in &accounts[..2] {
+        let tx = TransactionRequest::new().to(to).value(1337u64).from(*from);
+        hashes.push(provider.send_transaction(tx, None).await.unwrap().tx_hash());
+    }
+
+    let script = std::env::temp_dir().join(format!("anvil-order-{:?}.sh", to));
+    let write_script = |reply: String| {
+        std::fs::write(&script, format!("#!/bin/sh\ncat > /dev/null\necho '{}'\n", reply)).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    };
+
+    // only include the second transaction
+    write_script(format!("[\"{:?}\"]", hashes[1]));
+    api.set_transaction_order(TransactionOrder::External(script.clone()));
+    api.evm_mine(None).await.unwrap();
+
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.transactions, vec![hashes[1]]);
+    assert!(provider.get_transaction_receipt(hashes[0]).await.unwrap().is_none());
+
+    // malformed output falls back to the default order
+    write_script("not json".to_string());
+    api.evm_mine(None).await.unwrap();
+
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.transactions, vec![hashes[0]]);
+
+    // the transactions of a sender are included in nonce order, at the positions the command
+    // picked for them
+    let mut hashes = Vec::new();
+    for from in [accounts[0], accounts[0], accounts[1]] {
+        let tx = TransactionRequest::new().to(to).value(1337u64).from(from);
+        hashes.push(provider.send_transaction(tx, None).await.unwrap().tx_hash());
+    }
+    write_script(format!("[\"{:?}\", \"{:?}\", \"{:?}\"]", hashes[1], hashes[2], hashes[0]));
+    let expected = vec![hashes[0], hashes[2], hashes[1]];
+    assert_eq!(api.anvil_get_mempool_order().await.unwrap(), expected);
+    let pending = provider.get_block(BlockNumber::Pending).await.unwrap().unwrap();
+    assert_eq!(pending.transactions, expected);
+
+    api.evm_mine(None).await.unwrap();
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.transactions, expected);
+
+    std::fs::remove_file(script).unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_send_deposit_transaction() {
+    let (api, handle) = spawn(NodeConfig::test().with_optimism(true)).await;
+    let provider = handle.http_provider();
+
+    let from = Address::random();
+    let to = Address::random();
+    let deposit = DepositTransaction {
+        nonce: U256::zero(),
+        source_hash: H256::random(),
+        from,
+        kind: TransactionKind::Call(to),
+        mint: 1_000u64.into(),
+        value: 100u64.into(),
+        gas_limit: 21_000u64.into(),
+        is_system_tx: false,
+        input: Default::default(),
+    };
+    let mut raw = vec![DEPOSIT_TX_TYPE];
+    raw.extend_from_slice(&rlp::encode(&deposit));
+
+    let hash = api.send_raw_transaction(raw.into()).unwrap();
+    let receipt = provider.get_transaction_receipt(hash).await.unwrap().unwrap();
+    assert_eq!(receipt.status, Some(1u64.into()));
+    assert_eq!(receipt.from, from);
+
+    let tx = provider.get_transaction(hash).await.unwrap().unwrap();
+    assert_eq!(tx.transaction_type, Some(u64::from(DEPOSIT_TX_TYPE).into()));
+
+    // the minted wei pays for the value, gas is free
+    assert_eq!(provider.get_balance(to, None).await.unwrap(), 100u64.into());
+    assert_eq!(provider.get_balance(from, None).await.unwrap(), 900u64.into());
+    assert_eq!(provider.get_transaction_count(from, None).await.unwrap(), 1u64.into());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn optimism_receipts_have_l1_fee() {
+    let (api, handle) = spawn(NodeConfig::test().with_optimism(true)).await;
+    let provider = handle.http_provider();
+
+    // l1 base fee, overhead and scalar of the L1Block predeploy
+    api.anvil_set_storage_at(L1_BLOCK_ADDRESS, 1u64.into(), 1_000u64.into()).await.unwrap();
+    api.anvil_set_storage_at(L1_BLOCK_ADDRESS, 5u64.into(), 2_100u64.into()).await.unwrap();
+    api.anvil_set_storage_at(L1_BLOCK_ADDRESS, 6u64.into(), 1_000_000u64.into()).await.unwrap();
+
+    let from = handle.dev_wallets().next().unwrap().address();
+    let tx = TransactionRequest::new().from(from).to(Address::random()).value(1337u64);
+    let hash = provider.send_transaction(tx, None).await.unwrap().tx_hash();
+
+    let receipt: serde_json::Value =
+        provider.request("eth_getTransactionReceipt", [hash]).await.unwrap();
+    assert_eq!(receipt["l1GasPrice"], "0x3e8");
+    assert_eq!(receipt["l1FeeScalar"], "1");
+    let l1_gas_used: U256 = serde_json::from_value(receipt["l1GasUsed"].clone()).unwrap();
+    let l1_fee: U256 = serde_json::from_value(receipt["l1Fee"].clone()).unwrap();
+    assert!(l1_gas_used > 2_100u64.into());
+    assert_eq!(l1_fee, l1_gas_used * 1_000u64);
+
+    // deposits don't pay an L1 fee
+    let deposit = DepositTransaction {
+        nonce: U256::zero(),
+        source_hash: H256::random(),
+        from: Address::random(),
+        kind: TransactionKind::Call(Address::random()),
+        mint: U256::zero(),
+        value: U256::zero(),
+        gas_limit: 21_000u64.into(),
+        is_system_tx: false,
+        input: Default::default(),
+    };
+    let mut raw = vec![DEPOSIT_TX_TYPE];
+    raw.extend_from_slice(&rlp::encode(&deposit));
+    let hash = api.send_raw_transaction(raw.into()).unwrap();
+
+    let receipt: serde_json::Value =
+        provider.request("eth_getTransactionReceipt", [hash]).await.unwrap();
+    assert!(receipt.get("l1Fee").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_deposit_transaction_without_optimism() {
+    let (api, _handle) = spawn(NodeConfig::test()).await;
+
+    let deposit = DepositTransaction {
+        nonce: U256::zero(),
+        source_hash: H256::random(),
+        from: Address::random(),
+        kind: TransactionKind::Create,
+        mint: U256::zero(),
+        value: U256::zero(),
+        gas_limit: 100_000u64.into(),
+        is_system_tx: false,
+        input: Default::default(),
+    };
+    let mut raw = vec![DEPOSIT_TX_TYPE];
+    raw.extend_from_slice(&rlp::encode(&deposit));
+
+    assert!(api.send_raw_transaction(raw.into()).is_err());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_invalid_raw_transactions_with_specific_errors() {
+    let (api, handle) = spawn(NodeConfig::test()).await;
+    let provider = handle.http_provider();
+
+    let wallet = handle.dev_wallets().next().unwrap();
+    let from = wallet.address();
+    let chain_id = api.chain_id();
+    let gas_price = provider.get_gas_price().await.unwrap();
+    let base_fee =
+        provider.get_block(BlockNumber::Latest).await.unwrap().unwrap().base_fee_per_gas.unwrap();
+
+    let tx = TransactionRequest::new()
+        .from(from)
+        .to(Address::random())
+        .value(1u64)
+        .nonce(0u64)
+        .gas(21_000u64)
+        .gas_price(gas_price)
+        .chain_id(chain_id);
+
+    let cases = vec![
+        (tx.clone().chain_id(chain_id + 1), "invalid chain id for signer"),
+        (tx.clone().gas(20_999u64), "intrinsic gas too low"),
+        (tx.clone().gas_price(base_fee - 1u64), "max fee per gas less than block base fee"),
+        (tx.clone().value(handle.genesis_balance()), "Insufficient funds for gas * price + value"),
+    ];
+    for (tx, err) in cases {
+        let tx: TypedTransaction = tx.into();
+        let signature = wallet.sign_transaction(&tx).await.unwrap();
+        let res = api.send_raw_transaction(tx.rlp_signed(&signature));
+        assert!(res.unwrap_err().to_string().contains(err), "expected `{}`", err);
+    }
+
+    // nonce 0 is used after this transaction is mined
+    provider.send_transaction(tx.clone(), None).await.unwrap().await.unwrap();
+    let tx: TypedTransaction = tx.into();
+    let signature = wallet.sign_transaction(&tx).await.unwrap();
+    let res = api.send_raw_transaction(tx.rlp_signed(&signature));
+    assert!(res.unwrap_err().to_string().contains("nonce too low"));
+}
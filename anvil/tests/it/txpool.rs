@@ -1,9 +1,9 @@
 //! txpool related tests
 
-use anvil::{spawn, NodeConfig};
+use anvil::{eth::pool::PoolFullBehavior, spawn, NodeConfig};
 use ethers::{
     prelude::Middleware,
-    types::{TransactionRequest, U256},
+    types::{BlockNumber, TransactionRequest, U256},
 };
 
 #[tokio::test(flavor = "multi_thread")]
@@ -53,3 +53,111 @@ async fn geth_txpool() {
         assert_eq!(req, &tx);
     }
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_preview_mempool_order() {
+    let (api, handle) = spawn(NodeConfig::test().with_gas_limit(Some(42_000u64))).await;
+    let provider = handle.http_provider();
+    api.anvil_set_auto_mine(false).await.unwrap();
+
+    let accounts = provider.get_accounts().await.unwrap();
+    let gas_price = provider.get_gas_price().await.unwrap();
+    let mut hashes = Vec::new();
+    for (idx, account) in accounts.iter().take(3).enumerate() {
+        let tx = TransactionRequest::new()
+            .to(*account)
+            .from(*account)
+            .gas(21_000u64)
+            .gas_price(gas_price + idx);
+        hashes.push(provider.send_transaction(tx, None).await.unwrap().tx_hash());
+    }
+
+    // only two transactions fit into the block, ordered by fees
+    let order = api.anvil_get_mempool_order().await.unwrap();
+    assert_eq!(order, vec![hashes[2], hashes[1]]);
+
+    api.mine_one().await;
+    let block = provider.get_block(BlockNumber::Latest).await.unwrap().unwrap();
+    assert_eq!(block.transactions, order);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_limit_mempool() {
+    let (api, handle) =
+        spawn(NodeConfig::test().with_no_mining(true).with_mempool_limit(Some(2))).await;
+    let provider = handle.http_provider();
+
+    let accounts = provider.get_accounts().await.unwrap();
+    for from in &accounts[..2] {
+        let tx = TransactionRequest::new().to(*from).from(*from).value(1u64);
+        provider.send_transaction(tx, None).await.unwrap();
+    }
+
+    // the pool is full
+    let tx = TransactionRequest::new().to(accounts[2]).from(accounts[2]).value(1u64);
+    assert!(provider.send_transaction(tx.clone(), None).await.is_err());
+
+    // room is made by mining
+    api.mine_one().await;
+    provider.send_transaction(tx, None).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn can_evict_lowest_priority_from_full_mempool() {
+    let (_api, handle) = spawn(
+        NodeConfig::test()
+            .with_no_mining(true)
+            .with_mempool_limit(Some(2))
+            .with_mempool_full_behavior(PoolFullBehavior::Evict),
+    )
+    .await;
+    let provider = handle.http_provider();
+
+    let accounts = provider.get_accounts().await.unwrap();
+    let gas_price = provider.get_gas_price().await.unwrap();
+    let send = |from, gas_price: U256| {
+        let tx = TransactionRequest::new().to(from).from(from).value(1u64).gas_price(gas_price);
+        let provider = provider.clone();
+        async move { provider.send_transaction(tx, None).await.map(|tx| *tx) }
+    };
+
+    let cheap = send(accounts[0], gas_price).await.unwrap();
+    let medium = send(accounts[1], gas_price * 2).await.unwrap();
+
+    // a transaction that pays less than everything in the pool is rejected
+    assert!(send(accounts[2], gas_price).await.is_err());
+
+    // a higher paying transaction evicts the cheapest one
+    let expensive = send(accounts[3], gas_price * 3).await.unwrap();
+    let content = provider.txpool_content().await.unwrap();
+    let hashes: Vec<_> =
+        content.pending.values().flat_map(|txs| txs.values()).map(|tx| tx.hash).collect();
+    assert_eq!(hashes.len(), 2);
+    assert!(!hashes.contains(&cheap));
+    assert!(hashes.contains(&medium));
+    assert!(hashes.contains(&expensive));
+
+    // a transaction that fails validation doesn't evict anything
+    let invalid = TransactionRequest::new()
+        .to(accounts[4])
+        .from(accounts[4])
+        .value(U256::MAX)
+        .gas_price(gas_price * 10);
+    assert!(provider.send_transaction(invalid, None).await.is_err());
+    assert_eq!(provider.txpool_status().await.unwrap().pending, 2u64.into());
+
+    // transactions of the same sender are not evicted in favor of a follow-up transaction
+    let follow_up = TransactionRequest::new()
+        .to(accounts[1])
+        .from(accounts[1])
+        .value(1u64)
+        .nonce(1u64)
+        .gas_price(gas_price * 4);
+    let follow_up = *provider.send_transaction(follow_up, None).await.unwrap();
+    let content = provider.txpool_content().await.unwrap();
+    let hashes: Vec<_> =
+        content.pending.values().flat_map(|txs| txs.values()).map(|tx| tx.hash).collect();
+    assert_eq!(hashes.len(), 2);
+    assert!(hashes.contains(&medium));
+    assert!(hashes.contains(&follow_up));
+}
@@ -15,11 +15,12 @@ use futures::{
     Future, FutureExt,
 };
 
-use crate::executor::fork::cache::FlushJsonBlockCacheDB;
+use crate::executor::fork::cache::{BlockchainDbStats, FlushJsonBlockCacheDB};
 use std::{
     collections::{hash_map::Entry, HashMap, VecDeque},
     pin::Pin,
     sync::{
+        atomic::{AtomicUsize, Ordering},
         mpsc::{channel as oneshot_channel, Sender as OneshotSender},
         Arc,
     },
@@ -28,13 +29,17 @@ use tracing::{trace, warn};
 
 type AccountFuture<Err> =
     Pin<Box<dyn Future<Output = (Result<(U256, U256, Bytes), Err>, Address)> + Send>>;
-type StorageFuture<Err> = Pin<Box<dyn Future<Output = (Result<U256, Err>, Address, U256)> + Send>>;
+type StorageBatchFuture<Err> =
+    Pin<Box<dyn Future<Output = Vec<(Result<U256, Err>, Address, U256)>> + Send>>;
 type BlockHashFuture<Err> = Pin<Box<dyn Future<Output = (Result<H256, Err>, u64)> + Send>>;
 
+/// The default number of storage slots that are requested concurrently as one group
+pub const DEFAULT_STORAGE_BATCH_SIZE: usize = 32;
+
 /// Request variants that are executed by the provider
 enum ProviderRequest<Err> {
     Account(AccountFuture<Err>),
-    Storage(StorageFuture<Err>),
+    StorageBatch(StorageBatchFuture<Err>),
     BlockHash(BlockHashFuture<Err>),
 }
 
@@ -49,6 +54,10 @@ enum BackendRequest {
     BlockHash(u64, OneshotSender<H256>),
     /// Sets the pinned block to fetch data from
     SetPinnedBlock(BlockId),
+    /// Sets the maximum number of storage slots that are requested concurrently as one group
+    SetStorageBatchSize(usize),
+    /// Sets whether the handler panics if data can't be fetched
+    SetPanicOnFailure(bool),
 }
 
 /// Handles an internal provider and listens for requests.
@@ -66,6 +75,13 @@ pub struct BackendHandler<M: Middleware> {
     account_requests: HashMap<Address, Vec<OneshotSender<AccountInfo>>>,
     /// Listeners that wait for a `get_storage_at` response
     storage_requests: HashMap<(Address, U256), Vec<OneshotSender<U256>>>,
+    /// Storage slots that still need to be fetched, these are combined into groups
+    queued_storage: Vec<(Address, U256)>,
+    /// The maximum number of storage slots that are requested concurrently as one group
+    storage_batch_size: usize,
+    /// Whether to panic if data can't be fetched, otherwise the listeners of the failed request
+    /// are dropped, see [SharedBackend::set_panic_on_failure]
+    panic_on_failure: bool,
     /// Listeners that wait for a `get_block` response
     block_requests: HashMap<u64, Vec<OneshotSender<H256>>>,
     /// Incoming commands.
@@ -93,6 +109,9 @@ where
             pending_requests: Default::default(),
             account_requests: Default::default(),
             storage_requests: Default::default(),
+            queued_storage: Default::default(),
+            storage_batch_size: DEFAULT_STORAGE_BATCH_SIZE,
+            panic_on_failure: true,
             block_requests: Default::default(),
             queued_requests: Default::default(),
             incoming: rx,
@@ -112,8 +131,10 @@ where
                 trace!(target: "backendhandler", "received request basic address={:?}", addr);
                 let acc = self.db.accounts().read().get(&addr).cloned();
                 if let Some(basic) = acc {
+                    BlockchainDbStats::increment(&self.db.stats().account_hits);
                     let _ = sender.send(basic);
                 } else {
+                    BlockchainDbStats::increment(&self.db.stats().account_misses);
                     self.request_account(addr, sender);
                 }
             }
@@ -130,8 +151,10 @@ where
                 let value =
                     self.db.storage().read().get(&addr).and_then(|acc| acc.get(&idx).copied());
                 if let Some(value) = value {
+                    BlockchainDbStats::increment(&self.db.stats().storage_hits);
                     let _ = sender.send(value);
                 } else {
+                    BlockchainDbStats::increment(&self.db.stats().storage_misses);
                     // account present but not storage -> fetch storage
                     self.request_account_storage(addr, idx, sender);
                 }
@@ -139,6 +162,12 @@ where
             BackendRequest::SetPinnedBlock(block_id) => {
                 self.block_id = Some(block_id);
             }
+            BackendRequest::SetStorageBatchSize(batch_size) => {
+                self.storage_batch_size = batch_size.max(1);
+            }
+            BackendRequest::SetPanicOnFailure(panic_on_failure) => {
+                self.panic_on_failure = panic_on_failure;
+            }
         }
     }
 
@@ -154,23 +183,49 @@ where
                 entry.get_mut().push(listener);
             }
             Entry::Vacant(entry) => {
-                trace!(target: "backendhandler", "preparing storage request, address={:?}, idx={}", address, idx);
+                trace!(target: "backendhandler", "queueing storage request, address={:?}, idx={}", address, idx);
                 entry.insert(vec![listener]);
-                let provider = self.provider.clone();
-                let block_id = self.block_id;
-                let fut = Box::pin(async move {
-                    // serialize & deserialize back to U256
-                    let idx_req = H256::from_uint(&idx);
-                    let storage = provider.get_storage_at(address, idx_req, block_id).await;
-                    let storage =
-                        storage.map(|storage| storage.into_uint()).map_err(|err| eyre::eyre!(err));
-                    (storage, address, idx)
-                });
-                self.pending_requests.push(ProviderRequest::Storage(fut));
+                BlockchainDbStats::increment(&self.db.stats().storage_slots_fetched);
+                self.queued_storage.push((address, idx));
             }
         }
     }
 
+    /// Combines all queued storage slots into groups of at most `storage_batch_size` slots.
+    ///
+    /// This does not send a JSON-RPC batch itself: all slots of a group are requested concurrently
+    /// and resolved together, so that slots that were requested at the same time don't wait on
+    /// each other's roundtrip. A provider that batches concurrent requests, like anvil's fork
+    /// client, can send a group as a single batch.
+    fn dispatch_storage_requests(&mut self) {
+        if self.queued_storage.is_empty() {
+            return
+        }
+        let queued = std::mem::take(&mut self.queued_storage);
+        for batch in queued.chunks(self.storage_batch_size) {
+            trace!(target: "backendhandler", "preparing storage batch request, slots={}", batch.len());
+            let provider = self.provider.clone();
+            let block_id = self.block_id;
+            let batch = batch.to_vec();
+            let fut = Box::pin(async move {
+                let requests = batch.into_iter().map(|(address, idx)| {
+                    let provider = provider.clone();
+                    async move {
+                        // serialize & deserialize back to U256
+                        let idx_req = H256::from_uint(&idx);
+                        let storage = provider.get_storage_at(address, idx_req, block_id).await;
+                        let storage = storage
+                            .map(|storage| storage.into_uint())
+                            .map_err(|err| eyre::eyre!(err));
+                        (storage, address, idx)
+                    }
+                });
+                futures::future::join_all(requests).await
+            });
+            self.pending_requests.push(ProviderRequest::StorageBatch(fut));
+        }
+    }
+
     /// returns the future that fetches the account data
     fn get_account_req(&self, address: Address) -> ProviderRequest<eyre::Error> {
         trace!(target: "backendhandler", "preparing account request, address={:?}", address);
@@ -194,6 +249,7 @@ where
             }
             Entry::Vacant(entry) => {
                 entry.insert(vec![listener]);
+                BlockchainDbStats::increment(&self.db.stats().accounts_fetched);
                 self.pending_requests.push(self.get_account_req(address));
             }
         }
@@ -240,6 +296,9 @@ where
                 pin.on_request(req)
             }
 
+            // combine all storage requests received so far
+            pin.dispatch_storage_requests();
+
             // receive new requests to delegate to the underlying provider
             loop {
                 match Pin::new(&mut pin.incoming).poll_next(cx) {
@@ -261,9 +320,17 @@ where
                     ProviderRequest::Account(fut) => {
                         if let Poll::Ready((resp, addr)) = fut.poll_unpin(cx) {
                             // get the response
-                            let (balance, nonce, code) = resp.unwrap_or_else(|report| {
-                                panic!("Failed to get account for {}\n{}", addr, report);
-                            });
+                            let (balance, nonce, code) = match resp {
+                                Ok(resp) => resp,
+                                Err(report) if !pin.panic_on_failure => {
+                                    warn!(target: "backendhandler", "Failed to get account for {}: {}", addr, report);
+                                    pin.account_requests.remove(&addr);
+                                    continue
+                                }
+                                Err(report) => {
+                                    panic!("Failed to get account for {}\n{}", addr, report)
+                                }
+                            };
 
                             // convert it to revm-style types
                             let (code, code_hash) = if !code.0.is_empty() {
@@ -286,29 +353,53 @@ where
                             continue
                         }
                     }
-                    ProviderRequest::Storage(fut) => {
-                        if let Poll::Ready((resp, addr, idx)) = fut.poll_unpin(cx) {
-                            let value = resp.unwrap_or_else(|report| {
-                                panic!("Failed to get storage for {} at {}\n{}", addr, idx, report);
-                            });
-
-                            // update the cache
-                            pin.db.storage().write().entry(addr).or_default().insert(idx, value);
-
-                            // notify all listeners
-                            if let Some(listeners) = pin.storage_requests.remove(&(addr, idx)) {
-                                listeners.into_iter().for_each(|l| {
-                                    let _ = l.send(value);
-                                })
+                    ProviderRequest::StorageBatch(fut) => {
+                        if let Poll::Ready(batch) = fut.poll_unpin(cx) {
+                            for (resp, addr, idx) in batch {
+                                let value = match resp {
+                                    Ok(value) => value,
+                                    Err(report) if !pin.panic_on_failure => {
+                                        warn!(target: "backendhandler", "Failed to get storage for {} at {}: {}", addr, idx, report);
+                                        pin.storage_requests.remove(&(addr, idx));
+                                        continue
+                                    }
+                                    Err(report) => panic!(
+                                        "Failed to get storage for {} at {}\n{}",
+                                        addr, idx, report
+                                    ),
+                                };
+
+                                // update the cache
+                                pin.db
+                                    .storage()
+                                    .write()
+                                    .entry(addr)
+                                    .or_default()
+                                    .insert(idx, value);
+
+                                // notify all listeners
+                                if let Some(listeners) = pin.storage_requests.remove(&(addr, idx)) {
+                                    listeners.into_iter().for_each(|l| {
+                                        let _ = l.send(value);
+                                    })
+                                }
                             }
                             continue
                         }
                     }
                     ProviderRequest::BlockHash(fut) => {
                         if let Poll::Ready((block_hash, number)) = fut.poll_unpin(cx) {
-                            let value = block_hash.unwrap_or_else(|report| {
-                                panic!("Failed to get block hash for {}\n{}", number, report);
-                            });
+                            let value = match block_hash {
+                                Ok(value) => value,
+                                Err(report) if !pin.panic_on_failure => {
+                                    warn!(target: "backendhandler", "Failed to get block hash for {}: {}", number, report);
+                                    pin.block_requests.remove(&number);
+                                    continue
+                                }
+                                Err(report) => {
+                                    panic!("Failed to get block hash for {}\n{}", number, report)
+                                }
+                            };
 
                             // update the cache
                             pin.db.block_hashes().write().insert(number, value);
@@ -373,6 +464,8 @@ pub struct SharedBackend {
     /// There is only one instance of the type, so as soon as the last `SharedBackend` is deleted,
     /// `FlushJsonBlockCacheDB` is also deleted and the cache is flushed.
     _cache: Arc<FlushJsonBlockCacheDB>,
+    /// Counts the failed fetches for which the `DatabaseRef` functions returned a default value
+    failed_fetches: FailedFetches,
 }
 
 impl SharedBackend {
@@ -436,7 +529,7 @@ impl SharedBackend {
         let (backend, backend_rx) = channel(1);
         let _cache = Arc::new(FlushJsonBlockCacheDB(Arc::clone(db.cache())));
         let handler = BackendHandler::new(provider, db, backend_rx, pin_block);
-        (Self { backend, _cache }, handler)
+        (Self { backend, _cache, failed_fetches: Default::default() }, handler)
     }
 
     /// Updates the pinned block to fetch data from
@@ -447,6 +540,41 @@ impl SharedBackend {
         })
     }
 
+    /// Sets the maximum number of storage slots that are requested concurrently as one group
+    pub fn set_storage_batch_size(&self, batch_size: usize) -> eyre::Result<()> {
+        let req = BackendRequest::SetStorageBatchSize(batch_size);
+        self.backend.clone().try_send(req).map_err(|e| eyre::eyre!("{:?}", e))
+    }
+
+    /// Sets whether the backend panics if data can't be fetched from the provider, which is the
+    /// default
+    ///
+    /// If disabled, the failed value is not cached: [Self::try_basic] and [Self::try_storage]
+    /// return an error, while the `DatabaseRef` functions return the default value and count the
+    /// failure in [Self::failed_fetches].
+    pub fn set_panic_on_failure(&self, panic_on_failure: bool) -> eyre::Result<()> {
+        let req = BackendRequest::SetPanicOnFailure(panic_on_failure);
+        self.backend.clone().try_send(req).map_err(|e| eyre::eyre!("{:?}", e))
+    }
+
+    /// Returns the failed fetches for which the `DatabaseRef` functions returned a default value
+    pub fn failed_fetches(&self) -> &FailedFetches {
+        &self.failed_fetches
+    }
+
+    /// Returns the account info, or an error if it couldn't be fetched from the provider
+    pub fn try_basic(&self, address: Address) -> eyre::Result<AccountInfo> {
+        trace!( target: "sharedbackend", "request basic {:?}", address);
+        self.do_get_basic(address)
+    }
+
+    /// Returns the value of the storage slot, or an error if it couldn't be fetched from the
+    /// provider
+    pub fn try_storage(&self, address: Address, index: U256) -> eyre::Result<U256> {
+        trace!( target: "sharedbackend", "request storage {:?} at {:?}", address, index);
+        self.do_get_storage(address, index)
+    }
+
     fn do_get_basic(&self, address: Address) -> eyre::Result<AccountInfo> {
         tokio::task::block_in_place(|| {
             let (sender, rx) = oneshot_channel();
@@ -480,6 +608,7 @@ impl DatabaseRef for SharedBackend {
         trace!( target: "sharedbackend", "request basic {:?}", address);
         self.do_get_basic(address).unwrap_or_else(|_| {
             warn!( target: "sharedbackend", "Failed to send/recv `basic` for {}", address);
+            self.failed_fetches.record();
             Default::default()
         })
     }
@@ -493,6 +622,7 @@ impl DatabaseRef for SharedBackend {
         self.do_get_storage(address, index)
             .unwrap_or_else(|_| {
             warn!( target: "sharedbackend", "Failed to send/recv `storage` for {} at {}", address, index);
+            self.failed_fetches.record();
             Default::default()
         })
     }
@@ -505,15 +635,36 @@ impl DatabaseRef for SharedBackend {
         trace!( target: "sharedbackend", "request block hash for number {:?}", number);
         self.do_get_block_hash(number).unwrap_or_else(|_| {
             warn!( target: "sharedbackend", "Failed to send/recv `block_hash` for {}", number);
+            self.failed_fetches.record();
             Default::default()
         })
     }
 }
 
+/// Counts the fetches that failed while the state was read, for which a default value was used
+/// instead
+///
+/// All clones share the same count, so an execution can compare the count before and after it ran
+/// to find out whether it read made-up state.
+#[derive(Debug, Clone, Default)]
+pub struct FailedFetches(Arc<AtomicUsize>);
+
+impl FailedFetches {
+    /// Returns the number of failed fetches so far
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Counts a failed fetch
+    pub fn record(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::executor::{
-        fork::{BlockchainDbMeta, JsonBlockCacheDB},
+        fork::{BlockchainDbMeta, CacheAllowlist, JsonBlockCacheDB},
         Fork,
     };
     use ethers::{
@@ -625,4 +776,45 @@ mod tests {
         assert!(db.storage().read().contains_key(&address));
         assert_eq!(db.storage().read().get(&address).unwrap().len(), num_slots as usize);
     }
+
+    #[test]
+    fn only_writes_allowed_slots_to_cache() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let cache_path = tmpdir.path().join("storage.json");
+
+        let pinned: Address = "63091244180ae240c87d1f528f5f269134cb07b3".parse().unwrap();
+        let account = Address::random();
+        let other = Address::random();
+        let allowlist: CacheAllowlist = format!("{:?}:0x1, {:?}", pinned, account).parse().unwrap();
+
+        let meta = BlockchainDbMeta {
+            cfg_env: Default::default(),
+            block_env: Default::default(),
+            hosts: Default::default(),
+        };
+        let db = BlockchainDb::new_with_allowlist(
+            meta.clone(),
+            Some(cache_path.clone()),
+            Some(allowlist),
+        );
+        for address in [pinned, account, other] {
+            db.accounts().write().insert(address, Default::default());
+            db.storage().write().insert(
+                address,
+                [(1u64.into(), 1u64.into()), (2u64.into(), 2u64.into())].into_iter().collect(),
+            );
+        }
+        db.cache().flush();
+
+        let db = BlockchainDb::new(meta, Some(cache_path));
+        let accounts = db.accounts().read();
+        assert!(accounts.contains_key(&pinned));
+        assert!(accounts.contains_key(&account));
+        assert!(!accounts.contains_key(&other));
+
+        let storage = db.storage().read();
+        assert_eq!(storage[&pinned].keys().copied().collect::<Vec<_>>(), vec![U256::from(1u64)]);
+        assert_eq!(storage[&account].len(), 2);
+        assert!(!storage.contains_key(&other));
+    }
 }
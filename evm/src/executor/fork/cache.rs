@@ -1,6 +1,6 @@
 //! Cache related abstraction
 use ethers::{
-    types::{Address, H256, U256},
+    types::{Address, Block, Transaction, H256, U256},
     utils::keccak256,
 };
 use parking_lot::RwLock;
@@ -11,7 +11,11 @@ use std::{
     fs,
     io::BufWriter,
     path::PathBuf,
-    sync::Arc,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 use tracing::{trace, trace_span, warn};
 use tracing_error::InstrumentResult;
@@ -30,6 +34,8 @@ pub struct BlockchainDb {
     meta: Arc<RwLock<BlockchainDbMeta>>,
     /// the cache that can be flushed
     cache: Arc<JsonBlockCacheDB>,
+    /// counts how often data was served from the cache or had to be fetched
+    stats: Arc<BlockchainDbStats>,
 }
 
 impl BlockchainDb {
@@ -44,6 +50,18 @@ impl BlockchainDb {
     ///   - the file contains malformed data, or if it couldn't be read
     ///   - the provided `meta` differs from [BlockchainDbMeta] that's stored on disk
     pub fn new(meta: BlockchainDbMeta, cache_path: Option<PathBuf>) -> Self {
+        Self::new_with_allowlist(meta, cache_path, None)
+    }
+
+    /// Creates a new instance of the [BlockchainDb] that only writes the accounts and storage slots
+    /// of the [CacheAllowlist] to the `cache_path`
+    ///
+    /// See also [BlockchainDb::new()]
+    pub fn new_with_allowlist(
+        meta: BlockchainDbMeta,
+        cache_path: Option<PathBuf>,
+        allowlist: Option<CacheAllowlist>,
+    ) -> Self {
         // read cache and check if metadata matches
         let cache = cache_path
             .as_ref()
@@ -59,9 +77,15 @@ impl BlockchainDb {
                     }
                 })
             })
-            .unwrap_or_else(|| JsonBlockCacheDB::new(Arc::new(RwLock::new(meta)), cache_path));
+            .unwrap_or_else(|| JsonBlockCacheDB::new(Arc::new(RwLock::new(meta)), cache_path))
+            .with_allowlist(allowlist);
 
-        Self { db: Arc::clone(cache.db()), meta: Arc::clone(cache.meta()), cache: Arc::new(cache) }
+        Self {
+            db: Arc::clone(cache.db()),
+            meta: Arc::clone(cache.meta()),
+            cache: Arc::new(cache),
+            stats: Default::default(),
+        }
     }
 
     /// Returns the map that holds the account related info
@@ -79,6 +103,11 @@ impl BlockchainDb {
         &self.db.block_hashes
     }
 
+    /// Returns the map that holds all fetched blocks
+    pub fn blocks(&self) -> &RwLock<BTreeMap<H256, Block<Transaction>>> {
+        &self.db.blocks
+    }
+
     /// Returns the [revm::Env] related metadata
     pub fn meta(&self) -> &Arc<RwLock<BlockchainDbMeta>> {
         &self.meta
@@ -93,6 +122,51 @@ impl BlockchainDb {
     pub fn db(&self) -> &Arc<MemDb> {
         &self.db
     }
+
+    /// Returns the access counters
+    pub fn stats(&self) -> &Arc<BlockchainDbStats> {
+        &self.stats
+    }
+}
+
+/// Counters of how the [BlockchainDb] was accessed
+///
+/// A miss is every request that's not in the cache, while only the first miss of concurrent
+/// requests for the same value is fetched from the remote endpoint.
+#[derive(Debug, Default)]
+pub struct BlockchainDbStats {
+    pub account_hits: AtomicU64,
+    pub account_misses: AtomicU64,
+    pub accounts_fetched: AtomicU64,
+    pub storage_hits: AtomicU64,
+    pub storage_misses: AtomicU64,
+    pub storage_slots_fetched: AtomicU64,
+}
+
+impl BlockchainDbStats {
+    /// Increments the counter by one
+    pub fn increment(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the current value of the counter
+    pub fn get(counter: &AtomicU64) -> u64 {
+        counter.load(Ordering::Relaxed)
+    }
+
+    /// Sets all counters to zero
+    pub fn reset(&self) {
+        for counter in [
+            &self.account_hits,
+            &self.account_misses,
+            &self.accounts_fetched,
+            &self.storage_hits,
+            &self.storage_misses,
+            &self.storage_slots_fetched,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
 }
 
 /// relevant identifying markers in the context of [BlockchainDb]
@@ -172,6 +246,8 @@ pub struct MemDb {
     pub storage: RwLock<BTreeMap<Address, StorageInfo>>,
     /// All retrieved block hashes
     pub block_hashes: RwLock<BTreeMap<u64, H256>>,
+    /// All retrieved blocks, including their transactions
+    pub blocks: RwLock<BTreeMap<H256, Block<Transaction>>>,
 }
 
 impl MemDb {
@@ -180,6 +256,7 @@ impl MemDb {
         self.accounts.write().clear();
         self.storage.write().clear();
         self.block_hashes.write().clear();
+        self.blocks.write().clear();
     }
 
     // Inserts the account, replacing it if it exists already
@@ -235,6 +312,7 @@ impl Clone for MemDb {
             accounts: RwLock::new(self.accounts.read().clone()),
             storage: RwLock::new(self.storage.read().clone()),
             block_hashes: RwLock::new(self.block_hashes.read().clone()),
+            blocks: RwLock::new(self.blocks.read().clone()),
         }
     }
 }
@@ -245,6 +323,102 @@ impl DatabaseCommit for MemDb {
     }
 }
 
+/// Restricts which accounts and storage slots of a [JsonBlockCacheDB] are written to disk
+///
+/// Everything else is still kept in memory but fetched again from the endpoint after a restart.
+/// Block hashes and blocks are always written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheAllowlist {
+    /// Accounts whose info and entire storage are written
+    accounts: BTreeSet<Address>,
+    /// Single storage slots that are written, alongside the info of their account
+    slots: BTreeMap<Address, BTreeSet<U256>>,
+}
+
+impl CacheAllowlist {
+    /// Allows the info and all storage slots of the account
+    pub fn allow_account(&mut self, address: Address) {
+        self.accounts.insert(address);
+    }
+
+    /// Allows a single storage slot and the info of its account
+    pub fn allow_slot(&mut self, address: Address, slot: U256) {
+        self.slots.entry(address).or_default().insert(slot);
+    }
+
+    /// Returns true if the info of the account is written
+    pub fn allows_account(&self, address: &Address) -> bool {
+        self.accounts.contains(address) || self.slots.contains_key(address)
+    }
+
+    /// Returns true if the storage slot of the account is written
+    pub fn allows_slot(&self, address: &Address, slot: &U256) -> bool {
+        self.accounts.contains(address) ||
+            self.slots.get(address).map(|slots| slots.contains(slot)).unwrap_or_default()
+    }
+
+    /// Returns a copy of the `db` that only contains the allowed accounts and storage slots
+    fn filter(&self, db: &MemDb) -> MemDb {
+        let accounts = db
+            .accounts
+            .read()
+            .iter()
+            .filter(|(address, _)| self.allows_account(address))
+            .map(|(address, info)| (*address, info.clone()))
+            .collect();
+        let storage = db
+            .storage
+            .read()
+            .iter()
+            .filter_map(|(address, storage)| {
+                let storage: StorageInfo = storage
+                    .iter()
+                    .filter(|(slot, _)| self.allows_slot(address, slot))
+                    .map(|(slot, value)| (*slot, *value))
+                    .collect();
+                (!storage.is_empty()).then(|| (*address, storage))
+            })
+            .collect();
+        MemDb {
+            accounts: RwLock::new(accounts),
+            storage: RwLock::new(storage),
+            block_hashes: RwLock::new(db.block_hashes.read().clone()),
+            blocks: RwLock::new(db.blocks.read().clone()),
+        }
+    }
+}
+
+/// Parses a comma separated list of `<ADDRESS>` entries, which allow the entire account, and
+/// `<ADDRESS>:<SLOT>` entries, which allow a single storage slot
+impl FromStr for CacheAllowlist {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut allowlist = Self::default();
+        for entry in s.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (address, slot) = match entry.split_once(':') {
+                Some((address, slot)) => (address, Some(slot)),
+                None => (entry, None),
+            };
+            let address = Address::from_str(address)
+                .map_err(|err| format!("invalid address `{}`: {}", address, err))?;
+            match slot {
+                Some(slot) => {
+                    let parsed = if let Some(hex) = slot.strip_prefix("0x") {
+                        U256::from_str_radix(hex, 16).map_err(|err| err.to_string())
+                    } else {
+                        U256::from_dec_str(slot).map_err(|err| err.to_string())
+                    };
+                    let slot = parsed.map_err(|err| format!("invalid slot `{}`: {}", slot, err))?;
+                    allowlist.allow_slot(address, slot);
+                }
+                None => allowlist.allow_account(address),
+            }
+        }
+        Ok(allowlist)
+    }
+}
+
 /// A [BlockCacheDB] that stores the cached content in a json file
 #[derive(Debug)]
 pub struct JsonBlockCacheDB {
@@ -254,12 +428,25 @@ pub struct JsonBlockCacheDB {
     cache_path: Option<PathBuf>,
     /// Object that's stored in a json file
     data: JsonBlockCacheData,
+    /// If set, only these accounts and storage slots are written to the file
+    allowlist: Option<CacheAllowlist>,
 }
 
 impl JsonBlockCacheDB {
     /// Creates a new instance.
     fn new(meta: Arc<RwLock<BlockchainDbMeta>>, cache_path: Option<PathBuf>) -> Self {
-        Self { cache_path, data: JsonBlockCacheData { meta, data: Arc::new(Default::default()) } }
+        Self {
+            cache_path,
+            data: JsonBlockCacheData { meta, data: Arc::new(Default::default()) },
+            allowlist: None,
+        }
+    }
+
+    /// Sets the accounts and storage slots that are written to the file, `None` writes everything
+    #[must_use]
+    pub fn with_allowlist(mut self, allowlist: Option<CacheAllowlist>) -> Self {
+        self.allowlist = allowlist;
+        self
     }
 
     /// Loads the contents of the diskmap file and returns the read object
@@ -276,7 +463,7 @@ impl JsonBlockCacheDB {
         let file = std::fs::File::open(&path).in_current_span()?;
         let file = std::io::BufReader::new(file);
         let data = serde_json::from_reader(file).in_current_span()?;
-        Ok(Self { cache_path: Some(path), data })
+        Ok(Self { cache_path: Some(path), data, allowlist: None })
     }
 
     /// Returns the [MemDb] it holds access to
@@ -302,10 +489,15 @@ impl JsonBlockCacheDB {
             if let Some(parent) = path.parent() {
                 let _ = fs::create_dir_all(parent);
             }
+            let filtered = self.allowlist.as_ref().map(|allowlist| JsonBlockCacheData {
+                meta: Arc::clone(&self.data.meta),
+                data: Arc::new(allowlist.filter(&self.data.data)),
+            });
+            let data = filtered.as_ref().unwrap_or(&self.data);
             let _ = fs::File::create(path)
                 .map_err(|e| warn!(target: "cache", "Failed to open json cache for writing: {}", e))
                 .and_then(|f| {
-                    serde_json::to_writer(BufWriter::new(f), &self.data)
+                    serde_json::to_writer(BufWriter::new(f), data)
                         .map_err(|e| warn!(target: "cache" ,"Failed to write to json cache: {}", e))
                 });
             trace!(target: "cache", "saved json cache path={:?}", path);
@@ -316,7 +508,7 @@ impl JsonBlockCacheDB {
 /// The Data the [JsonBlockCacheDB] can read and flush
 ///
 /// This will be deserialized in a JSON object with the keys:
-/// `["meta", "accounts", "storage", "block_hashes"]`
+/// `["meta", "accounts", "storage", "block_hashes", "blocks"]`
 #[derive(Debug)]
 pub struct JsonBlockCacheData {
     pub meta: Arc<RwLock<BlockchainDbMeta>>,
@@ -328,7 +520,7 @@ impl Serialize for JsonBlockCacheData {
     where
         S: Serializer,
     {
-        let mut map = serializer.serialize_map(Some(4))?;
+        let mut map = serializer.serialize_map(Some(5))?;
 
         let meta = self.meta.read();
         map.serialize_entry("meta", &*meta)?;
@@ -346,6 +538,10 @@ impl Serialize for JsonBlockCacheData {
         map.serialize_entry("block_hashes", &*block_hashes)?;
         drop(block_hashes);
 
+        let blocks = self.data.blocks.read();
+        map.serialize_entry("blocks", &*blocks)?;
+        drop(blocks);
+
         map.end()
     }
 }
@@ -361,9 +557,13 @@ impl<'de> Deserialize<'de> for JsonBlockCacheData {
             accounts: BTreeMap<Address, AccountInfo>,
             storage: BTreeMap<Address, StorageInfo>,
             block_hashes: BTreeMap<u64, H256>,
+            // not present in cache files written before blocks were cached
+            #[serde(default)]
+            blocks: BTreeMap<H256, Block<Transaction>>,
         }
 
-        let Data { meta, accounts, storage, block_hashes } = Data::deserialize(deserializer)?;
+        let Data { meta, accounts, storage, block_hashes, blocks } =
+            Data::deserialize(deserializer)?;
 
         Ok(JsonBlockCacheData {
             meta: Arc::new(RwLock::new(meta)),
@@ -371,6 +571,7 @@ impl<'de> Deserialize<'de> for JsonBlockCacheData {
                 accounts: RwLock::new(accounts),
                 storage: RwLock::new(storage),
                 block_hashes: RwLock::new(block_hashes),
+                blocks: RwLock::new(blocks),
             }),
         })
     }
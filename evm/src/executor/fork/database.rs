@@ -2,7 +2,7 @@
 
 use crate::{
     executor::{
-        fork::{BlockchainDb, SharedBackend},
+        fork::{BlockchainDb, FailedFetches, SharedBackend},
         snapshot::Snapshots,
     },
     revm::db::CacheDB,
@@ -60,6 +60,34 @@ impl ForkedDatabase {
         &mut self.cache_db
     }
 
+    /// Returns the account, fetching it from the remote endpoint if it isn't cached
+    ///
+    /// Returns an error if it couldn't be fetched, in which case nothing is cached.
+    pub fn try_basic(&self, address: Address) -> eyre::Result<AccountInfo> {
+        match self.cache_db.cache().get(&address) {
+            Some(info) => Ok(info.clone()),
+            None => self.backend.try_basic(address),
+        }
+    }
+
+    /// Returns the value of the storage slot, fetching it from the remote endpoint if it isn't
+    /// cached
+    ///
+    /// Returns an error if it couldn't be fetched, in which case nothing is cached.
+    pub fn try_storage(&self, address: Address, index: U256) -> eyre::Result<U256> {
+        if let Some(value) = self.cache_db.storage().get(&address).and_then(|s| s.get(&index)) {
+            return Ok(*value)
+        }
+        self.backend.try_storage(address, index)?;
+        Ok(DatabaseRef::storage(&self.cache_db, address, index))
+    }
+
+    /// Returns the failed fetches for which a default value was read, see
+    /// [SharedBackend::failed_fetches]
+    pub fn failed_fetches(&self) -> &FailedFetches {
+        self.backend.failed_fetches()
+    }
+
     pub fn snapshots(&self) -> &Arc<Mutex<Snapshots<ForkDbSnapshot>>> {
         &self.snapshots
     }
@@ -80,6 +108,62 @@ impl ForkedDatabase {
         Ok(())
     }
 
+    /// Pins the fork to a newer `block_number` of the remote endpoint
+    ///
+    /// Unlike [Self::reset] this keeps the local changes, only the state that was fetched from the
+    /// remote endpoint is wiped, so that it's fetched again at the new block.
+    pub fn advance_pinned_block(&mut self, block_number: u64) -> Result<(), String> {
+        self.backend.set_pinned_block(block_number).map_err(|err| err.to_string())?;
+
+        // the local layer also holds all accounts and slots that were only read, which must not
+        // shadow the state of the new block
+        let (mut accounts, storage) = {
+            let remote = self.db.db();
+            let remote_accounts = remote.accounts.read();
+            let remote_storage = remote.storage.read();
+            let accounts = self
+                .cache_db
+                .cache()
+                .iter()
+                .filter(|(address, info)| {
+                    remote_accounts.get(*address).map_or(true, |remote| {
+                        remote.balance != info.balance ||
+                            remote.nonce != info.nonce ||
+                            remote.code_hash != info.code_hash
+                    })
+                })
+                .map(|(address, info)| (*address, info.clone()))
+                .collect::<Vec<_>>();
+            let mut storage = Vec::new();
+            for (address, slots) in self.cache_db.storage() {
+                let remote_slots = remote_storage.get(address);
+                for (slot, value) in slots {
+                    if remote_slots.and_then(|slots| slots.get(slot)) != Some(value) {
+                        storage.push((*address, *slot, *value));
+                    }
+                }
+            }
+            (accounts, storage)
+        };
+        // the code of modified accounts is only kept if it's part of the account
+        for (_, info) in accounts.iter_mut() {
+            if info.code.is_none() {
+                info.code = Some(DatabaseRef::code_by_hash(&self.cache_db, info.code_hash));
+            }
+        }
+
+        self.inner().db().clear();
+        self.cache_db = CacheDB::new(self.backend.clone());
+        for (address, info) in accounts {
+            self.cache_db.insert_cache(address, info);
+        }
+        for (address, slot, value) in storage {
+            self.cache_db.insert_cache_storage(address, slot, value);
+        }
+        trace!(target: "backend::forkdb", "Advanced to block {}", block_number);
+        Ok(())
+    }
+
     /// Flushes the cache to disk if configured
     pub fn flush_cache(&self) {
         self.db.cache().flush()
@@ -100,6 +184,13 @@ impl ForkedDatabase {
         }
     }
 
+    /// Replaces the local state with the local state of the `snapshot`
+    ///
+    /// Unlike [Self::revert_snapshot] this keeps the data fetched from the remote endpoint.
+    pub fn restore_snapshot(&mut self, snapshot: &ForkDbSnapshot) {
+        self.cache_db = snapshot.local.clone();
+    }
+
     pub fn insert_snapshot(&self) -> U256 {
         let snapshot = self.create_snapshot();
         let mut snapshots = self.snapshots().lock();
@@ -142,6 +233,12 @@ impl ForkedDatabase {
 
 impl Database for ForkedDatabase {
     fn basic(&mut self, address: Address) -> AccountInfo {
+        if let Err(err) = self.try_basic(address) {
+            // not cached, so that the account is fetched again on the next read
+            warn!(target: "backend::forkdb", "Failed to fetch account {:?}: {}", address, err);
+            self.failed_fetches().record();
+            return Default::default()
+        }
         self.cache_db.basic(address)
     }
 
@@ -150,6 +247,12 @@ impl Database for ForkedDatabase {
     }
 
     fn storage(&mut self, address: Address, index: U256) -> U256 {
+        if let Err(err) = self.try_storage(address, index) {
+            // not cached, so that the slot is fetched again on the next read
+            warn!(target: "backend::forkdb", "Failed to fetch storage {:?} at {}: {}", address, index, err);
+            self.failed_fetches().record();
+            return Default::default()
+        }
         Database::storage(&mut self.cache_db, address, index)
     }
 
@@ -1,10 +1,12 @@
 mod backend;
-pub use backend::{BackendHandler, SharedBackend};
+pub use backend::{BackendHandler, FailedFetches, SharedBackend, DEFAULT_STORAGE_BATCH_SIZE};
 
 mod init;
 pub use init::environment;
 
 mod cache;
-pub use cache::{BlockchainDb, BlockchainDbMeta, JsonBlockCacheDB, MemDb};
+pub use cache::{
+    BlockchainDb, BlockchainDbMeta, BlockchainDbStats, CacheAllowlist, JsonBlockCacheDB, MemDb,
+};
 
 pub mod database;
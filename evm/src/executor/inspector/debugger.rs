@@ -159,6 +159,7 @@ where
                 .get(&pc)
                 .expect("unknown ic for pc"),
             total_gas_used: gas_used(data.env.cfg.spec_id, total_gas_spent, gas.refunded() as u64),
+            gas_remaining: gas.limit().saturating_sub(total_gas_spent),
         });
 
         Return::Continue